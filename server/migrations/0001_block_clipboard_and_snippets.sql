@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS block_clipboard (
+  user_id TEXT NOT NULL PRIMARY KEY REFERENCES USERS(id) ON DELETE CASCADE,
+  block_id TEXT NOT NULL,
+  html TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS block_snippets (
+  id TEXT NOT NULL PRIMARY KEY,
+  name TEXT NOT NULL,
+  company TEXT REFERENCES companies (name) ON DELETE CASCADE,
+  block_id TEXT NOT NULL,
+  html TEXT NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS company_themes (
+  company TEXT NOT NULL PRIMARY KEY REFERENCES companies (name) ON DELETE CASCADE ON UPDATE CASCADE,
+  primary_colour TEXT NOT NULL DEFAULT '#1d1d1b',
+  secondary_colour TEXT NOT NULL DEFAULT '#ffffff',
+  accent_colour TEXT,
+  -- The names of fonts in `themes::template::FONTS`.
+  heading_font TEXT,
+  body_font TEXT,
+  logo TEXT,
+  emblem TEXT,
+  map_pb TEXT,
+  button_radius REAL,
+  button_style TEXT NOT NULL DEFAULT 'animated' CHECK (button_style IN ('animated', 'filled', 'outline'))
+);
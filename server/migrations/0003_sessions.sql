@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS sessions (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  user_agent TEXT,
+  ip TEXT,
+  created_at INTEGER NOT NULL,
+  last_activity INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id ON sessions (user_id);
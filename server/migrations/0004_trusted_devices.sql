@@ -0,0 +1,18 @@
+-- A trusted IP doesn't say which device was trusted, so its users do the second step once more on their devices.
+DROP TABLE IF EXISTS TRUSTED_IPS;
+
+CREATE TABLE TRUSTED_IPS (
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  device_id TEXT NOT NULL,
+  ip TEXT NOT NULL,
+  user_agent TEXT,
+  trusted_until INTEGER NOT NULL,
+  PRIMARY KEY(user_id, device_id)
+);
+
+CREATE TABLE IF NOT EXISTS known_devices (
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  device_id TEXT NOT NULL,
+  first_seen INTEGER NOT NULL,
+  PRIMARY KEY(user_id, device_id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS password_tokens (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  purpose TEXT NOT NULL CHECK (purpose IN ('invitation', 'reset')),
+  expires_at INTEGER NOT NULL,
+  used_at INTEGER
+);
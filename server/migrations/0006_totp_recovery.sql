@@ -0,0 +1,9 @@
+ALTER TABLE USERS ADD COLUMN totp_pending_secret TEXT;
+ALTER TABLE USERS ADD COLUMN totp_last_step INTEGER;
+
+CREATE TABLE IF NOT EXISTS recovery_codes (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  hash TEXT NOT NULL,
+  used_at INTEGER
+);
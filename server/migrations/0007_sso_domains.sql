@@ -0,0 +1 @@
+CREATE TABLE IF NOT EXISTS sso_domains (domain TEXT NOT NULL PRIMARY KEY);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS failed_logins (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id TEXT REFERENCES USERS(id) ON DELETE CASCADE,
+  email TEXT,
+  ip TEXT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('password', 'totp')),
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS failed_logins_user_id ON failed_logins (user_id, created_at);
+
+CREATE INDEX IF NOT EXISTS failed_logins_ip ON failed_logins (ip, created_at);
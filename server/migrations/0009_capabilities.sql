@@ -0,0 +1,90 @@
+ALTER TABLE USER_ROLES ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+INSERT INTO USER_ROLES(name, description) VALUES
+  ('admin', 'Can do everything.'),
+  ('content manager', 'Manages the parties, TMs, industries and labels.'),
+  ('user', 'Only has the permissions granted per company.')
+ON CONFLICT(name) DO UPDATE SET description=excluded.description;
+
+CREATE TABLE IF NOT EXISTS capabilities (
+  name TEXT NOT NULL PRIMARY KEY,
+  label TEXT NOT NULL,
+  scoped BOOLEAN NOT NULL DEFAULT 0,
+  position INTEGER NOT NULL
+);
+
+INSERT INTO CAPABILITIES(name, label, scoped, position) VALUES
+  ('create_project', 'Create', 1, 0),
+  ('edit_project', 'Edit', 1, 1),
+  ('publish', 'Publish', 1, 2),
+  ('unpublish', 'Unpublish', 1, 3),
+  ('delete_project', 'Delete', 1, 4),
+  ('view_form_submissions', 'Form submissions', 1, 5),
+  ('edit_site_pages', 'Site pages', 1, 6),
+  ('manage_parties', 'Manage parties', 0, 7),
+  ('manage_tms', 'Manage TMs', 0, 8),
+  ('manage_industries', 'Manage industries', 0, 9),
+  ('manage_labels', 'Manage labels', 0, 10),
+  ('manage_users', 'Manage users', 0, 11)
+ON CONFLICT(name) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS role_capabilities (
+  role TEXT NOT NULL REFERENCES USER_ROLES(name) ON DELETE CASCADE ON UPDATE CASCADE,
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(role, capability)
+);
+
+INSERT INTO ROLE_CAPABILITIES(role, capability) SELECT 'admin', name FROM CAPABILITIES WHERE true
+ON CONFLICT DO NOTHING;
+
+INSERT INTO ROLE_CAPABILITIES(role, capability) VALUES
+  ('content manager', 'manage_parties'),
+  ('content manager', 'manage_tms'),
+  ('content manager', 'manage_industries'),
+  ('content manager', 'manage_labels')
+ON CONFLICT DO NOTHING;
+
+-- The create and edit flags become capabilities. Editing used to cover everything else that was done per company, so
+-- it keeps doing so. The grants of deleted users and companies are left behind.
+CREATE TABLE permissions_new (
+  user TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  company TEXT REFERENCES COMPANIES(name) ON DELETE CASCADE,
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(user, company, capability)
+);
+
+INSERT INTO permissions_new(user, company, capability)
+SELECT p.user, p.company, c.name
+FROM PERMISSIONS p
+JOIN CAPABILITIES c ON (p.`create`=1 AND c.name='create_project')
+  OR (p.edit=1 AND c.name IN ('edit_project', 'publish', 'unpublish', 'delete_project', 'view_form_submissions', 'edit_site_pages'))
+WHERE p.user IN (SELECT id FROM USERS)
+  AND (p.company IS NULL OR p.company IN (SELECT name FROM COMPANIES));
+
+DROP TABLE PERMISSIONS;
+
+ALTER TABLE permissions_new RENAME TO PERMISSIONS;
+
+CREATE UNIQUE INDEX `permissions_unique_NULL_company` ON PERMISSIONS (
+    user,
+    IFNULL(company, ''),
+    capability
+);
+
+CREATE VIEW IF NOT EXISTS user_capabilities AS
+SELECT
+  user,
+  company,
+  capability,
+  0 AS everywhere
+FROM
+  PERMISSIONS
+UNION ALL
+SELECT
+  u.id AS user,
+  NULL AS company,
+  rc.capability,
+  1 AS everywhere
+FROM
+  USERS u
+  JOIN ROLE_CAPABILITIES rc ON rc.role = u.role;
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS api_tokens (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  all_companies BOOLEAN NOT NULL DEFAULT 0,
+  created_at INTEGER NOT NULL,
+  expires_at INTEGER NOT NULL,
+  last_used_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS api_tokens_user ON api_tokens (user_id);
+
+CREATE TABLE IF NOT EXISTS api_token_capabilities (
+  token_id TEXT NOT NULL REFERENCES API_TOKENS(id) ON DELETE CASCADE,
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(token_id, capability)
+);
+
+CREATE TABLE IF NOT EXISTS api_token_companies (
+  token_id TEXT NOT NULL REFERENCES API_TOKENS(id) ON DELETE CASCADE,
+  company TEXT REFERENCES COMPANIES(name) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS api_token_companies_unique ON api_token_companies (
+  token_id,
+  IFNULL(company, '')
+);
@@ -0,0 +1,4 @@
+ALTER TABLE PARTIES ADD COLUMN logo TEXT;
+
+-- The URL of a party is a link on the public pages, only http(s) URLs are accepted from now on.
+UPDATE PARTIES SET url=NULL WHERE url NOT LIKE 'http://%' AND url NOT LIKE 'https://%';
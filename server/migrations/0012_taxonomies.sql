@@ -0,0 +1,25 @@
+ALTER TABLE PUBLIEK_PRIVAAT_TYPES ADD COLUMN label TEXT;
+ALTER TABLE PUBLIEK_PRIVAAT_TYPES ADD COLUMN description TEXT;
+ALTER TABLE PUBLIEK_PRIVAAT_TYPES ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE TAGS ADD COLUMN label TEXT;
+ALTER TABLE TAGS ADD COLUMN description TEXT;
+ALTER TABLE TAGS ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE PARTY_TYPES ADD COLUMN description TEXT;
+ALTER TABLE PARTY_TYPES ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE TMS ADD COLUMN description TEXT;
+ALTER TABLE TMS ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE INDUSTRIES ADD COLUMN label TEXT;
+ALTER TABLE INDUSTRIES ADD COLUMN description TEXT;
+ALTER TABLE INDUSTRIES ADD COLUMN icon TEXT;
+ALTER TABLE INDUSTRIES ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS taxonomy_companies (
+  taxonomy TEXT NOT NULL,
+  name TEXT NOT NULL,
+  company TEXT NOT NULL REFERENCES companies (name) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (taxonomy, name, company)
+);
@@ -0,0 +1,298 @@
+ALTER TABLE CORE_NUMBERS ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE PROJECT_PARTIES ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE IMAGES ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+-- Keeps the order they were added in, which is the order they were shown in until now.
+UPDATE CORE_NUMBERS
+SET position = (
+  SELECT COUNT(*) FROM CORE_NUMBERS c
+  WHERE c.project_id = CORE_NUMBERS.project_id AND c.draft = CORE_NUMBERS.draft AND c.rowid < CORE_NUMBERS.rowid
+);
+
+UPDATE PROJECT_PARTIES
+SET position = (
+  SELECT COUNT(*) FROM PROJECT_PARTIES p
+  WHERE p.project_id = PROJECT_PARTIES.project_id
+    AND p.draft = PROJECT_PARTIES.draft
+    AND p.type = PROJECT_PARTIES.type
+    AND p.rowid < PROJECT_PARTIES.rowid
+);
+
+UPDATE IMAGES
+SET position = (
+  SELECT COUNT(*) FROM IMAGES i
+  WHERE i.project_id = IMAGES.project_id
+    AND i.draft = IMAGES.draft
+    AND i.company_name IS IMAGES.company_name
+    AND i.rowid < IMAGES.rowid
+);
+
+-- Publishing copies the positions along, the triggers are recreated from triggers.sql.
+DROP TRIGGER IF EXISTS AFTER_INSERT_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_UPDATE_PROJECTS;
+
+CREATE TRIGGER AFTER_INSERT_PROJECTS
+AFTER
+INSERT
+    ON PROJECTS
+    WHEN NEW.draft = 1
+    AND EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = NEW.id
+            AND draft = 0
+    ) BEGIN
+INSERT INTO
+    PROJECT_COMPANIES(
+        project_id,
+        draft,
+        company_name,
+        show_in_carousel,
+        introduction,
+        header_photo,
+        header_photo_copyright,
+        banner_photo,
+        banner_photo_copyright,
+        thumbnail,
+        weight,
+        custom_content,
+        custom_lightbox,
+        visible
+    )
+SELECT
+    project_id,
+    1,
+    company_name,
+    show_in_carousel,
+    introduction,
+    header_photo,
+    header_photo_copyright,
+    banner_photo,
+    banner_photo_copyright,
+    thumbnail,
+    weight,
+    custom_content,
+    custom_lightbox,
+    visible
+FROM
+    PROJECT_COMPANIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    CORE_NUMBERS(
+        id,
+        project_id,
+        title,
+        number,
+        draft,
+        position
+    )
+SELECT
+    id,
+    project_id,
+    title,
+    number,
+    1,
+    position
+FROM
+    CORE_NUMBERS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_PARTIES(
+        project_id,
+        draft,
+        party_name,
+        type,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    party_name,
+    type,
+    position
+FROM
+    PROJECT_PARTIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TMS(
+        project_id,
+        draft,
+        tm_name,
+        type
+    )
+SELECT
+    project_id,
+    1,
+    tm_name,
+    type
+FROM
+    PROJECT_TMS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_INDUSTRIES(
+        project_id,
+        draft,
+        industry
+    )
+SELECT
+    project_id,
+    1,
+    industry
+FROM
+    PROJECT_INDUSTRIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TAGS(
+        project_id,
+        tag,
+        draft
+    )
+SELECT
+    project_id,
+    tag,
+    1
+FROM
+    PROJECT_TAGS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_CONTENT(
+        project_id,
+        company_name,
+        id,
+        previous_entry,
+        draft,
+        text,
+        image,
+        image_copyright,
+        quote,
+        quote_small
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    previous_entry,
+    1,
+    text,
+    image,
+    image_copyright,
+    quote,
+    quote_small
+FROM
+    PROJECT_CONTENT
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    IMAGES(
+        project_id,
+        company_name,
+        id,
+        draft,
+        image,
+        image_copyright,
+        alt,
+        position
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    1,
+    image,
+    image_copyright,
+    alt,
+    position
+FROM
+    IMAGES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+END;
+
+CREATE TRIGGER AFTER_UPDATE_PROJECTS
+AFTER
+UPDATE
+    ON PROJECTS
+    WHEN NEW.draft = 0
+    AND OLD.draft = 1 BEGIN
+UPDATE
+    PROJECT_COMPANIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    CORE_NUMBERS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_PARTIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TMS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_INDUSTRIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_CONTENT
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    IMAGES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TAGS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+END;
\ No newline at end of file
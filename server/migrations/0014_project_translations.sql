@@ -0,0 +1,394 @@
+CREATE TABLE IF NOT EXISTS project_translations (
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  language TEXT NOT NULL CHECK (language IN ('fr', 'en')),
+  field TEXT NOT NULL CHECK (
+    field IN (
+      'name',
+      'location',
+      'introduction',
+      'content_text',
+      'content_quote',
+      'content_quote_small',
+      'core_number_title',
+      'image_alt'
+    )
+  ),
+  item TEXT NOT NULL DEFAULT '',
+  value TEXT NOT NULL,
+  slug TEXT GENERATED always AS (
+    CASE
+      WHEN field = 'name' THEN lower(
+        replace (
+          replace (
+            replace (
+              replace (
+                replace (replace (value, ' ', '-'), '!', ''),
+                '?',
+                ''
+              ),
+              ',',
+              ''
+            ),
+            '.',
+            ''
+          ),
+          char(10),
+          '-'
+        )
+      )
+    END
+  ) stored,
+  PRIMARY KEY (project_id, draft, language, field, item),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS project_translations_slug ON project_translations (language, slug, draft)
+WHERE
+  slug IS NOT NULL;
+
+-- Publishing copies the translations along, and deleting a content entry, core number or image deletes its
+-- translations. The triggers are recreated from triggers.sql.
+DROP TRIGGER IF EXISTS AFTER_INSERT_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_UPDATE_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_PROJECT_CONTENT;
+DROP TRIGGER IF EXISTS AFTER_DELETE_CORE_NUMBERS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_IMAGES;
+
+CREATE TRIGGER AFTER_INSERT_PROJECTS
+AFTER
+INSERT
+    ON PROJECTS
+    WHEN NEW.draft = 1
+    AND EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = NEW.id
+            AND draft = 0
+    ) BEGIN
+INSERT INTO
+    PROJECT_COMPANIES(
+        project_id,
+        draft,
+        company_name,
+        show_in_carousel,
+        introduction,
+        header_photo,
+        header_photo_copyright,
+        banner_photo,
+        banner_photo_copyright,
+        thumbnail,
+        weight,
+        custom_content,
+        custom_lightbox,
+        visible
+    )
+SELECT
+    project_id,
+    1,
+    company_name,
+    show_in_carousel,
+    introduction,
+    header_photo,
+    header_photo_copyright,
+    banner_photo,
+    banner_photo_copyright,
+    thumbnail,
+    weight,
+    custom_content,
+    custom_lightbox,
+    visible
+FROM
+    PROJECT_COMPANIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    CORE_NUMBERS(
+        id,
+        project_id,
+        title,
+        number,
+        draft,
+        position
+    )
+SELECT
+    id,
+    project_id,
+    title,
+    number,
+    1,
+    position
+FROM
+    CORE_NUMBERS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_PARTIES(
+        project_id,
+        draft,
+        party_name,
+        type,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    party_name,
+    type,
+    position
+FROM
+    PROJECT_PARTIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TMS(
+        project_id,
+        draft,
+        tm_name,
+        type
+    )
+SELECT
+    project_id,
+    1,
+    tm_name,
+    type
+FROM
+    PROJECT_TMS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_INDUSTRIES(
+        project_id,
+        draft,
+        industry
+    )
+SELECT
+    project_id,
+    1,
+    industry
+FROM
+    PROJECT_INDUSTRIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TAGS(
+        project_id,
+        tag,
+        draft
+    )
+SELECT
+    project_id,
+    tag,
+    1
+FROM
+    PROJECT_TAGS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_CONTENT(
+        project_id,
+        company_name,
+        id,
+        previous_entry,
+        draft,
+        text,
+        image,
+        image_copyright,
+        quote,
+        quote_small
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    previous_entry,
+    1,
+    text,
+    image,
+    image_copyright,
+    quote,
+    quote_small
+FROM
+    PROJECT_CONTENT
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    IMAGES(
+        project_id,
+        company_name,
+        id,
+        draft,
+        image,
+        image_copyright,
+        alt,
+        position
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    1,
+    image,
+    image_copyright,
+    alt,
+    position
+FROM
+    IMAGES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TRANSLATIONS(
+        project_id,
+        draft,
+        language,
+        field,
+        item,
+        value
+    )
+SELECT
+    project_id,
+    1,
+    language,
+    field,
+    item,
+    value
+FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+END;
+
+CREATE TRIGGER AFTER_UPDATE_PROJECTS
+AFTER
+UPDATE
+    ON PROJECTS
+    WHEN NEW.draft = 0
+    AND OLD.draft = 1 BEGIN
+UPDATE
+    PROJECT_COMPANIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    CORE_NUMBERS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_PARTIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TMS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_INDUSTRIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_CONTENT
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    IMAGES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TAGS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TRANSLATIONS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+END;
+
+-- The translations of a content entry, core number or image go with it.
+CREATE TRIGGER AFTER_DELETE_PROJECT_CONTENT
+AFTER
+DELETE
+    ON PROJECT_CONTENT BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field IN ('content_text', 'content_quote', 'content_quote_small')
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_CORE_NUMBERS
+AFTER
+DELETE
+    ON CORE_NUMBERS BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'core_number_title'
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_IMAGES
+AFTER
+DELETE
+    ON IMAGES BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'image_alt'
+    AND item = OLD.id;
+
+END;
\ No newline at end of file
@@ -0,0 +1,444 @@
+-- no-transaction
+-- PROJECTS and PROJECT_TRANSLATIONS are rebuilt to turn their generated slugs into plain columns, which needs the
+-- foreign keys off. That pragma does nothing inside a transaction, so this migration opens its own.
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+-- The triggers reference both tables, they are created again at the end.
+DROP TRIGGER IF EXISTS AFTER_INSERT_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_UPDATE_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_PROJECT_CONTENT;
+DROP TRIGGER IF EXISTS AFTER_DELETE_CORE_NUMBERS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_IMAGES;
+DROP TRIGGER IF EXISTS AFTER_DELETE_PROJECTS;
+
+CREATE TABLE projects_new (
+  id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  -- Derived from the name when the project is created, after that it only changes when it's edited. The slugs a
+  -- published project had before are kept in SLUG_HISTORY.
+  slug TEXT NOT NULL,
+  LOCATION TEXT,
+  YEAR INTEGER,
+  learn_more TEXT,
+  status BOOLEAN NOT NULL DEFAULT 0,
+  publiek_privaat TEXT NOT NULL DEFAULT 'publiek' REFERENCES publiek_privaat_types (name),
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  unique (slug, draft),
+  PRIMARY KEY (id, draft)
+);
+
+-- The slugs the projects have now are kept, so none of their URLs change.
+INSERT INTO projects_new (id, name, slug, location, year, learn_more, status, publiek_privaat, draft)
+SELECT id, name, slug, location, year, learn_more, status, publiek_privaat, draft
+FROM projects;
+
+DROP TABLE projects;
+ALTER TABLE projects_new RENAME TO projects;
+
+CREATE TABLE project_translations_new (
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  language TEXT NOT NULL CHECK (language IN ('fr', 'en')),
+  field TEXT NOT NULL CHECK (
+    field IN (
+      'name',
+      'location',
+      'introduction',
+      'content_text',
+      'content_quote',
+      'content_quote_small',
+      'core_number_title',
+      'image_alt'
+    )
+  ),
+  item TEXT NOT NULL DEFAULT '',
+  value TEXT NOT NULL,
+  -- The slug of the project in the language, only for the name.
+  slug TEXT,
+  PRIMARY KEY (project_id, draft, language, field, item),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
+
+INSERT INTO project_translations_new (project_id, draft, language, field, item, value, slug)
+SELECT project_id, draft, language, field, item, value, slug
+FROM project_translations;
+
+DROP TABLE project_translations;
+ALTER TABLE project_translations_new RENAME TO project_translations;
+
+CREATE UNIQUE INDEX project_translations_slug ON project_translations (language, slug, draft)
+WHERE
+  slug IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS slug_history (
+  slug TEXT NOT NULL,
+  language TEXT NOT NULL DEFAULT 'nl',
+  project_id TEXT NOT NULL,
+  replaced_at INTEGER NOT NULL DEFAULT (unixepoch()),
+  PRIMARY KEY (slug, language)
+);
+
+CREATE TRIGGER AFTER_INSERT_PROJECTS
+AFTER
+INSERT
+    ON PROJECTS
+    WHEN NEW.draft = 1
+    AND EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = NEW.id
+            AND draft = 0
+    ) BEGIN
+INSERT INTO
+    PROJECT_COMPANIES(
+        project_id,
+        draft,
+        company_name,
+        show_in_carousel,
+        introduction,
+        header_photo,
+        header_photo_copyright,
+        banner_photo,
+        banner_photo_copyright,
+        thumbnail,
+        weight,
+        custom_content,
+        custom_lightbox,
+        visible
+    )
+SELECT
+    project_id,
+    1,
+    company_name,
+    show_in_carousel,
+    introduction,
+    header_photo,
+    header_photo_copyright,
+    banner_photo,
+    banner_photo_copyright,
+    thumbnail,
+    weight,
+    custom_content,
+    custom_lightbox,
+    visible
+FROM
+    PROJECT_COMPANIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    CORE_NUMBERS(
+        id,
+        project_id,
+        title,
+        number,
+        draft,
+        position
+    )
+SELECT
+    id,
+    project_id,
+    title,
+    number,
+    1,
+    position
+FROM
+    CORE_NUMBERS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_PARTIES(
+        project_id,
+        draft,
+        party_name,
+        type,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    party_name,
+    type,
+    position
+FROM
+    PROJECT_PARTIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TMS(
+        project_id,
+        draft,
+        tm_name,
+        type
+    )
+SELECT
+    project_id,
+    1,
+    tm_name,
+    type
+FROM
+    PROJECT_TMS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_INDUSTRIES(
+        project_id,
+        draft,
+        industry
+    )
+SELECT
+    project_id,
+    1,
+    industry
+FROM
+    PROJECT_INDUSTRIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TAGS(
+        project_id,
+        tag,
+        draft
+    )
+SELECT
+    project_id,
+    tag,
+    1
+FROM
+    PROJECT_TAGS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_CONTENT(
+        project_id,
+        company_name,
+        id,
+        previous_entry,
+        draft,
+        text,
+        image,
+        image_copyright,
+        quote,
+        quote_small
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    previous_entry,
+    1,
+    text,
+    image,
+    image_copyright,
+    quote,
+    quote_small
+FROM
+    PROJECT_CONTENT
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    IMAGES(
+        project_id,
+        company_name,
+        id,
+        draft,
+        image,
+        image_copyright,
+        alt,
+        position
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    1,
+    image,
+    image_copyright,
+    alt,
+    position
+FROM
+    IMAGES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TRANSLATIONS(
+        project_id,
+        draft,
+        language,
+        field,
+        item,
+        value,
+        slug
+    )
+SELECT
+    project_id,
+    1,
+    language,
+    field,
+    item,
+    value,
+    slug
+FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+END;
+
+CREATE TRIGGER AFTER_UPDATE_PROJECTS
+AFTER
+UPDATE
+    ON PROJECTS
+    WHEN NEW.draft = 0
+    AND OLD.draft = 1 BEGIN
+UPDATE
+    PROJECT_COMPANIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    CORE_NUMBERS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_PARTIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TMS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_INDUSTRIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_CONTENT
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    IMAGES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TAGS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TRANSLATIONS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+END;
+
+-- The translations of a content entry, core number or image go with it.
+CREATE TRIGGER AFTER_DELETE_PROJECT_CONTENT
+AFTER
+DELETE
+    ON PROJECT_CONTENT BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field IN ('content_text', 'content_quote', 'content_quote_small')
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_CORE_NUMBERS
+AFTER
+DELETE
+    ON CORE_NUMBERS BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'core_number_title'
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_IMAGES
+AFTER
+DELETE
+    ON IMAGES BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'image_alt'
+    AND item = OLD.id;
+
+END;
+
+-- The old slugs of a project go when the last version of the project does.
+CREATE TRIGGER AFTER_DELETE_PROJECTS
+AFTER
+DELETE
+    ON PROJECTS
+    WHEN NOT EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = OLD.id
+    ) BEGIN
+DELETE FROM
+    SLUG_HISTORY
+WHERE
+    project_id = OLD.id;
+
+END;
+COMMIT;
+
+PRAGMA foreign_keys = ON;
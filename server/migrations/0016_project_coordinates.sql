@@ -0,0 +1,5 @@
+-- Where the project is on the map, geocoded from the location unless an editor placed the pin themselves. The existing
+-- projects get their pin when their location is edited or the pin is reset.
+ALTER TABLE projects ADD COLUMN latitude REAL;
+ALTER TABLE projects ADD COLUMN longitude REAL;
+ALTER TABLE projects ADD COLUMN manual_coordinates BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- A file that is being imported, kept between the upload and the import so the columns can be mapped first.
+CREATE TABLE IF NOT EXISTS imports (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  file_name TEXT NOT NULL,
+  -- JSON arrays of the column names and of the rows, every row an array of strings.
+  columns TEXT NOT NULL,
+  rows TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,8 @@
+-- The structure new projects can start from: parties, TMs, industries, labels, the titles of the core numbers and the
+-- outline of the content, saved from a project as JSON.
+CREATE TABLE IF NOT EXISTS project_templates (
+  id TEXT NOT NULL PRIMARY KEY,
+  name TEXT NOT NULL,
+  data TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,410 @@
+-- The related projects editors picked for a project, in the order the sites show them. Without any, the sites pick the
+-- projects that have the most in common with it.
+CREATE TABLE IF NOT EXISTS related_projects (
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  related_project_id TEXT NOT NULL,
+  position INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (project_id, draft, related_project_id),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
+
+-- Drafts and publishing take the related projects along, and deleting a project takes it out of the related projects
+-- of the others. The triggers are recreated from triggers.sql.
+DROP TRIGGER IF EXISTS AFTER_INSERT_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_UPDATE_PROJECTS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_PROJECT_CONTENT;
+DROP TRIGGER IF EXISTS AFTER_DELETE_CORE_NUMBERS;
+DROP TRIGGER IF EXISTS AFTER_DELETE_IMAGES;
+DROP TRIGGER IF EXISTS AFTER_DELETE_PROJECTS;
+
+CREATE TRIGGER AFTER_INSERT_PROJECTS
+AFTER
+INSERT
+    ON PROJECTS
+    WHEN NEW.draft = 1
+    AND EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = NEW.id
+            AND draft = 0
+    ) BEGIN
+INSERT INTO
+    PROJECT_COMPANIES(
+        project_id,
+        draft,
+        company_name,
+        show_in_carousel,
+        introduction,
+        header_photo,
+        header_photo_copyright,
+        banner_photo,
+        banner_photo_copyright,
+        thumbnail,
+        weight,
+        custom_content,
+        custom_lightbox,
+        visible
+    )
+SELECT
+    project_id,
+    1,
+    company_name,
+    show_in_carousel,
+    introduction,
+    header_photo,
+    header_photo_copyright,
+    banner_photo,
+    banner_photo_copyright,
+    thumbnail,
+    weight,
+    custom_content,
+    custom_lightbox,
+    visible
+FROM
+    PROJECT_COMPANIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    CORE_NUMBERS(
+        id,
+        project_id,
+        title,
+        number,
+        draft,
+        position
+    )
+SELECT
+    id,
+    project_id,
+    title,
+    number,
+    1,
+    position
+FROM
+    CORE_NUMBERS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_PARTIES(
+        project_id,
+        draft,
+        party_name,
+        type,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    party_name,
+    type,
+    position
+FROM
+    PROJECT_PARTIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TMS(
+        project_id,
+        draft,
+        tm_name,
+        type
+    )
+SELECT
+    project_id,
+    1,
+    tm_name,
+    type
+FROM
+    PROJECT_TMS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_INDUSTRIES(
+        project_id,
+        draft,
+        industry
+    )
+SELECT
+    project_id,
+    1,
+    industry
+FROM
+    PROJECT_INDUSTRIES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TAGS(
+        project_id,
+        tag,
+        draft
+    )
+SELECT
+    project_id,
+    tag,
+    1
+FROM
+    PROJECT_TAGS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_CONTENT(
+        project_id,
+        company_name,
+        id,
+        previous_entry,
+        draft,
+        text,
+        image,
+        image_copyright,
+        quote,
+        quote_small
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    previous_entry,
+    1,
+    text,
+    image,
+    image_copyright,
+    quote,
+    quote_small
+FROM
+    PROJECT_CONTENT
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    IMAGES(
+        project_id,
+        company_name,
+        id,
+        draft,
+        image,
+        image_copyright,
+        alt,
+        position
+    )
+SELECT
+    project_id,
+    company_name,
+    id,
+    1,
+    image,
+    image_copyright,
+    alt,
+    position
+FROM
+    IMAGES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    RELATED_PROJECTS(
+        project_id,
+        draft,
+        related_project_id,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    related_project_id,
+    position
+FROM
+    RELATED_PROJECTS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TRANSLATIONS(
+        project_id,
+        draft,
+        language,
+        field,
+        item,
+        value,
+        slug
+    )
+SELECT
+    project_id,
+    1,
+    language,
+    field,
+    item,
+    value,
+    slug
+FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+END;
+
+CREATE TRIGGER AFTER_UPDATE_PROJECTS
+AFTER
+UPDATE
+    ON PROJECTS
+    WHEN NEW.draft = 0
+    AND OLD.draft = 1 BEGIN
+UPDATE
+    PROJECT_COMPANIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    CORE_NUMBERS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_PARTIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TMS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_INDUSTRIES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_CONTENT
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    IMAGES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TAGS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    PROJECT_TRANSLATIONS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    RELATED_PROJECTS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+END;
+
+-- The translations of a content entry, core number or image go with it.
+CREATE TRIGGER AFTER_DELETE_PROJECT_CONTENT
+AFTER
+DELETE
+    ON PROJECT_CONTENT BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field IN ('content_text', 'content_quote', 'content_quote_small')
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_CORE_NUMBERS
+AFTER
+DELETE
+    ON CORE_NUMBERS BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'core_number_title'
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_IMAGES
+AFTER
+DELETE
+    ON IMAGES BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'image_alt'
+    AND item = OLD.id;
+
+END;
+
+-- The old slugs of a project go when the last version of the project does, and so does the project where it was
+-- picked as a related project.
+CREATE TRIGGER AFTER_DELETE_PROJECTS
+AFTER
+DELETE
+    ON PROJECTS
+    WHEN NOT EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = OLD.id
+    ) BEGIN
+DELETE FROM
+    SLUG_HISTORY
+WHERE
+    project_id = OLD.id;
+
+DELETE FROM
+    RELATED_PROJECTS
+WHERE
+    related_project_id = OLD.id;
+
+END;
@@ -0,0 +1,4 @@
+-- The provider's id of the account a user logs in with through single sign-on.
+ALTER TABLE USERS ADD COLUMN sso_subject TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_sso_subject ON USERS (sso_subject);
@@ -1,8 +1,52 @@
+-- The whole schema, for a new database. A change to it also gets a migration in ../migrations, which the server
+-- runs on start to bring an existing database up to date.
+
 CREATE TABLE user_roles (
-  name TEXT NOT NULL PRIMARY KEY
+  name TEXT NOT NULL PRIMARY KEY,
+  description TEXT NOT NULL DEFAULT ''
+);
+
+INSERT INTO USER_ROLES(name, description) VALUES
+  ('admin', 'Can do everything.'),
+  ('content manager', 'Manages the parties, TMs, industries and labels.'),
+  ('user', 'Only has the permissions granted per company.');
+
+-- Scoped capabilities are granted per company, the others only through a role.
+CREATE TABLE capabilities (
+  name TEXT NOT NULL PRIMARY KEY,
+  label TEXT NOT NULL,
+  scoped BOOLEAN NOT NULL DEFAULT 0,
+  position INTEGER NOT NULL
 );
 
-INSERT INTO USER_ROLES(name) VALUES ('admin'), ('user');
+INSERT INTO CAPABILITIES(name, label, scoped, position) VALUES
+  ('create_project', 'Create', 1, 0),
+  ('edit_project', 'Edit', 1, 1),
+  ('publish', 'Publish', 1, 2),
+  ('unpublish', 'Unpublish', 1, 3),
+  ('delete_project', 'Delete', 1, 4),
+  ('view_form_submissions', 'Form submissions', 1, 5),
+  ('edit_site_pages', 'Site pages', 1, 6),
+  ('manage_parties', 'Manage parties', 0, 7),
+  ('manage_tms', 'Manage TMs', 0, 8),
+  ('manage_industries', 'Manage industries', 0, 9),
+  ('manage_labels', 'Manage labels', 0, 10),
+  ('manage_users', 'Manage users', 0, 11);
+
+-- The capabilities a role grants, for every company.
+CREATE TABLE role_capabilities (
+  role TEXT NOT NULL REFERENCES USER_ROLES(name) ON DELETE CASCADE ON UPDATE CASCADE,
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(role, capability)
+);
+
+INSERT INTO ROLE_CAPABILITIES(role, capability) SELECT 'admin', name FROM CAPABILITIES;
+
+INSERT INTO ROLE_CAPABILITIES(role, capability) VALUES
+  ('content manager', 'manage_parties'),
+  ('content manager', 'manage_tms'),
+  ('content manager', 'manage_industries'),
+  ('content manager', 'manage_labels');
 
 CREATE TABLE USERS (
   id TEXT NOT NULL PRIMARY KEY,
@@ -11,61 +55,84 @@ CREATE TABLE USERS (
   last_name TEXT NOT NULL,
   password TEXT NOT NULL,
   totp_secret TEXT,
-  role TEXT NOT NULL DEFAULT 'user' REFERENCES USER_ROLES(name)
+  totp_pending_secret TEXT,
+  totp_last_step INTEGER,
+  role TEXT NOT NULL DEFAULT 'user' REFERENCES USER_ROLES(name),
+  -- The provider's id of the account the user logs in with through single sign-on.
+  sso_subject TEXT UNIQUE
 );
 
 CREATE TABLE IF NOT EXISTS TRUSTED_IPS (
-  user_id TEXT NOT NULL REFERENCES USERS(id),
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  device_id TEXT NOT NULL,
   ip TEXT NOT NULL,
-  PRIMARY KEY(user_id, ip)
+  user_agent TEXT,
+  trusted_until INTEGER NOT NULL,
+  PRIMARY KEY(user_id, device_id)
 );
 
-CREATE TABLE publiek_privaat_types (name TEXT NOT NULL PRIMARY KEY);
+-- Like the industries and tags, the label is what visitors see and the name is the value projects and URLs use.
+CREATE TABLE publiek_privaat_types (
+  name TEXT NOT NULL PRIMARY KEY,
+  label TEXT,
+  description TEXT,
+  position INTEGER NOT NULL DEFAULT 0
+);
 
 CREATE TABLE companies (name TEXT NOT NULL PRIMARY KEY);
 
+-- A capability granted to a user for one company, a NULL company stands for the projects without a company.
 CREATE TABLE PERMISSIONS(
   user TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
   company TEXT REFERENCES COMPANIES(name) ON DELETE CASCADE,
-  `create` BOOLEAN NOT NULL DEFAULT 0,
-  edit BOOLEAN NOT NULL DEFAULT 0,
-  PRIMARY KEY(user, company)
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(user, company, capability)
 );
 
 CREATE UNIQUE INDEX `permissions_unique_NULL_company` ON PERMISSIONS (
     user,
-    IFNULL(company, '')
+    IFNULL(company, ''),
+    capability
 );
 
+-- Everything a user may do: the per-company grants, and the capabilities of their role which hold everywhere.
+CREATE VIEW user_capabilities AS
+SELECT
+  user,
+  company,
+  capability,
+  0 AS everywhere
+FROM
+  PERMISSIONS
+UNION ALL
+SELECT
+  u.id AS user,
+  NULL AS company,
+  rc.capability,
+  1 AS everywhere
+FROM
+  USERS u
+  JOIN ROLE_CAPABILITIES rc ON rc.role = u.role;
+
 
 CREATE TABLE projects (
   id TEXT NOT NULL,
   name TEXT NOT NULL,
-  slug TEXT GENERATED always AS (
-    lower(
-      replace (
-        replace (
-          replace (
-            replace (
-              replace (replace (name, ' ', '-'), '!', ''),
-              '?',
-              ''
-            ),
-            ',',
-            ''
-          ),
-          '.',
-          ''
-        ),
-        char(10),
-        '-'
-      )
-    )
-  ) stored NOT NULL,
+  -- Derived from the name when the project is created, after that it only changes when it's edited. The slugs a
+  -- published project had before are kept in SLUG_HISTORY.
+  slug TEXT NOT NULL,
   LOCATION TEXT,
+  -- Where the project is on the map, geocoded from the location unless an editor placed the pin themselves.
+  latitude REAL,
+  longitude REAL,
+  manual_coordinates BOOLEAN NOT NULL DEFAULT 0,
   YEAR INTEGER,
   learn_more TEXT,
-  status BOOLEAN NOT NULL DEFAULT 0,
+  -- Where the project is in its lifecycle. The dates are ISO dates (YYYY-MM-DD), the year is what the sites show and
+  -- sort on when the project has neither.
+  lifecycle TEXT NOT NULL DEFAULT 'planned' CHECK (lifecycle IN ('planned', 'in_progress', 'delivered')),
+  start_date TEXT,
+  delivery_date TEXT,
   publiek_privaat TEXT NOT NULL DEFAULT 'publiek' REFERENCES publiek_privaat_types (name),
   draft BOOLEAN NOT NULL DEFAULT 0,
   unique (slug, draft),
@@ -73,7 +140,10 @@ CREATE TABLE projects (
 );
 
 CREATE TABLE tags (
-  name TEXT NOT NULL PRIMARY KEY
+  name TEXT NOT NULL PRIMARY KEY,
+  label TEXT,
+  description TEXT,
+  position INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE TABLE project_tags (
@@ -112,15 +182,37 @@ CREATE TABLE core_numbers (
   title TEXT NOT NULL,
   number TEXT NOT NULL,
   draft BOOLEAN NOT NULL DEFAULT 0,
+  position INTEGER NOT NULL DEFAULT 0,
   PRIMARY KEY (id, draft),
   FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
 );
 
-CREATE TABLE party_types (name TEXT PRIMARY KEY NOT NULL);
+-- The phases of a project, like the demolition or the construction of a wing, in order.
+CREATE TABLE project_phases (
+  id TEXT NOT NULL,
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  name TEXT NOT NULL,
+  start_date TEXT,
+  end_date TEXT,
+  position INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (id, draft),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
 
-CREATE TABLE parties (name TEXT PRIMARY KEY NOT NULL, url TEXT);
+CREATE TABLE party_types (
+  name TEXT PRIMARY KEY NOT NULL,
+  description TEXT,
+  position INTEGER NOT NULL DEFAULT 0
+);
 
-CREATE TABLE tms (name TEXT PRIMARY KEY NOT NULL);
+CREATE TABLE parties (name TEXT PRIMARY KEY NOT NULL, url TEXT, logo TEXT);
+
+CREATE TABLE tms (
+  name TEXT PRIMARY KEY NOT NULL,
+  description TEXT,
+  position INTEGER NOT NULL DEFAULT 0
+);
 
 CREATE TABLE tm_companies (
   name TEXT REFERENCES tms (name),
@@ -139,6 +231,8 @@ CREATE TABLE project_parties (
   draft BOOLEAN NOT NULL DEFAULT 0,
   party_name TEXT NOT NULL,
   TYPE TEXT NOT NULL REFERENCES party_types (name),
+  -- The order within the type.
+  position INTEGER NOT NULL DEFAULT 0,
   FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE,
   PRIMARY KEY (project_id, draft, party_name, TYPE)
 );
@@ -152,7 +246,13 @@ CREATE TABLE project_tms (
   PRIMARY KEY (project_id, draft, tm_name, TYPE)
 );
 
-CREATE TABLE industries (name TEXT NOT NULL PRIMARY KEY);
+CREATE TABLE industries (
+  name TEXT NOT NULL PRIMARY KEY,
+  label TEXT,
+  description TEXT,
+  icon TEXT,
+  position INTEGER NOT NULL DEFAULT 0
+);
 
 CREATE TABLE project_industries (
   project_id TEXT NOT NULL,
@@ -162,6 +262,15 @@ CREATE TABLE project_industries (
   PRIMARY KEY (project_id, draft, industry)
 );
 
+-- The companies whose sites offer an industry, label or publiek/privaat type as a filter. An entry without rows is
+-- offered on every site. TMs have their own companies in TM_COMPANIES.
+CREATE TABLE taxonomy_companies (
+  taxonomy TEXT NOT NULL,
+  name TEXT NOT NULL,
+  company TEXT NOT NULL REFERENCES companies (name) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (taxonomy, name, company)
+);
+
 CREATE TABLE project_content (
   project_id TEXT NOT NULL,
   company_name TEXT,
@@ -186,11 +295,86 @@ CREATE TABLE images (
   image TEXT,
   image_copyright TEXT,
   alt TEXT,
+  -- The order within the lightbox of the company.
+  position INTEGER NOT NULL DEFAULT 0,
   PRIMARY KEY (id, draft),
   FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE,
   FOREIGN key (project_id, company_name, draft) REFERENCES project_companies (project_id, company_name, draft)
 );
 
+-- The related projects editors picked for a project, in the order the sites show them. Without any, the sites pick the
+-- projects that have the most in common with it.
+CREATE TABLE related_projects (
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  related_project_id TEXT NOT NULL,
+  position INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (project_id, draft, related_project_id),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
+
+-- Translations of the texts of a project, the Dutch texts stay in the columns of the project itself. The item is the
+-- row the text belongs to: the id of a content entry, core number, image or phase, the company of an introduction, or
+-- '' for the name and location of the project.
+CREATE TABLE project_translations (
+  project_id TEXT NOT NULL,
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  language TEXT NOT NULL CHECK (language IN ('fr', 'en')),
+  field TEXT NOT NULL CHECK (
+    field IN (
+      'name',
+      'location',
+      'introduction',
+      'content_text',
+      'content_quote',
+      'content_quote_small',
+      'core_number_title',
+      'image_alt',
+      'phase_name'
+    )
+  ),
+  item TEXT NOT NULL DEFAULT '',
+  value TEXT NOT NULL,
+  -- The slug of the project in the language, only for the name.
+  slug TEXT,
+  PRIMARY KEY (project_id, draft, language, field, item),
+  FOREIGN key (project_id, draft) REFERENCES projects (id, draft) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX project_translations_slug ON project_translations (language, slug, draft)
+WHERE
+  slug IS NOT NULL;
+
+-- The slugs published projects had before, the sites redirect them to the current page. The language is the one of
+-- the slug, 'nl' for the slug of the project itself.
+CREATE TABLE slug_history (
+  slug TEXT NOT NULL,
+  language TEXT NOT NULL DEFAULT 'nl',
+  project_id TEXT NOT NULL,
+  replaced_at INTEGER NOT NULL DEFAULT (unixepoch()),
+  PRIMARY KEY (slug, language)
+);
+
+-- A file that is being imported, kept between the upload and the import so the columns can be mapped first.
+CREATE TABLE imports (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  file_name TEXT NOT NULL,
+  -- JSON arrays of the column names and of the rows, every row an array of strings.
+  columns TEXT NOT NULL,
+  rows TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
+-- The structure new projects can start from: parties, TMs, industries, labels, the titles of the core numbers and the
+-- outline of the content, saved from a project as JSON.
+CREATE TABLE project_templates (
+  id TEXT NOT NULL PRIMARY KEY,
+  name TEXT NOT NULL,
+  data TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+
 CREATE TABLE cookie_consent (
   ip TEXT NOT NULL PRIMARY KEY,
   date_modified INTEGER NOT NULL,
@@ -209,3 +393,113 @@ CREATE TABLE form_submissions (
   sent_to TEXT,
   recaptcha_score INTEGER
 );
+
+CREATE TABLE block_clipboard (
+  user_id TEXT NOT NULL PRIMARY KEY REFERENCES USERS(id) ON DELETE CASCADE,
+  block_id TEXT NOT NULL,
+  html TEXT NOT NULL
+);
+
+CREATE TABLE block_snippets (
+  id TEXT NOT NULL PRIMARY KEY,
+  name TEXT NOT NULL,
+  company TEXT REFERENCES companies (name) ON DELETE CASCADE,
+  block_id TEXT NOT NULL,
+  html TEXT NOT NULL
+);
+
+CREATE TABLE company_themes (
+  company TEXT NOT NULL PRIMARY KEY REFERENCES companies (name) ON DELETE CASCADE ON UPDATE CASCADE,
+  primary_colour TEXT NOT NULL DEFAULT '#1d1d1b',
+  secondary_colour TEXT NOT NULL DEFAULT '#ffffff',
+  accent_colour TEXT,
+  -- The names of fonts in `themes::template::FONTS`.
+  heading_font TEXT,
+  body_font TEXT,
+  logo TEXT,
+  emblem TEXT,
+  map_pb TEXT,
+  button_radius REAL,
+  button_style TEXT NOT NULL DEFAULT 'animated' CHECK (button_style IN ('animated', 'filled', 'outline'))
+);
+
+CREATE TABLE sessions (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  user_agent TEXT,
+  ip TEXT,
+  created_at INTEGER NOT NULL,
+  last_activity INTEGER NOT NULL
+);
+
+CREATE INDEX sessions_user_id ON sessions (user_id);
+
+CREATE TABLE known_devices (
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  device_id TEXT NOT NULL,
+  first_seen INTEGER NOT NULL,
+  PRIMARY KEY(user_id, device_id)
+);
+
+CREATE TABLE password_tokens (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  purpose TEXT NOT NULL CHECK (purpose IN ('invitation', 'reset')),
+  expires_at INTEGER NOT NULL,
+  used_at INTEGER
+);
+
+CREATE TABLE recovery_codes (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  hash TEXT NOT NULL,
+  used_at INTEGER
+);
+
+CREATE TABLE sso_domains (domain TEXT NOT NULL PRIMARY KEY);
+
+CREATE TABLE failed_logins (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id TEXT REFERENCES USERS(id) ON DELETE CASCADE,
+  email TEXT,
+  ip TEXT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('password', 'totp')),
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX failed_logins_user_id ON failed_logins (user_id, created_at);
+
+CREATE INDEX failed_logins_ip ON failed_logins (ip, created_at);
+
+-- Personal access tokens for scripted access. Only a SHA-256 hash of the token is stored.
+CREATE TABLE api_tokens (
+  id TEXT NOT NULL PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES USERS(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  all_companies BOOLEAN NOT NULL DEFAULT 0,
+  created_at INTEGER NOT NULL,
+  expires_at INTEGER NOT NULL,
+  last_used_at INTEGER
+);
+
+CREATE INDEX api_tokens_user ON api_tokens (user_id);
+
+-- The capabilities a token may use, on top of what its user may do.
+CREATE TABLE api_token_capabilities (
+  token_id TEXT NOT NULL REFERENCES API_TOKENS(id) ON DELETE CASCADE,
+  capability TEXT NOT NULL REFERENCES CAPABILITIES(name) ON DELETE CASCADE,
+  PRIMARY KEY(token_id, capability)
+);
+
+-- The companies a token is limited to when it isn't valid for all companies, a NULL company stands for the projects
+-- without a company.
+CREATE TABLE api_token_companies (
+  token_id TEXT NOT NULL REFERENCES API_TOKENS(id) ON DELETE CASCADE,
+  company TEXT REFERENCES COMPANIES(name) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX api_token_companies_unique ON api_token_companies (
+  token_id,
+  IFNULL(company, '')
+);
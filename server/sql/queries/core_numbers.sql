@@ -0,0 +1,10 @@
+-- The published core numbers of a project, used by the public sites and by the previews of the site editor.
+SELECT
+    number,
+    COALESCE(
+        (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=cn.project_id AND draft=0 AND language=$2 AND field='core_number_title' AND item=cn.id),
+        title
+    ) as "title!: String"
+FROM CORE_NUMBERS cn
+WHERE cn.project_id=$1 AND cn.draft=0
+ORDER BY cn.position
@@ -0,0 +1,14 @@
+-- The published projects a company shows in its carousel, used by the public sites and by the previews of the site
+-- editor.
+SELECT
+    COALESCE(tn.value, p.name) as "name!: String",
+    COALESCE(tn.slug, p.slug) as "slug: String",
+    COALESCE(tl.value, p.location) as "location: String",
+    COALESCE(pc.header_photo, pcb.header_photo) as "header_photo: String"
+FROM PROJECTS p
+JOIN PROJECT_COMPANIES pc ON pc.project_id=p.id AND pc.draft=p.draft AND pc.company_name=$1
+LEFT JOIN PROJECT_COMPANIES pcb ON pcb.project_id=p.id AND pcb.draft=p.draft AND pcb.company_name IS NULL
+LEFT JOIN PROJECT_TRANSLATIONS tn ON tn.project_id=p.id AND tn.draft=p.draft AND tn.language=$2 AND tn.field='name' AND tn.item=''
+LEFT JOIN PROJECT_TRANSLATIONS tl ON tl.project_id=p.id AND tl.draft=p.draft AND tl.language=$2 AND tl.field='location' AND tl.item=''
+WHERE p.draft=0 AND pc.visible=1 AND pc.show_in_carousel=1
+ORDER BY p.name
@@ -0,0 +1,42 @@
+-- The projects of a company's project grid, used by the public sites and by the previews of the site editor. $6 is a
+-- JSON array of tags that all have to be on the project.
+SELECT
+    COALESCE(tn.value, p.name) as "name!: String",
+    COALESCE(tn.slug, p.slug) as "slug!: String",
+    COALESCE(tl.value, p.location) as "location: String",
+    CASE
+        WHEN pc.thumbnail = 'base' THEN COALESCE(pcb.thumbnail, pcb.header_photo)
+        WHEN pc.thumbnail IS NULL THEN COALESCE(pc.header_photo, pcb.header_photo)
+        ELSE pc.thumbnail
+    END as "thumbnail: String"
+    FROM PROJECTS p
+    LEFT JOIN PROJECT_COMPANIES pc ON p.id = pc.project_id AND p.draft = pc.draft
+    LEFT JOIN PROJECT_INDUSTRIES pi ON p.id = pi.project_id AND p.draft = pi.draft
+    LEFT JOIN PROJECT_COMPANIES pcb ON pcb.project_id=p.id AND pcb.company_name IS NULL AND p.draft = pcb.draft
+    LEFT JOIN PROJECT_TAGS pt ON pt.project_id=p.id AND p.draft = pt.draft
+    LEFT JOIN PROJECT_TRANSLATIONS tn ON tn.project_id=p.id AND tn.draft=p.draft AND tn.language=$10 AND tn.field='name' AND tn.item=''
+    LEFT JOIN PROJECT_TRANSLATIONS tl ON tl.project_id=p.id AND tl.draft=p.draft AND tl.language=$10 AND tl.field='location' AND tl.item=''
+    WHERE
+        pc.company_name = $1
+        AND
+        ($2 IS NULL OR p.lifecycle = $2)
+        AND
+        ($11 IS NULL OR (p.lifecycle = 'delivered' AND COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) >= $11))
+        AND
+        ($12 IS NULL OR (p.lifecycle = 'delivered' AND COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) <= $12))
+        AND
+        ($3 IS NULL OR pi.industry = $3)
+        AND
+        ($4 IS NULL OR p.publiek_privaat = $4)
+        AND
+        ($5 IS NULL OR EXISTS(SELECT 1 FROM PROJECT_COMPANIES WHERE project_id=p.id AND company_name=$5))
+        AND
+        ($6 IS NULL OR pt.tag IN (SELECT value FROM json_each($6)))
+        AND
+        (p.name LIKE $7 OR tn.value LIKE $7)
+        AND p.draft=0
+        AND pc.visible=1
+    GROUP BY p.id
+    HAVING $6 IS NULL OR COUNT(DISTINCT pt.tag) = json_array_length($6)
+    ORDER BY pc.weight DESC, COALESCE(p.delivery_date, p.start_date, CAST(p.year AS TEXT)) DESC
+    LIMIT $8 OFFSET $9
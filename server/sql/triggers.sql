@@ -56,32 +56,60 @@ INSERT INTO
         project_id,
         title,
         number,
-        draft
+        draft,
+        position
     )
 SELECT
     id,
     project_id,
     title,
     number,
-    1
+    1,
+    position
 FROM
     CORE_NUMBERS
 WHERE
     project_id = NEW.id
     AND draft = 0;
 
+INSERT INTO
+    PROJECT_PHASES(
+        id,
+        project_id,
+        draft,
+        name,
+        start_date,
+        end_date,
+        position
+    )
+SELECT
+    id,
+    project_id,
+    1,
+    name,
+    start_date,
+    end_date,
+    position
+FROM
+    PROJECT_PHASES
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
 INSERT INTO
     PROJECT_PARTIES(
         project_id,
         draft,
         party_name,
-        type
+        type,
+        position
     )
 SELECT
     project_id,
     1,
     party_name,
-    type
+    type,
+    position
 FROM
     PROJECT_PARTIES
 WHERE
@@ -176,7 +204,8 @@ INSERT INTO
         draft,
         image,
         image_copyright,
-        alt
+        alt,
+        position
     )
 SELECT
     project_id,
@@ -185,13 +214,56 @@ SELECT
     1,
     image,
     image_copyright,
-    alt
+    alt,
+    position
 FROM
     IMAGES
 WHERE
     project_id = NEW.id
     AND draft = 0;
 
+INSERT INTO
+    RELATED_PROJECTS(
+        project_id,
+        draft,
+        related_project_id,
+        position
+    )
+SELECT
+    project_id,
+    1,
+    related_project_id,
+    position
+FROM
+    RELATED_PROJECTS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
+INSERT INTO
+    PROJECT_TRANSLATIONS(
+        project_id,
+        draft,
+        language,
+        field,
+        item,
+        value,
+        slug
+    )
+SELECT
+    project_id,
+    1,
+    language,
+    field,
+    item,
+    value,
+    slug
+FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = NEW.id
+    AND draft = 0;
+
 END;
 
 CREATE TRIGGER AFTER_UPDATE_PROJECTS
@@ -214,6 +286,13 @@ SET
 WHERE
     project_id = NEW.id;
 
+UPDATE
+    PROJECT_PHASES
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
 UPDATE
     PROJECT_PARTIES
 SET
@@ -256,4 +335,101 @@ SET
 WHERE
     project_id = NEW.id;
 
+UPDATE
+    PROJECT_TRANSLATIONS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+UPDATE
+    RELATED_PROJECTS
+SET
+    draft = 0
+WHERE
+    project_id = NEW.id;
+
+END;
+
+-- The translations of a content entry, core number, image or phase go with it.
+CREATE TRIGGER AFTER_DELETE_PROJECT_CONTENT
+AFTER
+DELETE
+    ON PROJECT_CONTENT BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field IN ('content_text', 'content_quote', 'content_quote_small')
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_CORE_NUMBERS
+AFTER
+DELETE
+    ON CORE_NUMBERS BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'core_number_title'
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_IMAGES
+AFTER
+DELETE
+    ON IMAGES BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'image_alt'
+    AND item = OLD.id;
+
+END;
+
+CREATE TRIGGER AFTER_DELETE_PROJECT_PHASES
+AFTER
+DELETE
+    ON PROJECT_PHASES BEGIN
+DELETE FROM
+    PROJECT_TRANSLATIONS
+WHERE
+    project_id = OLD.project_id
+    AND draft = OLD.draft
+    AND field = 'phase_name'
+    AND item = OLD.id;
+
+END;
+
+-- The old slugs of a project go when the last version of the project does, and so does the project where it was
+-- picked as a related project.
+CREATE TRIGGER AFTER_DELETE_PROJECTS
+AFTER
+DELETE
+    ON PROJECTS
+    WHEN NOT EXISTS(
+        SELECT
+            1
+        FROM
+            PROJECTS
+        WHERE
+            id = OLD.id
+    ) BEGIN
+DELETE FROM
+    SLUG_HISTORY
+WHERE
+    project_id = OLD.id;
+
+DELETE FROM
+    RELATED_PROJECTS
+WHERE
+    related_project_id = OLD.id;
+
 END;
\ No newline at end of file
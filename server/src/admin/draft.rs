@@ -48,20 +48,32 @@ pub async fn create_project_draft_if_necessary(
         "INSERT INTO PROJECTS (
             id,
             name,
+            slug,
             location,
+            latitude,
+            longitude,
+            manual_coordinates,
             year,
             learn_more,
-            status,
+            lifecycle,
+            start_date,
+            delivery_date,
             publiek_privaat,
             draft
         )
         SELECT
             id,
             name,
+            slug,
             location,
+            latitude,
+            longitude,
+            manual_coordinates,
             year,
             learn_more,
-            status,
+            lifecycle,
+            start_date,
+            delivery_date,
             publiek_privaat,
             1
             FROM PROJECTS
@@ -12,21 +12,42 @@ use modules::{
         update_content_entry_image_endpoint, update_content_entry_quote_endpoint,
         update_content_entry_text_endpoint,
     },
-    core_numbers::endpoint::{create_core_number, delete_core_number, update_core_number},
+    core_numbers::endpoint::{
+        create_core_number, delete_core_number, move_core_number, update_core_number,
+    },
+    duplicate::endpoint::duplicate_project,
+    export::endpoint::export,
     header::endpoint::{customise_header_photo, update_header_photo},
+    import::endpoint::{check, get_import_page, import, upload},
     industry::endpoint::{add_industry_project, delete_industry_project, full_industry_list},
     introduction::endpoint::{customise_introduction, update_introduction},
-    location::endpoint::update_project_location,
+    lifecycle::endpoint::{
+        create_phase, delete_phase, get_phases, move_phase, set_dates, set_lifecycle, update_phase,
+    },
+    location::endpoint::{reset_coordinates, set_coordinates, update_project_location},
     name::endpoint::set_name,
-    party_list::endpoint::{add_party_to_project, delete_party_from_project, project_parties},
+    party_list::endpoint::{
+        add_party_to_project, create_party, delete_party_from_project, get_parties,
+        get_party_projects, merge_party, move_party_on_project, project_parties, set_party_logo,
+        update_party,
+    },
     project::endpoint::{get_project, revert_changes, save_changes},
+    project_template::endpoint::{create_template, delete_template, get_template_select, get_templates},
     publiek_privaat::endpoint::set_publiek_privaat,
+    related_projects::endpoint::{
+        add_related_project, delete_related_project, get_related_projects, move_related_project,
+        related_project_search_list,
+    },
     show_in_carousel::endpoint::set_show_in_carousel,
-    status::endpoint::set_state,
+    slug::endpoint::set_slug,
+    taxonomy::endpoint::{
+        create_entry, delete_entry, get_taxonomy, move_entry, set_companies, set_icon, update_entry,
+    },
     tms::endpoint::{
         add_company_to_tm, add_party_to_tm, add_tm_to_project, delete_company_from_tm,
         delete_party_from_tm, delete_tm_from_project, tm_input, tms,
     },
+    translation::endpoint::{get_language_select, get_translations, set_translation},
     weight::endpoint::update_project_weight,
     year::endpoint::update_project_year,
 };
@@ -36,13 +57,15 @@ use url::Url;
 
 use crate::{
     admin::modules::{
-        company_list::endpoint::company_search_list, image::endpoint::{add_images_to_collection, customise, delete_image_to_collection}, industry::endpoint::industry_search_list, labels::endpoint::update_labels, party_list::endpoint::party_search_list_id, project::endpoint::{create_new_project, delete_project, name_for_id, projects, unpublish}, thumbnail::endpoint::set_thumbnail, visible::endpoint::set_visible
+        company_list::endpoint::company_search_list, image::endpoint::{add_images_to_collection, customise, delete_image_to_collection, move_image_in_collection}, industry::endpoint::industry_search_list, labels::endpoint::update_labels, party_list::endpoint::party_search_list_id, project::endpoint::{create_new_project, delete_project, name_for_id, projects, unpublish}, thumbnail::endpoint::set_thumbnail, visible::endpoint::set_visible
     }, auth::service::{auth_middleware, create_draft_middleware}, AppState
 };
 
 pub mod draft;
 pub mod index;
 pub mod modules;
+pub mod position;
+pub mod slug;
 
 pub trait AllowedCompanyType {}
 
@@ -193,21 +216,140 @@ pub fn router(state: AppState) -> Router<AppState> {
         )
         .route("/industries", get(full_industry_list))
         .route("/industries/search", get(industry_search_list))
+        .route(
+            "/parties",
+            get(get_parties).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/parties",
+            post(create_party).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
         .route("/parties/search", get(party_search_list_id))
+        .route(
+            "/parties/{party}",
+            patch(update_party).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/parties/{party}/logo",
+            patch(set_party_logo).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/parties/{party}/merge",
+            post(merge_party).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/parties/{party}/projects",
+            get(get_party_projects).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
         .route("/parties/testtest", get(server_error))
-        /*         .route("/parties", get(get_all_parties))
-         */
+        .route(
+            "/taxonomies/{taxonomy}",
+            get(get_taxonomy).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}",
+            post(create_entry).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}/{entry}",
+            patch(update_entry).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}/{entry}",
+            delete(delete_entry).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}/{entry}/icon",
+            patch(set_icon).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}/{entry}/companies",
+            put(set_companies).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/taxonomies/{taxonomy}/{entry}/move",
+            put(move_entry).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/import",
+            get(get_import_page).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/import",
+            post(upload).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/import/{import_id}",
+            post(import).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/import/{import_id}/check",
+            post(check).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/export",
+            get(export).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
         .route("/tms", get(tms))
-        .route("/tms/{id}/companies", post(add_company_to_tm))
-        .route("/tms/{id}/companies", delete(delete_company_from_tm))
-        .route("/tms/{id}/parties", post(add_party_to_tm))
-        .route("/tms/{id}/parties", delete(delete_party_from_tm))
+        .route(
+            "/tms/{id}/companies",
+            post(add_company_to_tm).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/tms/{id}/companies",
+            delete(delete_company_from_tm).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/tms/{id}/parties",
+            post(add_party_to_tm).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/tms/{id}/parties",
+            delete(delete_party_from_tm).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
         .route("/tms/search", get(tm_input))
         .route(
             "/projects",
             get(projects).layer(from_fn_with_state(state.clone(), auth_middleware)),
         )
-        .route("/projects", post(create_new_project))
+        .route(
+            "/projects",
+            post(create_new_project).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/duplicate",
+            post(duplicate_project).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/template",
+            post(create_template).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route("/projects/{project_id}/related", get(get_related_projects))
+        .route(
+            "/projects/{project_id}/related",
+            post(add_related_project).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route("/projects/{project_id}/related/search", get(related_project_search_list))
+        .route(
+            "/projects/{project_id}/related/{related_project_id}",
+            delete(delete_related_project).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/related/{related_project_id}/move",
+            put(move_related_project).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/project-templates",
+            get(get_templates).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/project-templates/select",
+            get(get_template_select).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/project-templates/{template_id}",
+            delete(delete_template).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
         .route(
             "/projects/{project_id}/show-in-carousel",
             patch(set_show_in_carousel)
@@ -223,13 +365,50 @@ pub fn router(state: AppState) -> Router<AppState> {
             patch(set_name).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
         .route(
-            "/projects/{project_id}/status",
-            patch(set_state).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+            "/projects/{project_id}/slug",
+            patch(set_slug).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/lifecycle",
+            patch(set_lifecycle).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/dates",
+            patch(set_dates).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route("/projects/{project_id}/phases", get(get_phases))
+        .route(
+            "/projects/{project_id}/phases",
+            post(create_phase).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/phases/{phase_id}",
+            put(update_phase).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/phases/{phase_id}",
+            delete(delete_phase).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/phases/{phase_id}/move",
+            put(move_phase).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
         .route(
             "/projects/{project_id}",
             get(get_project).layer(from_fn_with_state(state.clone(), auth_middleware)),
         )
+        .route(
+            "/projects/{project_id}/translations",
+            get(get_language_select).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/translations/{language}",
+            get(get_translations).layer(from_fn_with_state(state.clone(), auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/translations/{language}",
+            put(set_translation).layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
         .route(
             "/projects/{project_id}/revert-changes",
             post(revert_changes).layer(from_fn_with_state(state.clone(), auth_middleware)),
@@ -284,6 +463,16 @@ pub fn router(state: AppState) -> Router<AppState> {
             patch(update_project_location)
                 .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
+        .route(
+            "/projects/{project_id}/coordinates",
+            patch(set_coordinates)
+                .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/coordinates",
+            delete(reset_coordinates)
+                .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
         .route(
             "/projects/{project_id}/industries",
             post(add_industry_project)
@@ -323,6 +512,11 @@ pub fn router(state: AppState) -> Router<AppState> {
             delete(delete_image_to_collection)
                 .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
+        .route(
+            "/projects/{project_id}/lightbox/{image_id}/move",
+            put(move_image_in_collection)
+                .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
         .route(
             "/projects/{project_id}/publiek-privaat",
             patch(set_publiek_privaat)
@@ -344,6 +538,11 @@ pub fn router(state: AppState) -> Router<AppState> {
             delete(delete_party_from_project)
                 .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
+        .route(
+            "/projects/{project_id}/parties/{type}/{party}/move",
+            put(move_party_on_project)
+                .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
         .route(
             "/projects/{project_id}/parties/search",
             get(party_search_list_id),
@@ -403,6 +602,11 @@ pub fn router(state: AppState) -> Router<AppState> {
             put(update_core_number)
                 .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
         )
+        .route(
+            "/projects/{project_id}/core_numbers/{id}/move",
+            put(move_core_number)
+                .layer(from_fn_with_state(state.clone(), create_draft_middleware)),
+        )
         .route(
             "/projects/{project_id}/labels",
             put(update_labels)
@@ -34,10 +34,10 @@ pub async fn get_company_lists(
         PROJECT_COMPANIES pc ON c.name = pc.company_name 
         AND pc.project_id = $1
         AND pc.draft=$2
-    LEFT JOIN 
-        PERMISSIONS pm ON c.name = pm.company 
-        AND pm.user=$3
-    WHERE (pm.edit IS NULL OR pm.edit=1)
+    WHERE EXISTS(
+        SELECT 1 FROM user_capabilities uc
+        WHERE uc.user=$3 AND uc.capability='edit_project' AND (uc.everywhere=1 OR uc.company=c.name)
+    )
     "#,
         project_id,
         draft,
@@ -69,6 +69,20 @@ pub async fn does_project_have_company(
     .await
 }
 
+pub async fn project_companies(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar!(
+        r#"SELECT company_name as "company_name!" FROM PROJECT_COMPANIES WHERE project_id=$1 AND company_name IS NOT NULL AND draft=$2 ORDER BY company_name"#,
+        project_id,
+        draft
+    )
+    .fetch_all(conn)
+    .await
+}
+
 pub async fn add_company_to_project(
     conn: &mut SqliteConnection,
     project_id: &str,
@@ -129,13 +143,15 @@ pub async fn company_search_list(
             SELECT
                 name
             FROM COMPANIES c
-            JOIN PERMISSIONS p ON p.user=
+            WHERE EXISTS(
+                SELECT 1 FROM user_capabilities uc
+                WHERE uc.capability='create_project' AND (uc.everywhere=1 OR uc.company=c.name) AND uc.user=
     "#,
     );
 
     query_builder.push_bind(user_id);
 
-    query_builder.push(" AND c.name=p.company WHERE p.`create`=1 AND name LIKE ");
+    query_builder.push(") AND name LIKE ");
 
     query_builder.push_bind(filter);
 
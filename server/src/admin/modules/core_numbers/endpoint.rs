@@ -47,5 +47,25 @@ pub async fn delete_core_number(
 
     transaction.commit().await.map_err(|_| TransactionError::Commit)?;
 
+    Ok(page_buttons_update_wrapper(&project_id, true, &PreEscaped::default()))
+}
+
+#[derive(Deserialize)]
+pub struct MoveCoreNumberBody {
+    next: Option<String>
+}
+
+pub async fn move_core_number(
+    Path((project_id, core_number_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<MoveCoreNumberBody>
+) -> Result<Markup> {
+    service::move_core_number(&mut *transaction, &project_id, &core_number_id, &body.next).await.map_err(|err| match err {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
     Ok(page_buttons_update_wrapper(&project_id, true, &PreEscaped::default()))
 }
\ No newline at end of file
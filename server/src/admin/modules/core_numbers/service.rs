@@ -1,6 +1,8 @@
 use sqlx::{query, query_as, query_scalar, SqliteConnection};
 use uuid::Uuid;
 
+use crate::admin::position::{move_before, renumber};
+
 pub struct CoreNumber {
     pub id: String,
     pub title: String,
@@ -12,7 +14,7 @@ pub async fn get_project_core_numbers(
     project_id: &str,
     draft: bool
 ) -> Result<Vec<CoreNumber>, sqlx::Error> {
-    query_as!(CoreNumber, "SELECT id, title, number FROM CORE_NUMBERS WHERE project_id=$1 AND draft=$2", project_id, draft)
+    query_as!(CoreNumber, "SELECT id, title, number FROM CORE_NUMBERS WHERE project_id=$1 AND draft=$2 ORDER BY position", project_id, draft)
     .fetch_all(conn)
     .await
 }
@@ -58,7 +60,11 @@ pub async fn create_core_number(
 ) -> Result<CoreNumber, sqlx::Error> {
     let id = Uuid::new_v4().to_string();
 
-    query_as!(CoreNumber, "INSERT INTO CORE_NUMBERS(id, project_id, title, number, draft) VALUES($1, $2, $3, $4, $5) RETURNING id, title, number", id, project_id, "", "", 1)
+    query_as!(CoreNumber, "
+        INSERT INTO CORE_NUMBERS(id, project_id, title, number, draft, position)
+        VALUES($1, $2, $3, $4, $5, (SELECT COALESCE(MAX(position) + 1, 0) FROM CORE_NUMBERS WHERE project_id=$2 AND draft=1))
+        RETURNING id, title, number
+    ", id, project_id, "", "", 1)
     .fetch_one(conn)
     .await
 }
@@ -78,4 +84,28 @@ pub async fn get_project_id_for_core_number_id(conn: &mut SqliteConnection, core
     query_scalar!("SELECT project_id FROM CORE_NUMBERS WHERE id=$1", core_number_id)
     .fetch_one(&mut *conn)
     .await
+}
+
+/// Moves the core number of the draft in front of `next`, or to the end.
+pub async fn move_core_number(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    core_number_id: &str,
+    next: &Option<String>
+) -> Result<(), sqlx::Error> {
+    let mut ids = query_scalar!("SELECT id FROM CORE_NUMBERS WHERE project_id=$1 AND draft=1 ORDER BY position", project_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !move_before(&mut ids, &core_number_id.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &ids, async |conn, position, id| {
+        query!("UPDATE CORE_NUMBERS SET position=$1 WHERE id=$2 AND draft=1", position, id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+    })
+    .await
 }
\ No newline at end of file
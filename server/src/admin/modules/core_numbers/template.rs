@@ -6,7 +6,10 @@ use super::service::CoreNumber;
 
 pub fn core_number_template(project_id: &str, core_number: &CoreNumber) -> Markup {
     html!(
-        li.core-number {
+        li.core-number data-name=(core_number.id) data-move={"/api/projects/"(project_id)"/core_numbers/"(core_number.id)"/move"} {
+            div.move title="Drag to reorder" {
+                (self_replacing_svg("/move.svg"))
+            }
             form hx-put={"/api/projects/"(project_id)"/core_numbers/"(core_number.id)} hx-trigger="change" hx-swap="outerHTML" {
                 input.core-number-number name="number" value=(core_number.number) placeholder="Number" autocomplete="off";
                 textarea.core-number-title is="auto-size" name="title" autocomplete="off" placeholder="Title" { (core_number.title) }
@@ -18,7 +21,7 @@ pub fn core_number_template(project_id: &str, core_number: &CoreNumber) -> Marku
 
 pub fn core_numbers(project_id: &str, core_numbers: &Vec<CoreNumber>) -> Markup {
     html!(
-        ul #core-numbers {
+        ul #core-numbers data-reorder="horizontal" {
             @for core_number in core_numbers {
                 (core_number_template(project_id, core_number))
             }
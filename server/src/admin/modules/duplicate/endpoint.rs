@@ -0,0 +1,79 @@
+use axum::{
+    Extension,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::Result,
+};
+
+use crate::{
+    TransactionError,
+    admin::{DBTransaction, modules::project::service as project_service},
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+};
+
+use super::service;
+
+/// Copies the project into a new draft and opens it. The name of the copy is the answer to the prompt.
+pub async fn duplicate_project(
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Path(project_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<HeaderMap> {
+    capability::require_for_project(&mut transaction, &user, &project_id, Capability::EditProject).await?;
+
+    let draft = service::source_draft(&mut transaction, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // The copy ends up on the same sites as the original.
+    let companies = service::project_companies(&mut transaction, &project_id, draft)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    for company in &companies {
+        capability::require_for_company(&mut transaction, &user, company.as_deref(), Capability::CreateProject)
+            .await?;
+    }
+
+    let name = match headers
+        .get("HX-Prompt")
+        .and_then(|name| name.to_str().ok())
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) => name.to_string(),
+        None => {
+            let name = project_service::name_for_id(&mut transaction, &project_id)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            format!("{name} (copy)")
+        }
+    };
+
+    let new_id = service::duplicate_project(&mut transaction, &project_id, draft, &name)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to duplicate project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "HX-REDIRECT",
+        format!("/projects/{new_id}")
+            .parse()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    Ok(headers)
+}
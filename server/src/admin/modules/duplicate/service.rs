@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+use uuid::Uuid;
+
+use crate::admin::modules::{slug::service::unique_slug, translation::service::Language};
+
+/// The version of the project that is being worked on: the draft, or the published project when it has none.
+pub async fn source_draft(conn: &mut SqliteConnection, project_id: &str) -> Result<Option<bool>, sqlx::Error> {
+    query_scalar!(
+        r#"SELECT draft as "draft: bool" FROM PROJECTS WHERE id=$1 ORDER BY draft DESC LIMIT 1"#,
+        project_id
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// The companies of the copy, `None` for the base page.
+pub async fn project_companies(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+) -> Result<Vec<Option<String>>, sqlx::Error> {
+    query_scalar!(
+        "SELECT company_name FROM PROJECT_COMPANIES WHERE project_id=$1 AND draft=$2",
+        project_id,
+        draft
+    )
+    .fetch_all(conn)
+    .await
+}
+
+struct ContentLink {
+    id: String,
+    previous_entry: Option<String>,
+}
+
+struct Translation {
+    language: String,
+    field: String,
+    item: String,
+    value: String,
+    slug: Option<String>,
+}
+
+/// New ids for the rows, so the copy doesn't share any with the original.
+fn new_ids(ids: impl IntoIterator<Item = String>) -> HashMap<String, String> {
+    ids.into_iter()
+        .map(|id| (id, Uuid::new_v4().to_string()))
+        .collect()
+}
+
+/// Copies the project into a new draft under another name: the overrides of every company, the content with new ids
+/// in the same order, the lightboxes, core numbers, phases, parties, TMs, industries, labels, related projects and
+/// translations.
+pub async fn duplicate_project(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+    name: &str,
+) -> Result<String, sqlx::Error> {
+    let new_id = Uuid::new_v4().to_string();
+    let slug = unique_slug(&mut *conn, name, &new_id, Language::Nl).await?;
+
+    query!(
+        "INSERT INTO PROJECTS(
+            id,
+            name,
+            slug,
+            location,
+            latitude,
+            longitude,
+            manual_coordinates,
+            year,
+            learn_more,
+            lifecycle,
+            start_date,
+            delivery_date,
+            publiek_privaat,
+            draft
+        )
+        SELECT
+            $1,
+            $2,
+            $3,
+            location,
+            latitude,
+            longitude,
+            manual_coordinates,
+            year,
+            learn_more,
+            lifecycle,
+            start_date,
+            delivery_date,
+            publiek_privaat,
+            1
+        FROM PROJECTS
+        WHERE id=$4 AND draft=$5",
+        new_id,
+        name,
+        slug,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO PROJECT_COMPANIES(
+            project_id,
+            draft,
+            company_name,
+            show_in_carousel,
+            introduction,
+            thumbnail,
+            header_photo,
+            header_photo_copyright,
+            banner_photo,
+            banner_photo_copyright,
+            visible,
+            weight,
+            custom_lightbox,
+            custom_content
+        )
+        SELECT
+            $1,
+            1,
+            company_name,
+            show_in_carousel,
+            introduction,
+            thumbnail,
+            header_photo,
+            header_photo_copyright,
+            banner_photo,
+            banner_photo_copyright,
+            visible,
+            weight,
+            custom_lightbox,
+            custom_content
+        FROM PROJECT_COMPANIES
+        WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO PROJECT_PARTIES(project_id, draft, party_name, type, position)
+        SELECT $1, 1, party_name, type, position FROM PROJECT_PARTIES WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO PROJECT_TMS(project_id, draft, tm_name, type)
+        SELECT $1, 1, tm_name, type FROM PROJECT_TMS WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO PROJECT_INDUSTRIES(project_id, draft, industry)
+        SELECT $1, 1, industry FROM PROJECT_INDUSTRIES WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO PROJECT_TAGS(project_id, tag, draft)
+        SELECT $1, tag, 1 FROM PROJECT_TAGS WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "INSERT INTO RELATED_PROJECTS(project_id, draft, related_project_id, position)
+        SELECT $1, 1, related_project_id, position FROM RELATED_PROJECTS WHERE project_id=$2 AND draft=$3",
+        new_id,
+        project_id,
+        draft
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let core_numbers = new_ids(
+        query_scalar!("SELECT id FROM CORE_NUMBERS WHERE project_id=$1 AND draft=$2", project_id, draft)
+            .fetch_all(&mut *conn)
+            .await?,
+    );
+
+    for (id, copy_id) in &core_numbers {
+        query!(
+            "INSERT INTO CORE_NUMBERS(id, project_id, title, number, draft, position)
+            SELECT $1, $2, title, number, 1, position FROM CORE_NUMBERS WHERE id=$3 AND draft=$4",
+            copy_id,
+            new_id,
+            id,
+            draft
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let phases = new_ids(
+        query_scalar!("SELECT id FROM PROJECT_PHASES WHERE project_id=$1 AND draft=$2", project_id, draft)
+            .fetch_all(&mut *conn)
+            .await?,
+    );
+
+    for (id, copy_id) in &phases {
+        query!(
+            "INSERT INTO PROJECT_PHASES(id, project_id, draft, name, start_date, end_date, position)
+            SELECT $1, $2, 1, name, start_date, end_date, position FROM PROJECT_PHASES WHERE id=$3 AND draft=$4",
+            copy_id,
+            new_id,
+            id,
+            draft
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let images = new_ids(
+        query_scalar!("SELECT id FROM IMAGES WHERE project_id=$1 AND draft=$2", project_id, draft)
+            .fetch_all(&mut *conn)
+            .await?,
+    );
+
+    for (id, copy_id) in &images {
+        query!(
+            "INSERT INTO IMAGES(project_id, company_name, id, draft, image, image_copyright, alt, position)
+            SELECT $1, company_name, $2, 1, image, image_copyright, alt, position FROM IMAGES WHERE id=$3 AND draft=$4",
+            new_id,
+            copy_id,
+            id,
+            draft
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    // The entries point to the one before them, those links have to point to the copies.
+    let links = query_as!(
+        ContentLink,
+        "SELECT id, previous_entry FROM PROJECT_CONTENT WHERE project_id=$1 AND draft=$2",
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let content = new_ids(links.iter().map(|link| link.id.clone()));
+
+    for link in &links {
+        let copy_id = &content[&link.id];
+        let previous_entry = link
+            .previous_entry
+            .as_ref()
+            .and_then(|previous_entry| content.get(previous_entry));
+
+        query!(
+            "INSERT INTO PROJECT_CONTENT(
+                project_id,
+                company_name,
+                id,
+                previous_entry,
+                draft,
+                text,
+                image,
+                image_copyright,
+                quote,
+                quote_small
+            )
+            SELECT $1, company_name, $2, $3, 1, text, image, image_copyright, quote, quote_small
+            FROM PROJECT_CONTENT
+            WHERE id=$4 AND draft=$5",
+            new_id,
+            copy_id,
+            previous_entry,
+            link.id,
+            draft
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let translations = query_as!(
+        Translation,
+        "SELECT language, field, item, value, slug FROM PROJECT_TRANSLATIONS WHERE project_id=$1 AND draft=$2",
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for translation in translations {
+        // The item of an entry, core number, phase or image is its id, of an introduction the company.
+        let item = match translation.field.as_str() {
+            "content_text" | "content_quote" | "content_quote_small" => content.get(&translation.item),
+            "core_number_title" => core_numbers.get(&translation.item),
+            "phase_name" => phases.get(&translation.item),
+            "image_alt" => images.get(&translation.item),
+            _ => Some(&translation.item),
+        };
+
+        let Some(item) = item else {
+            continue;
+        };
+
+        let language = match translation.language.as_str() {
+            "fr" => Language::Fr,
+            _ => Language::En,
+        };
+
+        // The copy has its own name, so it gets a slug of its own in the language too.
+        let slug = match translation.slug {
+            Some(_) => Some(unique_slug(&mut *conn, &translation.value, &new_id, language).await?),
+            None => None,
+        };
+
+        query!(
+            "INSERT INTO PROJECT_TRANSLATIONS(project_id, draft, language, field, item, value, slug)
+            VALUES ($1, 1, $2, $3, $4, $5, $6)",
+            new_id,
+            translation.language,
+            translation.field,
+            item,
+            translation.value,
+            slug
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(new_id)
+}
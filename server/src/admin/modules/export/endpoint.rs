@@ -0,0 +1,81 @@
+use axum::{
+    Extension,
+    extract::Query,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response, Result},
+};
+use serde::Deserialize;
+
+use crate::{
+    admin::DBConnection,
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+};
+
+use super::service;
+
+#[derive(Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Xlsx,
+    Json,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+    #[serde(default)]
+    drafts: bool,
+}
+
+pub async fn export(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response> {
+    capability::require(&mut connection, &user, Capability::EditProject).await?;
+
+    let projects = service::get_projects(&mut connection, &user.id, query.drafts)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the projects to export: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let (body, content_type, extension) = match query.format {
+        ExportFormat::Json => (
+            service::to_json(&projects).map_err(|err| {
+                eprintln!("Failed to export the projects as JSON: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+            "application/json",
+            "json",
+        ),
+        ExportFormat::Xlsx => (
+            service::to_xlsx(&projects).map_err(|err| {
+                eprintln!("Failed to export the projects as XLSX: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        ),
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"projects-{}.{extension}\"",
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,611 @@
+use std::collections::HashMap;
+
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::Serialize;
+use sqlx::{query_as, SqliteConnection};
+
+/// A version of a project, the published one or the draft, with everything on it.
+#[derive(Serialize)]
+pub struct ExportProject {
+    pub id: String,
+    pub draft: bool,
+    pub name: String,
+    pub slug: String,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub year: Option<i64>,
+    pub lifecycle: String,
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
+    pub contract: String,
+    pub learn_more: Option<String>,
+    pub parties: Vec<ExportParty>,
+    pub industries: Vec<String>,
+    pub tags: Vec<String>,
+    /// The base page and the overrides of every company.
+    pub companies: Vec<ExportCompany>,
+    pub core_numbers: Vec<ExportCoreNumber>,
+    pub phases: Vec<ExportPhase>,
+    pub content: Vec<ExportContent>,
+    pub images: Vec<ExportImage>,
+    pub translations: Vec<ExportTranslation>,
+}
+
+struct ProjectRow {
+    id: String,
+    draft: bool,
+    name: String,
+    slug: String,
+    location: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    year: Option<i64>,
+    lifecycle: String,
+    start_date: Option<String>,
+    delivery_date: Option<String>,
+    contract: String,
+    learn_more: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportParty {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    #[serde(rename = "type")]
+    pub party_type: String,
+    pub name: String,
+}
+
+struct ExportName {
+    project_id: String,
+    draft: bool,
+    name: String,
+}
+
+#[derive(Serialize)]
+pub struct ExportCompany {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    /// Empty for the base page.
+    pub company: Option<String>,
+    pub visible: bool,
+    pub show_in_carousel: bool,
+    pub weight: i64,
+    pub introduction: Option<String>,
+    pub thumbnail: Option<String>,
+    pub header_photo: Option<String>,
+    pub header_photo_copyright: Option<String>,
+    pub banner_photo: Option<String>,
+    pub banner_photo_copyright: Option<String>,
+    pub custom_content: Option<bool>,
+    pub custom_lightbox: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct ExportCoreNumber {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    pub id: String,
+    pub title: String,
+    pub number: String,
+}
+
+#[derive(Serialize)]
+pub struct ExportPhase {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    pub id: String,
+    pub name: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportContent {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    pub id: String,
+    pub company: Option<String>,
+    pub previous_entry: Option<String>,
+    pub text: String,
+    pub image: Option<String>,
+    pub image_copyright: Option<String>,
+    pub quote: Option<String>,
+    pub quote_small: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportImage {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    pub id: String,
+    pub company: Option<String>,
+    pub image: Option<String>,
+    pub image_copyright: Option<String>,
+    pub alt: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportTranslation {
+    #[serde(skip)]
+    project_id: String,
+    #[serde(skip)]
+    draft: bool,
+    pub language: String,
+    pub field: String,
+    pub item: String,
+    pub value: String,
+}
+
+/// Splits the rows of a table over the project versions they belong to.
+fn group<T>(items: Vec<T>, key: impl Fn(&T) -> (String, bool)) -> HashMap<(String, bool), Vec<T>> {
+    let mut groups: HashMap<(String, bool), Vec<T>> = HashMap::new();
+
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+
+    groups
+}
+
+/// Every project the user can edit: the published version, and the draft when `drafts` is set or the project was never
+/// published.
+pub async fn get_projects(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    drafts: bool,
+) -> Result<Vec<ExportProject>, sqlx::Error> {
+    let projects = query_as!(ProjectRow, r#"
+        SELECT
+            p.id,
+            p.draft as "draft: bool",
+            p.name,
+            p.slug,
+            p.location,
+            p.latitude,
+            p.longitude,
+            p.year,
+            p.lifecycle,
+            p.start_date,
+            p.delivery_date,
+            p.publiek_privaat as contract,
+            p.learn_more
+        FROM PROJECTS p
+        WHERE
+            (p.draft=0 OR $2 OR NOT EXISTS(SELECT 1 FROM PROJECTS WHERE id=p.id AND draft=0))
+            AND EXISTS(
+                SELECT 1 FROM PROJECT_COMPANIES pc
+                JOIN user_capabilities uc ON uc.user=$1 AND uc.capability='edit_project'
+                WHERE pc.project_id=p.id AND pc.draft=p.draft
+                AND (uc.everywhere=1 OR uc.company IS pc.company_name)
+            )
+        ORDER BY p.name, p.draft
+    "#, user_id, drafts)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut parties = group(
+        query_as!(ExportParty, r#"
+            SELECT project_id, draft as "draft: bool", type as party_type, party_name as name
+            FROM PROJECT_PARTIES ORDER BY type, position
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |party| (party.project_id.clone(), party.draft),
+    );
+
+    let mut industries = group(
+        query_as!(ExportName, r#"SELECT project_id, draft as "draft: bool", industry as name FROM PROJECT_INDUSTRIES ORDER BY industry"#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |industry| (industry.project_id.clone(), industry.draft),
+    );
+
+    let mut tags = group(
+        query_as!(ExportName, r#"SELECT project_id, draft as "draft: bool", tag as name FROM PROJECT_TAGS ORDER BY tag"#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |tag| (tag.project_id.clone(), tag.draft),
+    );
+
+    let mut companies = group(
+        query_as!(ExportCompany, r#"
+            SELECT
+                project_id,
+                draft as "draft: bool",
+                company_name as company,
+                visible as "visible: bool",
+                show_in_carousel as "show_in_carousel: bool",
+                weight,
+                introduction,
+                thumbnail,
+                header_photo,
+                header_photo_copyright,
+                banner_photo,
+                banner_photo_copyright,
+                custom_content as "custom_content: bool",
+                custom_lightbox as "custom_lightbox: bool"
+            FROM PROJECT_COMPANIES
+            ORDER BY company_name IS NOT NULL, company_name
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |company| (company.project_id.clone(), company.draft),
+    );
+
+    let mut core_numbers = group(
+        query_as!(ExportCoreNumber, r#"
+            SELECT project_id, draft as "draft: bool", id, title, number FROM CORE_NUMBERS ORDER BY position
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |core_number| (core_number.project_id.clone(), core_number.draft),
+    );
+
+    let mut phases = group(
+        query_as!(ExportPhase, r#"
+            SELECT project_id, draft as "draft: bool", id, name, start_date, end_date FROM PROJECT_PHASES ORDER BY position
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |phase| (phase.project_id.clone(), phase.draft),
+    );
+
+    let mut content = group(
+        query_as!(ExportContent, r#"
+            SELECT
+                project_id,
+                draft as "draft: bool",
+                id,
+                company_name as company,
+                previous_entry,
+                text,
+                image,
+                image_copyright,
+                quote,
+                quote_small
+            FROM PROJECT_CONTENT
+            ORDER BY company_name IS NOT NULL, company_name, rowid
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |entry| (entry.project_id.clone(), entry.draft),
+    );
+
+    let mut images = group(
+        query_as!(ExportImage, r#"
+            SELECT project_id, draft as "draft: bool", id, company_name as company, image, image_copyright, alt
+            FROM IMAGES
+            ORDER BY company_name IS NOT NULL, company_name, position
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |image| (image.project_id.clone(), image.draft),
+    );
+
+    let mut translations = group(
+        query_as!(ExportTranslation, r#"
+            SELECT project_id, draft as "draft: bool", language, field, item, value
+            FROM PROJECT_TRANSLATIONS
+            ORDER BY language, field, item
+        "#)
+        .fetch_all(&mut *conn)
+        .await?,
+        |translation| (translation.project_id.clone(), translation.draft),
+    );
+
+    Ok(projects
+        .into_iter()
+        .map(|project| {
+            let key = (project.id.clone(), project.draft);
+            let names = |groups: &mut HashMap<(String, bool), Vec<ExportName>>| {
+                groups
+                    .remove(&key)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|entry| entry.name)
+                    .collect()
+            };
+
+            ExportProject {
+                industries: names(&mut industries),
+                tags: names(&mut tags),
+                parties: parties.remove(&key).unwrap_or_default(),
+                companies: companies.remove(&key).unwrap_or_default(),
+                core_numbers: core_numbers.remove(&key).unwrap_or_default(),
+                phases: phases.remove(&key).unwrap_or_default(),
+                content: content.remove(&key).unwrap_or_default(),
+                images: images.remove(&key).unwrap_or_default(),
+                translations: translations.remove(&key).unwrap_or_default(),
+                id: project.id,
+                draft: project.draft,
+                name: project.name,
+                slug: project.slug,
+                location: project.location,
+                latitude: project.latitude,
+                longitude: project.longitude,
+                year: project.year,
+                lifecycle: project.lifecycle,
+                start_date: project.start_date,
+                delivery_date: project.delivery_date,
+                contract: project.contract,
+                learn_more: project.learn_more,
+            }
+        })
+        .collect())
+}
+
+pub fn to_json(projects: &[ExportProject]) -> Result<Vec<u8>, serde_json::Error> {
+    serde_json::to_vec_pretty(&serde_json::json!({ "projects": projects }))
+}
+
+enum Cell<'a> {
+    Text(Option<&'a str>),
+    Number(Option<f64>),
+    Bool(Option<bool>),
+}
+
+fn text(value: &str) -> Cell<'_> {
+    Cell::Text(Some(value))
+}
+
+fn optional_text(value: &Option<String>) -> Cell<'_> {
+    Cell::Text(value.as_deref())
+}
+
+/// The columns the sheets of the entries start with.
+fn project_cells(project: &ExportProject) -> [Cell<'_>; 3] {
+    [
+        text(&project.id),
+        text(if project.draft { "draft" } else { "published" }),
+        text(&project.name),
+    ]
+}
+
+/// Adds a sheet with the columns in bold and a row for every entry.
+fn add_sheet(workbook: &mut Workbook, name: &str, columns: &[&str], rows: Vec<Vec<Cell<'_>>>) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let worksheet = workbook.add_worksheet().set_name(name)?;
+
+    for (column, title) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, column as u16, *title, &bold)?;
+    }
+
+    for (row, cells) in rows.into_iter().enumerate() {
+        let row = row as u32 + 1;
+
+        for (column, cell) in cells.into_iter().enumerate() {
+            let column = column as u16;
+
+            match cell {
+                Cell::Text(Some(value)) => worksheet.write_string(row, column, value).map(|_| ())?,
+                Cell::Number(Some(value)) => worksheet.write_number(row, column, value).map(|_| ())?,
+                Cell::Bool(Some(value)) => worksheet.write_boolean(row, column, value).map(|_| ())?,
+                _ => {}
+            }
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+
+    Ok(())
+}
+
+/// A sheet for the projects and one for every kind of entry on them, which start with the project they belong to.
+pub fn to_xlsx(projects: &[ExportProject]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+
+    let parties_of = |project: &ExportProject, party_type: &str| {
+        project
+            .parties
+            .iter()
+            .filter(|party| party.party_type == party_type)
+            .map(|party| party.name.as_str())
+            .collect::<Vec<&str>>()
+            .join("; ")
+    };
+
+    let joined: Vec<[String; 6]> = projects
+        .iter()
+        .map(|project| {
+            [
+                parties_of(project, "client"),
+                parties_of(project, "architect"),
+                parties_of(project, "contractor"),
+                project.industries.join("; "),
+                project.tags.join("; "),
+                project
+                    .companies
+                    .iter()
+                    .filter_map(|company| company.company.as_deref())
+                    .collect::<Vec<&str>>()
+                    .join("; "),
+            ]
+        })
+        .collect();
+
+    add_sheet(
+        &mut workbook,
+        "Projects",
+        &[
+            "ID", "Version", "Name", "Slug", "Location", "Latitude", "Longitude", "Year", "Lifecycle", "Start date",
+            "Delivery date", "Publiek/privaat", "Learn more", "Clients", "Architects", "Contractors", "Industries",
+            "Labels", "Companies",
+        ],
+        projects
+            .iter()
+            .zip(&joined)
+            .map(|(project, joined)| {
+                vec![
+                    text(&project.id),
+                    text(if project.draft { "draft" } else { "published" }),
+                    text(&project.name),
+                    text(&project.slug),
+                    optional_text(&project.location),
+                    Cell::Number(project.latitude),
+                    Cell::Number(project.longitude),
+                    Cell::Number(project.year.map(|year| year as f64)),
+                    text(&project.lifecycle),
+                    optional_text(&project.start_date),
+                    optional_text(&project.delivery_date),
+                    text(&project.contract),
+                    optional_text(&project.learn_more),
+                    text(&joined[0]),
+                    text(&joined[1]),
+                    text(&joined[2]),
+                    text(&joined[3]),
+                    text(&joined[4]),
+                    text(&joined[5]),
+                ]
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Companies",
+        &[
+            "Project ID", "Version", "Project", "Company", "Visible", "In carousel", "Weight", "Introduction", "Thumbnail",
+            "Header photo", "Header photo copyright", "Banner photo", "Banner photo copyright", "Own content",
+            "Own lightbox",
+        ],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.companies.iter().map(move |company| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([
+                        Cell::Text(Some(company.company.as_deref().unwrap_or("Base"))),
+                        Cell::Bool(Some(company.visible)),
+                        Cell::Bool(Some(company.show_in_carousel)),
+                        Cell::Number(Some(company.weight as f64)),
+                        optional_text(&company.introduction),
+                        optional_text(&company.thumbnail),
+                        optional_text(&company.header_photo),
+                        optional_text(&company.header_photo_copyright),
+                        optional_text(&company.banner_photo),
+                        optional_text(&company.banner_photo_copyright),
+                        Cell::Bool(company.custom_content),
+                        Cell::Bool(company.custom_lightbox),
+                    ]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Core numbers",
+        &["Project ID", "Version", "Project", "Title", "Number"],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.core_numbers.iter().map(move |core_number| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([text(&core_number.title), text(&core_number.number)]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Phases",
+        &["Project ID", "Version", "Project", "Phase", "Start date", "End date"],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.phases.iter().map(move |phase| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([text(&phase.name), optional_text(&phase.start_date), optional_text(&phase.end_date)]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Content",
+        &[
+            "Project ID", "Version", "Project", "Company", "Text", "Image", "Image copyright", "Quote", "Quote subtitle",
+        ],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.content.iter().map(move |entry| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([
+                        Cell::Text(Some(entry.company.as_deref().unwrap_or("Base"))),
+                        text(&entry.text),
+                        optional_text(&entry.image),
+                        optional_text(&entry.image_copyright),
+                        optional_text(&entry.quote),
+                        optional_text(&entry.quote_small),
+                    ]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Images",
+        &["Project ID", "Version", "Project", "Company", "Image", "Copyright", "Description"],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.images.iter().map(move |image| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([
+                        Cell::Text(Some(image.company.as_deref().unwrap_or("Base"))),
+                        optional_text(&image.image),
+                        optional_text(&image.image_copyright),
+                        optional_text(&image.alt),
+                    ]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    add_sheet(
+        &mut workbook,
+        "Translations",
+        &["Project ID", "Version", "Project", "Language", "Field", "Item", "Translation"],
+        projects
+            .iter()
+            .flat_map(|project| {
+                project.translations.iter().map(move |translation| {
+                    let mut cells = Vec::from(project_cells(project));
+                    cells.extend([
+                        text(&translation.language),
+                        text(&translation.field),
+                        text(&translation.item),
+                        text(&translation.value),
+                    ]);
+                    cells
+                })
+            })
+            .collect(),
+    )?;
+
+    workbook.save_to_buffer()
+}
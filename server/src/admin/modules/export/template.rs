@@ -0,0 +1,25 @@
+use maud::{html, Markup};
+
+pub fn export_template() -> Markup {
+    html!(
+        section.export {
+            h2 { "Export projects" }
+            p { "Every project you can edit, with its content, images and translations." }
+            // A plain download, not an HTMX request.
+            form action="/api/export" method="get" hx-boost="false" {
+                label {
+                    span { "Format" }
+                    select name="format" {
+                        option value="xlsx" { "Excel (XLSX)" }
+                        option value="json" { "JSON" }
+                    }
+                }
+                label {
+                    input type="checkbox" name="drafts" value="true";
+                    span { "Include the unpublished changes of the drafts" }
+                }
+                input type="submit" value="Download";
+            }
+        }
+    )
+}
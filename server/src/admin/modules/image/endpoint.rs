@@ -102,3 +102,33 @@ pub async fn customise(
     let content = template::lightbox(&project_id, &images, false);
     Ok(page_buttons_update_wrapper(&project_id, true, &content))
 }
+
+
+#[derive(Deserialize)]
+pub struct MoveImageBody {
+    next: Option<String>,
+}
+
+pub async fn move_image_in_collection(
+    Path((project_id, image_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<MoveImageBody>,
+) -> Result<Markup> {
+    service::move_image(&mut *transaction, &project_id, &image_id, &body.next)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(
+        &project_id,
+        true,
+        &PreEscaped("".to_string()),
+    ))
+}
\ No newline at end of file
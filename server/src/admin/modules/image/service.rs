@@ -1,7 +1,9 @@
 use serde::Deserialize;
-use sqlx::{SqliteConnection, query, query_as};
+use sqlx::{SqliteConnection, query, query_as, query_scalar};
 use uuid::Uuid;
 
+use crate::admin::position::{move_before, renumber};
+
 #[derive(Deserialize)]
 pub struct ImageData {
     pub id: Option<String>,
@@ -25,7 +27,8 @@ pub async fn customise(
             draft,
             image,
             image_copyright,
-            alt
+            alt,
+            position
         ) SELECT
             project_id,
             $1,
@@ -33,7 +36,8 @@ pub async fn customise(
             draft,
             image,
             image_copyright,
-            alt
+            alt,
+            position
         FROM IMAGES
         WHERE project_id=$2 AND company_name IS NULL
         RETURNING id, image, image_copyright, alt",
@@ -58,7 +62,7 @@ pub async fn get_images(
 ) -> Result<Vec<ImageData>, sqlx::Error> {
     query_as!(
         ImageData,
-        "SELECT id, image, image_copyright, alt FROM IMAGES where project_id=$1 AND (($2 IS NULL AND company_name IS NULL) OR company_name=$2) AND draft=$3",
+        "SELECT id, image, image_copyright, alt FROM IMAGES where project_id=$1 AND (($2 IS NULL AND company_name IS NULL) OR company_name=$2) AND draft=$3 ORDER BY position",
         project_id,
         company_name,
         draft
@@ -90,9 +94,11 @@ pub async fn add_image(
         draft,
         image,
         image_copyright,
-        alt
+        alt,
+        position
     ) VALUES (
-        $1, $2, $3, 1, $4, $5, $6
+        $1, $2, $3, 1, $4, $5, $6,
+        (SELECT COALESCE(MAX(position) + 1, 0) FROM IMAGES WHERE project_id=$2 AND (($3 IS NULL AND company_name IS NULL) OR company_name=$3) AND draft=1)
     ) RETURNING id, image, image_copyright, alt",
         id,
         project_id,
@@ -104,3 +110,36 @@ pub async fn add_image(
     .fetch_one(&mut *conn)
     .await
 }
+
+
+/// Moves the image in front of `next` in the lightbox it is in, the one of the base project or of a company, or to the
+/// end.
+pub async fn move_image(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    image_id: &str,
+    next: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    let mut ids = query_scalar!(
+        r#"SELECT i.id as "id!" FROM IMAGES i
+        JOIN IMAGES moved ON moved.id=$2 AND moved.draft=1
+        WHERE i.project_id=$1 AND i.draft=1 AND i.company_name IS moved.company_name
+        ORDER BY i.position"#,
+        project_id,
+        image_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !move_before(&mut ids, &image_id.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &ids, async |conn, position, id| {
+        query!("UPDATE IMAGES SET position=$1 WHERE id=$2 AND draft=1", position, id)
+            .execute(conn)
+            .await
+            .map(|_| ())
+    })
+    .await
+}
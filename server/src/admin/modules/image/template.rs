@@ -1,10 +1,13 @@
 use maud::{Markup, html};
 
-use crate::admin::modules::image::service::ImageData;
+use crate::{admin::modules::image::service::ImageData, self_replacing_svg};
 
 pub fn lightbox_image(project_id: &str, image: &ImageData) -> Markup {
     html!(
-        div.lightbox-image-wrapper {
+        div.lightbox-image-wrapper data-name=[&image.id] data-move={"/api/projects/"(project_id)"/lightbox/"(image.id.as_deref().unwrap_or_default())"/move"} {
+            div.move title="Drag to reorder" {
+                (self_replacing_svg("/move.svg"))
+            }
             img src=[&image.image] copyright=[&image.image_copyright];
             button.delete hx-delete={"/api/projects/"(project_id)"/lightbox/"(image.id.as_deref().unwrap_or_default())} hx-swap="delete" hx-target="closest .lightbox-image-wrapper" {}
         }
@@ -13,7 +16,7 @@ pub fn lightbox_image(project_id: &str, image: &ImageData) -> Markup {
 
 pub fn lightbox(project_id: &str, images: &Vec<ImageData>, disabled: bool) -> Markup {
     html!(
-        section #lightbox is="light-box" disabled[disabled] {
+        section #lightbox is="light-box" disabled[disabled] data-reorder="horizontal" {
             @if disabled {
                 div class="overlay" {
                     button hx-post={"/api/projects/"(project_id)"/lightbox/customise"} hx-target="#lightbox" hx-swap="outerHTML" { "Customise for this company" }
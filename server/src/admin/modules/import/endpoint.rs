@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use axum::{
+    Extension, Form,
+    extract::{Multipart, Path},
+    http::StatusCode,
+    response::Result,
+};
+use maud::{Markup, html};
+
+use crate::{
+    TransactionError,
+    admin::{DBConnection, DBTransaction, modules::export::template::export_template},
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+};
+
+use super::{
+    service::{self, ImportTable, Mapping},
+    template,
+};
+
+pub async fn get_import_page(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::CreateProject).await?;
+
+    Ok(html!(
+        (template::upload_template())
+        (export_template())
+    ))
+}
+
+/// Reads the file and asks how its columns map to the fields of a project.
+pub async fn upload(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    mut multipart: Multipart,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::CreateProject).await?;
+
+    let mut file = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| (StatusCode::BAD_REQUEST, "The upload failed."))?
+    {
+        if field.name() == Some("file") {
+            let file_name = field.file_name().unwrap_or_default().to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|_| (StatusCode::BAD_REQUEST, "The upload failed."))?;
+
+            file = Some((file_name, bytes));
+        }
+    }
+
+    let (file_name, bytes) = file.ok_or((StatusCode::BAD_REQUEST, "There is no file."))?;
+
+    let table = service::parse_file(&file_name, &bytes)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+
+    let import_id = service::save_import(&mut connection, &user.id, &file_name, &table)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to save the import of {file_name}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let mapping = Mapping::guess(&table.columns);
+
+    Ok(template::mapping_template(&import_id, &file_name, &table.columns, table.rows.len(), &mapping))
+}
+
+async fn get_import(connection: &mut sqlx::SqliteConnection, import_id: &str, user: &UserData) -> Result<ImportTable> {
+    let (_, table) = service::get_import(connection, import_id, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get import {import_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "The upload expired, upload the file again."))?;
+
+    Ok(table)
+}
+
+/// The dry run: what every row would become with the mapping, nothing is created.
+pub async fn check(
+    Path(import_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Markup> {
+    let table = get_import(&mut connection, &import_id, &user).await?;
+    let mapping = Mapping::from_form(&form, &table.columns);
+
+    let reports = service::check(&mut connection, &user, &table, &mapping)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to check import {import_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::report_template(&import_id, &reports))
+}
+
+/// Creates the projects, all of them or none when a row has an error.
+pub async fn import(
+    Path(import_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Markup> {
+    let table = get_import(&mut transaction, &import_id, &user).await?;
+    let mapping = Mapping::from_form(&form, &table.columns);
+
+    let reports = service::check(&mut transaction, &user, &table, &mapping)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to check import {import_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if reports.iter().any(|report| !report.errors.is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, "Some rows have errors, nothing was imported.").into());
+    }
+
+    let count = service::import_projects(&mut transaction, &reports)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to import {import_id}: {err}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "The import failed, nothing was imported.")
+        })?;
+
+    service::delete_import(&mut transaction, &import_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::imported_template(count))
+}
@@ -0,0 +1,716 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+};
+
+use calamine::{Reader, Xlsx};
+use chrono::{Datelike, Days, NaiveDate};
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+use uuid::Uuid;
+
+use crate::{
+    admin::modules::{
+        company_list::service as company_list_service, industry::service as industry_service,
+        introduction::service as introduction_service,
+        lifecycle::service::{self as lifecycle_service, Lifecycle},
+        location::service as location_service, party_list::service as party_service,
+        project::service as project_service,
+    },
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+    geocoding::Coordinates,
+};
+
+/// The rows of an uploaded file, every row has a value for every column.
+pub struct ImportTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    UnknownFormat,
+    Empty,
+    Csv(csv::Error),
+    Xlsx(calamine::XlsxError),
+    Json(serde_json::Error),
+    /// The JSON isn't a list of projects.
+    JsonShape,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnknownFormat => write!(f, "Only CSV, XLSX and JSON files can be imported."),
+            ParseError::Empty => write!(f, "The file has no rows."),
+            ParseError::Csv(err) => write!(f, "The CSV file can't be read: {err}"),
+            ParseError::Xlsx(err) => write!(f, "The XLSX file can't be read: {err}"),
+            ParseError::Json(err) => write!(f, "The JSON file can't be read: {err}"),
+            ParseError::JsonShape => write!(f, "The JSON file has to be a list of projects."),
+        }
+    }
+}
+
+/// The values of a cell with several, like the parties of a project. A value that is in it twice is only kept once.
+fn split_values(value: &str) -> Vec<String> {
+    let mut values: Vec<String> = vec![];
+
+    for value in value.split(['\n', ';', '|']).map(str::trim).filter(|value| !value.is_empty()) {
+        if !values.iter().any(|other| other.eq_ignore_ascii_case(value)) {
+            values.push(value.to_string());
+        }
+    }
+
+    values
+}
+
+fn parse_csv(bytes: &[u8]) -> Result<ImportTable, ParseError> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+
+    // Excel uses a semicolon in Belgium and a comma elsewhere.
+    let first_line = bytes.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    let semicolons = first_line.iter().filter(|byte| **byte == b';').count();
+    let commas = first_line.iter().filter(|byte| **byte == b',').count();
+    let delimiter = if semicolons > commas { b';' } else { b',' };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(bytes);
+
+    let columns = reader
+        .headers()
+        .map_err(ParseError::Csv)?
+        .iter()
+        .map(|column| column.trim().to_string())
+        .collect();
+
+    let rows = reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(str::to_string).collect()))
+        .collect::<Result<Vec<Vec<String>>, csv::Error>>()
+        .map_err(ParseError::Csv)?;
+
+    Ok(ImportTable { columns, rows })
+}
+
+fn parse_xlsx(bytes: &[u8]) -> Result<ImportTable, ParseError> {
+    let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes)).map_err(ParseError::Xlsx)?;
+
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or(ParseError::Empty)?
+        .map_err(ParseError::Xlsx)?;
+
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<String>>());
+
+    let columns = rows
+        .next()
+        .ok_or(ParseError::Empty)?
+        .into_iter()
+        .map(|column| column.trim().to_string())
+        .collect();
+
+    Ok(ImportTable {
+        columns,
+        rows: rows.collect(),
+    })
+}
+
+fn json_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(value) => value.clone(),
+        serde_json::Value::Array(values) => values.iter().map(json_cell).collect::<Vec<String>>().join("; "),
+        // Like the parties of an export, `{ "type": "client", "name": "..." }`.
+        serde_json::Value::Object(object) => object.get("name").map(json_cell).unwrap_or_default(),
+        value => value.to_string(),
+    }
+}
+
+/// A list of objects, or the `projects` of an export.
+fn parse_json(bytes: &[u8]) -> Result<ImportTable, ParseError> {
+    let value: serde_json::Value = serde_json::from_slice(bytes).map_err(ParseError::Json)?;
+
+    let projects = match &value {
+        serde_json::Value::Array(projects) => projects,
+        serde_json::Value::Object(object) => match object.get("projects") {
+            Some(serde_json::Value::Array(projects)) => projects,
+            _ => return Err(ParseError::JsonShape),
+        },
+        _ => return Err(ParseError::JsonShape),
+    };
+
+    let mut columns: Vec<String> = vec![];
+
+    for project in projects {
+        let serde_json::Value::Object(project) = project else {
+            return Err(ParseError::JsonShape);
+        };
+
+        for key in project.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let rows = projects
+        .iter()
+        .map(|project| {
+            columns
+                .iter()
+                .map(|column| project.get(column).map(json_cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    Ok(ImportTable { columns, rows })
+}
+
+/// Reads the file by its extension. Rows without any value are left out.
+pub fn parse_file(file_name: &str, bytes: &[u8]) -> Result<ImportTable, ParseError> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    let mut table = match extension.as_str() {
+        "csv" | "txt" => parse_csv(bytes)?,
+        "xlsx" => parse_xlsx(bytes)?,
+        "json" => parse_json(bytes)?,
+        _ => return Err(ParseError::UnknownFormat),
+    };
+
+    let width = table.columns.len();
+
+    table.rows.retain(|row| row.iter().any(|value| !value.trim().is_empty()));
+
+    for row in &mut table.rows {
+        row.resize(width, String::new());
+    }
+
+    if table.columns.is_empty() || table.rows.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    Ok(table)
+}
+
+pub async fn save_import(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    file_name: &str,
+    table: &ImportTable,
+) -> Result<String, sqlx::Error> {
+    // An import that was never finished is of no use a day later.
+    query!("DELETE FROM IMPORTS WHERE created_at < unixepoch() - 24 * 60 * 60")
+        .execute(&mut *conn)
+        .await?;
+
+    let id = Uuid::new_v4().to_string();
+    let columns = serde_json::to_string(&table.columns).unwrap_or_default();
+    let rows = serde_json::to_string(&table.rows).unwrap_or_default();
+
+    query!(
+        "INSERT INTO IMPORTS(id, user_id, file_name, columns, rows) VALUES ($1, $2, $3, $4, $5)",
+        id, user_id, file_name, columns, rows
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+struct ImportRow {
+    file_name: String,
+    columns: String,
+    rows: String,
+}
+
+/// The file the user uploaded, only they can continue its import.
+pub async fn get_import(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    user_id: &str,
+) -> Result<Option<(String, ImportTable)>, sqlx::Error> {
+    let import = query_as!(
+        ImportRow,
+        "SELECT file_name, columns, rows FROM IMPORTS WHERE id=$1 AND user_id=$2",
+        import_id, user_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(import.map(|import| {
+        (
+            import.file_name,
+            ImportTable {
+                columns: serde_json::from_str(&import.columns).unwrap_or_default(),
+                rows: serde_json::from_str(&import.rows).unwrap_or_default(),
+            },
+        )
+    }))
+}
+
+pub async fn delete_import(conn: &mut SqliteConnection, import_id: &str) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM IMPORTS WHERE id=$1", import_id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// What a column of the file can be imported as.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    Name,
+    Location,
+    Year,
+    Lifecycle,
+    StartDate,
+    DeliveryDate,
+    Contract,
+    Clients,
+    Architects,
+    Contractors,
+    Industries,
+    Companies,
+    Introduction,
+    Latitude,
+    Longitude,
+}
+
+impl Target {
+    pub const ALL: [Target; 15] = [
+        Target::Name,
+        Target::Location,
+        Target::Year,
+        Target::Lifecycle,
+        Target::StartDate,
+        Target::DeliveryDate,
+        Target::Contract,
+        Target::Clients,
+        Target::Architects,
+        Target::Contractors,
+        Target::Industries,
+        Target::Companies,
+        Target::Introduction,
+        Target::Latitude,
+        Target::Longitude,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Target::Name => "name",
+            Target::Location => "location",
+            Target::Year => "year",
+            Target::Lifecycle => "lifecycle",
+            Target::StartDate => "start_date",
+            Target::DeliveryDate => "delivery_date",
+            Target::Contract => "contract",
+            Target::Clients => "clients",
+            Target::Architects => "architects",
+            Target::Contractors => "contractors",
+            Target::Industries => "industries",
+            Target::Companies => "companies",
+            Target::Introduction => "introduction",
+            Target::Latitude => "latitude",
+            Target::Longitude => "longitude",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Target::Name => "Name",
+            Target::Location => "Location",
+            Target::Year => "Year",
+            Target::Lifecycle => "Lifecycle",
+            Target::StartDate => "Start date",
+            Target::DeliveryDate => "Delivery date",
+            Target::Contract => "Publiek/privaat",
+            Target::Clients => "Clients",
+            Target::Architects => "Architects",
+            Target::Contractors => "Contractors",
+            Target::Industries => "Industries",
+            Target::Companies => "Companies",
+            Target::Introduction => "Introduction",
+            Target::Latitude => "Latitude",
+            Target::Longitude => "Longitude",
+        }
+    }
+
+    /// Column names that are recognised as the target, in lowercase.
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Target::Name => &["name", "naam", "project", "projectnaam", "titel", "title"],
+            Target::Location => &["location", "locatie", "plaats", "adres", "address", "gemeente"],
+            Target::Year => &["year", "jaar", "bouwjaar", "oplevering"],
+            Target::Lifecycle => &["lifecycle", "status", "fase", "stage", "delivered", "opgeleverd"],
+            Target::StartDate => &["start_date", "start date", "start", "startdatum", "begin"],
+            Target::DeliveryDate => &["delivery_date", "delivery date", "delivery", "opleverdatum", "opgeleverd op"],
+            Target::Contract => &["contract", "publiek_privaat", "publiek/privaat", "publiek-privaat", "type"],
+            Target::Clients => &["client", "clients", "bouwheer", "bouwheren", "opdrachtgever", "opdrachtgevers"],
+            Target::Architects => &["architect", "architects", "architecten"],
+            Target::Contractors => &["contractor", "contractors", "aannemer", "aannemers"],
+            Target::Industries => &["industry", "industries", "sector", "sectoren"],
+            Target::Companies => &["company", "companies", "bedrijf", "bedrijven", "vennootschap"],
+            Target::Introduction => &["introduction", "introductie", "intro", "beschrijving", "description"],
+            Target::Latitude => &["latitude", "lat", "breedtegraad"],
+            Target::Longitude => &["longitude", "lng", "lon", "long", "lengtegraad"],
+        }
+    }
+}
+
+/// Which column every target is read from.
+pub struct Mapping(pub HashMap<Target, usize>);
+
+impl Mapping {
+    /// Matches the columns to the targets by their name.
+    pub fn guess(columns: &[String]) -> Self {
+        let mut mapping = HashMap::new();
+
+        for target in Target::ALL {
+            if let Some(index) = columns
+                .iter()
+                .position(|column| target.aliases().contains(&column.trim().to_lowercase().as_str()))
+            {
+                mapping.insert(target, index);
+            }
+        }
+
+        Mapping(mapping)
+    }
+
+    /// The mapping form, every target with the index of its column or nothing.
+    pub fn from_form(form: &HashMap<String, String>, columns: &[String]) -> Self {
+        let mut mapping = HashMap::new();
+
+        for target in Target::ALL {
+            if let Some(index) = form
+                .get(target.as_str())
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|index| *index < columns.len())
+            {
+                mapping.insert(target, index);
+            }
+        }
+
+        Mapping(mapping)
+    }
+
+    fn value<'a>(&self, row: &'a [String], target: Target) -> Option<&'a str> {
+        self.0
+            .get(&target)
+            .and_then(|index| row.get(*index))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// A project as it will be created.
+pub struct ImportProject {
+    pub name: String,
+    pub location: Option<String>,
+    pub year: Option<i64>,
+    pub lifecycle: Lifecycle,
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
+    pub contract: String,
+    pub parties: Vec<(String, &'static str)>,
+    pub industries: Vec<String>,
+    pub companies: Vec<String>,
+    pub introduction: Option<String>,
+    pub coordinates: Option<Coordinates>,
+}
+
+/// The outcome of checking a row, a row with errors can't be imported.
+pub struct RowReport {
+    /// The line of the row in the file, counting the column names.
+    pub line: usize,
+    pub project: ImportProject,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// The names the values of the file are checked against, by their lowercase version.
+struct ExistingData {
+    industries: HashMap<String, String>,
+    companies: HashMap<String, String>,
+    contracts: HashMap<String, String>,
+    parties: HashSet<String>,
+    projects: HashSet<String>,
+}
+
+fn by_lowercase(names: Vec<String>) -> HashMap<String, String> {
+    names.into_iter().map(|name| (name.to_lowercase(), name)).collect()
+}
+
+async fn existing_data(conn: &mut SqliteConnection) -> Result<ExistingData, sqlx::Error> {
+    let industries = query_scalar!("SELECT name FROM INDUSTRIES").fetch_all(&mut *conn).await?;
+    let companies = query_scalar!("SELECT name FROM COMPANIES").fetch_all(&mut *conn).await?;
+    let contracts = query_scalar!("SELECT name FROM PUBLIEK_PRIVAAT_TYPES").fetch_all(&mut *conn).await?;
+    let parties = query_scalar!("SELECT name FROM PARTIES").fetch_all(&mut *conn).await?;
+    let projects = query_scalar!("SELECT DISTINCT name FROM PROJECTS").fetch_all(&mut *conn).await?;
+
+    Ok(ExistingData {
+        industries: by_lowercase(industries),
+        companies: by_lowercase(companies),
+        contracts: by_lowercase(contracts),
+        parties: parties.into_iter().map(|party| party.to_lowercase()).collect(),
+        projects: projects.into_iter().map(|project| project.to_lowercase()).collect(),
+    })
+}
+
+/// The lifecycle by its name in English or Dutch. The yes and no of the old delivered column are delivered and in
+/// progress.
+fn parse_lifecycle(value: &str) -> Option<Lifecycle> {
+    match value.to_lowercase().as_str() {
+        "planned" | "gepland" | "in planning" => Some(Lifecycle::Planned),
+        "in_progress" | "in progress" | "in uitvoering" | "0" | "false" | "no" | "nee" => Some(Lifecycle::InProgress),
+        "delivered" | "opgeleverd" | "1" | "true" | "yes" | "ja" => Some(Lifecycle::Delivered),
+        _ => None,
+    }
+}
+
+/// A date as YYYY-MM-DD, from an ISO date, a Belgian date like 31/12/2024 or the day number of a spreadsheet date.
+fn parse_date(value: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d-%m-%Y"))
+        .ok()
+        .or_else(|| {
+            // Spreadsheets count the days from the end of 1899.
+            let days = value.parse::<f64>().ok().filter(|days| *days >= 1.0)?;
+            NaiveDate::from_ymd_opt(1899, 12, 30)?.checked_add_days(Days::new(days as u64))
+        })?;
+
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+fn parse_coordinate(value: Option<&str>) -> Option<Result<f64, ()>> {
+    value.map(|value| value.replace(',', ".").parse::<f64>().map_err(|_| ()))
+}
+
+/// Checks every row against the parties, industries, companies and contracts that exist, and against what the user is
+/// allowed to create. Nothing is written.
+pub async fn check(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    table: &ImportTable,
+    mapping: &Mapping,
+) -> Result<Vec<RowReport>, sqlx::Error> {
+    let existing = existing_data(&mut *conn).await?;
+    let max_year = chrono::Utc::now().year() as i64 + 10;
+
+    let mut allowed: HashMap<Option<String>, bool> = HashMap::new();
+    let mut names_in_file: HashSet<String> = HashSet::new();
+    let mut reports = vec![];
+
+    for (index, row) in table.rows.iter().enumerate() {
+        let mut errors = vec![];
+        let mut warnings = vec![];
+
+        let name = mapping.value(row, Target::Name).unwrap_or_default().to_string();
+
+        if name.is_empty() {
+            errors.push("The project has no name.".to_string());
+        } else if existing.projects.contains(&name.to_lowercase()) {
+            warnings.push(format!("There already is a project called {name}."));
+        } else if !names_in_file.insert(name.to_lowercase()) {
+            warnings.push(format!("{name} is in the file more than once."));
+        }
+
+        let year = match mapping.value(row, Target::Year) {
+            None => None,
+            Some(value) => match value.parse::<f64>().ok().filter(|year| year.fract() == 0.0) {
+                Some(year) if (1800..=max_year).contains(&(year as i64)) => Some(year as i64),
+                _ => {
+                    errors.push(format!("{value} isn't a year."));
+                    None
+                }
+            },
+        };
+
+        let lifecycle = match mapping.value(row, Target::Lifecycle) {
+            None => Lifecycle::Planned,
+            Some(value) => parse_lifecycle(value).unwrap_or_else(|| {
+                errors.push(format!("{value} isn't a lifecycle, use gepland, in uitvoering or opgeleverd."));
+                Lifecycle::Planned
+            }),
+        };
+
+        let [start_date, delivery_date] = [Target::StartDate, Target::DeliveryDate].map(|target| {
+            mapping.value(row, target).and_then(|value| {
+                parse_date(value).or_else(|| {
+                    errors.push(format!("{value} isn't a date, use YYYY-MM-DD."));
+                    None
+                })
+            })
+        });
+
+        if let (Some(start_date), Some(delivery_date)) = (&start_date, &delivery_date) {
+            if delivery_date < start_date {
+                errors.push("The delivery date is before the start date.".to_string());
+            }
+        }
+
+        let contract = match mapping.value(row, Target::Contract) {
+            None => "publiek".to_string(),
+            Some(value) => match existing.contracts.get(&value.to_lowercase()) {
+                Some(contract) => contract.clone(),
+                None => {
+                    errors.push(format!("{value} isn't a publiek/privaat type."));
+                    String::new()
+                }
+            },
+        };
+
+        let mut parties = vec![];
+
+        for (target, r#type) in [
+            (Target::Clients, "client"),
+            (Target::Architects, "architect"),
+            (Target::Contractors, "contractor"),
+        ] {
+            for party in split_values(mapping.value(row, target).unwrap_or_default()) {
+                if !existing.parties.contains(&party.to_lowercase()) {
+                    warnings.push(format!("{party} is a new party."));
+                }
+
+                parties.push((party, r#type));
+            }
+        }
+
+        let mut industries = vec![];
+
+        for industry in split_values(mapping.value(row, Target::Industries).unwrap_or_default()) {
+            match existing.industries.get(&industry.to_lowercase()) {
+                Some(industry) if !industries.contains(industry) => industries.push(industry.clone()),
+                Some(_) => {}
+                None => errors.push(format!("{industry} isn't an industry.")),
+            }
+        }
+
+        let mut companies = vec![];
+
+        for company in split_values(mapping.value(row, Target::Companies).unwrap_or_default()) {
+            match existing.companies.get(&company.to_lowercase()) {
+                Some(company) if !companies.contains(company) => companies.push(company.clone()),
+                Some(_) => {}
+                None => errors.push(format!("{company} isn't a company.")),
+            }
+        }
+
+        // A project without companies falls under the projects without a company.
+        let scopes: Vec<Option<String>> = if companies.is_empty() {
+            vec![None]
+        } else {
+            companies.iter().cloned().map(Some).collect()
+        };
+
+        for scope in scopes {
+            let is_allowed = match allowed.get(&scope) {
+                Some(is_allowed) => *is_allowed,
+                None => {
+                    let is_allowed =
+                        capability::has_company_capability(&mut *conn, user, scope.as_deref(), Capability::CreateProject)
+                            .await?;
+                    allowed.insert(scope.clone(), is_allowed);
+                    is_allowed
+                }
+            };
+
+            if !is_allowed {
+                errors.push(match &scope {
+                    Some(company) => format!("You can't create projects for {company}."),
+                    None => "You can't create projects without a company.".to_string(),
+                });
+            }
+        }
+
+        let latitude = parse_coordinate(mapping.value(row, Target::Latitude));
+        let longitude = parse_coordinate(mapping.value(row, Target::Longitude));
+
+        let coordinates = match (latitude, longitude) {
+            (None, None) => None,
+            (Some(Ok(latitude)), Some(Ok(longitude))) => Coordinates::new(latitude, longitude).or_else(|| {
+                errors.push("The coordinates are outside of the map.".to_string());
+                None
+            }),
+            _ => {
+                errors.push("The coordinates need a latitude and a longitude in degrees.".to_string());
+                None
+            }
+        };
+
+        reports.push(RowReport {
+            line: index + 2,
+            project: ImportProject {
+                name,
+                location: mapping.value(row, Target::Location).map(str::to_string),
+                year,
+                lifecycle,
+                start_date,
+                delivery_date,
+                contract,
+                parties,
+                industries,
+                companies,
+                introduction: mapping.value(row, Target::Introduction).map(str::to_string),
+                coordinates,
+            },
+            errors,
+            warnings,
+        });
+    }
+
+    Ok(reports)
+}
+
+/// Creates every project as a draft, they only show up on the sites once they're published.
+pub async fn import_projects(conn: &mut SqliteConnection, reports: &[RowReport]) -> Result<usize, sqlx::Error> {
+    for report in reports {
+        let project = &report.project;
+
+        let project_id = project_service::create_new(
+            &mut *conn,
+            &project.name,
+            &project.location,
+            &project.year,
+            project.lifecycle,
+            &project.contract,
+        )
+        .await?;
+
+        if project.start_date.is_some() || project.delivery_date.is_some() {
+            lifecycle_service::set_dates(&mut *conn, &project_id, &project.start_date, &project.delivery_date).await?;
+        }
+
+        // Before the companies are added, so they share the introduction instead of customising it.
+        if project.introduction.is_some() {
+            introduction_service::update_introduction(&mut *conn, &project_id, &None, &project.introduction).await?;
+        }
+
+        for (party, r#type) in &project.parties {
+            party_service::add_party_to_project(&mut *conn, &project_id, party, r#type).await?;
+        }
+
+        for company in &project.companies {
+            company_list_service::add_company_to_project(&mut *conn, &project_id, company).await?;
+        }
+
+        for industry in &project.industries {
+            industry_service::add_industry_to_project(&mut *conn, &project_id, industry).await?;
+        }
+
+        if let Some(coordinates) = project.coordinates {
+            location_service::set_manual_coordinates(&mut *conn, &project_id, coordinates).await?;
+        }
+    }
+
+    Ok(reports.len())
+}
@@ -0,0 +1,98 @@
+use maud::{html, Markup};
+
+use super::service::{Mapping, RowReport, Target};
+
+pub fn upload_template() -> Markup {
+    html!(
+        section.import {
+            h2 { "Import projects" }
+            p { "A CSV, XLSX or JSON file with a project on every row. The columns are matched to the fields in the next step." }
+            form.upload hx-post="/api/import" hx-encoding="multipart/form-data" hx-target="#import-step" hx-swap="innerHTML" {
+                input type="file" name="file" accept=".csv,.xlsx,.json" required;
+                input type="submit" value="Upload";
+            }
+            div #import-step {}
+        }
+    )
+}
+
+pub fn mapping_template(import_id: &str, file_name: &str, columns: &[String], rows: usize, mapping: &Mapping) -> Markup {
+    html!(
+        h3 { (file_name) " — " (rows) " rows" }
+        form #import-mapping hx-post={"/api/import/"(import_id)"/check"} hx-trigger="load, change" hx-target="#import-report" hx-swap="innerHTML" {
+            @for target in Target::ALL {
+                label {
+                    span { (target.label()) }
+                    select name=(target.as_str()) {
+                        option value="" { "Not imported" }
+                        @for (index, column) in columns.iter().enumerate() {
+                            option value=(index) selected[mapping.0.get(&target) == Some(&index)] { (column) }
+                        }
+                    }
+                }
+            }
+        }
+        div #import-report {}
+    )
+}
+
+pub fn report_template(import_id: &str, reports: &[RowReport]) -> Markup {
+    let with_errors = reports.iter().filter(|report| !report.errors.is_empty()).count();
+    let with_warnings = reports.iter().filter(|report| !report.warnings.is_empty()).count();
+
+    html!(
+        p.summary {
+            (reports.len() - with_errors) " of " (reports.len()) " projects can be imported"
+            @if with_warnings > 0 {
+                ", " (with_warnings) " with warnings"
+            }
+            "."
+        }
+        table.report {
+            thead {
+                tr {
+                    th { "Row" }
+                    th { "Name" }
+                    th { "Year" }
+                    th { "Companies" }
+                    th {}
+                }
+            }
+            tbody {
+                @for report in reports {
+                    tr.error[!report.errors.is_empty()] .warning[report.errors.is_empty() && !report.warnings.is_empty()] {
+                        td { (report.line) }
+                        td { (report.project.name) }
+                        td { @if let Some(year) = report.project.year { (year) } }
+                        td { (report.project.companies.join(", ")) }
+                        td {
+                            ul {
+                                @for error in &report.errors {
+                                    li.error { (error) }
+                                }
+                                @for warning in &report.warnings {
+                                    li.warning { (warning) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        @if with_errors > 0 {
+            p { "Fix the rows with errors in the file or leave their columns out to import." }
+        }
+        button.import disabled[with_errors > 0] hx-post={"/api/import/"(import_id)} hx-include="#import-mapping" hx-target="#import-step" hx-swap="innerHTML" hx-confirm={"Create " (reports.len()) " draft projects?"} {
+            "Import " (reports.len()) " projects"
+        }
+    )
+}
+
+pub fn imported_template(count: usize) -> Markup {
+    html!(
+        p.imported {
+            (count) " projects were created as drafts, they show up on the sites once they're published. "
+            a href="/projects" { "Go to the projects" }
+        }
+    )
+}
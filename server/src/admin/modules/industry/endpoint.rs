@@ -20,7 +20,7 @@ use super::template;
 pub async fn full_industry_list(
     DBConnection(mut connection): DBConnection,
 ) -> Result<Markup> {
-    let industries = query_scalar!("SELECT name FROM INDUSTRIES ORDER BY name")
+    let industries = query_scalar!("SELECT name FROM INDUSTRIES ORDER BY position, name")
         .fetch_all(&mut *connection)
         .await
         .map_err(|err| {
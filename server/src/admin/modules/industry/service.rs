@@ -24,7 +24,7 @@ pub async fn project_industry_data(
             INDUSTRIES i
         LEFT JOIN 
             PROJECT_INDUSTRIES pi ON i.name = pi.industry AND pi.project_id = $1 AND pi.draft = $2
-            ORDER BY i.name"#,
+            ORDER BY i.position, i.name"#,
         project_id,
         draft
     )
@@ -78,7 +78,7 @@ pub async fn industry_search_list(conn: &mut SqliteConnection, filter: &Option<S
         separated.push_bind(excluded);
     }
     
-    separated.push_unseparated(") ORDER BY name LIMIT 15");
+    separated.push_unseparated(") ORDER BY position, name LIMIT 15");
 
     let query=  query_builder.build_query_scalar();
 
@@ -10,7 +10,7 @@ pub async fn get_labels(
     project_id: &str,
     draft: bool,
 ) -> Result<Vec<Label>, sqlx::Error> {
-    query_as!(Label, r#"SELECT name, pt.tag IS NOT NULL as "active!: bool" FROM TAGS t LEFT JOIN PROJECT_TAGS pt ON t.name=pt.tag AND pt.project_id=$1 AND pt.draft=$2 ORDER BY t.name"#, project_id, draft)
+    query_as!(Label, r#"SELECT name, pt.tag IS NOT NULL as "active!: bool" FROM TAGS t LEFT JOIN PROJECT_TAGS pt ON t.name=pt.tag AND pt.project_id=$1 AND pt.draft=$2 ORDER BY t.position, t.name"#, project_id, draft)
     .fetch_all(conn)
     .await
 }
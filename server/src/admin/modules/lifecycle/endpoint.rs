@@ -0,0 +1,169 @@
+use axum::{extract::Path, http::StatusCode, response::Result, Form};
+use maud::{Markup, PreEscaped};
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use sqlx::SqliteTransaction;
+
+use crate::{admin::{draft::check_for_existing_project_draft, modules::project::template::page_buttons_update_wrapper, DBConnection, DBTransaction}, TransactionError};
+
+use super::{service::{self, Lifecycle}, template};
+
+#[derive(Deserialize)]
+pub struct SetLifecycleBody {
+    lifecycle: Lifecycle
+}
+
+pub async fn set_lifecycle(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<SetLifecycleBody>
+) -> Result<Markup> {
+    let lifecycle = service::set_lifecycle(&mut *transaction, &project_id, body.lifecycle).await.map_err(|err| {
+        eprintln!("Failed to update the lifecycle of project {project_id}: {err}");
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update the lifecycle")
+    })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    let template = template::lifecycle_template(&project_id, lifecycle, false);
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &template))
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct SetDatesBody {
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    start_date: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    delivery_date: Option<String>,
+}
+
+pub async fn set_dates(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<SetDatesBody>
+) -> Result<Markup> {
+    service::check_dates(&body.start_date, &body.delivery_date).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    service::set_dates(&mut *transaction, &project_id, &body.start_date, &body.delivery_date).await.map_err(|err| {
+        eprintln!("Failed to update the dates of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    let template = template::project_dates(&project_id, &body.start_date, &body.delivery_date);
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &template))
+}
+
+pub async fn get_phases(
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+) -> Result<Markup> {
+    let draft = check_for_existing_project_draft(&mut connection, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let phases = service::get_phases(&mut connection, &project_id, draft)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the phases of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::phases(&project_id, &phases))
+}
+
+/// The phases after a change to the draft, with the buttons to save the draft.
+async fn updated_phases(
+    mut transaction: SqliteTransaction<'_>,
+    project_id: &str,
+) -> Result<Markup> {
+    let phases = service::get_phases(&mut transaction, project_id, true)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(project_id, true, &template::phases(project_id, &phases)))
+}
+
+pub async fn create_phase(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+) -> Result<Markup> {
+    service::create_phase(&mut transaction, &project_id).await.map_err(|err| {
+        eprintln!("Failed to add a phase to project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    updated_phases(transaction, &project_id).await
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct UpdatePhaseBody {
+    name: String,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    start_date: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    end_date: Option<String>,
+}
+
+pub async fn update_phase(
+    Path((project_id, phase_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<UpdatePhaseBody>
+) -> Result<Markup> {
+    service::check_dates(&body.start_date, &body.end_date).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    let phase = service::update_phase(&mut transaction, &project_id, &phase_id, &body.name, &body.start_date, &body.end_date)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    let template = template::phase_template(&project_id, &phase);
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &template))
+}
+
+pub async fn delete_phase(
+    Path((project_id, phase_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+) -> Result<Markup> {
+    service::delete_phase(&mut transaction, &project_id, &phase_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    updated_phases(transaction, &project_id).await
+}
+
+#[derive(Deserialize)]
+pub struct MovePhaseBody {
+    next: Option<String>
+}
+
+pub async fn move_phase(
+    Path((project_id, phase_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<MovePhaseBody>
+) -> Result<Markup> {
+    service::move_phase(&mut transaction, &project_id, &phase_id, &body.next).await.map_err(|err| match err {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &PreEscaped::default()))
+}
@@ -1,9 +1,11 @@
 INSERT INTO PROJECTS(
     id, 
     name,
+    slug,
     draft
 ) VALUES (
     'id',
     'name',
+    'name',
     1
 );
\ No newline at end of file
@@ -0,0 +1,190 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+use uuid::Uuid;
+
+use crate::admin::position::{move_before, renumber};
+
+/// Where a project is in its lifecycle, as it's called in `PROJECTS.lifecycle`.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    Planned,
+    InProgress,
+    Delivered,
+}
+
+impl Lifecycle {
+    pub const ALL: [Lifecycle; 3] = [Lifecycle::Planned, Lifecycle::InProgress, Lifecycle::Delivered];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lifecycle::Planned => "planned",
+            Lifecycle::InProgress => "in_progress",
+            Lifecycle::Delivered => "delivered",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Lifecycle::Planned => "Planned",
+            Lifecycle::InProgress => "In progress",
+            Lifecycle::Delivered => "Delivered",
+        }
+    }
+
+    /// An unknown name is a planned project, like the default of the column.
+    pub fn from_name(name: &str) -> Lifecycle {
+        Lifecycle::ALL
+            .into_iter()
+            .find(|lifecycle| lifecycle.as_str() == name)
+            .unwrap_or(Lifecycle::Planned)
+    }
+
+    /// The one after it, after delivered comes planned again.
+    pub fn next(&self) -> Lifecycle {
+        match self {
+            Lifecycle::Planned => Lifecycle::InProgress,
+            Lifecycle::InProgress => Lifecycle::Delivered,
+            Lifecycle::Delivered => Lifecycle::Planned,
+        }
+    }
+}
+
+/// Checks a start and an end, both optional. They're ISO dates (YYYY-MM-DD), the format of the date inputs and of the
+/// date columns, and the end can't come before the start.
+pub fn check_dates(start: &Option<String>, end: &Option<String>) -> Result<(), &'static str> {
+    if [start, end].into_iter().flatten().any(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err()) {
+        return Err("Dates are written as YYYY-MM-DD.");
+    }
+
+    // ISO dates are in order when their text is.
+    match (start, end) {
+        (Some(start), Some(end)) if end < start => Err("The end can't come before the start."),
+        _ => Ok(()),
+    }
+}
+
+pub async fn set_lifecycle(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    lifecycle: Lifecycle,
+) -> Result<Lifecycle, sqlx::Error> {
+    let lifecycle = lifecycle.as_str();
+
+    query_scalar!("UPDATE PROJECTS SET lifecycle=$1 WHERE id=$2 AND draft=1 RETURNING lifecycle", lifecycle, project_id)
+    .fetch_one(conn)
+    .await
+    .map(|lifecycle| Lifecycle::from_name(&lifecycle))
+}
+
+pub async fn set_dates(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    start_date: &Option<String>,
+    delivery_date: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE PROJECTS SET start_date=$1, delivery_date=$2 WHERE id=$3 AND draft=1",
+        start_date,
+        delivery_date,
+        project_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub struct Phase {
+    pub id: String,
+    pub name: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+pub async fn get_phases(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+) -> Result<Vec<Phase>, sqlx::Error> {
+    query_as!(
+        Phase,
+        "SELECT id, name, start_date, end_date FROM PROJECT_PHASES WHERE project_id=$1 AND draft=$2 ORDER BY position",
+        project_id,
+        draft
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn create_phase(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<(), sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+
+    query!("
+        INSERT INTO PROJECT_PHASES(id, project_id, draft, name, position)
+        VALUES($1, $2, 1, '', (SELECT COALESCE(MAX(position) + 1, 0) FROM PROJECT_PHASES WHERE project_id=$2 AND draft=1))
+    ", id, project_id)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn update_phase(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    phase_id: &str,
+    name: &str,
+    start_date: &Option<String>,
+    end_date: &Option<String>,
+) -> Result<Phase, sqlx::Error> {
+    query_as!(
+        Phase,
+        "UPDATE PROJECT_PHASES SET name=$1, start_date=$2, end_date=$3 WHERE id=$4 AND project_id=$5 AND draft=1
+        RETURNING id, name, start_date, end_date",
+        name,
+        start_date,
+        end_date,
+        phase_id,
+        project_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn delete_phase(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    phase_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM PROJECT_PHASES WHERE id=$1 AND project_id=$2 AND draft=1", phase_id, project_id)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Moves the phase of the draft in front of `next`, or to the end.
+pub async fn move_phase(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    phase_id: &str,
+    next: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    let mut ids = query_scalar!("SELECT id FROM PROJECT_PHASES WHERE project_id=$1 AND draft=1 ORDER BY position", project_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !move_before(&mut ids, &phase_id.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &ids, async |conn, position, id| {
+        query!("UPDATE PROJECT_PHASES SET position=$1 WHERE id=$2 AND draft=1", position, id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+    })
+    .await
+}
@@ -0,0 +1,66 @@
+use maud::{Markup, html};
+
+use crate::self_replacing_svg;
+
+use super::service::{Lifecycle, Phase};
+
+/// Switches the project to the next stage of its lifecycle.
+pub fn lifecycle_template(project_id: &str, lifecycle: Lifecycle, disabled: bool) -> Markup {
+    html!(
+        button #lifecycle autocomplete="off" title="Lifecycle" hx-patch={"/api/projects/"(project_id)"/lifecycle"} disabled[disabled] name="lifecycle" value=(lifecycle.next().as_str()) data-lifecycle=(lifecycle.as_str()) hx-swap="outerHTML" {
+            (lifecycle.label())
+        }
+    )
+}
+
+pub fn project_dates(project_id: &str, start_date: &Option<String>, delivery_date: &Option<String>) -> Markup {
+    html!(
+        div.year.draft.dates hx-patch={"/api/projects/"(project_id)"/dates"} hx-trigger="change" hx-include="this" hx-swap="outerHTML" {
+            div.icon style="mask-image: url(/project_pagina/year.svg)" {}
+            input type="date" name="start_date" title="Start" autocomplete="off" value=[start_date];
+            span { "–" }
+            input type="date" name="delivery_date" title="Delivery" autocomplete="off" value=[delivery_date];
+        }
+    )
+}
+
+pub fn phases_loader(project_id: &str) -> Markup {
+    html!(
+        section #project-phases hx-get={"/api/projects/"(project_id)"/phases"} hx-trigger="load" hx-swap="outerHTML" {}
+    )
+}
+
+pub fn phase_template(project_id: &str, phase: &Phase) -> Markup {
+    let url = format!("/api/projects/{project_id}/phases/{}", phase.id);
+
+    html!(
+        li.phase data-name=(phase.id) data-move={(url)"/move"} {
+            div.move title="Drag to reorder" {
+                (self_replacing_svg("/move.svg"))
+            }
+            form hx-put=(url) hx-trigger="change" hx-target="closest .phase" hx-swap="outerHTML" {
+                input name="name" value=(phase.name) placeholder="Phase" autocomplete="off";
+                input type="date" name="start_date" title="Start" autocomplete="off" value=[&phase.start_date];
+                span { "–" }
+                input type="date" name="end_date" title="End" autocomplete="off" value=[&phase.end_date];
+            }
+            button.delete title="Remove" hx-delete=(url) hx-target="#project-phases" hx-swap="outerHTML" {}
+        }
+    )
+}
+
+pub fn phases(project_id: &str, phases: &[Phase]) -> Markup {
+    html!(
+        section #project-phases {
+            h2 { "Phases" }
+            ol data-reorder {
+                @for phase in phases {
+                    (phase_template(project_id, phase))
+                }
+            }
+            button.add-phase hx-post={"/api/projects/"(project_id)"/phases"} hx-target="#project-phases" hx-swap="outerHTML" {
+                (self_replacing_svg("/plus.svg"))
+            }
+        }
+    )
+}
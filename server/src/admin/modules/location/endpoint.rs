@@ -1,9 +1,9 @@
 use axum::{extract::{Path, State}, http::StatusCode, response::Result, Form};
 use maud::Markup;
 use serde::Deserialize;
+use sqlx::{Connection, SqliteConnection};
 
-
-use crate::{admin::{modules::project::template::page_buttons_update_wrapper, DBTransaction}, AppState, TransactionError};
+use crate::{admin::{modules::project::template::page_buttons_update_wrapper, DBConnection, DBTransaction}, geocoding::Coordinates, AppState, TransactionError};
 
 use super::{service as service, template::project_location};
 
@@ -12,18 +12,106 @@ pub struct UpdateProjectLocation {
     location: Option<String>
 }
 
+async fn location_template(conn: &mut SqliteConnection, project_id: &str, location: &Option<String>) -> Result<Markup> {
+    let coordinates = service::get_coordinates(&mut *conn, project_id).await.map_err(|err| {
+        eprintln!("Could not get the coordinates of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(project_location(project_id, location, &coordinates))
+}
+
 pub async fn update_project_location(
+    State(state): State<AppState>,
     Path(project_id): Path<String>,
-    DBTransaction(mut transaction): DBTransaction<'_>,
+    DBConnection(mut connection): DBConnection,
     Form(body): Form<UpdateProjectLocation>
 ) -> Result<Markup> {
+    let coordinates = service::get_coordinates(&mut connection, &project_id).await.map_err(|err| {
+        eprintln!("Could not get the coordinates of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // The provider is asked before the transaction is opened, so the database isn't locked while waiting for it.
+    let pin = if coordinates.manual {
+        service::Pin::Unchanged
+    } else {
+        service::geocode(state.geocoder.as_deref(), &project_id, &body.location).await
+    };
+
+    let mut transaction = connection.begin().await.map_err(|_| TransactionError::Begin)?;
+
+    service::update_geocoded_coordinates(&mut transaction, &project_id, pin, false).await.map_err(|err| {
+        eprintln!("Could not update the coordinates for project {project_id}: {err}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update the location.")
+    })?;
+
     service::update_project_location(&mut transaction, &project_id, &body.location).await.map_err(|err| {
         eprintln!("Could not update the location for project {project_id}: {err}");
         return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update the location.")
     })?;
 
+    let content = location_template(&mut transaction, &project_id, &body.location).await?;
+
     transaction.commit().await.map_err(|_| TransactionError::Commit)?;
 
-    let content = project_location(&project_id, &body.location);
     Ok(page_buttons_update_wrapper(&project_id, true, &content))
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize)]
+pub struct SetCoordinatesBody {
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Places the pin where the editor clicked or dropped it on the map.
+pub async fn set_coordinates(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<SetCoordinatesBody>
+) -> Result<Markup> {
+    let coordinates = Coordinates::new(body.latitude, body.longitude)
+        .ok_or((StatusCode::BAD_REQUEST, "The coordinates are outside of the map."))?;
+
+    service::set_manual_coordinates(&mut transaction, &project_id, coordinates).await.map_err(|err| {
+        eprintln!("Could not place the pin of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let location = service::get_location(&mut transaction, &project_id).await.map_err(|err| {
+        eprintln!("Could not get the location of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let content = location_template(&mut transaction, &project_id, &location).await?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &content))
+}
+
+/// Moves a pin that was placed by hand back to the geocoded location.
+pub async fn reset_coordinates(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+) -> Result<Markup> {
+    let location = service::get_location(&mut connection, &project_id).await.map_err(|err| {
+        eprintln!("Could not get the location of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let pin = service::geocode(state.geocoder.as_deref(), &project_id, &location).await;
+
+    let mut transaction = connection.begin().await.map_err(|_| TransactionError::Begin)?;
+
+    service::update_geocoded_coordinates(&mut transaction, &project_id, pin, true).await.map_err(|err| {
+        eprintln!("Could not reset the pin of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let content = location_template(&mut transaction, &project_id, &location).await?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &content))
+}
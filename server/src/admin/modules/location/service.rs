@@ -1,4 +1,6 @@
-use sqlx::{query, SqliteConnection};
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+
+use crate::geocoding::{Coordinates, Geocoder};
 
 pub async fn update_project_location(
     conn: &mut SqliteConnection,
@@ -9,4 +11,112 @@ pub async fn update_project_location(
     .execute(conn)
     .await
     .map(|_| ())
-}
\ No newline at end of file
+}
+
+pub async fn get_location(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    query_scalar!("SELECT location FROM PROJECTS WHERE id=$1 AND draft=1", project_id)
+    .fetch_one(conn)
+    .await
+}
+
+pub struct ProjectCoordinates {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Whether an editor placed the pin, a new location doesn't move it then.
+    pub manual: bool,
+}
+
+impl ProjectCoordinates {
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        Coordinates::new(self.latitude?, self.longitude?)
+    }
+}
+
+pub async fn get_coordinates(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<ProjectCoordinates, sqlx::Error> {
+    query_as!(ProjectCoordinates, r#"
+        SELECT latitude, longitude, manual_coordinates as "manual: bool"
+        FROM PROJECTS WHERE id=$1 AND draft=1
+    "#, project_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Places the pin by hand, it stays there until it's reset.
+pub async fn set_manual_coordinates(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    coordinates: Coordinates,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE PROJECTS SET latitude=$1, longitude=$2, manual_coordinates=1 WHERE id=$3 AND draft=1",
+        coordinates.latitude, coordinates.longitude, project_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Where the pin goes for a location.
+pub enum Pin {
+    /// `None` when there's no location or the provider doesn't know it. A pin that was geocoded from an older location
+    /// would be wrong, so it's removed then.
+    Geocoded(Option<Coordinates>),
+    /// Without a geocoder the pin stays where it is, it can only be placed by hand. It also stays when the provider
+    /// couldn't be reached, a hiccup shouldn't take the pin off the map.
+    Unchanged,
+}
+
+/// Asks the provider where the location is. This doesn't touch the database, so it's done before the transaction is
+/// opened and the database isn't locked while waiting for the provider. `project` only names it in the log.
+pub async fn geocode(geocoder: Option<&Geocoder>, project: &str, location: &Option<String>) -> Pin {
+    let location = location.as_deref().map(str::trim).filter(|location| !location.is_empty());
+
+    match (geocoder, location) {
+        (_, None) => Pin::Geocoded(None),
+        (None, Some(_)) => Pin::Unchanged,
+        (Some(geocoder), Some(location)) => match geocoder.geocode(location).await {
+            Ok(coordinates) => Pin::Geocoded(coordinates),
+            Err(err) => {
+                eprintln!("Could not geocode the location of project {project}: {err}");
+                Pin::Unchanged
+            }
+        },
+    }
+}
+
+/// Moves the pin, unless an editor placed it or `force` resets it.
+pub async fn update_geocoded_coordinates(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    pin: Pin,
+    force: bool,
+) -> Result<(), sqlx::Error> {
+    let coordinates = match pin {
+        Pin::Geocoded(coordinates) => coordinates,
+        Pin::Unchanged if force => {
+            return query!("UPDATE PROJECTS SET manual_coordinates=0 WHERE id=$1 AND draft=1", project_id)
+            .execute(conn)
+            .await
+            .map(|_| ());
+        }
+        Pin::Unchanged => return Ok(()),
+    };
+
+    let latitude = coordinates.map(|coordinates| coordinates.latitude);
+    let longitude = coordinates.map(|coordinates| coordinates.longitude);
+
+    query!(
+        "UPDATE PROJECTS SET latitude=$1, longitude=$2, manual_coordinates=0
+        WHERE id=$3 AND draft=1 AND (manual_coordinates=0 OR $4)",
+        latitude, longitude, project_id, force
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
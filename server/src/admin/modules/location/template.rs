@@ -1,10 +1,27 @@
 use maud::{html, Markup};
 
-pub fn project_location(project_id: &str, location: &Option<String>) -> Markup {
+use super::service::ProjectCoordinates;
+
+pub fn project_location(project_id: &str, location: &Option<String>, coordinates: &ProjectCoordinates) -> Markup {
+    let pin = coordinates.coordinates();
+
     html!(
-        div.year.draft hx-swap="outerHTML" {
-            div.icon style="mask-image: url(/project_pagina/location.svg)" {}
-            input placeholder="Location" value=[location] name="location" hx-patch={"/api/projects/"(project_id)"/location"} hx-trigger="change" hx-select="input" {}
+        div.project-location {
+            div.year.draft {
+                div.icon style="mask-image: url(/project_pagina/location.svg)" {}
+                input placeholder="Location" value=[location] name="location" hx-patch={"/api/projects/"(project_id)"/location"} hx-trigger="change" hx-target="closest .project-location" hx-select=".project-location" hx-swap="outerHTML" {}
+            }
+            div.location-map
+                data-coordinates={"/api/projects/"(project_id)"/coordinates"}
+                data-latitude=[pin.map(|pin| pin.latitude)]
+                data-longitude=[pin.map(|pin| pin.longitude)] {}
+            @if coordinates.manual {
+                button.reset-pin type="button" hx-delete={"/api/projects/"(project_id)"/coordinates"} hx-target="closest .project-location" hx-select=".project-location" hx-swap="outerHTML" {
+                    "Place the pin on the location"
+                }
+            } @else if location.is_some() && pin.is_none() {
+                span.no-pin { "The location wasn't found, click the map to place the pin." }
+            }
         }
     )
-}
\ No newline at end of file
+}
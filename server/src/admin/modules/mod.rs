@@ -11,11 +11,19 @@ pub mod weight;
 pub mod year;
 pub mod core_numbers;
 pub mod show_in_carousel;
-pub mod status;
+pub mod lifecycle;
 pub mod name;
 pub mod publiek_privaat;
 pub mod banner;
 pub mod image;
 pub mod visible;
 pub mod thumbnail;
-pub mod labels;
\ No newline at end of file
+pub mod labels;
+pub mod taxonomy;
+pub mod translation;
+pub mod slug;
+pub mod import;
+pub mod export;
+pub mod duplicate;
+pub mod project_template;
+pub mod related_projects;
\ No newline at end of file
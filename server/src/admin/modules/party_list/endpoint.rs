@@ -1,24 +1,26 @@
 use axum::{
-    Form,
+    Extension, Form,
     extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Result},
 };
 use axum_extra::extract::Query as ExtraQuery;
-use maud::Markup;
+use maud::{Markup, PreEscaped};
 use serde::Deserialize;
+use sqlx::SqliteConnection;
+use url::Url;
 
 use crate::{
     admin::{
         index::index, modules::{
             project::template::page_buttons_update_wrapper, tms::service::project_tm_data_type,
         }, DBConnection, DBTransaction
-    }, AppState, TransactionError
+    }, auth::{capability::{self, Capability}, service::UserData}, AppState, TransactionError
 };
 
 use super::{
     service::{self, project_party_data_type},
-    template::{party_list_template, search_list_for_existing_project},
+    template::{self, party_list_template, search_list_for_existing_project},
 };
 
 use super::super::tms::service as tm_service;
@@ -130,3 +132,264 @@ pub async fn delete_party_from_project(
     let content = party_list_template(&project_id, &parties, &tms, &query.r#type);
     Ok(page_buttons_update_wrapper(&project_id, true, &content))
 }
+
+#[derive(Deserialize)]
+pub struct MovePartyBody {
+    next: Option<String>,
+}
+
+pub async fn move_party_on_project(
+    Path((project_id, r#type, party)): Path<(String, String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<MovePartyBody>,
+) -> Result<Markup> {
+    service::move_party(&mut transaction, &project_id, &r#type, &party, &body.next)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            err => {
+                eprintln!("Failed to move {type} {party} on project {project_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &PreEscaped::default()))
+}
+
+async fn party_overview(conn: &mut SqliteConnection) -> Result<Markup> {
+    let parties = service::party_overview(conn)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the parties: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::parties(&parties))
+}
+
+pub async fn get_parties(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageParties).await?;
+
+    party_overview(&mut connection).await
+}
+
+/// The URL of a party is a link on the public pages, so only http(s) URLs are accepted, no `javascript:` ones.
+fn party_url(url: Option<String>) -> Result<Option<String>> {
+    let Some(url) = url.map(|url| url.trim().to_string()).filter(|url| !url.is_empty()) else {
+        return Ok(None);
+    };
+
+    match Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(Some(parsed.to_string())),
+        _ => Err((StatusCode::BAD_REQUEST, "The URL has to start with http:// or https://.").into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreatePartyBody {
+    name: String,
+    url: Option<String>,
+}
+
+pub async fn create_party(
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<CreatePartyBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageParties).await?;
+
+    let name = body.name.trim();
+
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let url = party_url(body.url)?;
+
+    let exists = service::check_if_party_exists(&mut transaction, name)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if exists {
+        return Err((StatusCode::CONFLICT, format!("There already is a party called {name}.")).into());
+    }
+
+    service::add_party(&mut transaction, name)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create party {name}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    service::set_party_url(&mut transaction, name, &url)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let content = party_overview(&mut transaction).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePartyBody {
+    name: String,
+    url: Option<String>,
+}
+
+/// Saves the name and URL of a party. A new name is applied to every project and TM the party is on.
+pub async fn update_party(
+    Path(party): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<UpdatePartyBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageParties).await?;
+
+    let name = body.name.trim();
+
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let url = party_url(body.url)?;
+
+    service::set_party_url(&mut transaction, &party, &url)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the URL of party {party}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if name != party {
+        let exists = service::check_if_party_exists(&mut transaction, name)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if exists {
+            return Err((StatusCode::CONFLICT, format!("There already is a party called {name}, merge them instead.")).into());
+        }
+
+        service::rename_party(&mut transaction, &party, name)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to rename party {party} to {name}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let content = party_overview(&mut transaction).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct SetPartyLogoBody {
+    image: Option<String>,
+}
+
+pub async fn set_party_logo(
+    Path(party): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<SetPartyLogoBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageParties).await?;
+
+    let logo = body.image.filter(|image| !image.is_empty());
+
+    service::set_party_logo(&mut transaction, &party, &logo)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the logo of party {party}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let party = service::get_party(&mut transaction, &party)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::party_row(&party, &[]))
+}
+
+#[derive(Deserialize)]
+pub struct MergePartyBody {
+    target: String,
+}
+
+pub async fn merge_party(
+    Path(party): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<MergePartyBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageParties).await?;
+
+    if body.target == party {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let exists = service::check_if_party_exists(&mut transaction, &body.target)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, format!("There is no party called {}.", body.target)).into());
+    }
+
+    service::merge_parties(&mut transaction, &party, &body.target)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to merge party {party} into {}: {err}", body.target);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let content = party_overview(&mut transaction).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+pub async fn get_party_projects(
+    Path(party): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageParties).await?;
+
+    let projects = service::party_projects(&mut connection, &party)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the projects of party {party}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::party_projects(&projects))
+}
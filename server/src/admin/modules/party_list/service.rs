@@ -1,5 +1,7 @@
 use sqlx::{query, query_as, query_scalar, QueryBuilder, Sqlite, SqliteConnection};
 
+use crate::admin::position::{move_before, renumber};
+
 pub struct Party {
     pub name: String,
     pub url: Option<String>
@@ -16,21 +18,21 @@ pub async fn party_search_list(conn: &mut SqliteConnection, filter: &Option<Stri
 
     let mut query_builder = QueryBuilder::<Sqlite>::new(r#"
             SELECT
-                DISTINCT party_name
-            FROM PROJECT_PARTIES
-            WHERE party_name LIKE 
+                name
+            FROM PARTIES
+            WHERE name LIKE 
     "#);
 
     query_builder.push_bind(filter);
 
-    query_builder.push("AND party_name NOT IN(");
+    query_builder.push("AND name NOT IN(");
 
     let mut separated = query_builder.separated(", ");
     for excluded in exclude {
         separated.push_bind(excluded);
     }
     
-    separated.push_unseparated(") ORDER BY party_name LIMIT 10");
+    separated.push_unseparated(") ORDER BY name LIMIT 10");
 
     let query=  query_builder.build_query_scalar();
 
@@ -48,6 +50,7 @@ pub async fn project_party_data_type(conn: &mut SqliteConnection, project_id: &s
     INNER JOIN
         PARTIES p ON pp.party_name = p.name AND pp.project_id = $1
     WHERE pp.type=$2 AND pp.draft=$3
+    ORDER BY pp.position
     ", project_id, r#type, draft)
     .fetch_all(conn)
     .await
@@ -85,15 +88,186 @@ pub async fn check_if_party_exists(conn: &mut SqliteConnection, name: &str) -> R
 }
 
 pub async fn add_party_to_project(conn: &mut SqliteConnection, project_id: &str, party_name: &str, r#type: &str) -> Result<(), sqlx::Error> {
-    query!("INSERT INTO PROJECT_PARTIES(project_id, party_name, draft, type) VALUES ($1, $2, 1, $3)", project_id, party_name, r#type)
-    .execute(conn)
+    // A party typed in on a project that doesn't exist yet is created along the way.
+    query!("INSERT OR IGNORE INTO PARTIES(name) VALUES ($1)", party_name)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("
+        INSERT INTO PROJECT_PARTIES(project_id, party_name, draft, type, position)
+        VALUES ($1, $2, 1, $3, (SELECT COALESCE(MAX(position) + 1, 0) FROM PROJECT_PARTIES WHERE project_id=$1 AND draft=1 AND type=$3))
+    ", project_id, party_name, r#type)
+    .execute(&mut *conn)
     .await
     .map(|_| ())
 }
 
+/// Moves the party in front of `next` among the parties of the same type on the project's draft, or to the end.
+pub async fn move_party(conn: &mut SqliteConnection, project_id: &str, r#type: &str, party_name: &str, next: &Option<String>) -> Result<(), sqlx::Error> {
+    let mut names = query_scalar!("SELECT party_name FROM PROJECT_PARTIES WHERE project_id=$1 AND draft=1 AND type=$2 ORDER BY position", project_id, r#type)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !move_before(&mut names, &party_name.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &names, async |conn, position, name| {
+        query!("UPDATE PROJECT_PARTIES SET position=$1 WHERE project_id=$2 AND draft=1 AND type=$3 AND party_name=$4", position, project_id, r#type, name)
+        .execute(conn)
+        .await
+        .map(|_| ())
+    })
+    .await
+}
+
 pub async fn delete_party_from_project(conn: &mut SqliteConnection, project_id: &str, party_name: &str, r#type: &str) -> Result<(), sqlx::Error> {
     query!("DELETE FROM PROJECT_PARTIES WHERE project_id=$1 AND party_name=$2 AND draft=1 AND type=$3", project_id, party_name, r#type)
     .execute(conn)
     .await
     .map(|_| ())
-}
\ No newline at end of file
+}
+
+pub struct PartyOverview {
+    pub name: String,
+    pub url: Option<String>,
+    pub logo: Option<String>,
+    /// The number of projects the party is on, in their draft or their published version.
+    pub projects: i64,
+}
+
+pub async fn party_overview(conn: &mut SqliteConnection) -> Result<Vec<PartyOverview>, sqlx::Error> {
+    query_as!(PartyOverview, r#"
+    SELECT
+        p.name,
+        p.url,
+        p.logo,
+        (SELECT COUNT(DISTINCT pp.project_id) FROM PROJECT_PARTIES pp WHERE pp.party_name = p.name) as "projects!: i64"
+    FROM PARTIES p
+    ORDER BY p.name
+    "#)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn get_party(conn: &mut SqliteConnection, name: &str) -> Result<Option<PartyOverview>, sqlx::Error> {
+    query_as!(PartyOverview, r#"
+    SELECT
+        p.name,
+        p.url,
+        p.logo,
+        (SELECT COUNT(DISTINCT pp.project_id) FROM PROJECT_PARTIES pp WHERE pp.party_name = p.name) as "projects!: i64"
+    FROM PARTIES p
+    WHERE p.name = $1
+    "#, name)
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn set_party_url(conn: &mut SqliteConnection, name: &str, url: &Option<String>) -> Result<(), sqlx::Error> {
+    query!("UPDATE PARTIES SET url=$1 WHERE name=$2", url, name)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn set_party_logo(conn: &mut SqliteConnection, name: &str, logo: &Option<String>) -> Result<(), sqlx::Error> {
+    query!("UPDATE PARTIES SET logo=$1 WHERE name=$2", logo, name)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Re-points every reference to `source` at `target` and removes `source`. The URL and logo of `source` are kept when
+/// `target` doesn't have its own. A project or TM that had both keeps a single entry.
+///
+/// This changes the published projects as well as the drafts, without going through publishing: a party is shared
+/// data, and a project that still showed the removed party would have nothing left to link to.
+pub async fn merge_parties(conn: &mut SqliteConnection, source: &str, target: &str) -> Result<(), sqlx::Error> {
+    query!("
+        UPDATE PARTIES SET
+            url = COALESCE(url, (SELECT url FROM PARTIES WHERE name = $1)),
+            logo = COALESCE(logo, (SELECT logo FROM PARTIES WHERE name = $1))
+        WHERE name = $2
+    ", source, target)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("UPDATE OR IGNORE PROJECT_PARTIES SET party_name=$2 WHERE party_name=$1", source, target)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("DELETE FROM PROJECT_PARTIES WHERE party_name=$1", source)
+    .execute(&mut *conn)
+    .await?;
+
+    // Dropping the duplicates leaves gaps in the order of the parties they were next to.
+    query!("
+        UPDATE PROJECT_PARTIES SET position = renumbered.position
+        FROM (
+            SELECT
+                project_id, draft, type, party_name,
+                ROW_NUMBER() OVER (PARTITION BY project_id, draft, type ORDER BY position, party_name) - 1 as position
+            FROM PROJECT_PARTIES
+            WHERE (project_id, draft, type) IN (SELECT project_id, draft, type FROM PROJECT_PARTIES WHERE party_name = $1)
+        ) renumbered
+        WHERE
+            PROJECT_PARTIES.project_id = renumbered.project_id
+            AND PROJECT_PARTIES.draft = renumbered.draft
+            AND PROJECT_PARTIES.type = renumbered.type
+            AND PROJECT_PARTIES.party_name = renumbered.party_name
+    ", target)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("UPDATE OR IGNORE TM_PARTY SET party=$2 WHERE party=$1", source, target)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("DELETE FROM TM_PARTY WHERE party=$1", source)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("DELETE FROM PARTIES WHERE name=$1", source)
+    .execute(&mut *conn)
+    .await
+    .map(|_| ())
+}
+
+/// Renames the party everywhere it is used, published projects included, like [`merge_parties`]. The new name must not
+/// be taken, that is a merge.
+pub async fn rename_party(conn: &mut SqliteConnection, name: &str, new_name: &str) -> Result<(), sqlx::Error> {
+    query!("INSERT INTO PARTIES(name, url, logo) SELECT $2, url, logo FROM PARTIES WHERE name=$1", name, new_name)
+    .execute(&mut *conn)
+    .await?;
+
+    merge_parties(conn, name, new_name).await
+}
+
+pub struct PartyProject {
+    pub id: String,
+    pub name: String,
+    /// Client, architect or contractor.
+    pub role: String,
+}
+
+/// The projects the party is on, with the role it has on them.
+pub async fn party_projects(conn: &mut SqliteConnection, name: &str) -> Result<Vec<PartyProject>, sqlx::Error> {
+    query_as!(PartyProject, r#"
+    SELECT DISTINCT
+        p.id,
+        p.name,
+        pp.type as role
+    FROM PROJECT_PARTIES pp
+    JOIN PROJECTS p ON p.id = pp.project_id AND p.draft = pp.draft
+    WHERE pp.party_name = $1
+    ORDER BY p.name
+    "#, name)
+    .fetch_all(conn)
+    .await
+}
+
+/// Names that only differ in case or spacing, like "Stad Antwerpen" and "Stad  Antwerpen", are likely the same party.
+pub fn normalise_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
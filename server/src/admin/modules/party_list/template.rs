@@ -1,7 +1,9 @@
 use maud::{Markup, html};
 
+use crate::self_replacing_svg;
+
 use super::super::tms::service::TM;
-use super::service::Party;
+use super::service::{normalise_name, Party, PartyOverview, PartyProject};
 
 pub fn party_list_template(
     project_id: &str,
@@ -13,11 +15,14 @@ pub fn party_list_template(
         div {
             div.icon style={"mask-image: url(/project_pagina/" (r#type) ".svg)"} {}
 
-            ul {
+            ul data-reorder {
                 @for party in parties {
-                    li.draft hx-delete={"/api/projects/"(project_id)"/parties"} hx-target="closest ul" hx-select="ul" hx-swap="outerHTML" hx-vals={
+                    li.draft data-name=(party.name) data-move={"/api/projects/"(project_id)"/parties/"(r#type)"/"(party.name)"/move"} hx-delete={"/api/projects/"(project_id)"/parties"} hx-target="closest ul" hx-select="ul" hx-swap="outerHTML" hx-vals={
                         r#"{"party":""#(party.name)r#"","type":""#(r#type)r#""}"#
                     } {
+                        div.move title="Drag to reorder" {
+                            (self_replacing_svg("/move.svg"))
+                        }
                         @if let Some(url) = &party.url {
                             a href=(url) {
                                 (party.name)
@@ -104,3 +109,81 @@ pub fn search_list_for_existing_project(
             }
     )
 }
+
+
+/// One party on the parties page. `duplicates` are the other parties with a name that is likely the same.
+pub fn party_row(party: &PartyOverview, duplicates: &[&str]) -> Markup {
+    html!(
+        li.party {
+            div.logo {
+                @if let Some(logo) = &party.logo {
+                    img src=(logo) alt=(party.name);
+                }
+                input type="hidden" name="image" hx-trigger="change" hx-patch={"/api/parties/"(party.name)"/logo"} hx-target="closest li" hx-swap="outerHTML";
+                button title="Choose a logo" onclick="update_header_photo(this.previousElementSibling)" {
+                    (self_replacing_svg("/edit.svg"))
+                }
+                @if party.logo.is_some() {
+                    button title="Remove the logo" hx-patch={"/api/parties/"(party.name)"/logo"} hx-target="closest li" hx-swap="outerHTML" {
+                        (self_replacing_svg("/clear.svg"))
+                    }
+                }
+            }
+            form.details hx-patch={"/api/parties/"(party.name)} hx-trigger="change" hx-target="#parties" hx-swap="outerHTML" {
+                input name="name" value=(party.name) required;
+                input name="url" type="url" value=[&party.url] placeholder="https://";
+            }
+            details hx-get={"/api/parties/"(party.name)"/projects"} hx-trigger="toggle once" hx-target="find ul" hx-swap="outerHTML" {
+                summary { (party.projects) " projects" }
+                ul {}
+            }
+            form.merge hx-post={"/api/parties/"(party.name)"/merge"} hx-target="#parties" hx-swap="outerHTML" hx-confirm={"Every project and TM with " (party.name) " will get the other party instead. Do you want to continue?"} {
+                input name="target" list="party-names" placeholder="Merge into" required;
+                input type="submit" value="Merge";
+            }
+            @if !duplicates.is_empty() {
+                span.duplicates { "Possibly the same as " (duplicates.join(", ")) }
+            }
+        }
+    )
+}
+
+pub fn parties(parties: &[PartyOverview]) -> Markup {
+    html!(
+        ul #parties {
+            datalist #party-names {
+                @for party in parties {
+                    option value=(party.name) {}
+                }
+            }
+            @for party in parties {
+                @let key = normalise_name(&party.name);
+                @let duplicates = parties
+                    .iter()
+                    .filter(|other| other.name != party.name && normalise_name(&other.name) == key)
+                    .map(|other| other.name.as_str())
+                    .collect::<Vec<_>>();
+                (party_row(party, &duplicates))
+            }
+            @if parties.is_empty() {
+                li { "No parties yet." }
+            }
+        }
+    )
+}
+
+pub fn party_projects(projects: &[PartyProject]) -> Markup {
+    html!(
+        ul {
+            @for project in projects {
+                li {
+                    a href={"/projects/"(project.id)} { (project.name) }
+                    span.role { (project.role) }
+                }
+            }
+            @if projects.is_empty() {
+                li { "Not on any project." }
+            }
+        }
+    )
+}
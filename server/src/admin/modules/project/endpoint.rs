@@ -25,11 +25,15 @@ use crate::{
             content::service::get_content,
             core_numbers::service::get_project_core_numbers,
             industry::service::project_industry_data,
+            lifecycle::service::Lifecycle,
             party_list::service::project_party_data,
             tms::service::project_tm_data,
         },
     },
-    auth::service::UserData,
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
 };
 
 use super::{
@@ -38,6 +42,8 @@ use super::{
 };
 
 use super::super::core_numbers::service as core_numbers_service;
+use super::super::project_template::service as project_template_service;
+use super::super::slug::service as slug_service;
 
 use super::template;
 
@@ -149,6 +155,12 @@ pub async fn save_changes(
     DBTransaction(mut transaction): DBTransaction<'_>,
     Extension(data): Extension<UserData>,
 ) -> Result<Markup> {
+    capability::require_for_project(&mut transaction, &data, &project_id, Capability::Publish).await?;
+
+    slug_service::record_replaced_slugs(&mut *transaction, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     service::delete_project(&mut *transaction, &project_id, false)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -206,6 +218,8 @@ pub async fn revert_changes(
     DBTransaction(mut transaction): DBTransaction<'_>,
     Extension(data): Extension<UserData>,
 ) -> Result<Markup> {
+    capability::require_for_project(&mut transaction, &data, &project_id, Capability::EditProject).await?;
+
     service::delete_project(&mut *transaction, &project_id, true)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -259,6 +273,8 @@ pub async fn unpublish(
     DBTransaction(mut transaction): DBTransaction<'_>,
     Extension(data): Extension<UserData>,
 ) -> Result<Markup> {
+    capability::require_for_project(&mut transaction, &data, &project_id, Capability::Unpublish).await?;
+
     core_numbers_service::delete_for_project_id(&mut *transaction, &project_id, false).await;
     service::delete_project(&mut *transaction, &project_id, false).await;
 
@@ -306,12 +322,16 @@ pub async fn unpublish(
     ))
 }
 
+fn default_lifecycle() -> Lifecycle {
+    Lifecycle::Planned
+}
+
 #[serde_as]
 #[derive(Deserialize, Debug)]
 pub struct CreateNewProjectBody {
     name: String,
-    #[serde(default)]
-    status: bool,
+    #[serde(default = "default_lifecycle")]
+    lifecycle: Lifecycle,
     contract: String,
     year: Option<i64>,
     location: Option<String>,
@@ -330,18 +350,31 @@ pub struct CreateNewProjectBody {
     #[serde_as(as = "OneOrMany<_, PreferMany>")]
     #[serde(default)]
     industry: Vec<String>,
+    /// The template the project starts from, empty for none.
+    #[serde(default)]
+    template: Option<String>,
 }
 
 pub async fn create_new_project(
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(data): Extension<UserData>,
     Json(body): Json<CreateNewProjectBody>,
 ) -> Result<HeaderMap> {
+    // A project without companies falls under the projects without a company.
+    if body.company.is_empty() {
+        capability::require_for_company(&mut transaction, &data, None, Capability::CreateProject).await?;
+    }
+
+    for company in &body.company {
+        capability::require_for_company(&mut transaction, &data, Some(company), Capability::CreateProject).await?;
+    }
+
     let project_id = service::create_new(
         &mut *transaction,
         &body.name,
         &body.location,
         &body.year,
-        body.status,
+        body.lifecycle,
         &body.contract,
     )
     .await
@@ -371,6 +404,15 @@ pub async fn create_new_project(
         industry_service::add_industry_to_project(&mut *transaction, &project_id, &industry).await;
     }
 
+    if let Some(template_id) = body.template.filter(|template_id| !template_id.is_empty()) {
+        project_template_service::apply_template(&mut *transaction, &template_id, &project_id)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to apply template {template_id} to project {project_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
     transaction
         .commit()
         .await
@@ -402,8 +444,11 @@ pub async fn name_for_id(
 
 pub async fn delete_project(
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(data): Extension<UserData>,
     Path(project_id): Path<String>,
 ) -> Result<()> {
+    capability::require_for_project(&mut transaction, &data, &project_id, Capability::DeleteProject).await?;
+
     service::delete_project(&mut *transaction, &project_id, false)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
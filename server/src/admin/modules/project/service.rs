@@ -3,17 +3,29 @@ use serde::Deserialize;
 use sqlx::{SqliteConnection, query, query_as, query_scalar};
 use uuid::Uuid;
 
-use crate::admin::modules::image::service::{self as image_service, ImageData};
+use crate::admin::modules::{
+    image::service::{self as image_service, ImageData},
+    lifecycle::service::Lifecycle,
+    slug::service::unique_slug,
+    translation::service::Language,
+};
 
 pub struct ProjectItem {
     pub id: String,
     pub name: String,
+    pub slug: String,
     pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub manual_coordinates: bool,
     pub year: Option<i64>,
     pub weight: Option<i64>,
     pub learn_more: Option<String>,
-    pub status: bool,
+    pub lifecycle: String,
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
     pub publiek_privaat: String,
+    pub publiek_privaat_label: String,
     pub header_photo: Option<String>,
     pub custom_header_photo: Option<bool>,
     pub header_photo_copyright: Option<String>,
@@ -40,11 +52,18 @@ pub async fn get_project_company_info(
     SELECT
         p.id, 
         p.name,
+        p.slug,
         p.location,
+        p.latitude,
+        p.longitude,
+        p.manual_coordinates,
         p.year,
         p.learn_more,
-        p.status,
+        p.lifecycle,
+        p.start_date,
+        p.delivery_date,
         p.publiek_privaat,
+        COALESCE((SELECT label FROM PUBLIEK_PRIVAAT_TYPES WHERE name = p.publiek_privaat), p.publiek_privaat) as "publiek_privaat_label!: String",
         p.draft,
         pc.show_in_carousel,
         pc.weight,
@@ -120,11 +139,11 @@ pub async fn full_list(
             PROJECT_COMPANIES pcb
             ON 
             p.id=pcb.project_id AND pc.draft==p.draft
-        LEFT JOIN 
-            PERMISSIONS pm
-            ON 
-            (pm.company=pc.company_name OR (pm.company IS NULL AND pc.company_name IS NULL))
-        WHERE p.name LIKE $1 AND pm.user=$2 AND pm.edit=1
+        WHERE p.name LIKE $1 AND EXISTS(
+            SELECT 1 FROM user_capabilities uc
+            WHERE uc.user=$2 AND uc.capability='edit_project'
+            AND (uc.everywhere=1 OR uc.company=pc.company_name OR (uc.company IS NULL AND pc.company_name IS NULL))
+        )
         GROUP BY p.id
         ORDER BY name"#, filter, user_id)
     .fetch_all(conn)
@@ -136,26 +155,30 @@ pub async fn create_new(
     name: &str,
     location: &Option<String>,
     year: &Option<i64>,
-    status: bool,
+    lifecycle: Lifecycle,
     publiek_privaat: &str,
 ) -> Result<String, sqlx::Error> {
+    let lifecycle = lifecycle.as_str();
     let project_id = Uuid::new_v4().to_string();
+    let slug = unique_slug(&mut *conn, name, &project_id, Language::Nl).await?;
 
     query!(
         "INSERT INTO PROJECTS(
             id, 
             name, 
+            slug,
             location, 
             year, 
-            status, 
+            lifecycle, 
             publiek_privaat, 
             draft
-        ) VALUES ($1, $2, $3, $4, $5, $6, 1)",
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, 1)",
         project_id,
         name,
+        slug,
         location,
         year,
-        status,
+        lifecycle,
         publiek_privaat
     )
     .execute(&mut *conn)
@@ -2,7 +2,7 @@ use maud::{Markup, PreEscaped, html};
 
 use crate::{
     admin::modules::{
-        banner::template::project_banner, company_list::{service::CompanyLists, template::company_list_template}, content::{service::ContentEntry, template::content_template}, core_numbers::{self, service::CoreNumber}, header::template::project_header, image::{service::ImageData, template::lightbox}, industry::{service::IndustryData, template::project_industries}, introduction::template::introduction_template, labels::{service::Label, template::labels_template}, location::template::project_location, party_list::{service::ProjectPartyData, template::party_list_template}, publiek_privaat::template::publiek_privaat_template, show_in_carousel::template::show_in_carousel_template, status::template::status_template, thumbnail::template::thumbnail_template, tms::service::ProjectTMData, visible::template::visible_template, weight::template::project_weight, year::template::project_year
+        banner::template::project_banner, company_list::{service::CompanyLists, template::company_list_template}, content::{service::ContentEntry, template::content_template}, core_numbers::{self, service::CoreNumber}, header::template::project_header, image::{service::ImageData, template::lightbox}, industry::{service::IndustryData, template::project_industries}, introduction::template::introduction_template, labels::{service::Label, template::labels_template}, lifecycle::{service::Lifecycle, template::{lifecycle_template, phases_loader, project_dates}}, location::{service::ProjectCoordinates, template::project_location}, party_list::{service::ProjectPartyData, template::party_list_template}, publiek_privaat::template::publiek_privaat_template, related_projects::template::related_projects_loader, show_in_carousel::template::show_in_carousel_template, slug::template::project_slug, thumbnail::template::thumbnail_template, tms::service::ProjectTMData, translation::template::language_select_loader, visible::template::visible_template, weight::template::project_weight, year::template::project_year
     },
     self_replacing_svg,
 };
@@ -33,6 +33,10 @@ pub fn page_buttons(project_id: &str, draft: bool, hx_swap_oob: bool) -> Markup
 
             button #unpublish hx-delete={"/api/projects/"(project_id)"/unpublish"} title="Unpublish the live site" hx-confirm="Are you sure you want to unpublish the live site? (A draft version will automatically be created)" hx-target="#page" { "UNPUBLISH" }
             
+            button #duplicate hx-post={"/api/projects/"(project_id)"/duplicate"} title="Copy the project into a new draft" hx-prompt="What's the name of the copy?" { "DUPLICATE" }
+
+            button #save-as-template hx-post={"/api/projects/"(project_id)"/template"} title="Save the project as a template for new projects" hx-prompt="What's the template's name?" hx-swap="none" { "TEMPLATE" }
+
             button #delete hx-delete={"/api/projects/"(project_id)} title="Delete the project" hx-confirm="Are you sure you want to delete this project?" hx-swap="delete" hx-target={"li[data-project-id='"(project_id)"'"} hx-on::after-request="page.close()" { "DELETE" }
 
             button #close-dialog hx-preserve="true" hx-push-url="true" hx-get="/projects" title="Close page" hx-select="#main-content" hx-swap="outerHTML" hx-target="#main-content" {}
@@ -58,8 +62,8 @@ pub fn project_template(
         div #selects new[!project.published] {
             div #project-settings {
                 (project_weight(&project.id, &company_name, &project.weight))
-                (publiek_privaat_template(&project.id, &project.publiek_privaat, company_name.is_some()))
-                (status_template(&project.id, project.status, company_name.is_some()))
+                (publiek_privaat_template(&project.id, &project.publiek_privaat_label, company_name.is_some()))
+                (lifecycle_template(&project.id, Lifecycle::from_name(&project.lifecycle), company_name.is_some()))
                 (show_in_carousel_template(&project.id, project.show_in_carousel, company_name.is_none()))
                 (visible_template(&project.id, project.visible, company_name.is_none()))
                 (thumbnail_template(&project.id, &project.thumbnail, company_name.is_none()))
@@ -68,6 +72,8 @@ pub fn project_template(
 
             (company_list_template(&project.id, &company_name, &company_lists.active, &company_lists.non_active))
 
+            (language_select_loader(&project.id))
+
             (page_buttons(&project.id, project.draft ,false))
 
             style #selects-extra-styling {}
@@ -87,7 +93,9 @@ pub fn project_template(
                         hr {}
                         (project_industries(&project.id, industry_data))
                         (project_year(&project.id, &project.year))
-                        (project_location(&project.id, &project.location))
+                        (project_dates(&project.id, &project.start_date, &project.delivery_date))
+                        (project_location(&project.id, &project.location, &ProjectCoordinates { latitude: project.latitude, longitude: project.longitude, manual: project.manual_coordinates }))
+                        (project_slug(&project.id, &project.slug))
                     }
 
                     (introduction_template(&project.id, company_name, &project.introduction, !project.custom_introduction.unwrap_or(false)))
@@ -95,11 +103,15 @@ pub fn project_template(
 
                 (core_numbers::template::core_numbers(&project.id, core_numbers))
 
+                (phases_loader(&project.id))
+
                 (content_template(&project.id, content, !project.custom_content.unwrap_or(false)))
 
                 (project_banner(&project.id, &project.banner_photo, &project.banner_photo_copyright, !project.custom_banner_photo.unwrap_or(false)))
 
                 (lightbox(&project.id, images, !project.custom_lightbox.unwrap_or(false)))
+
+                (related_projects_loader(&project.id))
             }
         }
 
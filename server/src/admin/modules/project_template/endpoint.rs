@@ -0,0 +1,85 @@
+use axum::{
+    Extension,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::Result,
+};
+use maud::Markup;
+
+use crate::{
+    admin::{DBConnection, modules::duplicate::service::source_draft},
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+};
+
+use super::{service, template};
+
+pub async fn get_templates(DBConnection(mut connection): DBConnection) -> Result<Markup> {
+    let templates = service::get_templates(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::templates_section(&templates))
+}
+
+pub async fn get_template_select(DBConnection(mut connection): DBConnection) -> Result<Markup> {
+    let templates = service::get_templates(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::template_select(&templates))
+}
+
+/// Saves the project as a template, under the name given in the prompt.
+pub async fn create_template(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Path(project_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<()> {
+    capability::require_for_project(&mut connection, &user, &project_id, Capability::EditProject).await?;
+
+    let name = headers
+        .get("HX-Prompt")
+        .and_then(|name| name.to_str().ok())
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .ok_or((StatusCode::BAD_REQUEST, "Missing the template name."))?;
+
+    let draft = source_draft(&mut connection, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    service::create_template(&mut connection, &project_id, draft, name)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to save project {project_id} as a template: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn delete_template(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Path(template_id): Path<String>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::CreateProject).await?;
+
+    service::delete_template(&mut connection, &template_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to delete template {template_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let templates = service::get_templates(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::templates_section(&templates))
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+use uuid::Uuid;
+
+use crate::admin::modules::{content::service::get_content, party_list::service as party_service};
+
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateParty {
+    #[serde(rename = "type")]
+    pub party_type: String,
+    pub name: String,
+}
+
+/// What a new project gets from the template. The texts and photos aren't part of it, apart from the content which
+/// is there as an outline to fill in.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TemplateData {
+    pub parties: Vec<TemplateParty>,
+    pub tms: Vec<TemplateParty>,
+    pub industries: Vec<String>,
+    pub labels: Vec<String>,
+    /// The titles, the numbers are left empty.
+    pub core_numbers: Vec<String>,
+    /// The texts of the entries on the base page, in order.
+    pub content: Vec<String>,
+}
+
+pub async fn get_templates(conn: &mut SqliteConnection) -> Result<Vec<ProjectTemplate>, sqlx::Error> {
+    query_as!(ProjectTemplate, "SELECT id, name FROM PROJECT_TEMPLATES ORDER BY name")
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn delete_template(conn: &mut SqliteConnection, template_id: &str) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM PROJECT_TEMPLATES WHERE id=$1", template_id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Saves the structure of a version of the project as a template.
+pub async fn create_template(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    let parties = query_as!(
+        TemplateParty,
+        r#"SELECT type as party_type, party_name as name FROM PROJECT_PARTIES WHERE project_id=$1 AND draft=$2 ORDER BY type, position"#,
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let tms = query_as!(
+        TemplateParty,
+        r#"SELECT type as party_type, tm_name as name FROM PROJECT_TMS WHERE project_id=$1 AND draft=$2 ORDER BY type, tm_name"#,
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let industries = query_scalar!(
+        "SELECT industry FROM PROJECT_INDUSTRIES WHERE project_id=$1 AND draft=$2 ORDER BY industry",
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let labels = query_scalar!(
+        "SELECT tag FROM PROJECT_TAGS WHERE project_id=$1 AND draft=$2 ORDER BY tag",
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let core_numbers = query_scalar!(
+        "SELECT title FROM CORE_NUMBERS WHERE project_id=$1 AND draft=$2 ORDER BY position",
+        project_id,
+        draft
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let content = get_content(&mut *conn, project_id, &None, draft)
+        .await?
+        .into_iter()
+        .map(|entry| entry.text)
+        .collect();
+
+    let data = serde_json::to_string(&TemplateData {
+        parties,
+        tms,
+        industries,
+        labels,
+        core_numbers,
+        content,
+    })
+    .unwrap_or_default();
+
+    let id = Uuid::new_v4().to_string();
+
+    query!(
+        "INSERT INTO PROJECT_TEMPLATES(id, name, data) VALUES ($1, $2, $3)",
+        id,
+        name,
+        data
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Fills in the draft of a new project from the template. What the project already has is kept, and the TMs,
+/// industries and labels that were removed since the template was saved are left out.
+pub async fn apply_template(
+    conn: &mut SqliteConnection,
+    template_id: &str,
+    project_id: &str,
+) -> Result<(), sqlx::Error> {
+    let data = query_scalar!("SELECT data FROM PROJECT_TEMPLATES WHERE id=$1", template_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let data: TemplateData = serde_json::from_str(&data).unwrap_or_default();
+
+    for party in &data.parties {
+        let exists = query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM PROJECT_PARTIES WHERE project_id=$1 AND draft=1 AND party_name=$2 AND type=$3) as "exists!: bool""#,
+            project_id,
+            party.name,
+            party.party_type
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if !exists {
+            party_service::add_party_to_project(&mut *conn, project_id, &party.name, &party.party_type).await?;
+        }
+    }
+
+    for tm in &data.tms {
+        query!(
+            "INSERT OR IGNORE INTO PROJECT_TMS(project_id, tm_name, draft, type)
+            SELECT $1, $2, 1, $3 WHERE EXISTS(SELECT 1 FROM TMS WHERE name=$2)",
+            project_id,
+            tm.name,
+            tm.party_type
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for industry in &data.industries {
+        query!(
+            "INSERT OR IGNORE INTO PROJECT_INDUSTRIES(project_id, draft, industry)
+            SELECT $1, 1, $2 WHERE EXISTS(SELECT 1 FROM INDUSTRIES WHERE name=$2)",
+            project_id,
+            industry
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for label in &data.labels {
+        query!(
+            "INSERT OR IGNORE INTO PROJECT_TAGS(project_id, tag, draft)
+            SELECT $1, $2, 1 WHERE EXISTS(SELECT 1 FROM TAGS WHERE name=$2)",
+            project_id,
+            label
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for title in &data.core_numbers {
+        let id = Uuid::new_v4().to_string();
+
+        query!(
+            "INSERT INTO CORE_NUMBERS(id, project_id, title, number, draft, position)
+            VALUES ($1, $2, $3, '', 1, (SELECT COALESCE(MAX(position) + 1, 0) FROM CORE_NUMBERS WHERE project_id=$2 AND draft=1))",
+            id,
+            project_id,
+            title
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    // A new project has no content yet, so the outline starts the chain of the base page.
+    let mut previous_entry: Option<String> = None;
+
+    for text in &data.content {
+        let id = Uuid::new_v4().to_string();
+
+        query!(
+            "INSERT INTO PROJECT_CONTENT(project_id, company_name, id, previous_entry, draft, text)
+            VALUES ($1, NULL, $2, $3, 1, $4)",
+            project_id,
+            id,
+            previous_entry,
+            text
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        previous_entry = Some(id);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use maud::{html, Markup};
+
+use super::service::ProjectTemplate;
+
+pub fn templates_section(templates: &[ProjectTemplate]) -> Markup {
+    html!(
+        section.taxonomy #project-templates {
+            h2 { "Project templates" }
+            p { "Saved from a project with its TEMPLATE button, new projects can start from them." }
+            ol {
+                @for template in templates {
+                    li {
+                        span { (template.name) }
+                        form.delete hx-delete={"/api/project-templates/"(template.id)} hx-target="closest section" hx-swap="outerHTML" hx-confirm={"Are you sure you want to delete " (template.name) "?"} {
+                            input type="submit" value="Delete";
+                        }
+                    }
+                }
+            }
+            @if templates.is_empty() {
+                p { "Nothing yet." }
+            }
+        }
+    )
+}
+
+/// The choice of template in the dialog that creates a project.
+pub fn template_select(templates: &[ProjectTemplate]) -> Markup {
+    html!(
+        @if !templates.is_empty() {
+            label.template {
+                select name="template" {
+                    option value="" { "No template" }
+                    @for template in templates {
+                        option value=(template.id) { (template.name) }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -1,27 +1,25 @@
-
-use axum::{extract::{Path, State}, http::StatusCode, response::{IntoResponse, Result}, Form};
+use axum::{extract::Path, http::StatusCode, response::Result};
 use maud::Markup;
-use serde::Deserialize;
 
-use crate::{admin::{modules::project::template::page_buttons_update_wrapper, DBTransaction}, AppState, TransactionError};
+use crate::{admin::{modules::project::template::page_buttons_update_wrapper, DBTransaction}, TransactionError};
 
 use super::{service as service, template};
 
-#[derive(Deserialize)]
-pub struct SetPubliekPrivaatBody {
-    r#type: String
-}
-
+/// Switches the project to the next publiek/privaat type.
 pub async fn set_publiek_privaat(
     Path(project_id): Path<String>,
     DBTransaction(mut transaction): DBTransaction<'_>,
-    Form(body): Form<SetPubliekPrivaatBody>
 ) -> Result<Markup> {
-    let r#type = service::set_publiek_privaat(&mut *transaction, &project_id, &body.r#type).await.map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let next = service::next_publiek_privaat(&mut transaction, &project_id).await.map_err(|err| {
+        eprintln!("Failed to find the next publiek/privaat type of project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    service::set_publiek_privaat(&mut transaction, &project_id, &next.name).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     transaction.commit().await.map_err(|_| TransactionError::Commit)?;
 
-    let template = template::publiek_privaat_template(&project_id, &r#type, false);
+    let template = template::publiek_privaat_template(&project_id, &next.label, false);
 
     Ok(page_buttons_update_wrapper(&project_id, true, &template))
-}
\ No newline at end of file
+}
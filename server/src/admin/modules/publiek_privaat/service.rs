@@ -1,4 +1,9 @@
-use sqlx::{query_scalar, SqliteConnection};
+use sqlx::{query_as, query_scalar, SqliteConnection};
+
+pub struct PubliekPrivaat {
+    pub name: String,
+    pub label: String,
+}
 
 pub async fn set_publiek_privaat(
     conn: &mut SqliteConnection,
@@ -10,26 +15,28 @@ pub async fn set_publiek_privaat(
     .await
 }
 
-const publiek_privaat_types: [(&str, &str); 3] = [
-    ("Publiek", "publiek"),
-    ("Privaat", "privaat"),
-    ("PPS", "pps"),
-];
-
-pub fn get_current_and_next(
-    r#type: &str
-) -> ((&str, &str), (&str, &str)) {
-    let mut index = publiek_privaat_types.iter().position(|t| t.1 == r#type).unwrap_or(0);
-
-    let current = publiek_privaat_types[index];
+/// The type after the current one of the project's draft, in the order of the taxonomy admin. After the last one
+/// comes the first one again.
+pub async fn next_publiek_privaat(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<PubliekPrivaat, sqlx::Error> {
+    let types = query_as!(
+        PubliekPrivaat,
+        r#"SELECT name, COALESCE(label, name) as "label!: String" FROM PUBLIEK_PRIVAAT_TYPES ORDER BY position, name"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
 
-    if index >= publiek_privaat_types.len() - 1 { 
-        index = 0;
-    } else {
-        index += 1;
-    }
+    let current = query_scalar!("SELECT publiek_privaat FROM PROJECTS WHERE id=$1 AND draft=1", project_id)
+        .fetch_one(&mut *conn)
+        .await?;
 
-    let next = publiek_privaat_types[index];
+    let index = types
+        .iter()
+        .position(|t| t.name == current)
+        .map(|index| (index + 1) % types.len())
+        .unwrap_or(0);
 
-    (current, next)
-}
\ No newline at end of file
+    types.into_iter().nth(index).ok_or(sqlx::Error::RowNotFound)
+}
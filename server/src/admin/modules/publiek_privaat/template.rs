@@ -1,13 +1,9 @@
 use maud::{Markup, html};
 
-use super::service;
-
-pub fn publiek_privaat_template(project_id: &str, r#type: &str, disabled: bool) -> Markup {
-    let (current, next) = service::get_current_and_next(&r#type);
-    
+pub fn publiek_privaat_template(project_id: &str, label: &str, disabled: bool) -> Markup {
     html!(        
-        button #publiek-privaat title="Aanbesteding" disabled[disabled] hx-patch={"/api/projects/"(project_id)"/publiek-privaat"} name="type" hx-swap="outerHTML" value=(next.1) {
-            (current.0)
+        button #publiek-privaat title="Aanbesteding" disabled[disabled] hx-patch={"/api/projects/"(project_id)"/publiek-privaat"} hx-swap="outerHTML" {
+            (label)
         }
     )
-}
\ No newline at end of file
+}
@@ -0,0 +1,135 @@
+use axum::{
+    Form,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Result,
+};
+use maud::{Markup, PreEscaped};
+use serde::Deserialize;
+use sqlx::SqliteTransaction;
+
+use crate::{
+    TransactionError,
+    admin::{
+        DBConnection, DBTransaction, draft::check_for_existing_project_draft,
+        modules::project::template::page_buttons_update_wrapper,
+    },
+};
+
+use super::{service, template};
+
+pub async fn get_related_projects(
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+) -> Result<Markup> {
+    let draft = check_for_existing_project_draft(&mut connection, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let related_projects = service::get_related_projects(&mut connection, &project_id, draft)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the related projects of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::related_projects(&project_id, &related_projects))
+}
+
+#[derive(Deserialize)]
+pub struct RelatedProjectSearchQuery {
+    filter: Option<String>,
+}
+
+pub async fn related_project_search_list(
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Query(query): Query<RelatedProjectSearchQuery>,
+) -> Result<Markup> {
+    let projects = service::related_project_search_list(&mut connection, &project_id, &query.filter)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::related_project_search_results(&project_id, &projects))
+}
+
+/// The section after a change to the draft, with the buttons to save the draft.
+async fn updated_section(
+    mut transaction: SqliteTransaction<'_>,
+    project_id: &str,
+) -> Result<Markup> {
+    let related_projects = service::get_related_projects(&mut transaction, project_id, true)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(
+        project_id,
+        true,
+        &template::related_projects(project_id, &related_projects),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct AddRelatedProjectBody {
+    related_project_id: String,
+}
+
+pub async fn add_related_project(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<AddRelatedProjectBody>,
+) -> Result<Markup> {
+    if body.related_project_id == project_id {
+        return Err((StatusCode::BAD_REQUEST, "A project can't be related to itself.").into());
+    }
+
+    service::add_related_project(&mut transaction, &project_id, &body.related_project_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to relate project {} to project {project_id}: {err}", body.related_project_id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    updated_section(transaction, &project_id).await
+}
+
+pub async fn delete_related_project(
+    Path((project_id, related_project_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+) -> Result<Markup> {
+    service::delete_related_project(&mut transaction, &project_id, &related_project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    updated_section(transaction, &project_id).await
+}
+
+#[derive(Deserialize)]
+pub struct MoveRelatedProjectBody {
+    next: Option<String>,
+}
+
+pub async fn move_related_project(
+    Path((project_id, related_project_id)): Path<(String, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<MoveRelatedProjectBody>,
+) -> Result<Markup> {
+    service::move_related_project(&mut transaction, &project_id, &related_project_id, &body.next)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &PreEscaped::default()))
+}
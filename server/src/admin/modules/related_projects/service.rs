@@ -0,0 +1,110 @@
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+
+use crate::admin::position::{move_before, renumber};
+
+pub struct RelatedProject {
+    pub id: String,
+    pub name: String,
+}
+
+/// The related projects picked for the project, in order. A project that was only ever a draft is named too.
+pub async fn get_related_projects(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+) -> Result<Vec<RelatedProject>, sqlx::Error> {
+    query_as!(RelatedProject, r#"
+        SELECT
+            rp.related_project_id as id,
+            (SELECT name FROM PROJECTS WHERE id=rp.related_project_id ORDER BY draft LIMIT 1) as "name!: String"
+        FROM RELATED_PROJECTS rp
+        WHERE rp.project_id=$1 AND rp.draft=$2
+        ORDER BY rp.position
+    "#, project_id, draft)
+    .fetch_all(conn)
+    .await
+}
+
+/// The published projects whose name matches the filter and that aren't related to the draft yet.
+pub async fn related_project_search_list(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    filter: &Option<String>,
+) -> Result<Vec<RelatedProject>, sqlx::Error> {
+    let filter = format!("%{}%", filter.as_deref().unwrap_or_default());
+
+    query_as!(RelatedProject, r#"
+        SELECT p.id, p.name
+        FROM PROJECTS p
+        WHERE
+            p.draft=0
+            AND p.id!=$1
+            AND p.name LIKE $2
+            AND NOT EXISTS(SELECT 1 FROM RELATED_PROJECTS WHERE project_id=$1 AND draft=1 AND related_project_id=p.id)
+        ORDER BY p.name
+        LIMIT 10
+    "#, project_id, filter)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn add_related_project(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    related_project_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!("
+        INSERT OR IGNORE INTO RELATED_PROJECTS(project_id, draft, related_project_id, position)
+        VALUES ($1, 1, $2, (SELECT COALESCE(MAX(position) + 1, 0) FROM RELATED_PROJECTS WHERE project_id=$1 AND draft=1))
+    ", project_id, related_project_id)
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn delete_related_project(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    related_project_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM RELATED_PROJECTS WHERE project_id=$1 AND draft=1 AND related_project_id=$2",
+        project_id,
+        related_project_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Moves the related project of the draft in front of `next`, or to the end.
+pub async fn move_related_project(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    related_project_id: &str,
+    next: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    let mut ids = query_scalar!(
+        "SELECT related_project_id FROM RELATED_PROJECTS WHERE project_id=$1 AND draft=1 ORDER BY position",
+        project_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !move_before(&mut ids, &related_project_id.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &ids, async |conn, position, id| {
+        query!(
+            "UPDATE RELATED_PROJECTS SET position=$1 WHERE project_id=$2 AND draft=1 AND related_project_id=$3",
+            position,
+            project_id,
+            id
+        )
+        .execute(conn)
+        .await
+        .map(|_| ())
+    })
+    .await
+}
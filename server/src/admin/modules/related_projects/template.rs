@@ -0,0 +1,49 @@
+use maud::{html, Markup};
+
+use crate::self_replacing_svg;
+
+use super::service::RelatedProject;
+
+pub fn related_projects_loader(project_id: &str) -> Markup {
+    html!(
+        section #related-projects hx-get={"/api/projects/"(project_id)"/related"} hx-trigger="load" hx-swap="outerHTML" {}
+    )
+}
+
+pub fn related_projects(project_id: &str, related_projects: &[RelatedProject]) -> Markup {
+    let url = format!("/api/projects/{project_id}/related");
+
+    html!(
+        section #related-projects {
+            h2 { "Related projects" }
+            @if related_projects.is_empty() {
+                p.hint { "The sites show the projects with the most in common, pick projects to show these instead." }
+            }
+            ol data-reorder {
+                @for related_project in related_projects {
+                    li data-name=(related_project.id) data-move={(url)"/"(related_project.id)"/move"} {
+                        div.move title="Drag to reorder" {
+                            (self_replacing_svg("/move.svg"))
+                        }
+                        span { (related_project.name) }
+                        button.delete title="Remove" hx-delete={(url)"/"(related_project.id)} hx-target="#related-projects" hx-swap="outerHTML" {}
+                    }
+                }
+            }
+            div.related-project-add {
+                input name="filter" placeholder="Type a project name" autocomplete="off" hx-get={(url)"/search"} hx-trigger="focus, input changed delay:500ms" hx-target="next .results";
+                div.results {}
+            }
+        }
+    )
+}
+
+pub fn related_project_search_results(project_id: &str, projects: &[RelatedProject]) -> Markup {
+    html!(
+        @for project in projects {
+            button name="related_project_id" value=(project.id) hx-post={"/api/projects/"(project_id)"/related"} hx-target="#related-projects" hx-swap="outerHTML" {
+                (project.name)
+            }
+        }
+    )
+}
@@ -0,0 +1,33 @@
+use axum::{extract::Path, http::StatusCode, response::Result, Form};
+use maud::Markup;
+use serde::Deserialize;
+
+use crate::{admin::{modules::project::template::page_buttons_update_wrapper, DBTransaction}, TransactionError};
+
+use super::{service, template::project_slug};
+
+#[derive(Deserialize)]
+pub struct SetSlugBody {
+    slug: String
+}
+
+/// Changes the slug of the draft. It's made URL safe and unique, the old one redirects once the draft is saved.
+pub async fn set_slug(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<SetSlugBody>
+) -> Result<Markup> {
+    if body.slug.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "The slug can't be empty.").into());
+    }
+
+    let slug = service::set_slug(&mut transaction, &project_id, &body.slug).await.map_err(|err| {
+        eprintln!("Could not update the slug for project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    transaction.commit().await.map_err(|_| TransactionError::Commit)?;
+
+    let content = project_slug(&project_id, &slug);
+    Ok(page_buttons_update_wrapper(&project_id, true, &content))
+}
\ No newline at end of file
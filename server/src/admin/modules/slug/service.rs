@@ -0,0 +1,104 @@
+use sqlx::{query, query_scalar, SqliteConnection};
+
+use crate::admin::{modules::translation::service::Language, slug::slugify};
+
+/// The paths under `/projecten/` the sites use themselves.
+const RESERVED_SLUGS: [&str; 4] = ["carousel", "filter", "in-de-kijker", "kaart"];
+
+async fn is_slug_taken(
+    conn: &mut SqliteConnection,
+    slug: &str,
+    project_id: &str,
+    language: &str,
+) -> Result<bool, sqlx::Error> {
+    if RESERVED_SLUGS.contains(&slug) {
+        return Ok(true);
+    }
+
+    query_scalar!(r#"
+        SELECT
+            EXISTS(SELECT 1 FROM PROJECTS WHERE $3 = 'nl' AND slug=$1 AND id!=$2)
+            OR EXISTS(SELECT 1 FROM PROJECT_TRANSLATIONS WHERE language=$3 AND slug=$1 AND project_id!=$2)
+            OR EXISTS(SELECT 1 FROM SLUG_HISTORY WHERE language=$3 AND slug=$1 AND project_id!=$2)
+        as "taken!: bool"
+    "#, slug, project_id, language)
+    .fetch_one(conn)
+    .await
+}
+
+/// The slug of `name` for the project in the language. When another project has or had that slug, -2, -3, ... is
+/// appended until it's free.
+pub async fn unique_slug(
+    conn: &mut SqliteConnection,
+    name: &str,
+    project_id: &str,
+    language: Language,
+) -> Result<String, sqlx::Error> {
+    let base = slugify(name);
+    let mut slug = base.clone();
+    let mut suffix = 1;
+
+    while is_slug_taken(&mut *conn, &slug, project_id, language.code()).await? {
+        suffix += 1;
+        slug = format!("{base}-{suffix}");
+    }
+
+    Ok(slug)
+}
+
+pub async fn set_slug(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    slug: &str,
+) -> Result<String, sqlx::Error> {
+    let slug = unique_slug(&mut *conn, slug, project_id, Language::Nl).await?;
+
+    query_scalar!("UPDATE PROJECTS SET slug=$1 WHERE id=$2 AND draft=1 RETURNING slug", slug, project_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Keeps the slugs of the published project that the draft changed, in Dutch and in the translations, so the sites
+/// can redirect them. Has to happen before the draft replaces the published project.
+pub async fn record_replaced_slugs(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!("
+        INSERT OR REPLACE INTO SLUG_HISTORY(slug, language, project_id)
+        SELECT p.slug, 'nl', p.id
+        FROM PROJECTS p
+        JOIN PROJECTS d ON d.id=p.id AND d.draft=1
+        WHERE p.id=$1 AND p.draft=0 AND p.slug != d.slug
+    ", project_id)
+    .execute(&mut *conn)
+    .await?;
+
+    query!("
+        INSERT OR REPLACE INTO SLUG_HISTORY(slug, language, project_id)
+        SELECT p.slug, p.language, p.project_id
+        FROM PROJECT_TRANSLATIONS p
+        WHERE p.project_id=$1 AND p.draft=0 AND p.field='name' AND p.slug IS NOT NULL
+        AND p.slug IS NOT (
+            SELECT d.slug FROM PROJECT_TRANSLATIONS d
+            WHERE d.project_id=p.project_id AND d.draft=1 AND d.language=p.language AND d.field='name' AND d.item=''
+        )
+    ", project_id)
+    .execute(&mut *conn)
+    .await?;
+
+    // A slug the project gets back is no longer an old one.
+    query!("
+        DELETE FROM SLUG_HISTORY
+        WHERE project_id=$1 AND (
+            (language='nl' AND slug IN (SELECT slug FROM PROJECTS WHERE id=$1 AND draft=1))
+            OR EXISTS(
+                SELECT 1 FROM PROJECT_TRANSLATIONS
+                WHERE project_id=$1 AND draft=1 AND language=SLUG_HISTORY.language AND slug=SLUG_HISTORY.slug
+            )
+        )
+    ", project_id)
+    .execute(&mut *conn)
+    .await
+    .map(|_| ())
+}
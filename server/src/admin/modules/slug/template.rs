@@ -0,0 +1,10 @@
+use maud::{html, Markup};
+
+pub fn project_slug(project_id: &str, slug: &str) -> Markup {
+    html!(
+        div.year.draft hx-swap="outerHTML" {
+            div.icon style="mask-image: url(/project_pagina/link.svg)" {}
+            input placeholder="Slug" value=(slug) name="slug" title={"/projecten/"(slug)} required hx-patch={"/api/projects/"(project_id)"/slug"} hx-trigger="change" hx-select="input" {}
+        }
+    )
+}
\ No newline at end of file
@@ -0,0 +1,342 @@
+use axum::{
+    Extension, Form,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Result,
+};
+use maud::Markup;
+use serde::Deserialize;
+use sqlx::SqliteConnection;
+
+use crate::{
+    TransactionError,
+    admin::{DBConnection, DBTransaction, modules::company_list::service::full_company_list},
+    auth::{capability, service::UserData},
+};
+
+use super::{
+    service::{self, Taxonomy},
+    template,
+};
+
+async fn taxonomy_section(conn: &mut SqliteConnection, taxonomy: Taxonomy) -> Result<Markup> {
+    let entries = service::get_entries(&mut *conn, taxonomy)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the {}: {err}", taxonomy.as_str());
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let companies = full_company_list(conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::taxonomy_section(taxonomy, &entries, &companies))
+}
+
+async fn entry_row(conn: &mut SqliteConnection, taxonomy: Taxonomy, name: &str) -> Result<Markup> {
+    let entry = service::get_entry(&mut *conn, taxonomy, name)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let companies = full_company_list(conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::entry_row(taxonomy, &entry, &companies))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub async fn get_taxonomy(
+    Path(taxonomy): Path<Taxonomy>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, taxonomy.capability()).await?;
+
+    taxonomy_section(&mut connection, taxonomy).await
+}
+
+#[derive(Deserialize)]
+pub struct CreateEntryBody {
+    name: String,
+}
+
+pub async fn create_entry(
+    Path(taxonomy): Path<Taxonomy>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<CreateEntryBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    let name = body.name.trim();
+
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let exists = service::check_if_entry_exists(&mut transaction, taxonomy, name)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if exists {
+        return Err((StatusCode::CONFLICT, format!("{name} already exists.")).into());
+    }
+
+    service::create_entry(&mut transaction, taxonomy, name)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create {name} in the {}: {err}", taxonomy.as_str());
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let content = taxonomy_section(&mut transaction, taxonomy).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEntryBody {
+    name: String,
+    label: Option<String>,
+    description: Option<String>,
+}
+
+/// Saves the details of an entry. A new name is applied to every project that uses the entry.
+pub async fn update_entry(
+    Path((taxonomy, entry)): Path<(Taxonomy, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<UpdateEntryBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    let name = body.name.trim();
+
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    service::set_details(
+        &mut transaction,
+        taxonomy,
+        &entry,
+        &non_empty(body.label),
+        &non_empty(body.description),
+    )
+    .await
+    .map_err(|err| {
+        eprintln!("Failed to update {entry} in the {}: {err}", taxonomy.as_str());
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if name != entry {
+        if taxonomy.fixed().contains(&entry.as_str()) {
+            return Err((StatusCode::CONFLICT, format!("{entry} is used by the editor and the sites, it can't be renamed.")).into());
+        }
+
+        let exists = service::check_if_entry_exists(&mut transaction, taxonomy, name)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if exists {
+            return Err((StatusCode::CONFLICT, format!("{name} already exists, delete {entry} with {name} as its replacement instead.")).into());
+        }
+
+        service::rename_entry(&mut transaction, taxonomy, &entry, name)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to rename {entry} to {name} in the {}: {err}", taxonomy.as_str());
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let content = taxonomy_section(&mut transaction, taxonomy).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct SetIconBody {
+    image: Option<String>,
+}
+
+pub async fn set_icon(
+    Path((taxonomy, entry)): Path<(Taxonomy, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<SetIconBody>,
+) -> Result<Markup> {
+    if !taxonomy.has_icon() {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    service::set_industry_icon(&mut transaction, &entry, &non_empty(body.image))
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the icon of industry {entry}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let content = entry_row(&mut transaction, taxonomy, &entry).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct SetCompaniesBody {
+    #[serde(default)]
+    company: Vec<String>,
+}
+
+pub async fn set_companies(
+    Path((taxonomy, entry)): Path<(Taxonomy, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    axum_extra::extract::Form(body): axum_extra::extract::Form<SetCompaniesBody>,
+) -> Result<Markup> {
+    if !taxonomy.is_filter() {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    service::set_companies(&mut transaction, taxonomy, &entry, &body.company)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the companies of {entry} in the {}: {err}", taxonomy.as_str());
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let content = entry_row(&mut transaction, taxonomy, &entry).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
+
+#[derive(Deserialize)]
+pub struct MoveEntryBody {
+    next: Option<String>,
+}
+
+pub async fn move_entry(
+    Path((taxonomy, entry)): Path<(Taxonomy, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<MoveEntryBody>,
+) -> Result<()> {
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    service::move_entry(&mut transaction, taxonomy, &entry, &body.next)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            err => {
+                eprintln!("Failed to move {entry} in the {}: {err}", taxonomy.as_str());
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct DeleteEntryQuery {
+    replacement: Option<String>,
+}
+
+/// Deletes an entry. When projects use it, they get the replacement instead, without one the entry is kept.
+pub async fn delete_entry(
+    Path((taxonomy, entry)): Path<(Taxonomy, String)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Query(query): Query<DeleteEntryQuery>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, taxonomy.capability()).await?;
+
+    if taxonomy.fixed().contains(&entry.as_str()) {
+        return Err((StatusCode::CONFLICT, format!("{entry} is used by the editor and the sites, it can't be deleted.")).into());
+    }
+
+    let current = service::get_entry(&mut transaction, taxonomy, &entry)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    match non_empty(query.replacement) {
+        Some(replacement) => {
+            if replacement == entry {
+                return Err(StatusCode::BAD_REQUEST.into());
+            }
+
+            let exists = service::check_if_entry_exists(&mut transaction, taxonomy, &replacement)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            if !exists {
+                return Err((StatusCode::NOT_FOUND, format!("There is no {replacement}.")).into());
+            }
+
+            service::merge_entries(&mut transaction, taxonomy, &entry, &replacement)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to replace {entry} with {replacement} in the {}: {err}", taxonomy.as_str());
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+        }
+        None => {
+            if current.projects > 0 {
+                return Err((StatusCode::CONFLICT, format!("{entry} is used by {} projects, choose a replacement.", current.projects)).into());
+            }
+
+            service::delete_entry(&mut transaction, taxonomy, &entry)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to delete {entry} from the {}: {err}", taxonomy.as_str());
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+        }
+    }
+
+    let content = taxonomy_section(&mut transaction, taxonomy).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(content)
+}
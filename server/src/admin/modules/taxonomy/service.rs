@@ -0,0 +1,695 @@
+use serde::Deserialize;
+use sqlx::{query, query_as, query_scalar, SqliteConnection};
+
+use crate::{
+    admin::position::{move_before, renumber},
+    auth::capability::Capability,
+};
+
+/// The lists projects are classified with. Every taxonomy has its own table, so the queries are written out for each
+/// of them.
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Taxonomy {
+    Industries,
+    Labels,
+    PubliekPrivaat,
+    Tms,
+    PartyTypes,
+}
+
+impl Taxonomy {
+    pub const ALL: [Taxonomy; 5] = [
+        Taxonomy::Industries,
+        Taxonomy::Labels,
+        Taxonomy::PubliekPrivaat,
+        Taxonomy::Tms,
+        Taxonomy::PartyTypes,
+    ];
+
+    /// The segment in the URL, and the name in `TAXONOMY_COMPANIES`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Taxonomy::Industries => "industries",
+            Taxonomy::Labels => "labels",
+            Taxonomy::PubliekPrivaat => "publiek-privaat",
+            Taxonomy::Tms => "tms",
+            Taxonomy::PartyTypes => "party-types",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Taxonomy::Industries => "Industries",
+            Taxonomy::Labels => "Labels",
+            Taxonomy::PubliekPrivaat => "Publiek/privaat",
+            Taxonomy::Tms => "TMs",
+            Taxonomy::PartyTypes => "Party types",
+        }
+    }
+
+    /// Publiek/privaat goes with the industries as both classify the project itself, the party types go with the
+    /// parties.
+    pub fn capability(&self) -> Capability {
+        match self {
+            Taxonomy::Industries | Taxonomy::PubliekPrivaat => Capability::ManageIndustries,
+            Taxonomy::Labels => Capability::ManageLabels,
+            Taxonomy::Tms => Capability::ManageTms,
+            Taxonomy::PartyTypes => Capability::ManageParties,
+        }
+    }
+
+    /// Whether the sites offer it as a filter, which gives the entries a label for visitors and a choice of
+    /// companies.
+    pub fn is_filter(&self) -> bool {
+        matches!(
+            self,
+            Taxonomy::Industries | Taxonomy::Labels | Taxonomy::PubliekPrivaat
+        )
+    }
+
+    pub fn has_icon(&self) -> bool {
+        *self == Taxonomy::Industries
+    }
+
+    /// The entries the code relies on: the party types the editor and the sites show, and the publiek/privaat type
+    /// new projects get. They can't be renamed or deleted.
+    pub fn fixed(&self) -> &'static [&'static str] {
+        match self {
+            Taxonomy::PartyTypes => &["client", "architect", "contractor", "tm"],
+            Taxonomy::PubliekPrivaat => &["publiek"],
+            _ => &[],
+        }
+    }
+}
+
+pub struct TaxonomyEntry {
+    pub name: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub position: i64,
+    pub projects: i64,
+    /// The companies whose sites offer the entry, comma separated. Empty when every site does.
+    pub companies: Option<String>,
+}
+
+impl TaxonomyEntry {
+    pub fn companies(&self) -> Vec<&str> {
+        self.companies
+            .as_deref()
+            .map(|companies| companies.split(',').collect())
+            .unwrap_or_default()
+    }
+}
+
+pub async fn get_entries(conn: &mut SqliteConnection, taxonomy: Taxonomy) -> Result<Vec<TaxonomyEntry>, sqlx::Error> {
+    let taxonomy_name = taxonomy.as_str();
+
+    match taxonomy {
+        Taxonomy::Industries => {
+            query_as!(
+                TaxonomyEntry,
+                r#"SELECT
+                    t.name,
+                    t.label,
+                    t.description,
+                    t.icon,
+                    t.position,
+                    (SELECT COUNT(DISTINCT project_id) FROM PROJECT_INDUSTRIES WHERE industry = t.name) as "projects!: i64",
+                    (SELECT GROUP_CONCAT(company) FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = t.name) as companies
+                FROM INDUSTRIES t
+                ORDER BY t.position, t.name"#,
+                taxonomy_name
+            )
+            .fetch_all(conn)
+            .await
+        }
+        Taxonomy::Labels => {
+            query_as!(
+                TaxonomyEntry,
+                r#"SELECT
+                    t.name,
+                    t.label,
+                    t.description,
+                    NULL as "icon: String",
+                    t.position,
+                    (SELECT COUNT(DISTINCT project_id) FROM PROJECT_TAGS WHERE tag = t.name) as "projects!: i64",
+                    (SELECT GROUP_CONCAT(company) FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = t.name) as companies
+                FROM TAGS t
+                ORDER BY t.position, t.name"#,
+                taxonomy_name
+            )
+            .fetch_all(conn)
+            .await
+        }
+        Taxonomy::PubliekPrivaat => {
+            query_as!(
+                TaxonomyEntry,
+                r#"SELECT
+                    t.name,
+                    t.label,
+                    t.description,
+                    NULL as "icon: String",
+                    t.position,
+                    (SELECT COUNT(DISTINCT id) FROM PROJECTS WHERE publiek_privaat = t.name) as "projects!: i64",
+                    (SELECT GROUP_CONCAT(company) FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = t.name) as companies
+                FROM PUBLIEK_PRIVAAT_TYPES t
+                ORDER BY t.position, t.name"#,
+                taxonomy_name
+            )
+            .fetch_all(conn)
+            .await
+        }
+        Taxonomy::Tms => {
+            query_as!(
+                TaxonomyEntry,
+                r#"SELECT
+                    t.name,
+                    NULL as "label: String",
+                    t.description,
+                    NULL as "icon: String",
+                    t.position,
+                    (SELECT COUNT(DISTINCT project_id) FROM PROJECT_TMS WHERE tm_name = t.name) as "projects!: i64",
+                    (SELECT GROUP_CONCAT(company) FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = t.name) as companies
+                FROM TMS t
+                ORDER BY t.position, t.name"#,
+                taxonomy_name
+            )
+            .fetch_all(conn)
+            .await
+        }
+        Taxonomy::PartyTypes => {
+            query_as!(
+                TaxonomyEntry,
+                r#"SELECT
+                    t.name,
+                    NULL as "label: String",
+                    t.description,
+                    NULL as "icon: String",
+                    t.position,
+                    (
+                        SELECT COUNT(DISTINCT project_id) FROM (
+                            SELECT project_id, type FROM PROJECT_PARTIES
+                            UNION ALL
+                            SELECT project_id, type FROM PROJECT_TMS
+                        ) WHERE type = t.name
+                    ) as "projects!: i64",
+                    (SELECT GROUP_CONCAT(company) FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = t.name) as companies
+                FROM PARTY_TYPES t
+                ORDER BY t.position, t.name"#,
+                taxonomy_name
+            )
+            .fetch_all(conn)
+            .await
+        }
+    }
+}
+
+/// The lists are short, so the entry is looked up in the whole list.
+pub async fn get_entry(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+) -> Result<Option<TaxonomyEntry>, sqlx::Error> {
+    let entries = get_entries(conn, taxonomy).await?;
+
+    Ok(entries.into_iter().find(|entry| entry.name == name))
+}
+
+pub async fn check_if_entry_exists(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+) -> Result<bool, sqlx::Error> {
+    match taxonomy {
+        Taxonomy::Industries => {
+            query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM INDUSTRIES WHERE name = $1) as "exists!: bool""#, name)
+                .fetch_one(conn)
+                .await
+        }
+        Taxonomy::Labels => {
+            query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM TAGS WHERE name = $1) as "exists!: bool""#, name)
+                .fetch_one(conn)
+                .await
+        }
+        Taxonomy::PubliekPrivaat => {
+            query_scalar!(
+                r#"SELECT EXISTS(SELECT 1 FROM PUBLIEK_PRIVAAT_TYPES WHERE name = $1) as "exists!: bool""#,
+                name
+            )
+            .fetch_one(conn)
+            .await
+        }
+        Taxonomy::Tms => {
+            query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM TMS WHERE name = $1) as "exists!: bool""#, name)
+                .fetch_one(conn)
+                .await
+        }
+        Taxonomy::PartyTypes => {
+            query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM PARTY_TYPES WHERE name = $1) as "exists!: bool""#, name)
+                .fetch_one(conn)
+                .await
+        }
+    }
+}
+
+/// Adds the entry at the end of the list.
+pub async fn create_entry(conn: &mut SqliteConnection, taxonomy: Taxonomy, name: &str) -> Result<(), sqlx::Error> {
+    match taxonomy {
+        Taxonomy::Industries => {
+            query!(
+                "INSERT INTO INDUSTRIES(name, position) VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM INDUSTRIES))",
+                name
+            )
+            .execute(conn)
+            .await
+        }
+        Taxonomy::Labels => {
+            query!(
+                "INSERT INTO TAGS(name, position) VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM TAGS))",
+                name
+            )
+            .execute(conn)
+            .await
+        }
+        Taxonomy::PubliekPrivaat => {
+            query!(
+                "INSERT INTO PUBLIEK_PRIVAAT_TYPES(name, position)
+                VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM PUBLIEK_PRIVAAT_TYPES))",
+                name
+            )
+            .execute(conn)
+            .await
+        }
+        Taxonomy::Tms => {
+            query!(
+                "INSERT INTO TMS(name, position) VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM TMS))",
+                name
+            )
+            .execute(conn)
+            .await
+        }
+        Taxonomy::PartyTypes => {
+            query!(
+                "INSERT INTO PARTY_TYPES(name, position) VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM PARTY_TYPES))",
+                name
+            )
+            .execute(conn)
+            .await
+        }
+    }
+    .map(|_| ())
+}
+
+/// Only the taxonomies the sites filter on have a label.
+pub async fn set_details(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+    label: &Option<String>,
+    description: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    match taxonomy {
+        Taxonomy::Industries => {
+            query!("UPDATE INDUSTRIES SET label = $1, description = $2 WHERE name = $3", label, description, name)
+                .execute(conn)
+                .await
+        }
+        Taxonomy::Labels => {
+            query!("UPDATE TAGS SET label = $1, description = $2 WHERE name = $3", label, description, name)
+                .execute(conn)
+                .await
+        }
+        Taxonomy::PubliekPrivaat => {
+            query!(
+                "UPDATE PUBLIEK_PRIVAAT_TYPES SET label = $1, description = $2 WHERE name = $3",
+                label,
+                description,
+                name
+            )
+            .execute(conn)
+            .await
+        }
+        Taxonomy::Tms => {
+            query!("UPDATE TMS SET description = $1 WHERE name = $2", description, name)
+                .execute(conn)
+                .await
+        }
+        Taxonomy::PartyTypes => {
+            query!("UPDATE PARTY_TYPES SET description = $1 WHERE name = $2", description, name)
+                .execute(conn)
+                .await
+        }
+    }
+    .map(|_| ())
+}
+
+pub async fn set_industry_icon(conn: &mut SqliteConnection, name: &str, icon: &Option<String>) -> Result<(), sqlx::Error> {
+    query!("UPDATE INDUSTRIES SET icon = $1 WHERE name = $2", icon, name)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Replaces `source` with `target` on every project, and removes `source`. The details of `target` are kept, the ones
+/// it lacks are taken from `source`.
+///
+/// The published projects change along with the drafts and the sites show the merge right away, it doesn't wait for
+/// the projects to be published. Their published version would otherwise keep pointing at an entry that is gone.
+pub async fn merge_entries(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    source: &str,
+    target: &str,
+) -> Result<(), sqlx::Error> {
+    // `UPDATE OR IGNORE` leaves a reference behind when the project already has the target, `delete_entry` removes
+    // those with the source.
+    match taxonomy {
+        Taxonomy::Industries => {
+            query!(
+                "UPDATE INDUSTRIES SET
+                    label = COALESCE(label, (SELECT label FROM INDUSTRIES WHERE name = $1)),
+                    description = COALESCE(description, (SELECT description FROM INDUSTRIES WHERE name = $1)),
+                    icon = COALESCE(icon, (SELECT icon FROM INDUSTRIES WHERE name = $1))
+                WHERE name = $2",
+                source,
+                target
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            query!("UPDATE OR IGNORE PROJECT_INDUSTRIES SET industry = $2 WHERE industry = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Taxonomy::Labels => {
+            query!(
+                "UPDATE TAGS SET
+                    label = COALESCE(label, (SELECT label FROM TAGS WHERE name = $1)),
+                    description = COALESCE(description, (SELECT description FROM TAGS WHERE name = $1))
+                WHERE name = $2",
+                source,
+                target
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            query!("UPDATE OR IGNORE PROJECT_TAGS SET tag = $2 WHERE tag = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Taxonomy::PubliekPrivaat => {
+            query!(
+                "UPDATE PUBLIEK_PRIVAAT_TYPES SET
+                    label = COALESCE(label, (SELECT label FROM PUBLIEK_PRIVAAT_TYPES WHERE name = $1)),
+                    description = COALESCE(description, (SELECT description FROM PUBLIEK_PRIVAAT_TYPES WHERE name = $1))
+                WHERE name = $2",
+                source,
+                target
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            query!("UPDATE PROJECTS SET publiek_privaat = $2 WHERE publiek_privaat = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Taxonomy::Tms => {
+            query!(
+                "UPDATE TMS SET description = COALESCE(description, (SELECT description FROM TMS WHERE name = $1))
+                WHERE name = $2",
+                source,
+                target
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            query!("UPDATE OR IGNORE PROJECT_TMS SET tm_name = $2 WHERE tm_name = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("UPDATE OR IGNORE TM_COMPANIES SET name = $2 WHERE name = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("UPDATE OR IGNORE TM_PARTY SET name = $2 WHERE name = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Taxonomy::PartyTypes => {
+            query!(
+                "UPDATE PARTY_TYPES SET
+                    description = COALESCE(description, (SELECT description FROM PARTY_TYPES WHERE name = $1))
+                WHERE name = $2",
+                source,
+                target
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            query!("UPDATE OR IGNORE PROJECT_PARTIES SET type = $2 WHERE type = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("UPDATE OR IGNORE PROJECT_TMS SET type = $2 WHERE type = $1", source, target)
+                .execute(&mut *conn)
+                .await?;
+        }
+    }
+
+    // An entry offered on every site stays that way, otherwise it is also offered where the source was.
+    let taxonomy_name = taxonomy.as_str();
+
+    query!(
+        "UPDATE OR IGNORE TAXONOMY_COMPANIES SET name = $3
+        WHERE taxonomy = $1 AND name = $2 AND EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = $3)",
+        taxonomy_name,
+        source,
+        target
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    delete_entry(conn, taxonomy, source).await
+}
+
+/// Renames the entry everywhere it is used, on the published projects too, so the sites show it right away like a
+/// merge. The new name must not be taken, that is a merge.
+pub async fn rename_entry(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+    new_name: &str,
+) -> Result<(), sqlx::Error> {
+    match taxonomy {
+        Taxonomy::Industries => {
+            query!(
+                "INSERT INTO INDUSTRIES(name, label, description, icon, position)
+                SELECT $2, label, description, icon, position FROM INDUSTRIES WHERE name = $1",
+                name,
+                new_name
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Taxonomy::Labels => {
+            query!(
+                "INSERT INTO TAGS(name, label, description, position)
+                SELECT $2, label, description, position FROM TAGS WHERE name = $1",
+                name,
+                new_name
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Taxonomy::PubliekPrivaat => {
+            query!(
+                "INSERT INTO PUBLIEK_PRIVAAT_TYPES(name, label, description, position)
+                SELECT $2, label, description, position FROM PUBLIEK_PRIVAAT_TYPES WHERE name = $1",
+                name,
+                new_name
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Taxonomy::Tms => {
+            query!(
+                "INSERT INTO TMS(name, description, position) SELECT $2, description, position FROM TMS WHERE name = $1",
+                name,
+                new_name
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Taxonomy::PartyTypes => {
+            query!(
+                "INSERT INTO PARTY_TYPES(name, description, position)
+                SELECT $2, description, position FROM PARTY_TYPES WHERE name = $1",
+                name,
+                new_name
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    let taxonomy_name = taxonomy.as_str();
+
+    query!(
+        "UPDATE TAXONOMY_COMPANIES SET name = $3 WHERE taxonomy = $1 AND name = $2",
+        taxonomy_name,
+        name,
+        new_name
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    merge_entries(conn, taxonomy, name, new_name).await
+}
+
+/// Removes the entry and what links to it. Only meant for entries no project uses, the others have to be merged.
+/// Publiek/privaat is a column of the project, which can't be removed, so it has to be merged first.
+pub async fn delete_entry(conn: &mut SqliteConnection, taxonomy: Taxonomy, name: &str) -> Result<(), sqlx::Error> {
+    let taxonomy_name = taxonomy.as_str();
+
+    query!("DELETE FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = $2", taxonomy_name, name)
+        .execute(&mut *conn)
+        .await?;
+
+    match taxonomy {
+        Taxonomy::Industries => {
+            query!("DELETE FROM PROJECT_INDUSTRIES WHERE industry = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM INDUSTRIES WHERE name = $1", name)
+                .execute(conn)
+                .await?;
+        }
+        Taxonomy::Labels => {
+            query!("DELETE FROM PROJECT_TAGS WHERE tag = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM TAGS WHERE name = $1", name)
+                .execute(conn)
+                .await?;
+        }
+        Taxonomy::PubliekPrivaat => {
+            query!("DELETE FROM PUBLIEK_PRIVAAT_TYPES WHERE name = $1", name)
+                .execute(conn)
+                .await?;
+        }
+        Taxonomy::Tms => {
+            query!("DELETE FROM PROJECT_TMS WHERE tm_name = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM TM_COMPANIES WHERE name = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM TM_PARTY WHERE name = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM TMS WHERE name = $1", name)
+                .execute(conn)
+                .await?;
+        }
+        Taxonomy::PartyTypes => {
+            query!("DELETE FROM PROJECT_PARTIES WHERE type = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM PROJECT_TMS WHERE type = $1", name)
+                .execute(&mut *conn)
+                .await?;
+
+            query!("DELETE FROM PARTY_TYPES WHERE name = $1", name)
+                .execute(conn)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves the entry in front of `next`, or to the end when there is none, and renumbers the list.
+pub async fn move_entry(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+    next: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    let mut names = get_entries(&mut *conn, taxonomy)
+        .await?
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect::<Vec<_>>();
+
+    if !move_before(&mut names, &name.to_string(), next.as_ref()) {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    renumber(conn, &names, async |conn, position, name| {
+        match taxonomy {
+            Taxonomy::Industries => {
+                query!("UPDATE INDUSTRIES SET position = $1 WHERE name = $2", position, name)
+                    .execute(conn)
+                    .await?
+            }
+            Taxonomy::Labels => {
+                query!("UPDATE TAGS SET position = $1 WHERE name = $2", position, name)
+                    .execute(conn)
+                    .await?
+            }
+            Taxonomy::PubliekPrivaat => {
+                query!("UPDATE PUBLIEK_PRIVAAT_TYPES SET position = $1 WHERE name = $2", position, name)
+                    .execute(conn)
+                    .await?
+            }
+            Taxonomy::Tms => {
+                query!("UPDATE TMS SET position = $1 WHERE name = $2", position, name)
+                    .execute(conn)
+                    .await?
+            }
+            Taxonomy::PartyTypes => {
+                query!("UPDATE PARTY_TYPES SET position = $1 WHERE name = $2", position, name)
+                    .execute(conn)
+                    .await?
+            }
+        };
+
+        Ok(())
+    })
+    .await
+}
+
+/// Limits the sites that offer the entry to those of `companies`, or offers it everywhere when there are none.
+pub async fn set_companies(
+    conn: &mut SqliteConnection,
+    taxonomy: Taxonomy,
+    name: &str,
+    companies: &[String],
+) -> Result<(), sqlx::Error> {
+    let taxonomy = taxonomy.as_str();
+
+    query!("DELETE FROM TAXONOMY_COMPANIES WHERE taxonomy = $1 AND name = $2", taxonomy, name)
+        .execute(&mut *conn)
+        .await?;
+
+    for company in companies {
+        query!(
+            "INSERT INTO TAXONOMY_COMPANIES(taxonomy, name, company) VALUES ($1, $2, $3)",
+            taxonomy,
+            name,
+            company
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use maud::{Markup, html};
+
+use crate::self_replacing_svg;
+
+use super::service::{Taxonomy, TaxonomyEntry};
+
+pub fn entry_row(taxonomy: Taxonomy, entry: &TaxonomyEntry, companies: &[String]) -> Markup {
+    let url = format!("/api/taxonomies/{}/{}", taxonomy.as_str(), entry.name);
+    let fixed = taxonomy.fixed().contains(&entry.name.as_str());
+    let active_companies = entry.companies();
+
+    html!(
+        li.entry data-name=(entry.name) data-move={(url)"/move"} {
+            div.move title="Drag to reorder" {
+                (self_replacing_svg("/move.svg"))
+            }
+            @if taxonomy.has_icon() {
+                div.icon {
+                    @if let Some(icon) = &entry.icon {
+                        img src=(icon) alt="";
+                    }
+                    input type="hidden" name="image" hx-trigger="change" hx-patch={(url)"/icon"} hx-target="closest li" hx-swap="outerHTML";
+                    button title="Choose an icon" onclick="update_header_photo(this.previousElementSibling)" {
+                        (self_replacing_svg("/edit.svg"))
+                    }
+                    @if entry.icon.is_some() {
+                        button title="Remove the icon" hx-patch={(url)"/icon"} hx-target="closest li" hx-swap="outerHTML" {
+                            (self_replacing_svg("/clear.svg"))
+                        }
+                    }
+                }
+            }
+            form.details hx-patch=(url) hx-trigger="change" hx-target="closest section" hx-swap="outerHTML" {
+                input name="name" value=(entry.name) required readonly[fixed] title=[fixed.then_some("Used by the editor and the sites, it can't be renamed.")];
+                @if taxonomy.is_filter() {
+                    input name="label" value=[&entry.label] placeholder="Label on the sites";
+                }
+                input name="description" value=[&entry.description] placeholder="Description";
+            }
+            @if taxonomy.is_filter() {
+                details.companies {
+                    summary {
+                        @if active_companies.is_empty() {
+                            "All sites"
+                        } @else {
+                            (active_companies.join(", "))
+                        }
+                    }
+                    form hx-put={(url)"/companies"} hx-trigger="change" hx-target="closest li" hx-swap="outerHTML" {
+                        @for company in companies {
+                            label {
+                                input type="checkbox" name="company" value=(company) checked[active_companies.contains(&company.as_str())];
+                                (company)
+                            }
+                        }
+                    }
+                }
+            }
+            span.projects { (entry.projects) " projects" }
+            @if !fixed {
+                form.delete hx-delete=(url) hx-target="closest section" hx-swap="outerHTML" hx-confirm={
+                    @if entry.projects > 0 {
+                        "Every project with " (entry.name) " will get the replacement instead. Do you want to continue?"
+                    } @else {
+                        "Are you sure you want to delete " (entry.name) "?"
+                    }
+                } {
+                    @if entry.projects > 0 {
+                        input name="replacement" list={(taxonomy.as_str())"-names"} placeholder="Replace with" required;
+                    }
+                    input type="submit" value="Delete";
+                }
+            }
+        }
+    )
+}
+
+pub fn taxonomy_section(taxonomy: Taxonomy, entries: &[TaxonomyEntry], companies: &[String]) -> Markup {
+    html!(
+        section.taxonomy id={"taxonomy-"(taxonomy.as_str())} {
+            h2 { (taxonomy.title()) }
+            form.add hx-post={"/api/taxonomies/"(taxonomy.as_str())} hx-target="closest section" hx-swap="outerHTML" {
+                input name="name" placeholder="Name" required;
+                input type="submit" value="Add";
+            }
+            datalist id={(taxonomy.as_str())"-names"} {
+                @for entry in entries {
+                    option value=(entry.name) {}
+                }
+            }
+            ol data-reorder {
+                @for entry in entries {
+                    (entry_row(taxonomy, entry, companies))
+                }
+            }
+            @if entries.is_empty() {
+                p { "Nothing yet." }
+            }
+        }
+    )
+}
@@ -1,5 +1,5 @@
 use axum::{
-    Form,
+    Extension, Form,
     extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Result},
@@ -18,6 +18,10 @@ use crate::{
             tms::template::{list_companies, list_parties, tm_list_companies, tm_list_parties},
         },
     },
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
 };
 
 use super::{
@@ -108,9 +112,12 @@ pub struct AddCompanyToTMBody {
 
 pub async fn add_company_to_tm(
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
     Path(tm): Path<String>,
     Form(body): Form<AddCompanyToTMBody>,
 ) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageTms).await?;
+
     service::add_company_to_tm(&mut *transaction, &tm, &body.company).await;
 
     let companies: (Vec<Company>, Vec<Company>) = get_all_companies(&mut *transaction, &tm)
@@ -137,8 +144,11 @@ pub struct DeleteCompanyFromTMQuery {
 pub async fn delete_company_from_tm(
     Path(tm): Path<String>,
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
     Query(query): Query<DeleteCompanyFromTMQuery>,
 ) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageTms).await?;
+
     service::delete_company_from_tm(&mut *transaction, &tm, &query.company).await;
 
     let companies: (Vec<Company>, Vec<Company>) = get_all_companies(&mut *transaction, &tm)
@@ -164,9 +174,12 @@ pub struct AddPartyToTMBody {
 
 pub async fn add_party_to_tm(
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
     Path(tm): Path<String>,
     Form(body): Form<AddPartyToTMBody>,
 ) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageTms).await?;
+
     service::add_party_to_tm(&mut *transaction, &tm, &body.party).await;
 
     let parties: (Vec<Party>, Vec<Party>) = get_all_parties(&mut *transaction, &tm)
@@ -192,9 +205,12 @@ pub struct DeletePartyFromTMQuery {
 
 pub async fn delete_party_from_tm(
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
     Path(tm): Path<String>,
     Query(query): Query<DeletePartyFromTMQuery>,
 ) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageTms).await?;
+
     service::delete_party_from_tm(&mut *transaction, &tm, &query.party).await;
 
     let parties: (Vec<Party>, Vec<Party>) = get_all_parties(&mut *transaction, &tm)
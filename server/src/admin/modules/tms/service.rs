@@ -29,7 +29,7 @@ pub async fn tm_search_list(conn: &mut SqliteConnection, project_id: &str, r#typ
         JOIN PROJECT_TMS pt
         ON t.name = pt.tm_name 
         WHERE pt.project_id = $1 AND pt.draft = $4 AND pt.type = $3 AND t.name LIKE $2
-        ORDER BY t.name LIMIT 10", project_id, filter, r#type, draft)
+        ORDER BY t.position, t.name LIMIT 10", project_id, filter, r#type, draft)
     .fetch_all(conn)
     .await
 }
@@ -0,0 +1,116 @@
+use axum::{Form, extract::Path, http::StatusCode, response::Result};
+use maud::{Markup, html};
+use serde::Deserialize;
+
+use crate::{
+    TransactionError,
+    admin::{
+        DBConnection, DBTransaction, draft::check_for_existing_project_draft,
+        modules::project::template::page_buttons_update_wrapper,
+    },
+};
+
+use super::{
+    service::{self, Field, Language},
+    template,
+};
+
+pub async fn get_language_select(
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+) -> Result<Markup> {
+    let draft = check_for_existing_project_draft(&mut connection, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let missing = service::missing_translations(&mut connection, &project_id, draft)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to count the missing translations of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::language_select(&project_id, Language::Nl, &missing, false))
+}
+
+pub async fn get_translations(
+    Path((project_id, language)): Path<(String, Language)>,
+    DBConnection(mut connection): DBConnection,
+) -> Result<Markup> {
+    if language == Language::Nl {
+        return Err((StatusCode::BAD_REQUEST, "Dutch texts are edited on the project itself.").into());
+    }
+
+    let draft = check_for_existing_project_draft(&mut connection, &project_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let texts = service::get_texts(&mut connection, &project_id, draft, language)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the {} texts of project {project_id}: {err}", language.code());
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let missing = service::missing_translations(&mut connection, &project_id, draft)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(html!(
+        (template::language_select(&project_id, language, &missing, true))
+        (template::translations_template(&project_id, language, &texts))
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct SetTranslationBody {
+    field: Field,
+    item: String,
+    content: Option<String>,
+}
+
+pub async fn set_translation(
+    Path((project_id, language)): Path<(String, Language)>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<SetTranslationBody>,
+) -> Result<Markup> {
+    if language == Language::Nl {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let content = body
+        .content
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty());
+
+    service::set_translation(&mut transaction, &project_id, language, body.field, &body.item, &content)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to save the {} translation of project {project_id}: {err}", language.code());
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let texts = service::get_texts(&mut transaction, &project_id, true, language)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let missing = service::missing_translations(&mut transaction, &project_id, true)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let text = texts
+        .iter()
+        .find(|text| text.field == body.field.as_str() && text.item == body.item)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    let content = html!(
+        (template::language_select(&project_id, language, &missing, true))
+        (template::translation_entry(&project_id, language, text))
+    );
+
+    Ok(page_buttons_update_wrapper(&project_id, true, &content))
+}
@@ -0,0 +1,3 @@
+pub mod template;
+pub mod service;
+pub mod endpoint;
\ No newline at end of file
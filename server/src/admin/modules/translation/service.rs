@@ -0,0 +1,195 @@
+use serde::Deserialize;
+use sqlx::{query, query_as, SqliteConnection};
+
+use crate::admin::modules::slug::service::unique_slug;
+
+/// The languages of the sites. Dutch is the language the projects are written in, the others are translations of it.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Nl,
+    Fr,
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::Nl, Language::Fr, Language::En];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Nl => "nl",
+            Language::Fr => "fr",
+            Language::En => "en",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Nl => "Nederlands",
+            Language::Fr => "Français",
+            Language::En => "English",
+        }
+    }
+}
+
+/// The texts of a project that can be translated, as they're called in `PROJECT_TRANSLATIONS`.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Name,
+    Location,
+    Introduction,
+    ContentText,
+    ContentQuote,
+    ContentQuoteSmall,
+    CoreNumberTitle,
+    PhaseName,
+    ImageAlt,
+}
+
+impl Field {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Location => "location",
+            Field::Introduction => "introduction",
+            Field::ContentText => "content_text",
+            Field::ContentQuote => "content_quote",
+            Field::ContentQuoteSmall => "content_quote_small",
+            Field::CoreNumberTitle => "core_number_title",
+            Field::PhaseName => "phase_name",
+            Field::ImageAlt => "image_alt",
+        }
+    }
+}
+
+pub struct TranslatableText {
+    pub field: String,
+    pub item: String,
+    /// The company of an introduction or content entry that was customised for it.
+    pub company: Option<String>,
+    pub source: String,
+    pub translation: Option<String>,
+}
+
+impl TranslatableText {
+    pub fn label(&self) -> &'static str {
+        match self.field.as_str() {
+            "name" => "Name",
+            "location" => "Location",
+            "introduction" => "Introduction",
+            "content_text" => "Content",
+            "content_quote" => "Quote",
+            "content_quote_small" => "Quote subtitle",
+            "core_number_title" => "Core number",
+            "phase_name" => "Phase",
+            "image_alt" => "Image description",
+            _ => "Text",
+        }
+    }
+
+    /// Whether the text is HTML from the text editor.
+    pub fn is_rich_text(&self) -> bool {
+        matches!(self.field.as_str(), "introduction" | "content_text")
+    }
+}
+
+/// Every text of the project with its translation in the language, in the order of the editor. Empty texts have
+/// nothing to translate and are left out.
+pub async fn get_texts(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+    language: Language,
+) -> Result<Vec<TranslatableText>, sqlx::Error> {
+    let language = language.code();
+
+    query_as!(TranslatableText, r#"
+        WITH texts(field, item, company, source, section, position) AS (
+            SELECT 'name', '', NULL, name, 0, 0 FROM PROJECTS WHERE id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'location', '', NULL, location, 1, 0 FROM PROJECTS WHERE id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'introduction', COALESCE(company_name, ''), company_name, introduction, 2, 0
+            FROM PROJECT_COMPANIES WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'core_number_title', id, NULL, title, 3, position FROM CORE_NUMBERS WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'content_text', id, company_name, text, 4, rowid FROM PROJECT_CONTENT WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'content_quote', id, company_name, quote, 4, rowid FROM PROJECT_CONTENT WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'content_quote_small', id, company_name, quote_small, 4, rowid FROM PROJECT_CONTENT WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'image_alt', id, company_name, alt, 5, position FROM IMAGES WHERE project_id=$1 AND draft=$2
+            UNION ALL
+            SELECT 'phase_name', id, NULL, name, 6, position FROM PROJECT_PHASES WHERE project_id=$1 AND draft=$2
+        )
+        SELECT
+            t.field as "field!: String",
+            t.item as "item!: String",
+            t.company as "company: String",
+            t.source as "source!: String",
+            pt.value as "translation: String"
+        FROM texts t
+        LEFT JOIN PROJECT_TRANSLATIONS pt
+        ON pt.project_id=$1 AND pt.draft=$2 AND pt.language=$3 AND pt.field=t.field AND pt.item=t.item
+        WHERE t.source IS NOT NULL AND trim(t.source) != ''
+        ORDER BY t.section, t.company, t.position
+    "#, project_id, draft, language)
+    .fetch_all(conn)
+    .await
+}
+
+/// How many texts of the project still need a translation, for every language but Dutch.
+pub async fn missing_translations(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    draft: bool,
+) -> Result<Vec<(Language, usize)>, sqlx::Error> {
+    let mut missing = vec![];
+
+    for language in Language::ALL.into_iter().filter(|language| *language != Language::Nl) {
+        let texts = get_texts(&mut *conn, project_id, draft, language).await?;
+
+        missing.push((language, texts.iter().filter(|text| text.translation.is_none()).count()));
+    }
+
+    Ok(missing)
+}
+
+/// Saves the translation on the draft, without a value the text goes back to Dutch. A translated name gives the project
+/// its slug in the language.
+pub async fn set_translation(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    language: Language,
+    field: Field,
+    item: &str,
+    value: &Option<String>,
+) -> Result<(), sqlx::Error> {
+    let slug = match (field, value) {
+        (Field::Name, Some(value)) => Some(unique_slug(&mut *conn, value, project_id, language).await?),
+        _ => None,
+    };
+
+    let language = language.code();
+    let field = field.as_str();
+
+    match value {
+        Some(value) => query!("
+            INSERT INTO PROJECT_TRANSLATIONS(project_id, draft, language, field, item, value, slug) VALUES ($1, 1, $2, $3, $4, $5, $6)
+            ON CONFLICT(project_id, draft, language, field, item) DO UPDATE SET value=excluded.value, slug=excluded.slug
+        ", project_id, language, field, item, value, slug)
+        .execute(&mut *conn)
+        .await
+        .map(|_| ()),
+        None => query!(
+            "DELETE FROM PROJECT_TRANSLATIONS WHERE project_id=$1 AND draft=1 AND language=$2 AND field=$3 AND item=$4",
+            project_id, language, field, item
+        )
+        .execute(&mut *conn)
+        .await
+        .map(|_| ()),
+    }
+}
@@ -0,0 +1,82 @@
+use maud::{Markup, PreEscaped, html};
+
+use super::service::{Language, TranslatableText};
+
+/// The language switcher of the project editor, with the number of texts that still need a translation.
+pub fn language_select(project_id: &str, current: Language, missing: &[(Language, usize)], oob: bool) -> Markup {
+    html!(
+        details #language-select hx-swap-oob=[oob.then_some("true")] {
+            summary title="Languages" {
+                (current.code().to_uppercase())
+            }
+
+            div #language-select-items {
+                button.language current[current == Language::Nl] hx-get={"/projects/"(project_id)} hx-target="#page" hx-select="#page > *" hx-push-url="true" {
+                    (Language::Nl.name())
+                }
+
+                @for (language, count) in missing {
+                    button.language current[current == *language] hx-get={"/api/projects/"(project_id)"/translations/"(language.code())} hx-target="#page-content" hx-swap="outerHTML" {
+                        (language.name())
+                        @if *count > 0 {
+                            span.missing title="Missing translations" { (count) }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+/// Loads the language switcher, so every view of the project editor gets it without counting the translations first.
+pub fn language_select_loader(project_id: &str) -> Markup {
+    html!(
+        div #language-select hx-get={"/api/projects/"(project_id)"/translations"} hx-trigger="load" hx-swap="outerHTML" {}
+    )
+}
+
+pub fn translation_entry(project_id: &str, language: Language, text: &TranslatableText) -> Markup {
+    html!(
+        form.translation missing[text.translation.is_none()] hx-put={"/api/projects/"(project_id)"/translations/"(language.code())} hx-trigger="change" hx-target="this" hx-swap="outerHTML" {
+            input type="hidden" name="field" value=(text.field);
+            input type="hidden" name="item" value=(text.item);
+
+            h3 {
+                (text.label())
+                @if let Some(company) = &text.company {
+                    span.company { (company) }
+                }
+            }
+
+            @if text.is_rich_text() {
+                div.source.content-0 {
+                    (PreEscaped(&text.source))
+                }
+                div.content-0 is="text-editor" {
+                    @if let Some(translation) = &text.translation {
+                        (PreEscaped(translation))
+                    }
+                }
+            } @else {
+                div.source { (text.source) }
+                textarea name="content" is="auto-size" autocomplete="off" placeholder=(text.source) {
+                    (text.translation.as_deref().unwrap_or_default())
+                }
+            }
+        }
+    )
+}
+
+pub fn translations_template(project_id: &str, language: Language, texts: &[TranslatableText]) -> Markup {
+    html!(
+        div #page-content.translations data-project-id=(project_id) lang=(language.code()) {
+            @for text in texts {
+                (translation_entry(project_id, language, text))
+            }
+
+            @if texts.is_empty() {
+                p { "This project has no texts to translate yet." }
+            }
+        }
+    )
+}
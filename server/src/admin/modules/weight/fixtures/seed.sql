@@ -1,10 +1,12 @@
 INSERT INTO PROJECTS(
     id, 
     name,
+    slug,
     draft
 ) VALUES (
     'id',
     'name',
+    'name',
     1
 );
 
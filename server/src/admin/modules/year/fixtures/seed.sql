@@ -1,11 +1,13 @@
 INSERT INTO PROJECTS(
     id, 
     name,
+    slug,
     draft,
     year
 ) VALUES (
     'id',
     'name',
+    'name',
     1,
     1980
 );
\ No newline at end of file
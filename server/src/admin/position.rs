@@ -0,0 +1,32 @@
+use sqlx::SqliteConnection;
+
+/// Moves `moved` in front of `next` in a list of ids or names, or to the end when there is no `next` or it isn't in
+/// the list. Returns false when `moved` isn't in the list. The index of an item is its new position.
+pub fn move_before<T: PartialEq>(list: &mut Vec<T>, moved: &T, next: Option<&T>) -> bool {
+    let Some(index) = list.iter().position(|item| item == moved) else {
+        return false;
+    };
+
+    let moved = list.remove(index);
+
+    let index = next
+        .and_then(|next| list.iter().position(|item| item == next))
+        .unwrap_or(list.len());
+
+    list.insert(index, moved);
+
+    true
+}
+
+/// Stores the index of every item as its position, with `set_position` writing a single item.
+pub async fn renumber<T>(
+    conn: &mut SqliteConnection,
+    list: &[T],
+    mut set_position: impl AsyncFnMut(&mut SqliteConnection, i64, &T) -> Result<(), sqlx::Error>,
+) -> Result<(), sqlx::Error> {
+    for (position, item) in list.iter().enumerate() {
+        set_position(&mut *conn, position as i64, item).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::admin::modules::{lifecycle::service::Lifecycle, project::service::ProjectItem};
+
+/// `lifecycle`, or the deprecated `status` flag of before projects had a lifecycle: `true` was a delivered project,
+/// `false` one in progress. `lifecycle` wins when both are sent.
+fn lifecycle_or_status(lifecycle: Option<Lifecycle>, status: Option<bool>) -> Option<Lifecycle> {
+    lifecycle.or(status.map(|delivered| if delivered { Lifecycle::Delivered } else { Lifecycle::InProgress }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateProjectBody {
+    pub name: String,
+    pub lifecycle: Option<Lifecycle>,
+    /// Deprecated, use `lifecycle`.
+    pub status: Option<bool>,
+    pub contract: String,
+    pub year: Option<i64>,
+    /// The dates are written as YYYY-MM-DD.
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
+    pub location: Option<String>,
+    #[serde(default)]
+    pub client: Vec<String>,
+    #[serde(default)]
+    pub architect: Vec<String>,
+    #[serde(default)]
+    pub contractor: Vec<String>,
+    #[serde(default)]
+    pub company: Vec<String>,
+    #[serde(default)]
+    pub industry: Vec<String>,
+}
+
+impl CreateProjectBody {
+    /// A project is planned unless the body says otherwise.
+    pub fn lifecycle(&self) -> Lifecycle {
+        lifecycle_or_status(self.lifecycle, self.status).unwrap_or(Lifecycle::Planned)
+    }
+}
+
+/// Only the fields that are present are changed. `year`, the dates and `location` can be set to `null` to clear them.
+#[derive(Deserialize)]
+pub struct UpdateProjectBody {
+    pub name: Option<String>,
+    pub lifecycle: Option<Lifecycle>,
+    /// Deprecated, use `lifecycle`.
+    pub status: Option<bool>,
+    pub contract: Option<String>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub year: Option<Option<i64>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub start_date: Option<Option<String>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub delivery_date: Option<Option<String>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub location: Option<Option<String>>,
+}
+
+impl UpdateProjectBody {
+    pub fn lifecycle(&self) -> Option<Lifecycle> {
+        lifecycle_or_status(self.lifecycle, self.status)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub lifecycle: Lifecycle,
+    pub contract: String,
+    pub year: Option<i64>,
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
+    pub location: Option<String>,
+    pub companies: Vec<String>,
+    /// Whether there are changes that aren't published yet.
+    pub draft: bool,
+    pub published: bool,
+}
+
+impl Project {
+    pub fn new(project: ProjectItem, companies: Vec<String>) -> Self {
+        Project {
+            id: project.id,
+            name: project.name,
+            lifecycle: Lifecycle::from_name(&project.lifecycle),
+            contract: project.publiek_privaat,
+            year: project.year,
+            start_date: project.start_date,
+            delivery_date: project.delivery_date,
+            location: project.location,
+            companies,
+            draft: project.draft,
+            published: project.published,
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response, Result},
+};
+use sqlx::{Connection, SqliteConnection};
+
+use crate::{
+    AppState, TransactionError,
+    admin::{
+        DBConnection, DBTransaction,
+        draft::{check_for_existing_project_draft, create_project_draft_if_necessary},
+        modules::{
+            company_list::service as company_list_service,
+            industry::service as industry_service,
+            lifecycle::service as lifecycle_service,
+            location::service as location_service,
+            name::service as name_service,
+            party_list::service as party_service,
+            project::service::{self as project_service, get_project_company_info},
+            publiek_privaat::service as publiek_privaat_service,
+            slug::service as slug_service,
+            year::service as year_service,
+        },
+    },
+    auth::{
+        capability::{self, Capability},
+        service::UserData,
+    },
+};
+
+use super::dto::{CreateProjectBody, Project, UpdateProjectBody};
+
+/// The project as it is being edited, i.e. its draft when it has one.
+async fn load_project(conn: &mut SqliteConnection, project_id: &str) -> Result<Project> {
+    let (project, _) = get_project_company_info(&mut *conn, project_id, &None)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            err => {
+                eprintln!("Failed to get project {project_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    let companies = company_list_service::project_companies(&mut *conn, project_id, project.draft)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the companies of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Project::new(project, companies))
+}
+
+pub async fn create_project(
+    State(state): State<AppState>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<CreateProjectBody>,
+) -> Result<Response> {
+    // A project without companies falls under the projects without a company.
+    if body.company.is_empty() {
+        capability::require_for_company(&mut connection, &user, None, Capability::CreateProject).await?;
+    }
+
+    for company in &body.company {
+        capability::require_for_company(&mut connection, &user, Some(company), Capability::CreateProject).await?;
+    }
+
+    lifecycle_service::check_dates(&body.start_date, &body.delivery_date)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    // The provider is asked before the transaction is opened, so the database isn't locked while waiting for it.
+    let pin = location_service::geocode(state.geocoder.as_deref(), &body.name, &body.location).await;
+
+    let mut transaction = connection.begin().await.map_err(|_| TransactionError::Begin)?;
+
+    let project_id = project_service::create_new(
+        &mut transaction,
+        &body.name,
+        &body.location,
+        &body.year,
+        body.lifecycle(),
+        &body.contract,
+    )
+    .await
+    .map_err(|err| {
+        eprintln!("Failed to create a project: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if body.start_date.is_some() || body.delivery_date.is_some() {
+        lifecycle_service::set_dates(&mut transaction, &project_id, &body.start_date, &body.delivery_date)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to set the dates of project {project_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let parties = [
+        (&body.client, "client"),
+        (&body.architect, "architect"),
+        (&body.contractor, "contractor"),
+    ];
+
+    for (names, r#type) in parties {
+        for party in names {
+            party_service::add_party_to_project(&mut transaction, &project_id, party, r#type)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to add {type} {party} to project {project_id}: {err}");
+                    StatusCode::BAD_REQUEST
+                })?;
+        }
+    }
+
+    for company in &body.company {
+        company_list_service::add_company_to_project(&mut transaction, &project_id, company)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to add company {company} to project {project_id}: {err}");
+                StatusCode::BAD_REQUEST
+            })?;
+    }
+
+    for industry in &body.industry {
+        industry_service::add_industry_to_project(&mut transaction, &project_id, industry)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to add industry {industry} to project {project_id}: {err}");
+                StatusCode::BAD_REQUEST
+            })?;
+    }
+
+    location_service::update_geocoded_coordinates(&mut transaction, &project_id, pin, false)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to set the coordinates of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let project = load_project(&mut transaction, &project_id).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok((StatusCode::CREATED, Json(project)).into_response())
+}
+
+pub async fn get_project(
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Json<Project>> {
+    capability::require_for_project(&mut connection, &user, &project_id, Capability::EditProject).await?;
+
+    Ok(Json(load_project(&mut connection, &project_id).await?))
+}
+
+pub async fn update_project(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<UpdateProjectBody>,
+) -> Result<Json<Project>> {
+    capability::require_for_project(&mut connection, &user, &project_id, Capability::EditProject).await?;
+
+    // The provider is asked before the transaction is opened, so the database isn't locked while waiting for it. A pin
+    // an editor placed isn't moved, see `update_geocoded_coordinates`.
+    let pin = match &body.location {
+        Some(location) => Some(location_service::geocode(state.geocoder.as_deref(), &project_id, location).await),
+        None => None,
+    };
+
+    let mut transaction = connection.begin().await.map_err(|_| TransactionError::Begin)?;
+
+    // Like in the editor, changes go to the draft and are only visible once the project is published.
+    create_project_draft_if_necessary(&mut transaction, &project_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Could not create a draft for project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let failed = |err: sqlx::Error| {
+        eprintln!("Failed to update project {project_id}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    if let Some(name) = &body.name {
+        name_service::set_name(&mut transaction, &project_id, name)
+            .await
+            .map_err(failed)?;
+    }
+
+    if let Some(lifecycle) = body.lifecycle() {
+        lifecycle_service::set_lifecycle(&mut transaction, &project_id, lifecycle)
+            .await
+            .map_err(failed)?;
+    }
+
+    // A date that isn't in the body keeps its value.
+    if body.start_date.is_some() || body.delivery_date.is_some() {
+        let current = load_project(&mut transaction, &project_id).await?;
+        let start_date = body.start_date.clone().unwrap_or(current.start_date);
+        let delivery_date = body.delivery_date.clone().unwrap_or(current.delivery_date);
+
+        lifecycle_service::check_dates(&start_date, &delivery_date)
+            .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+        lifecycle_service::set_dates(&mut transaction, &project_id, &start_date, &delivery_date)
+            .await
+            .map_err(failed)?;
+    }
+
+    if let Some(contract) = &body.contract {
+        publiek_privaat_service::set_publiek_privaat(&mut transaction, &project_id, contract)
+            .await
+            .map_err(failed)?;
+    }
+
+    if let Some(year) = body.year {
+        year_service::update_project_year(&mut transaction, &project_id, year)
+            .await
+            .map_err(failed)?;
+    }
+
+    if let (Some(location), Some(pin)) = (&body.location, pin) {
+        location_service::update_geocoded_coordinates(&mut transaction, &project_id, pin, false)
+            .await
+            .map_err(failed)?;
+
+        location_service::update_project_location(&mut transaction, &project_id, location)
+            .await
+            .map_err(failed)?;
+    }
+
+    let project = load_project(&mut transaction, &project_id).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(Json(project))
+}
+
+pub async fn publish_project(
+    Path(project_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+) -> Result<Json<Project>> {
+    capability::require_for_project(&mut transaction, &user, &project_id, Capability::Publish).await?;
+
+    let has_draft = check_for_existing_project_draft(&mut transaction, &project_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to check for a draft of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Without a draft there is nothing to publish, and removing the published version would lose the project.
+    if !has_draft {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    slug_service::record_replaced_slugs(&mut transaction, &project_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to keep the old slugs of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    project_service::delete_project(&mut transaction, &project_id, false)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to remove the published version of project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    project_service::update_project_draft(&mut transaction, &project_id, false)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to publish project {project_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let project = load_project(&mut transaction, &project_id).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(Json(project))
+}
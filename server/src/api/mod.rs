@@ -0,0 +1,29 @@
+use axum::{
+    Router,
+    middleware::from_fn_with_state,
+    routing::{get, post},
+};
+
+use crate::{AppState, auth::service::api_auth_middleware};
+
+pub mod dto;
+pub mod endpoint;
+
+/// A JSON API for scripts and integrations, authenticated with a personal API token.
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects",
+            post(endpoint::create_project).layer(from_fn_with_state(state.clone(), api_auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}",
+            get(endpoint::get_project)
+                .patch(endpoint::update_project)
+                .layer(from_fn_with_state(state.clone(), api_auth_middleware)),
+        )
+        .route(
+            "/projects/{project_id}/publish",
+            post(endpoint::publish_project).layer(from_fn_with_state(state, api_auth_middleware)),
+        )
+}
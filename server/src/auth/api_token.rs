@@ -0,0 +1,209 @@
+use std::time::{SystemTime, SystemTimeError, UNIX_EPOCH};
+
+use rand::{Rng, distr::Alphanumeric};
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, query, query_as, query_scalar};
+use uuid::Uuid;
+
+/// Lets a token be recognised in logs and secret scanners.
+const PREFIX: &str = "gvr_";
+
+#[derive(Debug)]
+pub enum ApiTokenError {
+    SQL(sqlx::Error),
+    SystemTimeError,
+}
+
+impl From<sqlx::Error> for ApiTokenError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiTokenError::SQL(err)
+    }
+}
+
+impl From<SystemTimeError> for ApiTokenError {
+    fn from(_: SystemTimeError) -> Self {
+        ApiTokenError::SystemTimeError
+    }
+}
+
+fn unix_now() -> Result<i64, SystemTimeError> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+/// The token has enough entropy that a fast hash is as good as a slow one, which keeps the lookup cheap on every
+/// request.
+fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub all_companies: bool,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+pub struct ApiTokenScope {
+    pub token_id: String,
+    /// A capability or a company, depending on the query.
+    pub value: Option<String>,
+}
+
+pub struct NewApiToken<'a> {
+    pub name: &'a str,
+    /// In seconds.
+    pub lifetime: i64,
+    pub capabilities: &'a [String],
+    pub all_companies: bool,
+    /// `None` stands for the projects without a company.
+    pub companies: &'a [Option<String>],
+}
+
+/// Creates a token for the user and returns it, it can't be retrieved afterwards. Capabilities the user doesn't have
+/// are dropped.
+pub async fn create(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    new_token: NewApiToken<'_>,
+) -> Result<String, ApiTokenError> {
+    let id = Uuid::new_v4().to_string();
+    let secret: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    let token = format!("{PREFIX}{secret}");
+    let token_hash = hash(&token);
+    let now = unix_now()?;
+    let expires_at = now + new_token.lifetime;
+
+    query!(
+        "INSERT INTO API_TOKENS(id, user_id, name, token_hash, all_companies, created_at, expires_at) VALUES($1, $2, $3, $4, $5, $6, $7)",
+        id,
+        user_id,
+        new_token.name,
+        token_hash,
+        new_token.all_companies,
+        now,
+        expires_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for capability in new_token.capabilities {
+        query!(
+            "INSERT INTO API_TOKEN_CAPABILITIES(token_id, capability) SELECT $1, $2 WHERE EXISTS(SELECT 1 FROM user_capabilities WHERE user=$3 AND capability=$2)",
+            id,
+            capability,
+            user_id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    if !new_token.all_companies {
+        for company in new_token.companies {
+            query!(
+                "INSERT INTO API_TOKEN_COMPANIES(token_id, company) VALUES($1, $2)",
+                id,
+                company
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    Ok(token)
+}
+
+pub struct TokenOwner {
+    pub token_id: String,
+    pub user_id: String,
+    pub role: String,
+}
+
+/// Looks up the owner of a token that exists and hasn't expired, and marks the token as used.
+pub async fn authenticate(conn: &mut SqliteConnection, token: &str) -> Result<Option<TokenOwner>, ApiTokenError> {
+    let token_hash = hash(token);
+    let now = unix_now()?;
+
+    let owner = query_as!(
+        TokenOwner,
+        r#"SELECT t.id as "token_id!", t.user_id, u.role FROM API_TOKENS t JOIN USERS u ON u.id = t.user_id WHERE t.token_hash=$1 AND t.expires_at > $2"#,
+        token_hash,
+        now
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(owner) = &owner {
+        query!(
+            "UPDATE API_TOKENS SET last_used_at=$1 WHERE id=$2",
+            now,
+            owner.token_id
+        )
+        .execute(conn)
+        .await?;
+    }
+
+    Ok(owner)
+}
+
+pub async fn get_tokens(conn: &mut SqliteConnection, user_id: &str) -> Result<Vec<ApiToken>, sqlx::Error> {
+    query_as!(
+        ApiToken,
+        r#"SELECT id, name, all_companies as "all_companies: bool", created_at, expires_at, last_used_at FROM API_TOKENS WHERE user_id=$1 ORDER BY created_at DESC"#,
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn get_token_capabilities(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Vec<ApiTokenScope>, sqlx::Error> {
+    query_as!(
+        ApiTokenScope,
+        r#"SELECT tc.token_id, tc.capability as "value: String" FROM API_TOKEN_CAPABILITIES tc JOIN API_TOKENS t ON t.id = tc.token_id WHERE t.user_id=$1"#,
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn get_token_companies(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Vec<ApiTokenScope>, sqlx::Error> {
+    query_as!(
+        ApiTokenScope,
+        r#"SELECT tc.token_id, tc.company as "value: String" FROM API_TOKEN_COMPANIES tc JOIN API_TOKENS t ON t.id = tc.token_id WHERE t.user_id=$1"#,
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn revoke(conn: &mut SqliteConnection, user_id: &str, token_id: &str) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM API_TOKENS WHERE id=$1 AND user_id=$2",
+        token_id,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// The capabilities the user has anywhere, which are the ones they can give a token.
+pub async fn user_capabilities(conn: &mut SqliteConnection, user_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar!(
+        r#"SELECT DISTINCT capability as "capability!: String" FROM user_capabilities WHERE user=$1"#,
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+}
@@ -0,0 +1,205 @@
+use axum::http::StatusCode;
+use sqlx::{SqliteConnection, query_scalar};
+
+use super::service::UserData;
+
+/// Something a user may be allowed to do. The scoped ones are granted per company, the others only through a role.
+#[derive(Clone, Copy, Debug)]
+pub enum Capability {
+    CreateProject,
+    EditProject,
+    Publish,
+    Unpublish,
+    DeleteProject,
+    ViewFormSubmissions,
+    EditSitePages,
+    ManageParties,
+    ManageTms,
+    ManageIndustries,
+    ManageLabels,
+    ManageUsers,
+}
+
+impl Capability {
+    /// The name in the `capabilities` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::CreateProject => "create_project",
+            Capability::EditProject => "edit_project",
+            Capability::Publish => "publish",
+            Capability::Unpublish => "unpublish",
+            Capability::DeleteProject => "delete_project",
+            Capability::ViewFormSubmissions => "view_form_submissions",
+            Capability::EditSitePages => "edit_site_pages",
+            Capability::ManageParties => "manage_parties",
+            Capability::ManageTms => "manage_tms",
+            Capability::ManageIndustries => "manage_industries",
+            Capability::ManageLabels => "manage_labels",
+            Capability::ManageUsers => "manage_users",
+        }
+    }
+}
+
+/// Whether the user's role grants the capability, or it was granted to them for at least one company. A request made
+/// with an API token is also limited to the capabilities of the token.
+pub async fn has_capability(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    capability: Capability,
+) -> Result<bool, sqlx::Error> {
+    let capability = capability.as_str();
+
+    query_scalar!(
+        r#"
+        SELECT
+            EXISTS(SELECT 1 FROM user_capabilities WHERE user=$1 AND capability=$2)
+            AND ($3 IS NULL OR EXISTS(SELECT 1 FROM API_TOKEN_CAPABILITIES WHERE token_id=$3 AND capability=$2))
+        as "allowed!: bool"
+        "#,
+        user.id,
+        capability,
+        user.api_token
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Whether the user has the capability for the company, `None` being the projects without a company. A request made
+/// with an API token is also limited to the capabilities and companies of the token.
+pub async fn has_company_capability(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    company: Option<&str>,
+    capability: Capability,
+) -> Result<bool, sqlx::Error> {
+    let capability = capability.as_str();
+
+    query_scalar!(
+        r#"
+        SELECT
+            EXISTS(SELECT 1 FROM user_capabilities WHERE user=$1 AND capability=$2 AND (everywhere=1 OR company IS $3))
+            AND ($4 IS NULL OR EXISTS(
+                SELECT
+                    1
+                FROM
+                    API_TOKENS t
+                    JOIN API_TOKEN_CAPABILITIES tc ON tc.token_id = t.id
+                WHERE
+                    t.id = $4
+                    AND tc.capability = $2
+                    AND (
+                        t.all_companies = 1
+                        OR EXISTS(SELECT 1 FROM API_TOKEN_COMPANIES WHERE token_id = t.id AND company IS $3)
+                    )
+            ))
+        as "allowed!: bool"
+        "#,
+        user.id,
+        capability,
+        company,
+        user.api_token
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Whether the user has the capability for one of the companies of the project, in its draft or its published version.
+/// Every project has a base `PROJECT_COMPANIES` row without a company, so a grant for the projects without a company
+/// covers every project. A request made with an API token also needs the token to cover that company.
+pub async fn has_project_capability(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    project_id: &str,
+    capability: Capability,
+) -> Result<bool, sqlx::Error> {
+    let capability = capability.as_str();
+
+    query_scalar!(
+        r#"
+        WITH project_scope(company) AS (
+            SELECT company_name FROM PROJECT_COMPANIES WHERE project_id = $2
+            UNION ALL
+            SELECT NULL WHERE NOT EXISTS(SELECT 1 FROM PROJECT_COMPANIES WHERE project_id = $2)
+        )
+        SELECT EXISTS(
+            SELECT
+                1
+            FROM
+                project_scope ps
+            WHERE
+                EXISTS(
+                    SELECT
+                        1
+                    FROM
+                        user_capabilities uc
+                    WHERE
+                        uc.user = $1
+                        AND uc.capability = $3
+                        AND (uc.everywhere = 1 OR uc.company IS ps.company)
+                )
+                AND ($4 IS NULL OR EXISTS(
+                    SELECT
+                        1
+                    FROM
+                        API_TOKENS t
+                        JOIN API_TOKEN_CAPABILITIES tc ON tc.token_id = t.id
+                    WHERE
+                        t.id = $4
+                        AND tc.capability = $3
+                        AND (
+                            t.all_companies = 1
+                            OR EXISTS(SELECT 1 FROM API_TOKEN_COMPANIES WHERE token_id = t.id AND company IS ps.company)
+                        )
+                ))
+        ) as "allowed!: bool"
+        "#,
+        user.id,
+        project_id,
+        capability,
+        user.api_token
+    )
+    .fetch_one(conn)
+    .await
+}
+
+fn check(allowed: Result<bool, sqlx::Error>, user: &UserData, capability: Capability) -> Result<(), StatusCode> {
+    match allowed {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(err) => {
+            eprintln!("Failed to check capability {} of user {}: {err}", capability.as_str(), user.id);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Responds with 403 Forbidden when the user doesn't have the capability.
+pub async fn require(conn: &mut SqliteConnection, user: &UserData, capability: Capability) -> Result<(), StatusCode> {
+    check(has_capability(conn, user, capability).await, user, capability)
+}
+
+pub async fn require_for_company(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    company: Option<&str>,
+    capability: Capability,
+) -> Result<(), StatusCode> {
+    check(
+        has_company_capability(conn, user, company, capability).await,
+        user,
+        capability,
+    )
+}
+
+pub async fn require_for_project(
+    conn: &mut SqliteConnection,
+    user: &UserData,
+    project_id: &str,
+    capability: Capability,
+) -> Result<(), StatusCode> {
+    check(
+        has_project_capability(conn, user, project_id, capability).await,
+        user,
+        capability,
+    )
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_with::{OneOrMany, formats::PreferMany, serde_as};
 
 use crate::auth::models::User;
 
@@ -27,8 +28,12 @@ impl UserDTO {
 
 #[derive(Deserialize)]
 pub struct TotpVerifyBody {
-    pub id: String,
-    pub code: u32,
+    /// The pre-auth token from the password step.
+    pub token: String,
+    /// A TOTP code or a recovery code.
+    pub code: String,
+    #[serde(default)]
+    pub trust_device: bool,
 }
 
 #[derive(Deserialize)]
@@ -36,8 +41,7 @@ pub struct CreateUserBody {
     pub first_name: String,
     pub last_name: String,
     pub email: String,
-    pub role: String,
-    pub password: String
+    pub role: String
 }
 
 #[derive(Deserialize)]
@@ -51,10 +55,78 @@ pub struct SetPermissionQuery {
     pub company: Option<String>
 }
 
+#[serde_as]
 #[derive(Deserialize)]
 pub struct SetPermissionBody {
+    /// The capabilities that are checked for the company.
+    #[serde(default)]
+    #[serde_as(as = "OneOrMany<_, PreferMany>")]
+    pub capability: Vec<String>
+}
+
+#[derive(Deserialize)]
+pub struct CreateRoleBody {
+    pub name: String,
+    #[serde(default)]
+    pub description: String
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct SetRoleCapabilitiesBody {
+    #[serde(default)]
+    #[serde_as(as = "OneOrMany<_, PreferMany>")]
+    pub capability: Vec<String>
+}
+
+#[derive(Deserialize)]
+pub struct PasswordTokenQuery {
+    pub token: String
+}
+
+#[derive(Deserialize)]
+pub struct SetPasswordBody {
+    pub token: String,
+    pub password: String,
+    pub password_confirm: String
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordBody {
+    pub email: String
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordBody {
+    pub current_password: String,
+    pub password: String,
+    pub password_confirm: String
+}
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>
+}
+
+#[derive(Deserialize)]
+pub struct SsoDomainBody {
+    pub domain: String
+}
+#[serde_as]
+#[derive(Deserialize)]
+pub struct CreateApiTokenBody {
+    pub name: String,
+    pub expires_in_days: i64,
     #[serde(default)]
-    pub create: bool,
+    #[serde_as(as = "OneOrMany<_, PreferMany>")]
+    pub capability: Vec<String>,
     #[serde(default)]
-    pub edit: bool
-}
\ No newline at end of file
+    pub all_companies: bool,
+    /// An empty value stands for the projects without a company.
+    #[serde(default)]
+    #[serde_as(as = "OneOrMany<_, PreferMany>")]
+    pub company: Vec<String>
+}
@@ -6,288 +6,1735 @@ use axum::{
     Extension, Form,
     extract::{Path, Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
-    response::{Html, IntoResponse, Response, Result},
+    response::{Html, IntoResponse, Redirect, Response, Result},
 };
-use maud::{Markup, PreEscaped};
+use maud::{Markup, PreEscaped, html};
 use qrcode::{QrCode, render::svg};
-
-use cookie::time::{Duration, OffsetDateTime};
+use sqlx::{SqliteConnection, SqliteTransaction};
+use uuid::Uuid;
 
 use crate::{
     AppState, TransactionError,
     admin::{DBConnection, DBTransaction},
     auth::{
+        api_token,
+        capability::{self, Capability},
+        models::User,
+        oidc,
+        password::{self, PasswordTokenError, Purpose},
+        throttle::{self, AttemptKind},
         service::{UserData, generate_token_for_user_id, get_user_auth_data, validate_paseto},
         template::twofa_form,
     },
+    mail,
 };
 
-use super::service::{self, generate_totp_uri};
+use super::service::{self, CreateUserError, generate_totp_uri};
 
 use super::dto;
 use super::template;
 
 pub async fn login(
+    State(state): State<AppState>,
+    request_headers: HeaderMap,
     DBTransaction(mut transaction): DBTransaction<'_>,
+    jar: CookieJar,
     Form(body): Form<dto::LoginBody>,
-) -> Result<Markup> {
-    let user = service::get_user_from_email(&mut *transaction, &body.email)
+) -> Result<Response> {
+    let sso = state.oidc.as_deref().map(|oidc| oidc.name.as_str());
+    // nginx sets X-Real-IP to the address the request came from. X-Forwarded-For starts with whatever the client sent,
+    // which would give every attempt its own throttle.
+    let ip = request_headers
+        .get("X-Real-IP")
+        .and_then(|ip| ip.to_str().ok())
+        .unwrap_or_default();
+
+    let user = match service::get_user_from_email(&mut *transaction, &body.email).await {
+        Ok(user) => Some(user),
+        Err(sqlx::Error::RowNotFound) => None,
+        Err(err) => {
+            eprintln!("Failed to look up the user with email {}: {err}", body.email);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+
+    let retry_after = throttle::retry_after(&mut *transaction, user.as_ref().map(|user| user.id.as_str()), ip)
         .await
-        .map_err(|err| StatusCode::UNAUTHORIZED)?;
+        .map_err(|err| {
+            eprintln!("Failed to check the failed logins: {err:?}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    let matches = bcrypt::verify(body.password, &user.password)
-        .map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(wait) = retry_after {
+        let message = format!("Too many failed attempts, try again in {}.", throttle::describe_wait(wait));
+        return Ok(template::login_form(sso, Some(&message)).into_response());
+    }
 
-    if matches {
-        let mut svg = None;
+    let Some(user) = user else {
+        throttle::record_failure(&mut *transaction, None, Some(&body.email), ip, AttemptKind::Password)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to record a failed login: {err:?}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
 
-        if user.totp_secret.is_none() {
-            let rand_string = service::generate_totp_secret();
+        transaction
+            .commit()
+            .await
+            .map_err(|_| TransactionError::Commit)?;
 
-            service::set_user_totp_secret(&mut *transaction, &user.email, &rand_string).await;
+        return Ok(template::login_form(sso, Some("The email address or password is incorrect.")).into_response());
+    };
 
-            transaction
-                .commit()
-                .await
-                .map_err(|_| TransactionError::Commit)?;
+    let sso_only = service::is_sso_only(&mut *transaction, &user.email)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to check the SSO domains: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-            let uri = service::generate_totp_uri(&rand_string);
-            let code = QrCode::new(uri).map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if sso_only {
+        return Ok(template::login_form(sso, Some("Your organisation requires you to log in with single sign-on.")).into_response());
+    }
 
-            svg = Some(code.render::<svg::Color>().build())
-        }
+    let matches = bcrypt::verify(body.password, &user.password).map_err(|err| {
+        eprintln!("Failed to verify the password of user {}: {err}", user.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !matches {
+        record_failure(&state, transaction, &user, ip, AttemptKind::Password).await?;
+
+        return Ok(template::login_form(sso, Some("The email address or password is incorrect.")).into_response());
+    }
+
+    let mut svg = None;
+
+    if user.totp_secret.is_none() {
+        let secret = service::generate_totp_secret();
+
+        service::set_pending_totp_secret(&mut *transaction, &user.id, &secret)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to start the TOTP enrolment of user {}: {err}", user.id);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
 
-        let form = twofa_form(&user.id, svg);
+        transaction
+            .commit()
+            .await
+            .map_err(|_| TransactionError::Commit)?;
 
-        Ok(form)
+        svg = Some(enrolment_qr_code(&secret)?);
     } else {
-        Err(StatusCode::UNAUTHORIZED.into())
+        let device_id = jar.get("device").map(|device| device.value());
+
+        let auth_data = get_user_auth_data(&mut *transaction, &user.id, device_id, ip)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to get the user auth data for user {}: {err:?}", user.id);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        // A trusted device skips the TOTP step.
+        if auth_data.trusted_device {
+            return Ok(start_session(&state, transaction, jar, &request_headers, &user, None, false)
+                .await?
+                .into_response());
+        }
+    }
+
+    let pre_auth_token = service::generate_pre_auth_token(&user.id, &state.paseto).map_err(|err| {
+        eprintln!("Failed to create a pre-auth token for user {}: {err:?}", user.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(twofa_form(&pre_auth_token, svg, None).into_response())
+}
+
+/// Logs a failed password or TOTP attempt, and tells the owner when it locked their account.
+async fn record_failure(
+    state: &AppState,
+    mut transaction: SqliteTransaction<'_>,
+    user: &User,
+    ip: &str,
+    kind: AttemptKind,
+) -> Result<()> {
+    let locked = throttle::record_failure(&mut *transaction, Some(&user.id), Some(&user.email), ip, kind)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to record a failed login of user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    if locked {
+        let body = template::account_locked_email(user, ip);
+
+        if let Err(err) = mail::send(&state.mailer, &user.email, "Your website editor account was locked", body) {
+            eprintln!("Failed to send the account locked email to user {}: {err:?}", user.id);
+        }
+    }
+
+    Ok(())
+}
+
+fn enrolment_qr_code(secret: &str) -> Result<String, StatusCode> {
+    let code = QrCode::new(service::generate_totp_uri(secret)).map_err(|err| {
+        eprintln!("Failed to create the TOTP enrolment QR code: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(code.render::<svg::Color>().build())
+}
+
+/// Starts (or continues) a session for a user that passed authentication, remembers their device and sends them to
+/// the editor.
+async fn start_session(
+    state: &AppState,
+    mut transaction: SqliteTransaction<'_>,
+    mut jar: CookieJar,
+    request_headers: &HeaderMap,
+    user: &User,
+    session_id: Option<String>,
+    trust_device: bool,
+) -> Result<(HeaderMap, CookieJar)> {
+    let user_agent = request_headers
+        .get("User-Agent")
+        .and_then(|user_agent| user_agent.to_str().ok());
+    let ip = request_headers
+        .get("X-Real-IP")
+        .and_then(|ip| ip.to_str().ok());
+
+    let session_id = match session_id {
+        Some(session_id) => session_id,
+        None => service::create_session(&mut *transaction, &user.id, user_agent, ip)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to create a session for user {}: {err:?}", user.id);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+    };
+
+    let device_id = jar
+        .get("device")
+        .map(|device| device.value().to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let new_device = service::register_device(&mut *transaction, &user.id, &device_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to register device {device_id} for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    throttle::record_success(&mut *transaction, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to clear the failed logins of user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if trust_device {
+        service::trust_device(
+            &mut *transaction,
+            &user.id,
+            &device_id,
+            ip.unwrap_or_default(),
+            user_agent,
+            state.sessions.trusted_device_window,
+        )
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to trust device {device_id} for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
     }
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    if new_device {
+        let body = template::new_device_email(user, user_agent, ip);
+
+        if let Err(err) = mail::send(&state.mailer, &user.email, "New login to the website editor", body) {
+            eprintln!("Failed to send the new device email to user {}: {err:?}", user.id);
+        }
+    }
+
+    let pub_token = generate_token_for_user_id(
+        &user.id,
+        &session_id,
+        state.sessions.reauth_window,
+        &state.paseto,
+    )
+    .map_err(|err| {
+        eprintln!("Failed to create a token for user {}: {err:?}", user.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", HeaderValue::from_static("/"));
+
+    jar = jar
+        .add(service::token_cookie(pub_token))
+        .add(service::device_cookie(device_id));
+
+    Ok((headers, jar))
 }
 
 pub async fn totp_verify(
     State(state): State<AppState>,
+    request_headers: HeaderMap,
     DBTransaction(mut transaction): DBTransaction<'_>,
-    mut jar: CookieJar,
+    jar: CookieJar,
     Form(body): Form<dto::TotpVerifyBody>,
-) -> Result<(HeaderMap, axum_extra::extract::CookieJar)> {
-    let secret = match service::get_user_totp_secret(&mut *transaction, &body.id)
-        .await
-        .map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?
-    {
-        Some(secret) => secret,
-        None => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+) -> Result<Response> {
+    let sso = state.oidc.as_deref().map(|oidc| oidc.name.as_str());
+
+    let Some(user_id) = service::validate_pre_auth_token(&body.token, &state.paseto) else {
+        return Ok(template::login_form(sso, Some("Your login expired, please log in again.")).into_response());
     };
 
-    let valid = service::verify_totp(&secret, body.code)
-        .map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let user = match service::get_user_from_id(&mut *transaction, &user_id).await {
+        Ok(user) => user,
+        Err(err) => {
+            eprintln!("{err}");
+            return Err(StatusCode::UNAUTHORIZED.into());
+        }
+    };
 
-    let mut headers = HeaderMap::new();
+    let ip = request_headers
+        .get("X-Real-IP")
+        .and_then(|ip| ip.to_str().ok())
+        .unwrap_or_default();
 
-    if valid {
-        let user = match service::get_user_from_id(&mut *transaction, &body.id).await {
-            Ok(user) => user,
-            Err(err) => {
-                eprintln!("{err}");
-                return Err(StatusCode::UNAUTHORIZED.into());
-            }
+    let retry_after = throttle::retry_after(&mut *transaction, Some(&user.id), ip)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to check the failed logins of user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if let Some(wait) = retry_after {
+        let svg = match (&user.totp_secret, &user.totp_pending_secret) {
+            (None, Some(pending_secret)) => Some(enrolment_qr_code(pending_secret)?),
+            _ => None,
         };
+        let message = format!("Too many failed attempts, try again in {}.", throttle::describe_wait(wait));
 
-        let permissions = match service::get_permissions(&mut *transaction, &user.id).await {
-            Ok(permissions) => permissions,
-            Err(err) => {
-                eprintln!("{err}");
-                return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        return Ok(twofa_form(&body.token, svg, Some(&message)).into_response());
+    }
+
+    let code = body.code.trim();
+    let totp_code = code.parse::<u32>().ok().filter(|_| code.len() == 6);
+
+    match (&user.totp_secret, &user.totp_pending_secret) {
+        (Some(secret), _) => {
+            // Anything that doesn't look like a TOTP code is tried as a recovery code.
+            let valid = match totp_code {
+                Some(totp_code) => {
+                    match service::verify_totp(secret, totp_code, user.totp_last_step).map_err(|err| {
+                        eprintln!("Failed to verify the TOTP code of user {}: {err}", user.id);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?
+                    {
+                        Some(step) => service::use_totp_step(&mut *transaction, &user.id, step)
+                            .await
+                            .map_err(|err| {
+                                eprintln!("Failed to use a TOTP code of user {}: {err}", user.id);
+                                StatusCode::INTERNAL_SERVER_ERROR
+                            })?,
+                        None => false,
+                    }
+                }
+                None => service::use_recovery_code(&mut *transaction, &user.id, code)
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Failed to use a recovery code of user {}: {err}", user.id);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?,
+            };
+
+            if !valid {
+                record_failure(&state, transaction, &user, ip, AttemptKind::Totp).await?;
+
+                return Ok(twofa_form(&body.token, None, Some("This code is invalid or was already used.")).into_response());
             }
-        };
 
-        let pub_token = generate_token_for_user_id(&user.id, &state.paseto.private_key)
-            .map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?;
+            // Stepping up from an expired 2FA window continues the existing session instead of starting a new one.
+            let existing_session = match jar.get("token").map(|token| validate_paseto(token.value(), &state.paseto)) {
+                Some(Ok((id, _, session_id))) if id == user.id => {
+                    match service::session_exists(&mut *transaction, &session_id, &id).await {
+                        Ok(true) => Some(session_id),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
 
-        headers.append(
-            "HX-Redirect",
-            HeaderValue::from_str("/").map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?,
-        );
+            Ok(start_session(
+                &state,
+                transaction,
+                jar,
+                &request_headers,
+                &user,
+                existing_session,
+                body.trust_device,
+            )
+            .await?
+            .into_response())
+        }
+        (None, Some(pending_secret)) => {
+            let step = match totp_code {
+                Some(totp_code) => service::verify_totp(pending_secret, totp_code, None).map_err(|err| {
+                    eprintln!("Failed to verify the TOTP code of user {}: {err}", user.id);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?,
+                None => None,
+            };
+
+            let Some(step) = step else {
+                record_failure(&state, transaction, &user, ip, AttemptKind::Totp).await?;
+
+                let svg = enrolment_qr_code(pending_secret)?;
+                return Ok(twofa_form(&body.token, Some(svg), Some("This code is invalid, please try again.")).into_response());
+            };
+
+            service::confirm_totp_secret(&mut *transaction, &user.id, step)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to confirm the TOTP secret of user {}: {err}", user.id);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            let codes = service::generate_recovery_codes(&mut *transaction, &user.id)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to generate recovery codes for user {}: {err:?}", user.id);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
 
-        let mut now = OffsetDateTime::now_utc();
-        now += Duration::weeks(26);
+            // The user is logged in, but first gets to see their recovery codes instead of being redirected.
+            let (_, jar) = start_session(
+                &state,
+                transaction,
+                jar,
+                &request_headers,
+                &user,
+                None,
+                body.trust_device,
+            )
+            .await?;
 
-        let mut token_cookie = Cookie::new("token", pub_token);
-        token_cookie.set_http_only(true);
-        token_cookie.set_expires(now);
-        token_cookie.set_path("/");
+            Ok((jar, template::recovery_codes(&codes, true)).into_response())
+        }
+        (None, None) => Err(StatusCode::UNAUTHORIZED.into()),
+    }
+}
 
-        jar = jar.add(token_cookie);
+pub async fn logout(
+    State(state): State<AppState>,
+    DBConnection(mut connection): DBConnection,
+    jar: CookieJar,
+) -> Result<Response> {
+    if let Some(token) = jar.get("token") {
+        if let Ok((id, _, session_id)) = validate_paseto(token.value(), &state.paseto) {
+            service::revoke_session(&mut connection, &id, &session_id)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to revoke session {session_id}: {err}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+        }
     }
 
-    Ok((headers, jar))
+    logged_out(jar)
 }
 
-pub async fn logout(jar: CookieJar) -> Result<Response> {
+/// Removes the token cookie and sends the user back to the login page.
+fn logged_out(jar: CookieJar) -> Result<Response> {
     let mut token_cookie = Cookie::from("token");
     token_cookie.set_http_only(true);
     token_cookie.set_path("/");
 
     let mut headers = HeaderMap::new();
-    headers.append(
-        "HX-Redirect",
-        HeaderValue::from_str("/login").map_err(|err| StatusCode::INTERNAL_SERVER_ERROR)?,
-    );
+    headers.append("HX-Redirect", HeaderValue::from_static("/login"));
     Ok((headers, jar.remove(token_cookie)).into_response())
 }
 
 pub async fn get_permissions(
     Path(user_id): Path<String>,
     DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    permission_matrix(&mut connection, &user_id, None).await
+}
+
+/// Renders the permissions of a user, or only the row of `company` when it is given.
+async fn permission_matrix(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    company: Option<&Option<String>>,
 ) -> Result<Markup> {
-    let permissions = service::get_permissions(&mut connection, &user_id)
+    let capabilities = service::get_capabilities(&mut *conn)
         .await
         .map_err(|err| {
-            println!("{err}");
+            eprintln!("Failed to get the capabilities: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let permissions = service::get_permissions(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the permissions of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let role_capabilities = service::get_user_role_capabilities(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the role capabilities of user {user_id}: {err}");
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    Ok(template::permissions(&permissions, &user_id))
+    if let Some(company) = company {
+        return Ok(template::permission_row(
+            user_id,
+            company,
+            &capabilities,
+            &permissions,
+            &role_capabilities,
+        ));
+    }
+
+    let companies = service::get_permission_companies(&mut *conn)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the companies: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::permissions(
+        user_id,
+        &companies,
+        &capabilities,
+        &permissions,
+        &role_capabilities,
+    ))
 }
 
 pub async fn get_users(
     DBConnection(mut connection): DBConnection,
-    Extension(user_id): Extension<UserData>,
+    Extension(user): Extension<UserData>,
 ) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
     let users = service::get_users(&mut connection)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(template::user_list(&users))
+    let roles = service::get_roles(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::user_list(&users, &roles))
 }
 
 pub async fn set_permission(
-    State(state): State<AppState>,
     Path(user_id): Path<String>,
     DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
     Query(query): Query<dto::SetPermissionQuery>,
     Form(body): Form<dto::SetPermissionBody>,
 ) -> Result<Markup> {
-    let permission = service::update_permission(
-        &mut transaction,
-        &user_id,
-        &query.company,
-        body.create,
-        body.edit,
-    )
-    .await
-    .map_err(|err| {
-        println!("{err}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    capability::require(&mut transaction, &user, Capability::ManageUsers).await?;
 
-    Ok(template::permission_row(
-        &permission,
-        &user_id,
-        &query.company.as_deref().unwrap_or("Base"),
-        &query.company,
-    ))
-}
+    service::update_permissions(&mut transaction, &user_id, &query.company, &body.capability)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the permissions of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-pub async fn create_user(
-    DBTransaction(mut transaction): DBTransaction<'_>,
-    Form(body): Form<dto::CreateUserBody>,
-) -> Result<Markup> {
-    let user = service::create_user(
-        &mut transaction,
-        &body.first_name,
-        &body.last_name,
-        &body.email,
-        &body.role,
-        &body.password,
-    )
-    .await
-    .map_err(|err| {
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let row = permission_matrix(&mut transaction, &user_id, Some(&query.company)).await?;
 
     transaction
         .commit()
         .await
         .map_err(|_| TransactionError::Commit)?;
 
-    Ok(template::user_template(&user))
+    Ok(row)
 }
 
-pub async fn delete_user(
-    DBTransaction(mut transaction): DBTransaction<'_>,
-    Path(id): Path<String>,
-) -> Result<()> {
-    service::delete_user(&mut transaction, &id)
+pub async fn get_roles(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    role_overview(&mut connection, None).await
+}
+
+/// Renders all roles with their capabilities, or only the row of `role` when it is given.
+async fn role_overview(conn: &mut SqliteConnection, role: Option<&str>) -> Result<Markup> {
+    let roles = service::get_roles(&mut *conn)
         .await
         .map_err(|err| {
-            println!("{err}");
+            eprintln!("Failed to get the roles: {err}");
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    Ok(())
-}
-
-pub async fn update_user_data(
-    Path(user_id): Path<String>,
-    DBTransaction(mut transaction): DBTransaction<'_>,
-    Form(body): Form<dto::UpdateUserDataBody>,
-) -> Result<Markup> {
-    let user = service::update_user_data(&mut transaction, &user_id, &body.email, &body.role)
+    let capabilities = service::get_capabilities(&mut *conn)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|err| {
+            eprintln!("Failed to get the capabilities: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    transaction
-        .commit()
+    let role_capabilities = service::get_role_capabilities(&mut *conn)
         .await
-        .map_err(|_| TransactionError::Commit)?;
+        .map_err(|err| {
+            eprintln!("Failed to get the role capabilities: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    Ok(template::user_template(&user))
+    match role {
+        Some(role) => {
+            let role = roles
+                .iter()
+                .find(|candidate| candidate.name == role)
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            Ok(template::role_row(role, &capabilities, &role_capabilities))
+        }
+        None => Ok(template::roles(&roles, &capabilities, &role_capabilities)),
+    }
 }
 
-pub async fn get_login_page(
-    headers: HeaderMap,
-    State(state): State<AppState>,
+/// The options of the role selects.
+pub async fn get_role_options(
     DBConnection(mut connection): DBConnection,
-    jar: CookieJar,
-) -> Response {
-    let token = match jar.get("token") {
-        Some(token) => token.value(),
-        None => return (StatusCode::OK, template::login_form()).into_response(),
-    };
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
 
-    let (id, last_action) = match validate_paseto(token, &state.paseto.public_key) {
-        Ok(id) => id,
-        Err(_) => return (StatusCode::OK, template::login_form()).into_response(),
-    };
+    let roles = service::get_roles(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let ip = match headers.get("X-Real-IP") {
-        Some(ip) => match ip.to_str() {
-            Ok(ip) => ip,
-            Err(_) => return (StatusCode::OK, template::login_form()).into_response(),
-        },
-        None => return (StatusCode::OK, template::login_form()).into_response(),
-    };
+    Ok(template::role_options(&roles, "user"))
+}
 
-    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
-        Ok(d) => d.as_secs(),
-        Err(err) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
+pub async fn create_role(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<dto::CreateRoleBody>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
 
-    let secs_multiplier = (24 * 60 * 60) as u64;
-    let secs_until_2fa = last_action + 3 * secs_multiplier;
-    let secs_until_reauth = last_action + 7 * secs_multiplier;
+    let name = body.name.trim().to_lowercase();
 
-    if now > secs_until_reauth {
-        return (StatusCode::OK, template::login_form()).into_response();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
     }
 
-    if now > secs_until_2fa {
-        return (StatusCode::OK, template::twofa_form(&id, None)).into_response();
-    }
+    service::create_role(&mut connection, &name, body.description.trim())
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create role {name}: {err}");
+            StatusCode::CONFLICT
+        })?;
 
-    return (
-        StatusCode::TEMPORARY_REDIRECT,
+    role_overview(&mut connection, None).await
+}
+
+pub async fn set_role_capabilities(
+    Path(role): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<dto::SetRoleCapabilitiesBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageUsers).await?;
+
+    // The admin role always keeps every capability, so the users can't be locked out of the editor.
+    if role == "admin" {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    service::update_role_capabilities(&mut transaction, &role, &body.capability)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to update the capabilities of role {role}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let row = role_overview(&mut transaction, Some(&role)).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(row)
+}
+
+pub async fn delete_role(
+    Path(role): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    if role == "admin" || role == "user" {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let deleted = service::delete_role(&mut connection, &role)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to delete role {role}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !deleted {
+        return Err((StatusCode::CONFLICT, "This role is still assigned to a user.").into());
+    }
+
+    role_overview(&mut connection, None).await
+}
+
+pub async fn create_user(
+    State(state): State<AppState>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(current_user): Extension<UserData>,
+    Form(body): Form<dto::CreateUserBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &current_user, Capability::ManageUsers).await?;
+
+    // The user can't log in until they set their own password through the invitation link.
+    let unusable_password = Uuid::new_v4().to_string();
+
+    let user = service::create_user(
+        &mut transaction,
+        &body.first_name,
+        &body.last_name,
+        &body.email,
+        &body.role,
+        &unusable_password,
+    )
+    .await
+    .map_err(|err| match err {
+        CreateUserError::SQL(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+            (StatusCode::CONFLICT, "A user with this email address already exists.").into_response()
+        }
+        CreateUserError::SQL(err) => {
+            eprintln!("Failed to create user {}: {err}", body.email);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        // Already logged by `create_user`.
+        CreateUserError::HashError => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    })?;
+
+    let token = password::create_token(&mut transaction, &user.id, Purpose::Invitation, &state.paseto)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create an invitation for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let roles = service::get_roles(&mut transaction)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    send_password_link(&state, &user.email, &user.first_name, Purpose::Invitation, &token);
+
+    Ok(template::user_template(&user, &roles))
+}
+
+pub async fn resend_invitation(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(current_user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &current_user, Capability::ManageUsers).await?;
+
+    let user = service::get_user_from_id(&mut transaction, &user_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let token = password::create_token(&mut transaction, &user.id, Purpose::Invitation, &state.paseto)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create an invitation for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    send_password_link(&state, &user.email, &user.first_name, Purpose::Invitation, &token);
+
+    Ok(html!("Invitation sent"))
+}
+
+/// Emails the user a link to the set-password page. Failures are only logged, an admin can resend an invitation and a
+/// user can request a new reset link.
+fn send_password_link(state: &AppState, email: &str, first_name: &str, purpose: Purpose, token: &str) {
+    let link = format!("{}/set-password?token={token}", state.admin_base_url);
+
+    let subject = match purpose {
+        Purpose::Invitation => "Your account for the website editor",
+        Purpose::Reset => "Reset your password for the website editor",
+    };
+
+    if let Err(err) = mail::send(&state.mailer, email, subject, template::password_email(first_name, purpose, &link)) {
+        eprintln!("Failed to send the {} email to {email}: {err:?}", purpose.as_str());
+    }
+}
+
+pub async fn get_set_password_form(
+    State(state): State<AppState>,
+    DBConnection(mut connection): DBConnection,
+    Query(query): Query<dto::PasswordTokenQuery>,
+) -> Result<Markup> {
+    match password::validate_token(&mut connection, &query.token, &state.paseto).await {
+        Ok(_) => Ok(template::set_password_form(&query.token, None)),
+        Err(PasswordTokenError::Invalid) => Ok(template::password_link_expired()),
+        Err(err) => {
+            eprintln!("Failed to validate a password token: {err:?}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into())
+        }
+    }
+}
+
+pub async fn set_password(
+    State(state): State<AppState>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<dto::SetPasswordBody>,
+) -> Result<Markup> {
+    let token = match password::validate_token(&mut transaction, &body.token, &state.paseto).await {
+        Ok(token) => token,
+        Err(PasswordTokenError::Invalid) => return Ok(template::password_link_expired()),
+        Err(err) => {
+            eprintln!("Failed to validate a password token: {err:?}");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+
+    let user = service::get_user_from_id(&mut transaction, &token.user_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    if body.password != body.password_confirm {
+        return Ok(template::set_password_form(&body.token, Some("The passwords don't match.")));
+    }
+
+    if let Err(message) = state.password_policy.check(&body.password, &user.email) {
+        return Ok(template::set_password_form(&body.token, Some(&message)));
+    }
+
+    let hash = password::hash(&body.password).map_err(|err| {
+        eprintln!("Failed to create a hash: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    password::set_password(&mut transaction, &user.id, &hash)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to set the password of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    password::use_token(&mut transaction, &token.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to use password token {}: {err:?}", token.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    service::revoke_sessions(&mut transaction, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke the sessions of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // An invited user enrols TOTP right away, with the same form the first login shows.
+    let svg = match (token.purpose, &user.totp_secret) {
+        (Purpose::Invitation, None) => {
+            let secret = service::generate_totp_secret();
+
+            service::set_pending_totp_secret(&mut transaction, &user.id, &secret)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to start the TOTP enrolment of user {}: {err}", user.id);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            Some(enrolment_qr_code(&secret)?)
+        }
+        _ => None,
+    };
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    let sso = state.oidc.as_deref().map(|oidc| oidc.name.as_str());
+
+    Ok(match svg {
+        Some(svg) => {
+            let pre_auth_token = service::generate_pre_auth_token(&user.id, &state.paseto).map_err(|err| {
+                eprintln!("Failed to create a pre-auth token for user {}: {err:?}", user.id);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+            twofa_form(&pre_auth_token, Some(svg), None)
+        }
+        None => template::login_form(sso, None),
+    })
+}
+
+pub async fn get_forgot_password_form() -> Markup {
+    template::forgot_password_form(None)
+}
+
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Form(body): Form<dto::ForgotPasswordBody>,
+) -> Result<Markup> {
+    // The response is the same whether or not the address is known, so the form can't be used to look up accounts.
+    let sent = template::forgot_password_form(Some(
+        "If there is an account for this address, we sent it a link to reset the password.",
+    ));
+
+    let user = match service::get_user_from_email(&mut transaction, &body.email).await {
+        Ok(user) => user,
+        Err(sqlx::Error::RowNotFound) => return Ok(sent),
+        Err(err) => {
+            eprintln!("Failed to look up user {}: {err}", body.email);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+
+    // Their password is never used, the organisation manages it.
+    match service::is_sso_only(&mut transaction, &user.email).await {
+        Ok(false) => {}
+        Ok(true) => return Ok(sent),
+        Err(err) => {
+            eprintln!("Failed to check the SSO domains: {err}");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    }
+
+    let token = password::create_token(&mut transaction, &user.id, Purpose::Reset, &state.paseto)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to create a reset token for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    send_password_link(&state, &user.email, &user.first_name, Purpose::Reset, &token);
+
+    Ok(sent)
+}
+
+pub async fn get_change_password_form() -> Markup {
+    template::change_password_form(None)
+}
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(current_user): Extension<UserData>,
+    Form(body): Form<dto::ChangePasswordBody>,
+) -> Result<Markup> {
+    let user = service::get_user_from_id(&mut transaction, &current_user.id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let matches = bcrypt::verify(&body.current_password, &user.password).map_err(|err| {
+        eprintln!("Failed to verify the password of user {}: {err}", user.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !matches {
+        return Ok(template::change_password_form(Some("The current password is incorrect.")));
+    }
+
+    if body.password != body.password_confirm {
+        return Ok(template::change_password_form(Some("The passwords don't match.")));
+    }
+
+    if let Err(message) = state.password_policy.check(&body.password, &user.email) {
+        return Ok(template::change_password_form(Some(&message)));
+    }
+
+    let hash = password::hash(&body.password).map_err(|err| {
+        eprintln!("Failed to create a hash: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    password::set_password(&mut transaction, &user.id, &hash)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to set the password of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    service::revoke_other_sessions(&mut transaction, &user.id, &current_user.session_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke the sessions of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::change_password_form(Some(
+        "Your password was changed and your other sessions were logged out.",
+    )))
+}
+
+pub async fn delete_user(
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Path(id): Path<String>,
+) -> Result<()> {
+    capability::require(&mut transaction, &user, Capability::ManageUsers).await?;
+
+    service::delete_user(&mut transaction, &id)
+        .await
+        .map_err(|err| {
+            println!("{err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(())
+}
+
+pub async fn update_user_data(
+    Path(user_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(current_user): Extension<UserData>,
+    Form(body): Form<dto::UpdateUserDataBody>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &current_user, Capability::ManageUsers).await?;
+
+    let previous = service::get_user_from_id(&mut transaction, &user_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let user = service::update_user_data(&mut transaction, &user_id, &body.email, &body.role)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if user.role != previous.role {
+        service::revoke_sessions(&mut transaction, &user_id)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to revoke the sessions of user {user_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let roles = service::get_roles(&mut transaction)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::user_template(&user, &roles))
+}
+
+pub async fn get_login_page(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    DBConnection(mut connection): DBConnection,
+    jar: CookieJar,
+) -> Response {
+    let sso = state.oidc.as_deref().map(|oidc| oidc.name.as_str());
+
+    let token = match jar.get("token") {
+        Some(token) => token.value(),
+        None => return (StatusCode::OK, template::login_form(sso, None)).into_response(),
+    };
+
+    let (id, last_action, session_id) = match validate_paseto(token, &state.paseto) {
+        Ok(id) => id,
+        Err(_) => return (StatusCode::OK, template::login_form(sso, None)).into_response(),
+    };
+
+    match service::session_exists(&mut connection, &session_id, &id).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::OK, template::login_form(sso, None)).into_response(),
+        Err(err) => {
+            eprintln!("Failed to look up session {session_id}: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let ip = match headers.get("X-Real-IP") {
+        Some(ip) => match ip.to_str() {
+            Ok(ip) => ip,
+            Err(_) => return (StatusCode::OK, template::login_form(sso, None)).into_response(),
+        },
+        None => return (StatusCode::OK, template::login_form(sso, None)).into_response(),
+    };
+
+    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(err) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    if now > last_action + state.sessions.reauth_window {
+        return (StatusCode::OK, template::login_form(sso, None)).into_response();
+    }
+
+    if now > last_action + state.sessions.twofa_window {
+        let device_id = jar.get("device").map(|device| device.value());
+
+        let trusted = match get_user_auth_data(&mut connection, &id, device_id, ip).await {
+            Ok(auth_data) => auth_data.trusted_device,
+            Err(err) => {
+                eprintln!("Failed to get the user auth data for user {id}: {err:?}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        if !trusted {
+            return match service::generate_pre_auth_token(&id, &state.paseto) {
+                Ok(pre_auth_token) => (StatusCode::OK, template::twofa_form(&pre_auth_token, None, None)).into_response(),
+                Err(err) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+        }
+    }
+
+    return (
+        StatusCode::TEMPORARY_REDIRECT,
         PreEscaped("/projects".to_string()),
     ).into_response();
 }
+
+pub async fn get_sessions(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let sessions = service::get_sessions(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the sessions of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::session_list(&sessions, &user.id, Some(&user.session_id)))
+}
+
+pub async fn revoke_session(
+    Path(session_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<()> {
+    service::revoke_session(&mut connection, &user.id, &session_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke session {session_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn logout_everywhere(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    jar: CookieJar,
+) -> Result<Response> {
+    service::revoke_sessions(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke the sessions of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    logged_out(jar)
+}
+
+pub async fn get_api_tokens(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    // A token can't be used to mint or see other tokens.
+    if user.api_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    api_token_overview(&mut connection, &user.id, None).await
+}
+
+async fn api_token_overview(conn: &mut SqliteConnection, user_id: &str, created: Option<&str>) -> Result<Markup> {
+    let tokens = api_token::get_tokens(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the API tokens of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let token_capabilities = api_token::get_token_capabilities(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the capabilities of the API tokens of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let token_companies = api_token::get_token_companies(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the companies of the API tokens of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let user_capabilities = api_token::user_capabilities(&mut *conn, user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the capabilities of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let capabilities = service::get_capabilities(&mut *conn)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the capabilities: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .filter(|capability| user_capabilities.contains(&capability.name))
+        .collect::<Vec<_>>();
+
+    let companies = service::get_permission_companies(&mut *conn)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the companies: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::api_tokens(
+        &tokens,
+        &token_capabilities,
+        &token_companies,
+        &capabilities,
+        &companies,
+        created,
+    ))
+}
+
+pub async fn create_api_token(
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<dto::CreateApiTokenBody>,
+) -> Result<Markup> {
+    if user.api_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let name = body.name.trim();
+
+    if name.is_empty() || !(1..=365).contains(&body.expires_in_days) {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let companies = body
+        .company
+        .into_iter()
+        .map(|company| (!company.is_empty()).then_some(company))
+        .collect::<Vec<_>>();
+
+    let token = api_token::create(
+        &mut transaction,
+        &user.id,
+        api_token::NewApiToken {
+            name,
+            lifetime: body.expires_in_days * 24 * 60 * 60,
+            capabilities: &body.capability,
+            all_companies: body.all_companies,
+            companies: &companies,
+        },
+    )
+    .await
+    .map_err(|err| {
+        eprintln!("Failed to create an API token for user {}: {err:?}", user.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let overview = api_token_overview(&mut transaction, &user.id, Some(&token)).await?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(overview)
+}
+
+pub async fn revoke_api_token(
+    Path(token_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<()> {
+    if user.api_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    api_token::revoke(&mut connection, &user.id, &token_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke API token {token_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_user_sessions(
+    Path(user_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    let sessions = service::get_sessions(&mut connection, &user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the sessions of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::session_list(&sessions, &user_id, None))
+}
+
+pub async fn revoke_user_session(
+    Path((user_id, session_id)): Path<(String, String)>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<()> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    service::revoke_session(&mut connection, &user_id, &session_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke session {session_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn revoke_user_sessions(
+    Path(user_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    service::revoke_sessions(&mut connection, &user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke the sessions of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::session_list(&[], &user_id, None))
+}
+
+pub async fn get_trusted_devices(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let devices = service::get_trusted_devices(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the trusted devices of user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::trusted_device_list(&devices, &user.id, false))
+}
+
+pub async fn revoke_trusted_device(
+    Path(device_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<()> {
+    service::revoke_trusted_device(&mut connection, &user.id, &device_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke trusted device {device_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_user_trusted_devices(
+    Path(user_id): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    let devices = service::get_trusted_devices(&mut connection, &user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the trusted devices of user {user_id}: {err:?}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::trusted_device_list(&devices, &user_id, true))
+}
+
+pub async fn revoke_user_trusted_device(
+    Path((user_id, device_id)): Path<(String, String)>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<()> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    service::revoke_trusted_device(&mut connection, &user_id, &device_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to revoke trusted device {device_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn reset_totp(
+    Path(user_id): Path<String>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut transaction, &user, Capability::ManageUsers).await?;
+
+    service::reset_totp(&mut transaction, &user_id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to reset the 2FA of user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(html!("2FA was reset"))
+}
+
+pub async fn get_recovery_codes(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let count = service::count_recovery_codes(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to count the recovery codes of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::recovery_codes_overview(count))
+}
+
+pub async fn regenerate_recovery_codes(
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let codes = service::generate_recovery_codes(&mut transaction, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to generate recovery codes for user {}: {err:?}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::recovery_codes(&codes, false))
+}
+
+pub async fn oidc_login(State(state): State<AppState>, jar: CookieJar) -> Result<Response> {
+    let oidc = state.oidc.as_deref().ok_or(StatusCode::NOT_FOUND)?;
+
+    let (url, flow_cookie) = oidc.authorize(oidc::Flow::Login);
+
+    Ok((jar.add(flow_cookie), Redirect::to(&url)).into_response())
+}
+
+/// Whether the user's account is linked to an account of the provider, for the account page.
+pub async fn get_sso_link(
+    State(state): State<AppState>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let Some(oidc) = state.oidc.as_deref() else {
+        return Ok(html!());
+    };
+
+    let user = service::get_user_from_id(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::sso_link(&oidc.name, user.sso_subject.is_some()))
+}
+
+/// Starts a login with the provider, which links the provider's account to the account the user is logged in with.
+pub async fn start_sso_link(
+    State(state): State<AppState>,
+    Extension(user): Extension<UserData>,
+    jar: CookieJar,
+) -> Result<Response> {
+    let oidc = state.oidc.as_deref().ok_or(StatusCode::NOT_FOUND)?;
+
+    if user.api_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let (url, flow_cookie) = oidc.authorize(oidc::Flow::Link);
+
+    Ok((jar.add(flow_cookie), Redirect::to(&url)).into_response())
+}
+
+pub async fn unlink_sso(
+    State(state): State<AppState>,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    let oidc = state.oidc.as_deref().ok_or(StatusCode::NOT_FOUND)?;
+
+    if user.api_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    service::unlink_sso_subject(&mut transaction, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to unlink the SSO account of user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|_| TransactionError::Commit)?;
+
+    Ok(template::sso_link(&oidc.name, false))
+}
+
+/// The user the session cookie belongs to, when the session is still open and the user logged in recently, with
+/// their second factor if they have one.
+async fn recently_logged_in_user(
+    state: &AppState,
+    conn: &mut SqliteConnection,
+    jar: &CookieJar,
+) -> Result<Option<String>> {
+    let Some(token) = jar.get("token") else {
+        return Ok(None);
+    };
+
+    let Ok((id, last_action, session_id)) = validate_paseto(token.value(), &state.paseto) else {
+        return Ok(None);
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .as_secs();
+
+    if now > last_action + state.sessions.twofa_window {
+        return Ok(None);
+    }
+
+    let exists = service::session_exists(conn, &session_id, &id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to look up session {session_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(exists.then_some(id))
+}
+
+/// Where the provider sends the user back to. Any failure sends them back to the login page.
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    request_headers: HeaderMap,
+    DBTransaction(mut transaction): DBTransaction<'_>,
+    jar: CookieJar,
+    Query(query): Query<dto::OidcCallbackQuery>,
+) -> Result<Response> {
+    let oidc = state.oidc.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let failed = |jar: CookieJar| (jar.remove(oidc::flow_cookie_removal()), Redirect::to("/login")).into_response();
+
+    if let Some(error) = &query.error {
+        eprintln!(
+            "The OpenID provider returned an error: {error} ({})",
+            query.error_description.as_deref().unwrap_or_default()
+        );
+        return Ok(failed(jar));
+    }
+
+    let (Some(code), Some(csrf_state), Some(flow_cookie)) = (query.code, query.state, jar.get(oidc::FLOW_COOKIE))
+    else {
+        return Ok(failed(jar));
+    };
+
+    let (flow, identity) = match oidc.identify(flow_cookie.value(), &csrf_state, code).await {
+        Ok(identity) => identity,
+        Err(err) => {
+            eprintln!("Failed to complete an SSO login: {err:?}");
+            return Ok(failed(jar));
+        }
+    };
+
+    if flow == oidc::Flow::Link {
+        let Some(user_id) = recently_logged_in_user(&state, &mut transaction, &jar).await? else {
+            return Ok(failed(jar));
+        };
+
+        let linked = service::link_sso_subject(&mut transaction, &user_id, &identity.subject)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to link an SSO account to user {user_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        if !linked {
+            eprintln!("The SSO account of {} is already linked to another user.", identity.subject);
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|_| TransactionError::Commit)?;
+
+        return Ok((jar.remove(oidc::flow_cookie_removal()), Redirect::to("/password")).into_response());
+    }
+
+    let user = service::resolve_sso_user(&mut transaction, &identity, oidc.provision_role.as_deref())
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to resolve the user for SSO account {}: {err}", identity.subject);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let user = match user {
+        service::SsoUser::Linked(user) | service::SsoUser::Provisioned(user) => user,
+        service::SsoUser::Refused => {
+            eprintln!(
+                "There is no user for SSO account {} ({}).",
+                identity.subject,
+                identity.email.as_deref().unwrap_or("no verified email address")
+            );
+            return Ok(failed(jar));
+        }
+    };
+
+    // The provider takes care of the second factor.
+    let (_, jar) = start_session(&state, transaction, jar, &request_headers, &user, None, false).await?;
+
+    Ok((jar.remove(oidc::flow_cookie_removal()), Redirect::to("/")).into_response())
+}
+
+pub async fn get_sso_domains(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    let domains = service::get_sso_domains(&mut connection)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the SSO domains: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(template::sso_domains(&domains))
+}
+
+pub async fn add_sso_domain(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<dto::SsoDomainBody>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    let domain = body.domain.trim().trim_start_matches('@').to_lowercase();
+
+    if !domain.is_empty() {
+        service::add_sso_domain(&mut connection, &domain)
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to add SSO domain {domain}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    get_sso_domains(DBConnection(connection), Extension(user)).await
+}
+
+pub async fn delete_sso_domain(
+    Path(domain): Path<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require(&mut connection, &user, Capability::ManageUsers).await?;
+
+    service::delete_sso_domain(&mut connection, &domain)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to delete SSO domain {domain}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    get_sso_domains(DBConnection(connection), Extension(user)).await
+}
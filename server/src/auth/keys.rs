@@ -0,0 +1,341 @@
+use std::{
+    ffi::OsString,
+    fs::{self, OpenOptions, Permissions},
+    io::{self, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    time::{SystemTime, SystemTimeError, UNIX_EPOCH},
+};
+
+use core::convert::TryFrom;
+use pasetors::{
+    Public,
+    claims::{Claims, ClaimsValidationRules},
+    errors::Error,
+    footer::Footer,
+    keys::{AsymmetricKeyPair, AsymmetricPublicKey, AsymmetricSecretKey, Generate},
+    paserk::{FormatAsPaserk, Id},
+    public,
+    token::{TrustedToken, UntrustedToken},
+    version4::V4,
+};
+
+const KEY_DIR: &str = "./keys";
+/// The signing key, its public key is stored next to it with a `.pub` extension.
+const SIGNING_KEY: &str = "paseto";
+/// The public keys of the previous signing keys, named after the moment they were retired.
+const RETIRED_DIR: &str = "retired";
+
+#[derive(Debug)]
+pub enum KeyError {
+    Io(io::Error),
+    Pasetors(Error),
+    SystemTimeError,
+    /// `PASETO_SECRET_KEY` is set, but `PASETO_PUBLIC_KEY` isn't.
+    MissingPublicKey,
+    /// The keys come from the environment, so they have to be rotated there.
+    FromEnvironment,
+    /// A key in `PASETO_PREVIOUS_PUBLIC_KEYS` isn't prefixed with the moment it was retired.
+    MissingRetiredAt,
+}
+
+impl From<io::Error> for KeyError {
+    fn from(err: io::Error) -> Self {
+        KeyError::Io(err)
+    }
+}
+
+impl From<Error> for KeyError {
+    fn from(err: Error) -> Self {
+        KeyError::Pasetors(err)
+    }
+}
+
+impl From<SystemTimeError> for KeyError {
+    fn from(_: SystemTimeError) -> Self {
+        KeyError::SystemTimeError
+    }
+}
+
+struct VerifyingKey {
+    kid: String,
+    key: AsymmetricPublicKey<V4>,
+    /// When the key stopped signing tokens, `None` for the current key.
+    retired_at: Option<u64>,
+}
+
+impl VerifyingKey {
+    fn new(key: AsymmetricPublicKey<V4>, retired_at: Option<u64>) -> Result<Self, Error> {
+        let mut kid = String::new();
+        Id::from(&key).fmt(&mut kid).map_err(|_| Error::PaserkParsing)?;
+
+        Ok(VerifyingKey { kid, key, retired_at })
+    }
+}
+
+/// The key tokens are signed with and every key they are still accepted from. Tokens carry the id of their key in a
+/// `kid` footer, so a rotated key keeps working for the tokens it signed until its grace period is over.
+pub struct Paseto {
+    secret_key: AsymmetricSecretKey<V4>,
+    signing_kid: Id,
+    /// The current key comes first.
+    public_keys: Vec<VerifyingKey>,
+    /// How long a retired key is still accepted, checked on every token since the server outlives it.
+    grace: u64,
+}
+
+/// How long a retired key is still accepted, from `PASETO_KEY_GRACE_HOURS`. The default outlasts both a session and
+/// an invitation.
+pub fn grace_from_env() -> u64 {
+    std::env::var("PASETO_KEY_GRACE_HOURS")
+        .ok()
+        .map(|value| value.parse::<u64>().expect("PASETO_KEY_GRACE_HOURS must be a number of hours."))
+        .unwrap_or(7 * 24)
+        * 60
+        * 60
+}
+
+fn unix_now() -> Result<u64, SystemTimeError> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+fn key_dir() -> PathBuf {
+    PathBuf::from(KEY_DIR)
+}
+
+fn public_key_path(secret_key_path: &Path) -> PathBuf {
+    let mut path = OsString::from(secret_key_path.as_os_str());
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// Writes the file through a temporary one, so a crash never leaves half a key behind.
+fn write_key(path: &Path, contents: &str, mode: u32) -> io::Result<()> {
+    let mut temporary = OsString::from(path.as_os_str());
+    temporary.push(".tmp");
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(&temporary, path)
+}
+
+/// Tightens the permissions of a file or directory that others can read, e.g. one created by an older version.
+fn restrict(path: &Path, mode: u32) -> io::Result<()> {
+    let current = fs::metadata(path)?.permissions().mode() & 0o777;
+
+    if current & !mode != 0 {
+        eprintln!("{} had permissions {current:o}, restricting them to {mode:o}.", path.display());
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+    }
+
+    Ok(())
+}
+
+fn create_dir(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        fs::create_dir_all(path)?;
+    }
+
+    restrict(path, 0o700)
+}
+
+fn generate(secret_key_path: &Path) -> Result<(), KeyError> {
+    let key_pair = AsymmetricKeyPair::<V4>::generate()?;
+    let mut secret_key = String::new();
+    let mut public_key = String::new();
+    key_pair.secret.fmt(&mut secret_key).map_err(|_| Error::PaserkParsing)?;
+    key_pair.public.fmt(&mut public_key).map_err(|_| Error::PaserkParsing)?;
+
+    write_key(secret_key_path, &secret_key, 0o600)?;
+    write_key(&public_key_path(secret_key_path), &public_key, 0o644)?;
+
+    Ok(())
+}
+
+/// Reads the public keys of the retired signing keys, and removes the ones that are past their grace period.
+fn retired_keys(grace: u64) -> Result<Vec<VerifyingKey>, KeyError> {
+    let dir = key_dir().join(RETIRED_DIR);
+
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let now = unix_now()?;
+    let mut keys = Vec::new();
+
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let retired_at = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok());
+
+        let Some(retired_at) = retired_at else {
+            eprintln!("Ignoring {}, it isn't named after the moment it was retired.", path.display());
+            continue;
+        };
+
+        if retired_at + grace < now {
+            fs::remove_file(&path)?;
+            continue;
+        }
+
+        let key = AsymmetricPublicKey::<V4>::try_from(fs::read_to_string(&path)?.trim())?;
+        keys.push(VerifyingKey::new(key, Some(retired_at))?);
+    }
+
+    // The most recently retired key is the most likely to have signed a token.
+    keys.sort_by(|a, b| b.retired_at.cmp(&a.retired_at));
+
+    Ok(keys)
+}
+
+impl Paseto {
+    /// Loads the keys from `PASETO_SECRET_KEY`, `PASETO_PUBLIC_KEY` and the comma separated
+    /// `PASETO_PREVIOUS_PUBLIC_KEYS` when the first is set, and from the key directory otherwise. A signing key is
+    /// generated when the directory doesn't have one yet.
+    pub fn load(grace: u64) -> Result<Self, KeyError> {
+        match std::env::var("PASETO_SECRET_KEY") {
+            Ok(secret_key) => Self::from_env(&secret_key, grace),
+            Err(_) => Self::from_files(grace),
+        }
+    }
+
+    /// The previous keys are written as `<retired at>:<public key>`, the moment being a Unix timestamp.
+    fn from_env(secret_key: &str, grace: u64) -> Result<Self, KeyError> {
+        let secret_key = AsymmetricSecretKey::<V4>::try_from(secret_key.trim())?;
+        let public_key = std::env::var("PASETO_PUBLIC_KEY").map_err(|_| KeyError::MissingPublicKey)?;
+        let public_key = AsymmetricPublicKey::<V4>::try_from(public_key.trim())?;
+
+        let mut public_keys = vec![VerifyingKey::new(public_key, None)?];
+
+        for previous in std::env::var("PASETO_PREVIOUS_PUBLIC_KEYS").unwrap_or_default().split(',') {
+            if previous.trim().is_empty() {
+                continue;
+            }
+
+            let (retired_at, key) = previous
+                .trim()
+                .split_once(':')
+                .and_then(|(retired_at, key)| Some((retired_at.parse::<u64>().ok()?, key)))
+                .ok_or(KeyError::MissingRetiredAt)?;
+
+            let key = AsymmetricPublicKey::<V4>::try_from(key)?;
+            public_keys.push(VerifyingKey::new(key, Some(retired_at))?);
+        }
+
+        Ok(Self::new(secret_key, public_keys, grace))
+    }
+
+    fn from_files(grace: u64) -> Result<Self, KeyError> {
+        let dir = key_dir();
+        let secret_key_path = dir.join(SIGNING_KEY);
+
+        create_dir(&dir)?;
+
+        if !secret_key_path.exists() {
+            generate(&secret_key_path)?;
+        }
+
+        restrict(&secret_key_path, 0o600)?;
+
+        let secret_key = AsymmetricSecretKey::<V4>::try_from(fs::read_to_string(&secret_key_path)?.trim())?;
+        let public_key =
+            AsymmetricPublicKey::<V4>::try_from(fs::read_to_string(public_key_path(&secret_key_path))?.trim())?;
+
+        let mut public_keys = vec![VerifyingKey::new(public_key, None)?];
+        public_keys.extend(retired_keys(grace)?);
+
+        Ok(Self::new(secret_key, public_keys, grace))
+    }
+
+    fn new(secret_key: AsymmetricSecretKey<V4>, public_keys: Vec<VerifyingKey>, grace: u64) -> Self {
+        let signing_kid = Id::from(&public_keys[0].key);
+
+        Paseto {
+            secret_key,
+            signing_kid,
+            public_keys,
+            grace,
+        }
+    }
+
+    /// Replaces the signing key in the key directory with a new one, and keeps the public key of the old one for the
+    /// grace period. The server picks up the new key when it restarts. Returns the id of the new key.
+    pub fn rotate(grace: u64) -> Result<String, KeyError> {
+        if std::env::var("PASETO_SECRET_KEY").is_ok() {
+            return Err(KeyError::FromEnvironment);
+        }
+
+        let dir = key_dir();
+        let secret_key_path = dir.join(SIGNING_KEY);
+        let retired_dir = dir.join(RETIRED_DIR);
+
+        create_dir(&dir)?;
+        create_dir(&retired_dir)?;
+
+        if public_key_path(&secret_key_path).exists() {
+            let public_key = fs::read_to_string(public_key_path(&secret_key_path))?;
+            write_key(&retired_dir.join(format!("{}.pub", unix_now()?)), &public_key, 0o644)?;
+        }
+
+        generate(&secret_key_path)?;
+
+        // Removes the keys that are past their grace period.
+        retired_keys(grace)?;
+
+        let public_key =
+            AsymmetricPublicKey::<V4>::try_from(fs::read_to_string(public_key_path(&secret_key_path))?.trim())?;
+
+        Ok(VerifyingKey::new(public_key, None)?.kid)
+    }
+
+    pub fn sign(&self, claims: &Claims, implicit_assertion: &[u8]) -> Result<String, Error> {
+        let mut footer = Footer::new();
+        footer.key_id(&self.signing_kid);
+
+        public::sign(&self.secret_key, claims, Some(&footer), Some(implicit_assertion))
+    }
+
+    pub fn verify(
+        &self,
+        token: &UntrustedToken<Public, V4>,
+        validation_rules: &ClaimsValidationRules,
+        implicit_assertion: &[u8],
+    ) -> Result<TrustedToken, Error> {
+        let now = unix_now().map_err(|_| Error::TokenValidation)?;
+        let mut public_keys = self
+            .public_keys
+            .iter()
+            .filter(|key| key.retired_at.is_none_or(|retired_at| now <= retired_at + self.grace));
+
+        if token.untrusted_footer().is_empty() {
+            // Tokens from before the keys had ids were signed with the key that was in use then, which is either still
+            // the current key or one of the retired ones.
+            return public_keys
+                .find_map(|key| public::verify(&key.key, token, validation_rules, None, Some(implicit_assertion)).ok())
+                .ok_or(Error::TokenValidation);
+        }
+
+        let mut footer = Footer::new();
+        footer.parse_bytes(token.untrusted_footer())?;
+
+        let kid = footer
+            .get_claim("kid")
+            .and_then(|kid| kid.as_str())
+            .ok_or(Error::TokenValidation)?;
+
+        let key = public_keys
+            .find(|key| key.kid == kid)
+            .ok_or(Error::TokenValidation)?;
+
+        public::verify(&key.key, token, validation_rules, Some(&footer), Some(implicit_assertion))
+    }
+}
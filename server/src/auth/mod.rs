@@ -1,15 +1,25 @@
-use axum::{http::StatusCode, middleware::from_fn_with_state, routing::{delete, get, patch, post, put}, Router};
+use axum::{http::StatusCode, Extension, middleware::from_fn_with_state, routing::{delete, get, patch, post, put}, Router};
 
-use crate::{auth::service::auth_middleware, AppState};
+use crate::{auth::service::{auth_middleware, UserData}, AppState};
 
 pub mod template;
 pub mod service;
 pub mod endpoint;
 pub mod models;
 pub mod dto;
+pub mod password;
+pub mod oidc;
+pub mod throttle;
+pub mod capability;
+pub mod api_token;
+pub mod keys;
 
-async fn ok() -> StatusCode {
-    StatusCode::OK
+/// The gate nginx puts in front of the pages, which only accepts a session. API tokens are meant for the JSON API.
+async fn ok(Extension(user): Extension<UserData>) -> StatusCode {
+    match user.api_token {
+        Some(_) => StatusCode::UNAUTHORIZED,
+        None => StatusCode::OK,
+    }
 }
 
 pub fn router(state: AppState) -> Router<AppState> {
@@ -18,11 +28,47 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/login", get(endpoint::get_login_page))
         .route("/login", post(endpoint::login))
         .route("/totp/verify", post(endpoint::totp_verify))
+        .route("/oidc/login", get(endpoint::oidc_login))
+        .route("/oidc/callback", get(endpoint::oidc_callback))
+        .route("/oidc/account", get(endpoint::get_sso_link).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/oidc/link", get(endpoint::start_sso_link).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/oidc/link", delete(endpoint::unlink_sso).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/sso-domains", get(endpoint::get_sso_domains).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/sso-domains", post(endpoint::add_sso_domain).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/sso-domains/{domain}", delete(endpoint::delete_sso_domain).layer(from_fn_with_state(state.clone(), auth_middleware)))
         .route("/logout", delete(endpoint::logout))
-        .route("/users", get(endpoint::get_users).layer(from_fn_with_state(state, auth_middleware)))     
-        .route("/users", post(endpoint::create_user))     
-        .route("/users/{user_id}/permissions", get(endpoint::get_permissions))
-        .route("/users/{user_id}/permissions", patch(endpoint::set_permission))
-        .route("/users/{user_id}", put(endpoint::update_user_data))
-        .route("/users/{user_id}", delete(endpoint::delete_user))
+        .route("/password", get(endpoint::get_change_password_form).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/password", put(endpoint::change_password).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/recovery-codes", get(endpoint::get_recovery_codes).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/recovery-codes", post(endpoint::regenerate_recovery_codes).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/password/set", get(endpoint::get_set_password_form))
+        .route("/password/set", post(endpoint::set_password))
+        .route("/password/forgot", get(endpoint::get_forgot_password_form))
+        .route("/password/forgot", post(endpoint::forgot_password))
+        .route("/sessions", get(endpoint::get_sessions).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/sessions", delete(endpoint::logout_everywhere).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/sessions/{session_id}", delete(endpoint::revoke_session).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/api-tokens", get(endpoint::get_api_tokens).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/api-tokens", post(endpoint::create_api_token).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/api-tokens/{token_id}", delete(endpoint::revoke_api_token).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/devices", get(endpoint::get_trusted_devices).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/devices/{device_id}", delete(endpoint::revoke_trusted_device).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users", get(endpoint::get_users).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users", post(endpoint::create_user).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/invitation", post(endpoint::resend_invitation).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/totp/reset", post(endpoint::reset_totp).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/permissions", get(endpoint::get_permissions).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/permissions", patch(endpoint::set_permission).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/sessions", get(endpoint::get_user_sessions).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/sessions", delete(endpoint::revoke_user_sessions).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/sessions/{session_id}", delete(endpoint::revoke_user_session).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/devices", get(endpoint::get_user_trusted_devices).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}/devices/{device_id}", delete(endpoint::revoke_user_trusted_device).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}", put(endpoint::update_user_data).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/users/{user_id}", delete(endpoint::delete_user).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/roles", get(endpoint::get_roles).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/roles", post(endpoint::create_role).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/roles/options", get(endpoint::get_role_options).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/roles/{role}", patch(endpoint::set_role_capabilities).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/roles/{role}", delete(endpoint::delete_role).layer(from_fn_with_state(state, auth_middleware)))
 }
\ No newline at end of file
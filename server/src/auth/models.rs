@@ -13,11 +13,45 @@ pub struct User {
     pub last_name: String,
     pub password: String,
     pub totp_secret: Option<String>,
-    pub role: String
+    pub totp_pending_secret: Option<String>,
+    pub totp_last_step: Option<i64>,
+    pub role: String,
+    pub sso_subject: Option<String>
 }
 
+/// A capability granted to a user for one company, `None` being the projects without a company.
 pub struct Permission {
     pub company: Option<String>,
-    pub create: Option<bool>,
-    pub edit: Option<bool>
+    pub capability: String
+}
+
+pub struct CapabilityInfo {
+    pub name: String,
+    pub label: String,
+    pub scoped: bool
+}
+
+pub struct Role {
+    pub name: String,
+    pub description: String
+}
+
+pub struct RoleCapability {
+    pub role: String,
+    pub capability: String
+}
+
+pub struct Session {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: i64,
+    pub last_activity: i64
+}
+
+pub struct TrustedDevice {
+    pub device_id: String,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub trusted_until: i64
 }
\ No newline at end of file
@@ -0,0 +1,487 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use oauth2::{
+    AuthUrl, AuthorizationCode, Client, ClientId, ClientSecret, CsrfToken, EndpointNotSet, EndpointSet,
+    ExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, StandardRevocableToken,
+    StandardTokenResponse, TokenUrl,
+    basic::{BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse, BasicTokenType},
+    reqwest,
+};
+use serde::{Deserialize, Serialize};
+
+/// The token endpoint of an OpenID provider also returns an ID token, which holds the user's identity.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IdTokenFields {
+    pub id_token: String,
+}
+
+impl ExtraTokenFields for IdTokenFields {}
+
+type OidcTokenResponse = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+
+type OidcClient = Client<
+    BasicErrorResponse,
+    OidcTokenResponse,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointSet,
+>;
+
+#[derive(Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+/// Single sign-on through an OpenID Connect provider, using the authorization code flow with PKCE.
+pub struct Oidc {
+    client: OidcClient,
+    http_client: reqwest::Client,
+    issuer: String,
+    client_id: String,
+    /// The label of the login button, e.g. "Microsoft 365".
+    pub name: String,
+    /// The role of users that are created on their first SSO login, no users are created when this isn't set.
+    pub provision_role: Option<String>,
+}
+
+/// The cookie that carries the CSRF state, PKCE verifier and nonce from the login redirect to the callback.
+pub const FLOW_COOKIE: &str = "oidc";
+
+#[derive(Debug)]
+pub enum OidcError {
+    Request(String),
+    /// The provider's discovery document couldn't be parsed or has an endpoint that isn't a URL.
+    InvalidDiscovery(String),
+    /// The ID token is malformed or doesn't belong to this login.
+    InvalidIdToken(&'static str),
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    exp: u64,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::Single(audience) => audience == client_id,
+            Audience::Multiple(audiences) => audiences.iter().any(|audience| audience == client_id),
+        }
+    }
+}
+
+/// Whether the user is logging in, or linking the provider's account to the account they're logged in with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Flow {
+    Login,
+    Link,
+}
+
+impl Flow {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Flow::Login => "login",
+            Flow::Link => "link",
+        }
+    }
+}
+
+/// The identity of a user, as asserted by the provider.
+#[derive(Debug)]
+pub struct Identity {
+    /// The provider's id of the account, which never changes or gets reused.
+    pub subject: String,
+    /// Only set when the provider verified it.
+    pub email: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl Oidc {
+    /// Reads the `OIDC_*` environment variables and fetches the provider's configuration. SSO is disabled when
+    /// `OIDC_ISSUER` isn't set or the provider can't be discovered.
+    pub async fn from_env() -> Option<Self> {
+        let issuer = std::env::var("OIDC_ISSUER").ok().filter(|issuer| !issuer.is_empty())?;
+        let client_id = std::env::var("OIDC_CLIENT_ID").expect("Failed to find the OIDC_CLIENT_ID environment variable.");
+        let client_secret =
+            std::env::var("OIDC_CLIENT_SECRET").expect("Failed to find the OIDC_CLIENT_SECRET environment variable.");
+        let redirect_url =
+            std::env::var("OIDC_REDIRECT_URL").expect("Failed to find the OIDC_REDIRECT_URL environment variable.");
+        let name = std::env::var("OIDC_NAME").unwrap_or("single sign-on".to_string());
+        let provision_role = std::env::var("OIDC_PROVISION_ROLE").ok().filter(|role| !role.is_empty());
+
+        // Password logins keep working while the provider can't be reached, SSO is off until the next restart.
+        match Self::discover(&issuer, client_id, client_secret, redirect_url, name, provision_role).await {
+            Ok(oidc) => Some(oidc),
+            Err(err) => {
+                eprintln!("Failed to discover the OpenID provider at {issuer}, SSO is disabled: {err:?}");
+                None
+            }
+        }
+    }
+
+    /// Fetches the provider's configuration from its discovery document.
+    pub async fn discover(
+        issuer: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+        name: String,
+        provision_role: Option<String>,
+    ) -> Result<Self, OidcError> {
+        // Following redirects would open the token request up to SSRF.
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to create the OIDC HTTP client.");
+
+        let discovery_url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let discovery = http_client
+            .get(&discovery_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| OidcError::Request(err.to_string()))?
+            .bytes()
+            .await
+            .map_err(|err| OidcError::Request(err.to_string()))?;
+        let discovery: Discovery =
+            serde_json::from_slice(&discovery).map_err(|err| OidcError::InvalidDiscovery(err.to_string()))?;
+
+        let auth_url = AuthUrl::new(discovery.authorization_endpoint)
+            .map_err(|err| OidcError::InvalidDiscovery(format!("Invalid authorization endpoint: {err}")))?;
+        let token_url = TokenUrl::new(discovery.token_endpoint)
+            .map_err(|err| OidcError::InvalidDiscovery(format!("Invalid token endpoint: {err}")))?;
+
+        let client = Client::new(ClientId::new(client_id.clone()))
+            .set_client_secret(ClientSecret::new(client_secret))
+            .set_auth_uri(auth_url)
+            .set_token_uri(token_url)
+            .set_redirect_uri(RedirectUrl::new(redirect_url).expect("Invalid OIDC_REDIRECT_URL."));
+
+        Ok(Oidc {
+            client,
+            http_client,
+            issuer: discovery.issuer,
+            client_id,
+            name,
+            provision_role,
+        })
+    }
+
+    /// Returns the URL to send the user to, and the cookie that has to come back with the callback.
+    pub fn authorize(&self, flow: Flow) -> (String, Cookie<'static>) {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let nonce = CsrfToken::new_random();
+
+        let (url, csrf_state) = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .add_scope(Scope::new("openid".to_string()))
+            .add_scope(Scope::new("email".to_string()))
+            .add_scope(Scope::new("profile".to_string()))
+            .add_extra_param("nonce", nonce.secret())
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        let mut cookie = Cookie::new(
+            FLOW_COOKIE,
+            format!("{}.{}.{}.{}", csrf_state.secret(), pkce_verifier.secret(), nonce.secret(), flow.as_str()),
+        );
+        cookie.set_http_only(true);
+        cookie.set_secure(true);
+        // The callback is a top-level navigation from the provider, which Lax still sends the cookie with.
+        cookie.set_same_site(SameSite::Lax);
+        cookie.set_path("/");
+        cookie.set_max_age(cookie::time::Duration::minutes(10));
+
+        (url.to_string(), cookie)
+    }
+
+    /// Exchanges the authorization code for the user's identity, after checking the callback belongs to the login
+    /// started with `flow_cookie`.
+    pub async fn identify(&self, flow_cookie: &str, state: &str, code: String) -> Result<(Flow, Identity), OidcError> {
+        let mut parts = flow_cookie.split('.');
+
+        let (Some(expected_state), Some(pkce_verifier), Some(nonce), Some(flow)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(OidcError::InvalidIdToken("The login cookie is malformed."));
+        };
+
+        let flow = match flow {
+            "login" => Flow::Login,
+            "link" => Flow::Link,
+            _ => return Err(OidcError::InvalidIdToken("The login cookie is malformed.")),
+        };
+
+        if expected_state != state {
+            return Err(OidcError::InvalidIdToken("The state doesn't match."));
+        }
+
+        let token_response = self
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier.to_string()))
+            .request_async(&self.http_client)
+            .await
+            .map_err(|err| OidcError::Request(err.to_string()))?;
+
+        let claims = decode_id_token(&token_response.extra_fields().id_token)?;
+
+        // The ID token comes straight from the token endpoint over TLS, which OpenID Connect Core (3.1.3.7) allows in
+        // place of checking its signature. The claims still have to be checked.
+        if claims.iss != self.issuer {
+            return Err(OidcError::InvalidIdToken("The issuer doesn't match."));
+        }
+
+        if !claims.aud.contains(&self.client_id) {
+            return Err(OidcError::InvalidIdToken("The audience doesn't match."));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        if claims.exp < now {
+            return Err(OidcError::InvalidIdToken("The ID token expired."));
+        }
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("The nonce doesn't match."));
+        }
+
+        // An address the provider didn't verify can be any address, e.g. one typed in by a user of another tenant.
+        // Without it, the account can only log in once the user linked it.
+        let email = claims
+            .email
+            .filter(|email| email.contains('@') && claims.email_verified == Some(true))
+            .map(|email| email.to_lowercase());
+
+        let mut names = claims.name.as_deref().unwrap_or_default().splitn(2, ' ');
+        let first_name = claims
+            .given_name
+            .unwrap_or_else(|| names.next().unwrap_or_default().to_string());
+        let last_name = claims
+            .family_name
+            .unwrap_or_else(|| names.next().unwrap_or_default().to_string());
+
+        Ok((
+            flow,
+            Identity {
+                subject: claims.sub,
+                email,
+                first_name,
+                last_name,
+            },
+        ))
+    }
+}
+
+fn decode_id_token(id_token: &str) -> Result<IdTokenClaims, OidcError> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or(OidcError::InvalidIdToken("The ID token is malformed."))?;
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| OidcError::InvalidIdToken("The ID token is malformed."))?;
+
+    serde_json::from_slice(&payload).map_err(|_| OidcError::InvalidIdToken("The ID token is malformed."))
+}
+
+/// Removes the flow cookie once the callback was handled.
+pub fn flow_cookie_removal() -> Cookie<'static> {
+    let mut cookie = Cookie::from(FLOW_COOKIE);
+    cookie.set_path("/");
+    cookie
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{Json, Router, extract::State, routing::{get, post}};
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const CLIENT_ID: &str = "editor";
+
+    /// An OpenID provider that answers every code with the ID token set in `claims`.
+    async fn mock_issuer() -> (String, Arc<Mutex<Value>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let claims = Arc::new(Mutex::new(Value::Null));
+
+        let discovery = json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{issuer}/authorize"),
+            "token_endpoint": format!("{issuer}/token"),
+        });
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(move || async move { Json(discovery) }))
+            .route(
+                "/token",
+                post(|State(claims): State<Arc<Mutex<Value>>>| async move {
+                    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#);
+                    let payload = URL_SAFE_NO_PAD.encode(claims.lock().unwrap().to_string());
+
+                    Json(json!({
+                        "access_token": "access",
+                        "token_type": "Bearer",
+                        "id_token": format!("{header}.{payload}."),
+                    }))
+                }),
+            )
+            .with_state(claims.clone());
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (issuer, claims)
+    }
+
+    async fn oidc(issuer: &str) -> Oidc {
+        Oidc::discover(
+            issuer,
+            CLIENT_ID.to_string(),
+            "secret".to_string(),
+            "https://admin.example.com/api/auth/oidc/callback".to_string(),
+            "Example".to_string(),
+            None,
+        )
+        .await
+        .expect("Failed to discover the mock provider.")
+    }
+
+    /// Starts a login and returns the flow cookie, the state and the claims of a valid ID token for it.
+    fn start(oidc: &Oidc, issuer: &str, flow: Flow) -> (String, String, Value) {
+        let (_, cookie) = oidc.authorize(flow);
+        let parts: Vec<&str> = cookie.value().split('.').collect();
+
+        let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 300;
+        let claims = json!({
+            "iss": issuer,
+            "sub": "subject-1",
+            "aud": CLIENT_ID,
+            "exp": exp,
+            "nonce": parts[2],
+            "email": "Jan@Example.com",
+            "email_verified": true,
+            "given_name": "Jan",
+            "family_name": "Peeters",
+        });
+
+        (cookie.value().to_string(), parts[0].to_string(), claims)
+    }
+
+    #[tokio::test]
+    async fn verified_email() {
+        let (issuer, id_token) = mock_issuer().await;
+        let oidc = oidc(&issuer).await;
+        let (cookie, state, claims) = start(&oidc, &issuer, Flow::Link);
+        *id_token.lock().unwrap() = claims;
+
+        let (flow, identity) = oidc.identify(&cookie, &state, "code".to_string()).await.unwrap();
+
+        assert_eq!(flow, Flow::Link);
+        assert_eq!(identity.subject, "subject-1");
+        assert_eq!(identity.email.as_deref(), Some("jan@example.com"));
+        assert_eq!(identity.first_name, "Jan");
+        assert_eq!(identity.last_name, "Peeters");
+    }
+
+    #[tokio::test]
+    async fn unverified_email() {
+        let (issuer, id_token) = mock_issuer().await;
+        let oidc = oidc(&issuer).await;
+
+        for email_verified in [json!(false), Value::Null] {
+            let (cookie, state, mut claims) = start(&oidc, &issuer, Flow::Login);
+            claims["email_verified"] = email_verified;
+            *id_token.lock().unwrap() = claims;
+
+            let (_, identity) = oidc.identify(&cookie, &state, "code".to_string()).await.unwrap();
+
+            assert_eq!(identity.subject, "subject-1");
+            assert_eq!(identity.email, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn username_is_not_an_email() {
+        let (issuer, id_token) = mock_issuer().await;
+        let oidc = oidc(&issuer).await;
+        let (cookie, state, mut claims) = start(&oidc, &issuer, Flow::Login);
+        claims.as_object_mut().unwrap().remove("email");
+        claims["preferred_username"] = json!("jan@example.com");
+        *id_token.lock().unwrap() = claims;
+
+        let (_, identity) = oidc.identify(&cookie, &state, "code".to_string()).await.unwrap();
+
+        assert_eq!(identity.email, None);
+    }
+
+    #[tokio::test]
+    async fn invalid_id_tokens() {
+        let (issuer, id_token) = mock_issuer().await;
+        let oidc = oidc(&issuer).await;
+
+        let invalid: [(&str, Value); 4] = [
+            ("iss", json!("https://other.example.com")),
+            ("aud", json!(["other"])),
+            ("nonce", json!("other")),
+            ("exp", json!(1)),
+        ];
+
+        for (claim, value) in invalid {
+            let (cookie, state, mut claims) = start(&oidc, &issuer, Flow::Login);
+            claims[claim] = value;
+            *id_token.lock().unwrap() = claims;
+
+            let result = oidc.identify(&cookie, &state, "code".to_string()).await;
+
+            assert!(matches!(result, Err(OidcError::InvalidIdToken(_))), "{claim} wasn't checked");
+        }
+    }
+
+    #[tokio::test]
+    async fn state_mismatch() {
+        let (issuer, id_token) = mock_issuer().await;
+        let oidc = oidc(&issuer).await;
+        let (cookie, _, claims) = start(&oidc, &issuer, Flow::Login);
+        *id_token.lock().unwrap() = claims;
+
+        let result = oidc.identify(&cookie, "other", "code".to_string()).await;
+
+        assert!(matches!(result, Err(OidcError::InvalidIdToken(_))));
+    }
+}
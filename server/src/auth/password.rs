@@ -0,0 +1,276 @@
+use std::{
+    collections::HashSet,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use core::convert::TryFrom;
+use core::time::Duration;
+use pasetors::{
+    claims::{Claims, ClaimsValidationRules},
+    token::UntrustedToken,
+    version4::V4,
+};
+use sqlx::{SqliteConnection, query, query_scalar};
+use uuid::Uuid;
+
+use super::keys::Paseto;
+
+/// Password tokens are signed with the same key as the session tokens, so they get their own implicit assertion to
+/// make sure the one can't be used as the other.
+const IMPLICIT_ASSERTION: &[u8] = b"password token";
+
+/// What a password token may be used for.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Purpose {
+    /// Sent to a new user, to set their first password and enrol TOTP.
+    Invitation,
+    /// Sent from the forgot-password form.
+    Reset,
+}
+
+impl Purpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Purpose::Invitation => "invitation",
+            Purpose::Reset => "reset",
+        }
+    }
+
+    /// How long a link stays valid, in seconds.
+    fn lifetime(&self) -> u64 {
+        match self {
+            Purpose::Invitation => 7 * 24 * 60 * 60,
+            Purpose::Reset => 60 * 60,
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "invitation" => Some(Purpose::Invitation),
+            "reset" => Some(Purpose::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// The requirements for new passwords.
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    breached: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    /// Reads `PASSWORD_MIN_LENGTH` and `BREACHED_PASSWORDS_FILE`, a plain text file with one known breached password
+    /// per line.
+    pub fn from_env() -> Self {
+        let min_length = std::env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .map(|value| value.parse::<usize>().expect("PASSWORD_MIN_LENGTH must be a number."))
+            .unwrap_or(12);
+
+        let breached = match std::env::var("BREACHED_PASSWORDS_FILE") {
+            Ok(path) => match fs::read_to_string(&path) {
+                Ok(contents) => contents
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect(),
+                Err(err) => {
+                    eprintln!("Failed to read the breached passwords list {path}: {err}");
+                    HashSet::new()
+                }
+            },
+            Err(_) => HashSet::new(),
+        };
+
+        PasswordPolicy {
+            min_length,
+            breached,
+        }
+    }
+
+    /// Returns a message for the user when the password doesn't meet the policy.
+    pub fn check(&self, password: &str, email: &str) -> Result<(), String> {
+        if password.chars().count() < self.min_length {
+            return Err(format!(
+                "The password has to be at least {} characters long.",
+                self.min_length
+            ));
+        }
+
+        // bcrypt only looks at the first 72 bytes.
+        if password.len() > 72 {
+            return Err("The password can't be longer than 72 bytes.".to_string());
+        }
+
+        if password.eq_ignore_ascii_case(email) {
+            return Err("The password can't be your email address.".to_string());
+        }
+
+        if self.breached.contains(password) {
+            return Err(
+                "This password appears in a list of breached passwords, please choose another one.".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+pub fn hash(password: &str) -> Result<String, bcrypt::BcryptError> {
+    bcrypt::hash_with_result(password, 12).map(|hash| hash.to_string())
+}
+
+#[derive(Debug)]
+pub enum PasswordTokenError {
+    SQL(sqlx::Error),
+    Pasetors(pasetors::errors::Error),
+    SystemTimeError,
+    /// The token is malformed, expired, used or meant for something else.
+    Invalid,
+}
+
+fn unix_now() -> Result<i64, PasswordTokenError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .map_err(|_| PasswordTokenError::SystemTimeError)
+}
+
+/// Creates a signed, single-use token for the user, replacing any unused token with the same purpose.
+pub async fn create_token(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    purpose: Purpose,
+    paseto: &Paseto,
+) -> Result<String, PasswordTokenError> {
+    let id = Uuid::new_v4().to_string();
+    let purpose_name = purpose.as_str();
+    let expires_at = unix_now()? + purpose.lifetime() as i64;
+
+    query!(
+        "DELETE FROM password_tokens WHERE user_id=$1 AND purpose=$2 AND used_at IS NULL",
+        user_id,
+        purpose_name
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(PasswordTokenError::SQL)?;
+
+    query!(
+        "INSERT INTO password_tokens(id, user_id, purpose, expires_at) VALUES($1, $2, $3, $4)",
+        id,
+        user_id,
+        purpose_name,
+        expires_at
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(PasswordTokenError::SQL)?;
+
+    let mut claims = Claims::new().map_err(PasswordTokenError::Pasetors)?;
+    claims
+        .subject(user_id)
+        .map_err(PasswordTokenError::Pasetors)?;
+    claims
+        .token_identifier(&id)
+        .map_err(PasswordTokenError::Pasetors)?;
+    claims
+        .issuer("tophat.be")
+        .map_err(PasswordTokenError::Pasetors)?;
+    claims
+        .set_expires_in(&Duration::from_secs(purpose.lifetime()))
+        .map_err(PasswordTokenError::Pasetors)?;
+    claims
+        .add_additional("purpose", purpose_name)
+        .map_err(PasswordTokenError::Pasetors)?;
+
+    paseto
+        .sign(&claims, IMPLICIT_ASSERTION)
+        .map_err(PasswordTokenError::Pasetors)
+}
+
+pub struct PasswordToken {
+    pub id: String,
+    pub user_id: String,
+    pub purpose: Purpose,
+}
+
+/// Checks the signature and expiry of the token, and that it hasn't been used yet.
+pub async fn validate_token(
+    conn: &mut SqliteConnection,
+    token: &str,
+    paseto: &Paseto,
+) -> Result<PasswordToken, PasswordTokenError> {
+    let mut validation_rules = ClaimsValidationRules::new();
+    validation_rules.validate_issuer_with("tophat.be");
+
+    let untrusted_token = UntrustedToken::<pasetors::Public, V4>::try_from(token)
+        .map_err(|_| PasswordTokenError::Invalid)?;
+
+    let trusted_token = paseto
+        .verify(&untrusted_token, &validation_rules, IMPLICIT_ASSERTION)
+        .map_err(|_| PasswordTokenError::Invalid)?;
+
+    let claims = trusted_token
+        .payload_claims()
+        .ok_or(PasswordTokenError::Invalid)?;
+    let claim = |name: &str| {
+        claims
+            .get_claim(name)
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+            .ok_or(PasswordTokenError::Invalid)
+    };
+
+    let id = claim("jti")?;
+    let user_id = claim("sub")?;
+    let purpose = Purpose::parse(&claim("purpose")?).ok_or(PasswordTokenError::Invalid)?;
+    let now = unix_now()?;
+
+    let unused = query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM password_tokens WHERE id=$1 AND user_id=$2 AND used_at IS NULL AND expires_at > $3) as "exists!: bool""#,
+        id,
+        user_id,
+        now
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(PasswordTokenError::SQL)?;
+
+    if !unused {
+        return Err(PasswordTokenError::Invalid);
+    }
+
+    Ok(PasswordToken {
+        id,
+        user_id,
+        purpose,
+    })
+}
+
+pub async fn use_token(conn: &mut SqliteConnection, token_id: &str) -> Result<(), PasswordTokenError> {
+    let now = unix_now()?;
+
+    query!(
+        "UPDATE password_tokens SET used_at=$1 WHERE id=$2",
+        now,
+        token_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+    .map_err(PasswordTokenError::SQL)
+}
+
+pub async fn set_password(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    hash: &str,
+) -> Result<(), sqlx::Error> {
+    query!("UPDATE USERS SET password=$1 WHERE id=$2", hash, user_id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
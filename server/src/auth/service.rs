@@ -8,28 +8,29 @@ use crate::{
     admin::{DBConnection, DBTransaction, draft::create_project_draft_if_necessary},
 };
 
-use super::models::{Permission, User, UserBase};
+use super::api_token;
+use super::keys::Paseto;
+use super::oidc::Identity;
+use super::models::{CapabilityInfo, Permission, Role, RoleCapability, Session, TrustedDevice, User, UserBase};
 use axum::{
     extract::{Path, Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
     middleware::Next,
     response::{IntoResponse, Response, Result},
 };
-use axum_extra::extract::CookieJar;
+use axum_extra::extract::{CookieJar, cookie::Cookie};
+use cookie::time::OffsetDateTime;
 use core::convert::TryFrom;
 use core::time::Duration;
 use otpauth::TOTP;
-use pasetors::keys::AsymmetricPublicKey;
 use pasetors::{
     claims::{Claims, ClaimsValidationRules},
-    keys::AsymmetricSecretKey,
-    public,
     token::UntrustedToken,
     version4::V4,
 };
 use rand::{Rng, distr::Alphanumeric};
 use serde::Deserialize;
-use sqlx::{SqliteConnection, query_as, query_scalar};
+use sqlx::{SqliteConnection, query, query_as, query_scalar};
 use uuid::Uuid;
 
 pub fn verify_token(token: &Option<String>) -> bool {
@@ -41,21 +42,34 @@ pub fn generate_totp_uri(secret: &str) -> String {
     totp.to_uri("GVR Content Editor", "Top Hat")
 }
 
-pub async fn get_user_totp_secret(
-    conn: &mut SqliteConnection,
-    id: &str,
-) -> Result<Option<String>, sqlx::Error> {
-    query_scalar!("SELECT totp_secret from USERS WHERE id=$1", id)
-        .fetch_one(conn)
-        .await
-}
-
-pub fn verify_totp(secret: &str, code: u32) -> Result<bool, SystemTimeError> {
+/// Returns the 30 second step the code belongs to, when it is valid for the current or the previous step (to allow
+/// for a code typed right at the end of its window). Steps up to and including `last_step` were already used and are
+/// rejected, so a code can't be replayed.
+pub fn verify_totp(secret: &str, code: u32, last_step: Option<i64>) -> Result<Option<i64>, SystemTimeError> {
     let totp = TOTP::new(secret);
 
-    let timestamp1 = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let current_step = (SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / 30) as i64;
+
+    Ok([current_step, current_step - 1]
+        .into_iter()
+        .filter(|step| last_step.is_none_or(|last_step| *step > last_step))
+        .find(|step| totp.verify(code, 30, (*step * 30) as u64)))
+}
 
-    Ok(totp.verify(code, 30, timestamp1))
+/// Records the step of a used code, returning false when it (or a later one) was already used in the meantime.
+pub async fn use_totp_step(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    step: i64,
+) -> Result<bool, sqlx::Error> {
+    query!(
+        "UPDATE USERS SET totp_last_step=$1 WHERE id=$2 AND (totp_last_step IS NULL OR totp_last_step < $1)",
+        step,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|result| result.rows_affected() > 0)
 }
 
 pub async fn get_user_from_email(
@@ -84,62 +98,221 @@ pub fn generate_totp_secret() -> String {
         .collect()
 }
 
-pub async fn set_user_totp_secret(
+/// Stores a secret the user is enrolling, it only becomes their TOTP secret after they entered a valid code for it.
+pub async fn set_pending_totp_secret(
     conn: &mut SqliteConnection,
-    email: &str,
+    user_id: &str,
     secret: &str,
 ) -> Result<(), sqlx::Error> {
-    query_scalar!(
-        "UPDATE USERS SET totp_secret=$1 WHERE email=$2",
+    query!(
+        "UPDATE USERS SET totp_pending_secret=$1 WHERE id=$2",
         secret,
-        email
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn confirm_totp_secret(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    step: i64,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE USERS SET totp_secret=totp_pending_secret, totp_pending_secret=NULL, totp_last_step=$1 WHERE id=$2 AND totp_pending_secret IS NOT NULL",
+        step,
+        user_id
     )
     .execute(conn)
     .await
     .map(|_| ())
 }
 
+/// Removes the user's second factor, so they enrol again on their next login. Their recovery codes, trusted devices
+/// and sessions go with it.
+pub async fn reset_totp(conn: &mut SqliteConnection, user_id: &str) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE USERS SET totp_secret=NULL, totp_pending_secret=NULL, totp_last_step=NULL WHERE id=$1",
+        user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!("DELETE FROM recovery_codes WHERE user_id=$1", user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    query!("DELETE FROM TRUSTED_IPS WHERE user_id=$1", user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    revoke_sessions(conn, user_id).await
+}
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug)]
+pub enum RecoveryCodeError {
+    SQL(sqlx::Error),
+    HashError,
+}
+
+fn generate_recovery_code() -> String {
+    let characters: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(|byte| char::from(byte).to_ascii_lowercase())
+        .collect();
+
+    format!("{}-{}", &characters[..5], &characters[5..])
+}
+
+/// Replaces the user's recovery codes with new ones. Only their hashes are stored, so this is the only time the codes
+/// can be shown.
+pub async fn generate_recovery_codes(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Vec<String>, RecoveryCodeError> {
+    query!("DELETE FROM recovery_codes WHERE user_id=$1", user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(RecoveryCodeError::SQL)?;
+
+    let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+
+    for _ in 0..RECOVERY_CODE_COUNT {
+        let code = generate_recovery_code();
+        let id = Uuid::new_v4().to_string();
+        let hash = bcrypt::hash_with_result(&code, 10)
+            .map_err(|err| {
+                eprintln!("Failed to create a hash: {err}");
+                RecoveryCodeError::HashError
+            })?
+            .to_string();
+
+        query!(
+            "INSERT INTO recovery_codes(id, user_id, hash) VALUES($1, $2, $3)",
+            id,
+            user_id,
+            hash
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(RecoveryCodeError::SQL)?;
+
+        codes.push(code);
+    }
+
+    Ok(codes)
+}
+
+struct RecoveryCode {
+    id: String,
+    hash: String,
+}
+
+/// Uses up the recovery code, returning false when it isn't one of the user's unused codes.
+pub async fn use_recovery_code(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let code = code.trim().to_ascii_lowercase();
+
+    let recovery_codes = query_as!(
+        RecoveryCode,
+        "SELECT id, hash FROM recovery_codes WHERE user_id=$1 AND used_at IS NULL",
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let Some(recovery_code) = recovery_codes
+        .iter()
+        .find(|recovery_code| bcrypt::verify(&code, &recovery_code.hash).unwrap_or(false))
+    else {
+        return Ok(false);
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+
+    query!(
+        "UPDATE recovery_codes SET used_at=$1 WHERE id=$2",
+        now,
+        recovery_code.id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())?;
+
+    Ok(true)
+}
+
+pub async fn count_recovery_codes(conn: &mut SqliteConnection, user_id: &str) -> Result<i64, sqlx::Error> {
+    query_scalar!(
+        "SELECT COUNT(*) FROM recovery_codes WHERE user_id=$1 AND used_at IS NULL",
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
 pub async fn get_permissions(
     conn: &mut SqliteConnection,
     user: &str,
 ) -> Result<Vec<Permission>, sqlx::Error> {
-    sqlx::query_as!(
+    query_as!(
         Permission,
-        r#"
-        SELECT
-            company,
-            p.`create`,
-            p.edit
-        FROM
-            PERMISSIONS p
-        WHERE
-            company IS NULL
-            AND user = $1
-        UNION
-        SELECT
-            NULL AS company,
-            NULL AS `create`,
-            NULL AS edit
-        WHERE
-            NOT EXISTS (
-                SELECT
-                    1
-                FROM
-                    PERMISSIONS p
-                WHERE
-                    p.company IS NULL
-                    AND user = $1
-            )
-        UNION
-        SELECT
-            name as company,
-            "create",
-            edit
-        FROM
-            COMPANIES c
-            LEFT JOIN PERMISSIONS p ON c.name = p.company
-            AND user = $1
-    "#,
+        "SELECT company, capability FROM PERMISSIONS WHERE user=$1",
+        user
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// The companies a permission can be granted for, `None` (the projects without a company) first.
+pub async fn get_permission_companies(conn: &mut SqliteConnection) -> Result<Vec<Option<String>>, sqlx::Error> {
+    let companies = query_scalar!("SELECT name FROM COMPANIES ORDER BY name")
+        .fetch_all(conn)
+        .await?;
+
+    Ok(std::iter::once(None)
+        .chain(companies.into_iter().map(Some))
+        .collect())
+}
+
+pub async fn get_capabilities(conn: &mut SqliteConnection) -> Result<Vec<CapabilityInfo>, sqlx::Error> {
+    query_as!(
+        CapabilityInfo,
+        r#"SELECT name, label, scoped as "scoped: bool" FROM CAPABILITIES ORDER BY position"#
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn get_roles(conn: &mut SqliteConnection) -> Result<Vec<Role>, sqlx::Error> {
+    query_as!(Role, "SELECT name, description FROM USER_ROLES ORDER BY name")
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn get_role_capabilities(conn: &mut SqliteConnection) -> Result<Vec<RoleCapability>, sqlx::Error> {
+    query_as!(RoleCapability, "SELECT role, capability FROM ROLE_CAPABILITIES")
+        .fetch_all(conn)
+        .await
+}
+
+/// The capabilities the user has everywhere, through their role.
+pub async fn get_user_role_capabilities(
+    conn: &mut SqliteConnection,
+    user: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar!(
+        "SELECT rc.capability FROM ROLE_CAPABILITIES rc JOIN USERS u ON u.role = rc.role WHERE u.id=$1",
         user
     )
     .fetch_all(conn)
@@ -203,20 +376,88 @@ pub async fn create_user(
     )
     .fetch_one(conn)
     .await
-    .map_err(|err| CreateUserError::SQL(err))
+    .map_err(CreateUserError::SQL)
 }
 
-pub async fn update_permission(
+/// Replaces the capabilities the user has for one company. Capabilities that can't be scoped to a company are ignored.
+pub async fn update_permissions(
     conn: &mut SqliteConnection,
     user: &str,
     company: &Option<String>,
-    create: bool,
-    edit: bool,
-) -> Result<Permission, sqlx::Error> {
-    sqlx::query_as!(Permission, r#"
-        INSERT INTO PERMISSIONS(user, company, `create`, edit) VALUES($1, $2, $3, $4) ON CONFLICT(user, IFNULL(company, '')) DO UPDATE SET `create`=$3, edit=$4 WHERE user=$1 AND (company=$2 OR (company IS NULL AND $2 IS NULL)) RETURNING company, `create`, edit"#, user, company, create, edit)
-    .fetch_one(conn)
+    capabilities: &[String],
+) -> Result<Vec<Permission>, sqlx::Error> {
+    query!(
+        "DELETE FROM PERMISSIONS WHERE user=$1 AND company IS $2",
+        user,
+        company
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for capability in capabilities {
+        query!(
+            "INSERT INTO PERMISSIONS(user, company, capability) SELECT $1, $2, name FROM CAPABILITIES WHERE name=$3 AND scoped=1",
+            user,
+            company,
+            capability
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    query_as!(
+        Permission,
+        "SELECT company, capability FROM PERMISSIONS WHERE user=$1 AND company IS $2",
+        user,
+        company
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn create_role(conn: &mut SqliteConnection, name: &str, description: &str) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO USER_ROLES(name, description) VALUES($1, $2)",
+        name,
+        description
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Replaces the capabilities of a role.
+pub async fn update_role_capabilities(
+    conn: &mut SqliteConnection,
+    role: &str,
+    capabilities: &[String],
+) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM ROLE_CAPABILITIES WHERE role=$1", role)
+        .execute(&mut *conn)
+        .await?;
+
+    for capability in capabilities {
+        query!(
+            "INSERT INTO ROLE_CAPABILITIES(role, capability) VALUES($1, $2)",
+            role,
+            capability
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes a role, returning false when it is still assigned to a user.
+pub async fn delete_role(conn: &mut SqliteConnection, role: &str) -> Result<bool, sqlx::Error> {
+    query!(
+        "DELETE FROM USER_ROLES WHERE name=$1 AND NOT EXISTS(SELECT 1 FROM USERS WHERE role=$1)",
+        role
+    )
+    .execute(conn)
     .await
+    .map(|result| result.rows_affected() > 0)
 }
 
 pub async fn update_user_data(
@@ -238,6 +479,7 @@ pub async fn delete_user(conn: &mut SqliteConnection, id: &str) -> Result<(), sq
         .map(|_| ())
 }
 
+#[derive(Debug)]
 pub enum GenerateTokenForUserId {
     Pasetors(pasetors::errors::Error),
     SystemTimeError,
@@ -245,18 +487,23 @@ pub enum GenerateTokenForUserId {
 
 pub fn generate_token_for_user_id(
     user_id: &str,
-    private_key: &AsymmetricSecretKey<V4>,
+    session_id: &str,
+    expires_in: u64,
+    paseto: &Paseto,
 ) -> Result<String, GenerateTokenForUserId> {
-    let mut claims = Claims::new().map_err(|err| GenerateTokenForUserId::Pasetors(err))?;
+    let mut claims = Claims::new().map_err(GenerateTokenForUserId::Pasetors)?;
     claims
         .subject(user_id)
-        .map_err(|err| GenerateTokenForUserId::Pasetors(err))?;
+        .map_err(GenerateTokenForUserId::Pasetors)?;
+    claims
+        .token_identifier(session_id)
+        .map_err(GenerateTokenForUserId::Pasetors)?;
     claims
         .issuer("tophat.be")
-        .map_err(|err| GenerateTokenForUserId::Pasetors(err))?;
+        .map_err(GenerateTokenForUserId::Pasetors)?;
     claims
-        .set_expires_in(&Duration::from_secs(60 * 60 * 24 * 7))
-        .map_err(|err| GenerateTokenForUserId::Pasetors(err))?;
+        .set_expires_in(&Duration::from_secs(expires_in))
+        .map_err(GenerateTokenForUserId::Pasetors)?;
     claims
         .add_additional(
             "last-action",
@@ -265,17 +512,64 @@ pub fn generate_token_for_user_id(
                 .map_err(|_| GenerateTokenForUserId::SystemTimeError)?
                 .as_secs(),
         )
-        .map_err(|err| GenerateTokenForUserId::Pasetors(err))?;
+        .map_err(GenerateTokenForUserId::Pasetors)?;
 
     Ok(
-        pasetors::public::sign(private_key, &claims, None, Some(b"implicit assertion"))
-            .map_err(|err| GenerateTokenForUserId::Pasetors(err))?,
+        paseto.sign(&claims, b"implicit assertion")
+            .map_err(GenerateTokenForUserId::Pasetors)?,
     )
 }
 
+/// Issued after a correct password, to carry the user from the password step to the TOTP step.
+const PRE_AUTH_ASSERTION: &[u8] = b"pre-auth";
+
+pub fn generate_pre_auth_token(
+    user_id: &str,
+    paseto: &Paseto,
+) -> Result<String, GenerateTokenForUserId> {
+    let mut claims = Claims::new().map_err(GenerateTokenForUserId::Pasetors)?;
+    claims
+        .subject(user_id)
+        .map_err(GenerateTokenForUserId::Pasetors)?;
+    claims
+        .issuer("tophat.be")
+        .map_err(GenerateTokenForUserId::Pasetors)?;
+    claims
+        .set_expires_in(&Duration::from_secs(10 * 60))
+        .map_err(GenerateTokenForUserId::Pasetors)?;
+
+    paseto.sign(&claims, PRE_AUTH_ASSERTION)
+        .map_err(GenerateTokenForUserId::Pasetors)
+}
+
+/// Returns the id of the user the pre-auth token was issued to, when it is valid and hasn't expired.
+pub fn validate_pre_auth_token(
+    token: &str,
+    paseto: &Paseto,
+) -> Option<String> {
+    let mut validation_rules = ClaimsValidationRules::new();
+    validation_rules.validate_issuer_with("tophat.be");
+
+    let untrusted_token = UntrustedToken::<pasetors::Public, V4>::try_from(token).ok()?;
+    let trusted_token = paseto
+        .verify(&untrusted_token, &validation_rules, PRE_AUTH_ASSERTION)
+        .ok()?;
+
+    trusted_token
+        .payload_claims()?
+        .get_claim("sub")?
+        .as_str()
+        .map(|user_id| user_id.to_string())
+}
+
 #[derive(Clone, Debug)]
 pub struct UserData {
     pub id: String,
+    pub role: String,
+    /// Empty when the request was made with an API token.
+    pub session_id: String,
+    /// The id of the API token the request was made with, which limits what the user can do.
+    pub api_token: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -283,6 +577,44 @@ pub struct MiddlewareQuery {
     role: Option<String>,
 }
 
+/// Authenticates a request to the JSON API, made with an API token or with the session of the user.
+pub async fn api_auth_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    DBConnection(mut connection): DBConnection,
+    jar: CookieJar,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(authorization) = headers.get(AUTHORIZATION) else {
+        return auth_middleware(State(state), headers, DBConnection(connection), jar, req, next).await;
+    };
+
+    let Some(api_token) = authorization.to_str().ok().and_then(|val| val.strip_prefix("Bearer ")) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    let owner = match api_token::authenticate(&mut connection, api_token.trim()).await {
+        Ok(Some(owner)) => owner,
+        Ok(None) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(err) => {
+            eprintln!("Failed to authenticate an API token: {err:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    req.extensions_mut().insert(UserData {
+        id: owner.user_id,
+        role: owner.role,
+        session_id: String::new(),
+        api_token: Some(owner.token_id),
+    });
+
+    next.run(req).await
+}
+
+/// Authenticates the session of the user. API tokens are refused here, they're only accepted by the JSON API through
+/// `api_auth_middleware`: the rest of the admin includes managing the account itself.
 pub async fn auth_middleware(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -291,17 +623,21 @@ pub async fn auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Response {
+    if headers.contains_key(AUTHORIZATION) {
+        return (StatusCode::UNAUTHORIZED, "API tokens are only accepted by /api/v1.").into_response();
+    }
+
     let token = match jar.get("token") {
         Some(token) => token.value(),
-        None => return StatusCode::UNAUTHORIZED.into_response(),
+        None => return step_up(),
     };
 
-    let (id, last_action) = match validate_paseto(token, &state.paseto.public_key) {
+    let (id, last_action, session_id) = match validate_paseto(token, &state.paseto) {
         Ok(val) => val,
-        Err(_) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(_) => return step_up(),
     };
 
-    let ip = match headers.get("X-Forwarded-For") {
+    let ip = match headers.get("X-Real-IP") {
         Some(val) => match val.to_str() {
             Ok(ip) => ip,
             Err(_) => {
@@ -322,10 +658,21 @@ pub async fn auth_middleware(
         None => "user",
     };
 
-    let auth_data = match get_user_auth_data(&mut connection, &id, &ip).await {
+    match touch_session(&mut connection, &session_id, &id, ip).await {
+        Ok(true) => {}
+        Ok(false) => return step_up(),
+        Err(err) => {
+            eprintln!("Failed to update session {session_id}: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let device_id = jar.get("device").map(|device| device.value());
+
+    let auth_data = match get_user_auth_data(&mut connection, &id, device_id, &ip).await {
         Ok(auth_data) => auth_data,
         Err(err) => {
-            eprintln!("Failed to get the user auth data for user {id}: {err}");
+            eprintln!("Failed to get the user auth data for user {id}: {err:?}");
             return StatusCode::INTERNAL_SERVER_ERROR.into_response();
         },
     };
@@ -340,22 +687,66 @@ pub async fn auth_middleware(
             Err(err) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         };
 
-    let secs_multiplier = (24 * 60 * 60) as u64;
-    let secs_until_2fa = (last_action as u64) + 3 * secs_multiplier;
-    let secs_until_reauth = (last_action as u64) + 7 * secs_multiplier;
-
-    if now > secs_until_reauth {
-        return StatusCode::UNAUTHORIZED.into_response();
+    if now > last_action + state.sessions.reauth_window {
+        return step_up();
     }
 
-    if now > secs_until_2fa {
-        return StatusCode::UNAUTHORIZED.into_response();
+    if now > last_action + state.sessions.twofa_window && !auth_data.trusted_device {
+        return step_up();
     }
 
-    req.extensions_mut().insert(UserData { id });
+    req.extensions_mut().insert(UserData {
+        id: id.clone(),
+        role: auth_data.role,
+        session_id: session_id.clone(),
+        api_token: None,
+    });
 
     let res = next.run(req).await;
-    res
+
+    if now < last_action + state.sessions.refresh_after {
+        return res;
+    }
+
+    // Slide the session along with the user's activity by re-issuing the token with a fresh `last-action`.
+    match generate_token_for_user_id(&id, &session_id, state.sessions.reauth_window, &state.paseto) {
+        Ok(token) => (jar.add(token_cookie(token)), res).into_response(),
+        Err(_) => {
+            eprintln!("Failed to refresh the token of user {id}.");
+            res
+        }
+    }
+}
+
+/// Sends the user back to the login page, which asks for a TOTP code or a full login depending on how long they were
+/// inactive. HTMX follows the `HX-Redirect` header, even for an error response.
+fn step_up() -> Response {
+    (StatusCode::UNAUTHORIZED, [("HX-Redirect", "/login")]).into_response()
+}
+
+pub fn token_cookie(token: String) -> Cookie<'static> {
+    let mut expires = OffsetDateTime::now_utc();
+    expires += cookie::time::Duration::weeks(26);
+
+    let mut token_cookie = Cookie::new("token", token);
+    token_cookie.set_http_only(true);
+    token_cookie.set_expires(expires);
+    token_cookie.set_path("/");
+
+    token_cookie
+}
+
+/// The cookie that identifies the browser, so it can be recognised as a known or trusted device.
+pub fn device_cookie(device_id: String) -> Cookie<'static> {
+    let mut expires = OffsetDateTime::now_utc();
+    expires += cookie::time::Duration::weeks(52);
+
+    let mut device_cookie = Cookie::new("device", device_id);
+    device_cookie.set_http_only(true);
+    device_cookie.set_expires(expires);
+    device_cookie.set_path("/");
+
+    device_cookie
 }
 
 pub async fn create_draft_middleware(
@@ -394,8 +785,8 @@ pub async fn create_draft_middleware(
 
 pub fn validate_paseto(
     token: &str,
-    public_key: &AsymmetricPublicKey<pasetors::version4::V4>,
-) -> Result<(String, u64)> {
+    paseto: &Paseto,
+) -> Result<(String, u64, String)> {
     let mut validation_rules = ClaimsValidationRules::new();
     validation_rules.allow_non_expiring();
     validation_rules.validate_issuer_with("tophat.be");
@@ -403,13 +794,7 @@ pub fn validate_paseto(
     let untrusted_token =
         UntrustedToken::<pasetors::Public, pasetors::version4::V4>::try_from(token).map_err(|t| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let trusted_token = match public::verify(
-        public_key,
-        &untrusted_token,
-        &validation_rules,
-        None,
-        Some(b"implicit assertion"),
-    ) {
+    let trusted_token = match paseto.verify(&untrusted_token, &validation_rules, b"implicit assertion") {
         Ok(trusted_token) => trusted_token,
         Err(err) => {
             eprintln!("{err}");
@@ -451,7 +836,7 @@ pub fn validate_paseto(
         Some(claims) => match claims.get_claim("last-action") {
             Some(last_action) => match last_action.as_u64() {
                 Some(last_action) => last_action,
-                None => return Ok((id, 0)),
+                None => 0,
             },
             None => {
                 return Err((
@@ -472,22 +857,529 @@ pub fn validate_paseto(
         }
     };
 
-    Ok((id, last_action))
+    let session_id = match trusted_token.payload_claims() {
+        Some(claims) => match claims.get_claim("jti").and_then(|jti| jti.as_str()) {
+            Some(session_id) => session_id.to_string(),
+            None => {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    "Failed to verify the PASETO token",
+                )
+                    .into_response()
+                    .into());
+            }
+        },
+        None => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "Failed to verify the PASETO token",
+            )
+                .into_response()
+                .into());
+        }
+    };
+
+    Ok((id, last_action, session_id))
 }
 
 #[derive(Debug)]
 pub struct UserAuthData {
     pub id: String,
     pub role: String,
-    pub trusted_ip: bool,
+    pub trusted_device: bool,
 }
 
+/// `trusted_device` tells whether the user trusted this device (identified by its `device` cookie) from this IP address.
 pub async fn get_user_auth_data(
     conn: &mut SqliteConnection,
     user_id: &str,
+    device_id: Option<&str>,
     ip: &str,
-) -> Result<UserAuthData, sqlx::Error> {
-    query_as!(UserAuthData, r#"SELECT id, role, EXISTS(SELECT 1 FROM TRUSTED_IPS WHERE user_id=id AND ip=$2) as "trusted_ip!: bool" FROM USERS WHERE id=$1"#, user_id, ip)
+) -> Result<UserAuthData, SessionError> {
+    let now = unix_now().map_err(|_| SessionError::SystemTimeError)?;
+
+    query_as!(UserAuthData, r#"SELECT id, role, EXISTS(SELECT 1 FROM TRUSTED_IPS WHERE user_id=id AND device_id=$2 AND ip=$3 AND trusted_until > $4) as "trusted_device!: bool" FROM USERS WHERE id=$1"#, user_id, device_id, ip, now)
     .fetch_one(conn)
     .await
+    .map_err(SessionError::SQL)
+}
+
+fn unix_now() -> Result<i64, SystemTimeError> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    SQL(sqlx::Error),
+    SystemTimeError,
+}
+
+/// Registers a new session for the user, the id of which goes into the token as its `jti` claim.
+pub async fn create_session(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    user_agent: Option<&str>,
+    ip: Option<&str>,
+) -> Result<String, SessionError> {
+    let id = Uuid::new_v4().to_string();
+    let now = unix_now().map_err(|_| SessionError::SystemTimeError)?;
+
+    query!(
+        "INSERT INTO sessions(id, user_id, user_agent, ip, created_at, last_activity) VALUES($1, $2, $3, $4, $5, $5)",
+        id,
+        user_id,
+        user_agent,
+        ip,
+        now
+    )
+    .execute(conn)
+    .await
+    .map_err(SessionError::SQL)?;
+
+    Ok(id)
+}
+
+/// Marks the session as active, returning false when it doesn't exist (anymore).
+pub async fn touch_session(
+    conn: &mut SqliteConnection,
+    session_id: &str,
+    user_id: &str,
+    ip: &str,
+) -> Result<bool, SessionError> {
+    let now = unix_now().map_err(|_| SessionError::SystemTimeError)?;
+
+    query!(
+        "UPDATE sessions SET last_activity=$1, ip=$2 WHERE id=$3 AND user_id=$4",
+        now,
+        ip,
+        session_id,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(SessionError::SQL)
+}
+
+pub async fn session_exists(
+    conn: &mut SqliteConnection,
+    session_id: &str,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM sessions WHERE id=$1 AND user_id=$2) as "exists!: bool""#,
+        session_id,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn get_sessions(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Vec<Session>, sqlx::Error> {
+    query_as!(
+        Session,
+        "SELECT id, user_agent, ip, created_at, last_activity FROM sessions WHERE user_id=$1 ORDER BY last_activity DESC",
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn revoke_session(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    session_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM sessions WHERE id=$1 AND user_id=$2",
+        session_id,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Revokes all of the user's sessions except the one they are using.
+pub async fn revoke_other_sessions(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    session_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM sessions WHERE user_id=$1 AND id!=$2",
+        user_id,
+        session_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+/// Revokes all of the user's sessions, which logs them out on every device.
+pub async fn revoke_sessions(conn: &mut SqliteConnection, user_id: &str) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM sessions WHERE user_id=$1", user_id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Remembers the device for the user, returning true when it is new while the user has logged in on other devices before.
+pub async fn register_device(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    device_id: &str,
+) -> Result<bool, SessionError> {
+    let now = unix_now().map_err(|_| SessionError::SystemTimeError)?;
+
+    let has_other_devices = query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM known_devices WHERE user_id=$1 AND device_id!=$2) as "exists!: bool""#,
+        user_id,
+        device_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(SessionError::SQL)?;
+
+    let inserted = query!(
+        "INSERT INTO known_devices(user_id, device_id, first_seen) VALUES($1, $2, $3) ON CONFLICT DO NOTHING",
+        user_id,
+        device_id,
+        now
+    )
+    .execute(conn)
+    .await
+    .map_err(SessionError::SQL)?
+    .rows_affected()
+        > 0;
+
+    Ok(inserted && has_other_devices)
+}
+
+pub async fn trust_device(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    device_id: &str,
+    ip: &str,
+    user_agent: Option<&str>,
+    window: u64,
+) -> Result<(), SessionError> {
+    let trusted_until = unix_now().map_err(|_| SessionError::SystemTimeError)? + window as i64;
+
+    query!(
+        "INSERT INTO TRUSTED_IPS(user_id, device_id, ip, user_agent, trusted_until) VALUES($1, $2, $3, $4, $5)
+        ON CONFLICT(user_id, device_id) DO UPDATE SET ip=excluded.ip, user_agent=excluded.user_agent, trusted_until=excluded.trusted_until",
+        user_id,
+        device_id,
+        ip,
+        user_agent,
+        trusted_until
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+    .map_err(SessionError::SQL)
+}
+
+pub async fn get_trusted_devices(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Vec<TrustedDevice>, SessionError> {
+    let now = unix_now().map_err(|_| SessionError::SystemTimeError)?;
+
+    query_as!(
+        TrustedDevice,
+        "SELECT device_id, ip, user_agent, trusted_until FROM TRUSTED_IPS WHERE user_id=$1 AND trusted_until > $2 ORDER BY trusted_until DESC",
+        user_id,
+        now
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(SessionError::SQL)
+}
+
+pub async fn revoke_trusted_device(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    device_id: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM TRUSTED_IPS WHERE user_id=$1 AND device_id=$2",
+        user_id,
+        device_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn get_user_for_sso_subject(
+    conn: &mut SqliteConnection,
+    subject: &str,
+) -> Result<Option<User>, sqlx::Error> {
+    query_as!(User, "SELECT * from USERS WHERE sso_subject=$1", subject)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Links the provider's account to the user, unless it already is linked to another one. Returns whether it got
+/// linked.
+pub async fn link_sso_subject(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    subject: &str,
+) -> Result<bool, sqlx::Error> {
+    query!(
+        "UPDATE USERS SET sso_subject=$1
+        WHERE id=$2 AND NOT EXISTS(SELECT 1 FROM USERS WHERE sso_subject=$1 AND id!=$2)",
+        subject,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map(|result| result.rows_affected() == 1)
+}
+
+pub async fn unlink_sso_subject(conn: &mut SqliteConnection, user_id: &str) -> Result<(), sqlx::Error> {
+    query!("UPDATE USERS SET sso_subject=NULL WHERE id=$1", user_id)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// How an SSO login resolved to a user.
+pub enum SsoUser {
+    /// The provider's account is linked to this user.
+    Linked(User),
+    /// A user was created for the provider's account.
+    Provisioned(User),
+    /// The provider's account can't log in: it isn't linked, and its email address isn't verified, isn't on an SSO
+    /// domain or belongs to an account that can also log in with its password.
+    Refused,
+}
+
+/// Finds the user the provider's account logs in as. An account only gets linked automatically when its verified email
+/// address is on an SSO domain, where it can't log in with its password, and it has no second factor. Any other
+/// account has to be linked by the user from their account page, after logging in with their password.
+pub async fn resolve_sso_user(
+    conn: &mut SqliteConnection,
+    identity: &Identity,
+    provision_role: Option<&str>,
+) -> Result<SsoUser, sqlx::Error> {
+    if let Some(user) = get_user_for_sso_subject(&mut *conn, &identity.subject).await? {
+        return Ok(SsoUser::Linked(user));
+    }
+
+    let Some(email) = &identity.email else {
+        return Ok(SsoUser::Refused);
+    };
+
+    if !is_sso_only(&mut *conn, email).await? {
+        return Ok(SsoUser::Refused);
+    }
+
+    let user = query_as!(User, "SELECT * from USERS WHERE email=$1 COLLATE NOCASE", email)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    // The provider's login doesn't replace a second factor on its own, an admin has to reset it before the account
+    // can be linked.
+    if let Some(user) = user {
+        if user.sso_subject.is_some()
+            || user.totp_secret.is_some()
+            || !link_sso_subject(&mut *conn, &user.id, &identity.subject).await?
+        {
+            return Ok(SsoUser::Refused);
+        }
+
+        return get_user_from_id(&mut *conn, &user.id).await.map(SsoUser::Linked);
+    }
+
+    let Some(role) = provision_role else {
+        return Ok(SsoUser::Refused);
+    };
+
+    // The user can only log in through SSO, the password can't be used on an SSO domain.
+    let unusable_password = Uuid::new_v4().to_string();
+
+    let user = create_user(&mut *conn, &identity.first_name, &identity.last_name, email, role, &unusable_password)
+        .await
+        .map_err(|err| match err {
+            CreateUserError::SQL(err) => err,
+            CreateUserError::HashError => sqlx::Error::Protocol("Failed to hash the password.".to_string()),
+        })?;
+
+    link_sso_subject(&mut *conn, &user.id, &identity.subject).await?;
+
+    get_user_from_id(&mut *conn, &user.id).await.map(SsoUser::Provisioned)
+}
+
+pub async fn get_sso_domains(conn: &mut SqliteConnection) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar!("SELECT domain FROM sso_domains ORDER BY domain")
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn add_sso_domain(conn: &mut SqliteConnection, domain: &str) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO sso_domains(domain) VALUES($1) ON CONFLICT DO NOTHING",
+        domain
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn delete_sso_domain(conn: &mut SqliteConnection, domain: &str) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM sso_domains WHERE domain=$1", domain)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Whether users with this email address have to log in through single sign-on.
+pub async fn is_sso_only(conn: &mut SqliteConnection, email: &str) -> Result<bool, sqlx::Error> {
+    let domain = email.rsplit_once('@').map(|(_, domain)| domain.to_lowercase()).unwrap_or_default();
+
+    query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM sso_domains WHERE domain=$1) as "exists!: bool""#,
+        domain
+    )
+    .fetch_one(conn)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Connection, SqliteConnection};
+
+    use super::*;
+
+    async fn database() -> SqliteConnection {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+
+        sqlx::raw_sql(include_str!("../../sql/projects.sql")).execute(&mut conn).await.unwrap();
+        sqlx::raw_sql(include_str!("../../sql/triggers.sql")).execute(&mut conn).await.unwrap();
+        add_sso_domain(&mut conn, "example.com").await.unwrap();
+
+        conn
+    }
+
+    fn identity(subject: &str, email: Option<&str>) -> Identity {
+        Identity {
+            subject: subject.to_string(),
+            email: email.map(str::to_string),
+            first_name: "Jan".to_string(),
+            last_name: "Peeters".to_string(),
+        }
+    }
+
+    async fn user(conn: &mut SqliteConnection, email: &str) -> UserBase {
+        let Ok(user) = create_user(conn, "Jan", "Peeters", email, "user", "password").await else {
+            panic!("Failed to create {email}");
+        };
+
+        user
+    }
+
+    #[tokio::test]
+    async fn linked_subject() {
+        let mut conn = database().await;
+        let existing = user(&mut conn, "jan@other.com").await;
+        assert!(link_sso_subject(&mut conn, &existing.id, "subject-1").await.unwrap());
+
+        // The subject decides, not the address.
+        let resolved = resolve_sso_user(&mut conn, &identity("subject-1", None), None).await.unwrap();
+
+        assert!(matches!(resolved, SsoUser::Linked(user) if user.id == existing.id));
+    }
+
+    #[tokio::test]
+    async fn unverified_email_is_refused() {
+        let mut conn = database().await;
+        user(&mut conn, "jan@example.com").await;
+
+        let resolved = resolve_sso_user(&mut conn, &identity("subject-1", None), Some("user")).await.unwrap();
+
+        assert!(matches!(resolved, SsoUser::Refused));
+    }
+
+    #[tokio::test]
+    async fn other_domain_is_refused() {
+        let mut conn = database().await;
+        user(&mut conn, "jan@other.com").await;
+
+        // Neither bound to the existing account, nor provisioned.
+        for email in ["jan@other.com", "piet@other.com"] {
+            let resolved =
+                resolve_sso_user(&mut conn, &identity("subject-1", Some(email)), Some("user")).await.unwrap();
+
+            assert!(matches!(resolved, SsoUser::Refused), "{email} wasn't refused");
+        }
+    }
+
+    #[tokio::test]
+    async fn sso_domain_account_gets_linked() {
+        let mut conn = database().await;
+        let existing = user(&mut conn, "Jan@Example.com").await;
+
+        let resolved =
+            resolve_sso_user(&mut conn, &identity("subject-1", Some("jan@example.com")), None).await.unwrap();
+
+        assert!(matches!(resolved, SsoUser::Linked(user) if user.id == existing.id));
+        assert!(get_user_for_sso_subject(&mut conn, "subject-1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn account_with_second_factor_is_refused() {
+        let mut conn = database().await;
+        let existing = user(&mut conn, "jan@example.com").await;
+        query!("UPDATE USERS SET totp_secret='secret' WHERE id=$1", existing.id)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let resolved =
+            resolve_sso_user(&mut conn, &identity("subject-1", Some("jan@example.com")), None).await.unwrap();
+
+        assert!(matches!(resolved, SsoUser::Refused));
+    }
+
+    #[tokio::test]
+    async fn account_linked_to_another_subject_is_refused() {
+        let mut conn = database().await;
+        let existing = user(&mut conn, "jan@example.com").await;
+        assert!(link_sso_subject(&mut conn, &existing.id, "subject-1").await.unwrap());
+
+        let resolved =
+            resolve_sso_user(&mut conn, &identity("subject-2", Some("jan@example.com")), None).await.unwrap();
+
+        assert!(matches!(resolved, SsoUser::Refused));
+    }
+
+    #[tokio::test]
+    async fn subject_is_linked_once() {
+        let mut conn = database().await;
+        let first = user(&mut conn, "jan@other.com").await;
+        let second = user(&mut conn, "piet@other.com").await;
+
+        assert!(link_sso_subject(&mut conn, &first.id, "subject-1").await.unwrap());
+        assert!(!link_sso_subject(&mut conn, &second.id, "subject-1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn provisioning() {
+        let mut conn = database().await;
+
+        let refused =
+            resolve_sso_user(&mut conn, &identity("subject-1", Some("jan@example.com")), None).await.unwrap();
+        assert!(matches!(refused, SsoUser::Refused));
+
+        let provisioned =
+            resolve_sso_user(&mut conn, &identity("subject-1", Some("jan@example.com")), Some("user")).await.unwrap();
+        assert!(matches!(provisioned, SsoUser::Provisioned(user) if user.sso_subject.as_deref() == Some("subject-1")));
+    }
 }
@@ -1,7 +1,10 @@
+use chrono::DateTime;
 use maud::{Markup, PreEscaped, html};
 
 
-use super::models::{Permission, UserBase};
+use super::models::{CapabilityInfo, Permission, Role, RoleCapability, Session, TrustedDevice, User, UserBase};
+use super::api_token::{ApiToken, ApiTokenScope};
+use super::password::Purpose;
 
 pub fn totp_verify_form(email: &str, svg: Option<String>) -> Markup {
     html!(
@@ -17,15 +20,15 @@ pub fn totp_verify_form(email: &str, svg: Option<String>) -> Markup {
 }
 
 
-pub fn user_list(users: &Vec<UserBase>) -> Markup {
+pub fn user_list(users: &Vec<UserBase>, roles: &[Role]) -> Markup {
     html!(
         @for user in users {
-            (user_template(user))
+            (user_template(user, roles))
         }
     )
 }
 
-pub fn user_template(user: &UserBase) -> Markup {
+pub fn user_template(user: &UserBase, roles: &[Role]) -> Markup {
     html!(
         details {
             summary.name {
@@ -42,12 +45,7 @@ pub fn user_template(user: &UserBase) -> Markup {
                     label {
                         "Role: "
                         select name="role" {
-                            option value="admin" selected[user.role == "admin"] {
-                                "Admin"
-                            }
-                            option value="user" selected[user.role == "user"] {
-                                "User"
-                            }
+                            (role_options(roles, &user.role))
                         }
                     }
                 }
@@ -55,17 +53,27 @@ pub fn user_template(user: &UserBase) -> Markup {
                 div.toggles {
                     label { "Permissions" }
 
-                    div.table {
-                        span{}
-                        span style="text-align: center" {
-                            "Create"
-                        }
-                        span style="text-align: center" {
-                            "Edit"
-                        }
+                    div.table hx-trigger={"click from:closest details"} hx-get={"/api/auth/users/"(user.id)"/permissions"} hx-swap="outerHTML" {}
+                }
 
-                        div.table hx-trigger={"click from:closest details"} hx-get={"/api/auth//users/"(user.id)"/permissions"} hx-swap="outerHTML" {}
-                    }
+                div.sessions {
+                    label { "Sessions" }
+
+                    ul hx-trigger={"click from:closest details"} hx-get={"/api/auth/users/"(user.id)"/sessions"} hx-swap="outerHTML" {}
+                }
+
+                div.sessions {
+                    label { "Trusted devices" }
+
+                    ul hx-trigger={"click from:closest details"} hx-get={"/api/auth/users/"(user.id)"/devices"} hx-swap="outerHTML" {}
+                }
+
+                button hx-post={"/api/auth/users/"(user.id)"/invitation"} hx-swap="innerHTML" {
+                    "Resend invitation"
+                }
+
+                button hx-post={"/api/auth/users/"(user.id)"/totp/reset"} hx-swap="innerHTML" hx-confirm="Do you really want to reset the 2FA of this user? They will have to set it up again on their next login." {
+                    "Reset 2FA"
                 }
 
                 button hx-delete={"/api/auth/users/"(user.id)} hx-target="closest details" hx-swap="delete" hx-confirm="Do you really want to delete this user?" {
@@ -76,50 +84,525 @@ pub fn user_template(user: &UserBase) -> Markup {
     )
 }
 
-pub fn permission_row(permission: &Permission, user_id: &str, company_name: &str, company_value: &Option<String>) -> Markup {
+/// The name of a role as it is shown, e.g. "Content manager".
+fn role_label(name: &str) -> String {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn role_options(roles: &[Role], selected: &str) -> Markup {
+    html!(
+        @for role in roles {
+            option value=(role.name) title=(role.description) selected[role.name == selected] {
+                (role_label(&role.name))
+            }
+        }
+    )
+}
+
+/// One company of the permission matrix. The capabilities the user's role grants are checked, and can't be unchecked.
+pub fn permission_row(
+    user_id: &str,
+    company: &Option<String>,
+    capabilities: &[CapabilityInfo],
+    permissions: &[Permission],
+    role_capabilities: &[String],
+) -> Markup {
     html!(
-        form hx-patch={"/api/auth//users/"(user_id)"/permissions"(company_value.as_deref().map(|c| format!("?company={c}")).unwrap_or_default())} hx-swap="outerHTML" hx-trigger="change" {
+        form hx-patch={"/api/auth/users/"(user_id)"/permissions"(company.as_deref().map(|c| format!("?company={c}")).unwrap_or_default())} hx-swap="outerHTML" hx-trigger="change" {
             span {
-                (company_name)
+                (company.as_deref().unwrap_or("Base"))
             }
-            input name="create" type="checkbox" value="true" checked[permission.create.unwrap_or(false)];
+            @for capability in capabilities.iter().filter(|capability| capability.scoped) {
+                @let from_role = role_capabilities.contains(&capability.name);
+                @let granted = permissions
+                    .iter()
+                    .any(|permission| &permission.company == company && permission.capability == capability.name);
 
-            input name="edit" type="checkbox" value="true" checked[permission.edit.unwrap_or(false)];
+                input name="capability" type="checkbox" value=(capability.name) title=(capability.label) checked[from_role || granted] disabled[from_role];
+            }
+        }
+    )
+}
+
+/// The permission matrix of a user: a row per company and a column per capability that can be granted per company.
+pub fn permissions(
+    user_id: &str,
+    companies: &[Option<String>],
+    capabilities: &[CapabilityInfo],
+    permissions: &[Permission],
+    role_capabilities: &[String],
+) -> Markup {
+    let columns = capabilities.iter().filter(|capability| capability.scoped).count();
 
+    html!(
+        div.table style={"grid-template-columns: 1fr repeat(" (columns) ", 5rem);"} {
+            span {}
+            @for capability in capabilities.iter().filter(|capability| capability.scoped) {
+                span.capability { (capability.label) }
+            }
+            @for company in companies {
+                (permission_row(user_id, company, capabilities, permissions, role_capabilities))
+            }
         }
     )
 }
 
-pub fn permissions(permissions: &Vec<Permission>, user_id: &str) -> Markup {
+/// The capabilities of one role. Those of the admin role can't be changed.
+pub fn role_row(role: &Role, capabilities: &[CapabilityInfo], role_capabilities: &[RoleCapability]) -> Markup {
+    let locked = role.name == "admin";
+
     html!(
-        @for permission in permissions {
-            @let company = permission.company.as_deref().unwrap_or("Base");
-            (permission_row(&permission, user_id, &company, &permission.company))
+        form hx-patch={"/api/auth/roles/"(role.name)} hx-swap="outerHTML" hx-trigger="change" {
+            span title=(role.description) {
+                (role_label(&role.name))
+            }
+            @for capability in capabilities {
+                @let granted = role_capabilities
+                    .iter()
+                    .any(|role_capability| role_capability.role == role.name && role_capability.capability == capability.name);
+
+                input name="capability" type="checkbox" value=(capability.name) title=(capability.label) checked[granted] disabled[locked];
+            }
+            @if locked || role.name == "user" {
+                span {}
+            } @else {
+                button type="button" hx-delete={"/api/auth/roles/"(role.name)} hx-target="#roles" hx-swap="outerHTML" hx-confirm="Do you really want to delete this role?" {
+                    "Remove"
+                }
+            }
+        }
+    )
+}
+
+/// The roles and the capabilities they grant for every company.
+pub fn roles(roles: &[Role], capabilities: &[CapabilityInfo], role_capabilities: &[RoleCapability]) -> Markup {
+    html!(
+        div #roles tabindex="-1" {
+            p { "A role grants its capabilities for every company, on top of the permissions a user gets per company." }
+            div.table style={"grid-template-columns: 10rem repeat(" (capabilities.len()) ", 4rem) 5rem;"} {
+                span {}
+                @for capability in capabilities {
+                    span.capability { (capability.label) }
+                }
+                span {}
+                @for role in roles {
+                    (role_row(role, capabilities, role_capabilities))
+                }
+            }
+            form #add-role hx-post="/api/auth/roles" hx-target="#roles" hx-swap="outerHTML" {
+                input name="name" placeholder="Role name";
+                input name="description" placeholder="Description";
+                input type="submit" value="Add";
+            }
         }
     )
 }
 
-pub fn login_form() -> Markup {
+/// `sso` is the name of the single sign-on provider, when it is configured.
+pub fn login_form(sso: Option<&str>, error: Option<&str>) -> Markup {
     html!(
         form hx-post="/api/auth/login" hx-swap="outerHTML" {
             h1 { "Groep Van Roey Website Editor" }
+            @if let Some(error) = error {
+                p.error { (error) }
+            }
             input type="email" name="email" placeholder="email";
             input type="password" name="password" placeholder="password";
             input type="submit" value="Log in";
+            a href="/forgot-password" { "Forgot password?" }
+            @if let Some(sso) = sso {
+                a.sso href="/api/auth/oidc/login" hx-boost="false" { "Log in with " (sso) }
+            }
         }
     )
 }
 
-pub fn twofa_form(id: &str, svg: Option<String>) -> Markup {
+/// The TOTP step of the login, `pre_auth_token` proves the password step was passed.
+pub fn twofa_form(pre_auth_token: &str, svg: Option<String>, error: Option<&str>) -> Markup {
+    let enrolling = svg.is_some();
+
     html!(
         form hx-post="/api/auth/totp/verify" hx-swap="outerHTML" {
             h1 { "Groep Van Roey Website Editor"}
             @if let Some(svg) = svg {
+                p { "Scan this code with your authenticator app and enter the code it shows to finish setting up two-factor authentication." }
                 (PreEscaped(svg))
             }
-            input type="hidden" name="id" value=(id);
-            input name="code" placeholder="code";
+            @if let Some(error) = error {
+                p.error { (error) }
+            }
+            input type="hidden" name="token" value=(pre_auth_token);
+            input name="code" placeholder=(if enrolling { "code" } else { "code or recovery code" }) autocomplete="one-time-code";
+            label.trust-device {
+                input type="checkbox" name="trust_device" value="true";
+                "Trust this device for 30 days"
+            }
             input type="submit" value="verify";
         }
     )
-}
\ No newline at end of file
+}
+
+/// A short description of the device behind a user agent, e.g. "Firefox on Windows".
+fn device(user_agent: &str) -> String {
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .iter()
+    .find(|(needle, _)| user_agent.contains(needle))
+    .map(|(_, name)| *name);
+
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(needle, _)| user_agent.contains(needle))
+    .map(|(_, name)| *name);
+
+    match (browser, os) {
+        (Some(browser), Some(os)) => format!("{browser} on {os}"),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => "Unknown device".to_string(),
+    }
+}
+
+fn timestamp(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0)
+        .map(|datetime| datetime.format("%Y/%m/%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// The sessions of a user. `current` is the session of the user looking at their own sessions, which is left out when
+/// an admin is managing someone else's.
+pub fn session_list(sessions: &[Session], user_id: &str, current: Option<&str>) -> Markup {
+    html!(
+        ul.sessions {
+            @for session in sessions {
+                @let revoke_url = match current {
+                    Some(_) => format!("/api/auth/sessions/{}", session.id),
+                    None => format!("/api/auth/users/{user_id}/sessions/{}", session.id),
+                };
+                li {
+                    span.device title=[session.user_agent.as_deref()] {
+                        (session.user_agent.as_deref().map(device).unwrap_or("Unknown device".to_string()))
+                    }
+                    span.ip { (session.ip.as_deref().unwrap_or("-")) }
+                    span.last-activity { (timestamp(session.last_activity)) }
+                    @if current == Some(session.id.as_str()) {
+                        span.current { "This device" }
+                    } @else {
+                        button hx-delete=(revoke_url) hx-target="closest li" hx-swap="delete" {
+                            "Revoke"
+                        }
+                    }
+                }
+            }
+            @if sessions.is_empty() {
+                li { "No active sessions." }
+            }
+            li {
+                @match current {
+                    Some(_) => {
+                        button hx-delete="/api/auth/sessions" hx-confirm="Do you want to log out on every device?" {
+                            "Log out everywhere"
+                        }
+                    }
+                    None => {
+                        button hx-delete={"/api/auth/users/"(user_id)"/sessions"} hx-target="closest ul" hx-swap="outerHTML" hx-confirm="Do you want to log this user out on every device?" {
+                            "Revoke all sessions"
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+
+/// The devices on which the user skips the TOTP step.
+pub fn trusted_device_list(devices: &[TrustedDevice], user_id: &str, admin: bool) -> Markup {
+    html!(
+        ul.sessions.trusted-devices {
+            @for trusted_device in devices {
+                @let revoke_url = if admin {
+                    format!("/api/auth/users/{user_id}/devices/{}", trusted_device.device_id)
+                } else {
+                    format!("/api/auth/devices/{}", trusted_device.device_id)
+                };
+                li {
+                    span.device title=[trusted_device.user_agent.as_deref()] {
+                        (trusted_device.user_agent.as_deref().map(device).unwrap_or("Unknown device".to_string()))
+                    }
+                    span.ip { (trusted_device.ip) }
+                    span.last-activity { "Until " (timestamp(trusted_device.trusted_until)) }
+                    button hx-delete=(revoke_url) hx-target="closest li" hx-swap="delete" {
+                        "Revoke"
+                    }
+                }
+            }
+            @if devices.is_empty() {
+                li { "No trusted devices." }
+            }
+        }
+    )
+}
+
+pub fn new_device_email(user: &User, user_agent: Option<&str>, ip: Option<&str>) -> Markup {
+    html!(
+        p { "Hi " (user.first_name) "," }
+        p { "Your account was just used to log in to the website editor from a new device:" }
+        ul {
+            li { "Device: " (user_agent.map(device).unwrap_or("Unknown device".to_string())) }
+            li { "IP address: " (ip.unwrap_or("unknown")) }
+        }
+        p { "If this wasn't you, change your password and log out of your other sessions from the Sessions page." }
+    )
+}
+
+pub fn account_locked_email(user: &User, ip: &str) -> Markup {
+    html!(
+        p { "Hi " (user.first_name) "," }
+        p { "There were too many failed attempts to log in to your account on the website editor, so it is locked for the next 15 minutes." }
+        p { "The last attempt came from IP address " (ip) "." }
+        p { "If this wasn't you, someone may know your password. Change it once you can log in again, or use the forgot password link on the login page." }
+    )
+}
+
+pub fn password_email(first_name: &str, purpose: Purpose, link: &str) -> Markup {
+    html!(
+        p { "Hi " (first_name) "," }
+        @match purpose {
+            Purpose::Invitation => {
+                p { "An account was created for you on the Groep Van Roey website editor. Use the link below to choose a password and set up two-factor authentication. The link can be used once and expires in 7 days." }
+            }
+            Purpose::Reset => {
+                p { "Someone asked to reset the password of your account on the Groep Van Roey website editor. Use the link below to choose a new one. The link can be used once and expires in an hour." }
+                p { "If this wasn't you, you can ignore this email." }
+            }
+        }
+        p { a href=(link) { (link) } }
+    )
+}
+
+pub fn set_password_form(token: &str, error: Option<&str>) -> Markup {
+    html!(
+        form hx-post="/api/auth/password/set" hx-swap="outerHTML" {
+            h1 { "Choose a password" }
+            @if let Some(error) = error {
+                p.error { (error) }
+            }
+            input type="hidden" name="token" value=(token);
+            input type="password" name="password" placeholder="new password" autocomplete="new-password";
+            input type="password" name="password_confirm" placeholder="repeat the password" autocomplete="new-password";
+            input type="submit" value="Save";
+        }
+    )
+}
+
+pub fn password_link_expired() -> Markup {
+    html!(
+        form {
+            h1 { "This link has expired" }
+            p { "The link was already used or is no longer valid." }
+            a href="/forgot-password" { "Request a new link" }
+        }
+    )
+}
+
+pub fn forgot_password_form(message: Option<&str>) -> Markup {
+    html!(
+        form hx-post="/api/auth/password/forgot" hx-swap="outerHTML" {
+            h1 { "Forgot password" }
+            @if let Some(message) = message {
+                p { (message) }
+            } @else {
+                input type="email" name="email" placeholder="email";
+                input type="submit" value="Send a reset link";
+            }
+            a href="/login" { "Back to login" }
+        }
+    )
+}
+
+pub fn change_password_form(message: Option<&str>) -> Markup {
+    html!(
+        form.change-password hx-put="/api/auth/password" hx-swap="outerHTML" {
+            @if let Some(message) = message {
+                p.message { (message) }
+            }
+            input type="password" name="current_password" placeholder="Current password" autocomplete="current-password";
+            input type="password" name="password" placeholder="New password" autocomplete="new-password";
+            input type="password" name="password_confirm" placeholder="Repeat the new password" autocomplete="new-password";
+            input type="submit" value="Change password";
+        }
+    )
+}
+
+
+/// The user's new recovery codes. `continue_to_editor` is set right after enrolment, when the user still has to be sent
+/// on to the editor.
+pub fn recovery_codes(codes: &[String], continue_to_editor: bool) -> Markup {
+    html!(
+        form.recovery-codes {
+            h2 { "Recovery codes" }
+            p { "Each of these codes can be used once in place of a code from your authenticator app, for when you lose access to it. Store them somewhere safe, they won't be shown again." }
+            ol {
+                @for code in codes {
+                    li { code { (code) } }
+                }
+            }
+            @if continue_to_editor {
+                a href="/" { "Continue" }
+            }
+        }
+    )
+}
+
+pub fn recovery_codes_overview(count: i64) -> Markup {
+    html!(
+        div.recovery-codes {
+            p { "You have " (count) " unused recovery codes." }
+            button hx-post="/api/auth/recovery-codes" hx-target="closest .recovery-codes" hx-swap="outerHTML" hx-confirm="This replaces your current recovery codes, do you want to continue?" {
+                "Generate new recovery codes"
+            }
+        }
+    )
+}
+
+/// Whether the account is linked to an account of the provider, which is what logs in through single sign-on.
+pub fn sso_link(provider: &str, linked: bool) -> Markup {
+    html!(
+        div.sso-link {
+            @if linked {
+                p { "Your account is linked to " (provider) "." }
+                button hx-delete="/api/auth/oidc/link" hx-target="closest .sso-link" hx-swap="outerHTML" hx-confirm="You won't be able to log in with single sign-on anymore, do you want to continue?" {
+                    "Unlink"
+                }
+            } @else {
+                p { "Link your account to " (provider) " to log in with it." }
+                a href="/api/auth/oidc/link" { "Link" }
+            }
+        }
+    )
+}
+
+pub fn sso_domains(domains: &[String]) -> Markup {
+    html!(
+        form #sso-domains hx-post="/api/auth/sso-domains" hx-swap="outerHTML" {
+            p { "Users with an address on these domains can only log in with single sign-on." }
+            @for domain in domains {
+                span.domain {
+                    (domain)
+                    button type="button" hx-delete={"/api/auth/sso-domains/"(domain)} hx-target="#sso-domains" hx-swap="outerHTML" {
+                        "Remove"
+                    }
+                }
+            }
+            input name="domain" placeholder="example.com";
+            input type="submit" value="Add";
+        }
+    )
+}
+
+/// The API tokens of the user and a form to create one. `created` is a token that was just created, which is shown
+/// only this once.
+pub fn api_tokens(
+    tokens: &[ApiToken],
+    token_capabilities: &[ApiTokenScope],
+    token_companies: &[ApiTokenScope],
+    capabilities: &[CapabilityInfo],
+    companies: &[Option<String>],
+    created: Option<&str>,
+) -> Markup {
+    html!(
+        div #api-tokens {
+            @if let Some(created) = created {
+                div.created-token {
+                    p { "Copy your new token now, it won't be shown again." }
+                    code { (created) }
+                }
+            }
+            ul.sessions.api-tokens {
+                @for token in tokens {
+                    @let scope = |scopes: &[ApiTokenScope]| scopes
+                        .iter()
+                        .filter(|scope| scope.token_id == token.id)
+                        .map(|scope| scope.value.clone().unwrap_or("Base".to_string()))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    li {
+                        span.device title=(scope(token_capabilities)) { (token.name) }
+                        span.ip {
+                            @if token.all_companies {
+                                "All companies"
+                            } @else {
+                                (scope(token_companies))
+                            }
+                        }
+                        span.last-activity {
+                            "Until " (timestamp(token.expires_at))
+                            @if let Some(last_used_at) = token.last_used_at {
+                                br;
+                                "Used " (timestamp(last_used_at))
+                            }
+                        }
+                        button hx-delete={"/api/auth/api-tokens/"(token.id)} hx-target="closest li" hx-swap="delete" hx-confirm="Do you want to revoke this token?" {
+                            "Revoke"
+                        }
+                    }
+                }
+                @if tokens.is_empty() {
+                    li { "No API tokens." }
+                }
+            }
+            form.new-api-token hx-post="/api/auth/api-tokens" hx-target="#api-tokens" hx-swap="outerHTML" {
+                input name="name" placeholder="Name" required;
+                label {
+                    "Expires after "
+                    select name="expires_in_days" {
+                        option value="7" { "7 days" }
+                        option value="30" selected { "30 days" }
+                        option value="90" { "90 days" }
+                        option value="365" { "a year" }
+                    }
+                }
+                fieldset {
+                    legend { "Capabilities" }
+                    @for capability in capabilities {
+                        label {
+                            input type="checkbox" name="capability" value=(capability.name);
+                            (capability.label)
+                        }
+                    }
+                }
+                fieldset {
+                    legend { "Companies" }
+                    label {
+                        input type="checkbox" name="all_companies" value="true";
+                        "All companies"
+                    }
+                    @for company in companies {
+                        label {
+                            input type="checkbox" name="company" value=(company.as_deref().unwrap_or(""));
+                            (company.as_deref().unwrap_or("Base"))
+                        }
+                    }
+                }
+                input type="submit" value="Create token";
+            }
+        }
+    )
+}
@@ -0,0 +1,196 @@
+use std::time::{SystemTime, SystemTimeError, UNIX_EPOCH};
+
+use sqlx::{SqliteConnection, query, query_as};
+
+/// Failed attempts older than this are forgotten.
+const WINDOW: i64 = 60 * 60;
+
+/// The limits for one account or one IP address.
+struct Limits {
+    /// Attempts that can fail before the backoff starts.
+    free_failures: i64,
+    /// After this many failures, no attempts are accepted until the lockout has passed.
+    max_failures: i64,
+    lockout: i64,
+}
+
+const ACCOUNT_LIMITS: Limits = Limits {
+    free_failures: 3,
+    max_failures: 10,
+    lockout: 15 * 60,
+};
+
+/// An office shares its IP address, so it gets more room than a single account.
+const IP_LIMITS: Limits = Limits {
+    free_failures: 10,
+    max_failures: 50,
+    lockout: 60 * 60,
+};
+
+/// The longest the backoff between two attempts gets before the lockout.
+const MAX_BACKOFF: i64 = 5 * 60;
+
+#[derive(Clone, Copy)]
+pub enum AttemptKind {
+    Password,
+    Totp,
+}
+
+impl AttemptKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AttemptKind::Password => "password",
+            AttemptKind::Totp => "totp",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThrottleError {
+    SQL(sqlx::Error),
+    SystemTimeError,
+}
+
+impl From<sqlx::Error> for ThrottleError {
+    fn from(err: sqlx::Error) -> Self {
+        ThrottleError::SQL(err)
+    }
+}
+
+impl From<SystemTimeError> for ThrottleError {
+    fn from(_: SystemTimeError) -> Self {
+        ThrottleError::SystemTimeError
+    }
+}
+
+fn unix_now() -> Result<i64, SystemTimeError> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+struct Failures {
+    count: i64,
+    last: Option<i64>,
+}
+
+/// When the next attempt is allowed, given the recent failures: free attempts first, then a backoff that doubles with
+/// every failure, and finally a lockout.
+fn next_attempt_at(failures: &Failures, limits: &Limits) -> i64 {
+    let Some(last) = failures.last else {
+        return 0;
+    };
+
+    if failures.count >= limits.max_failures {
+        return last + limits.lockout;
+    }
+
+    if failures.count < limits.free_failures {
+        return 0;
+    }
+
+    let backoff = 1_i64
+        .checked_shl((failures.count - limits.free_failures + 1) as u32)
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF);
+
+    last + backoff
+}
+
+/// Returns how many seconds the client has to wait before it can try again, or `None` when it can try now.
+pub async fn retry_after(
+    conn: &mut SqliteConnection,
+    user_id: Option<&str>,
+    ip: &str,
+) -> Result<Option<i64>, ThrottleError> {
+    let now = unix_now()?;
+    let since = now - WINDOW;
+
+    let ip_failures = query_as!(
+        Failures,
+        r#"SELECT COUNT(*) as "count!: i64", MAX(created_at) as "last?: i64" FROM failed_logins WHERE ip=$1 AND created_at > $2"#,
+        ip,
+        since
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut next_attempt = next_attempt_at(&ip_failures, &IP_LIMITS);
+
+    if let Some(user_id) = user_id {
+        let account_failures = query_as!(
+            Failures,
+            r#"SELECT COUNT(*) as "count!: i64", MAX(created_at) as "last?: i64" FROM failed_logins WHERE user_id=$1 AND created_at > $2"#,
+            user_id,
+            since
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        next_attempt = next_attempt.max(next_attempt_at(&account_failures, &ACCOUNT_LIMITS));
+    }
+
+    Ok((next_attempt > now).then_some(next_attempt - now))
+}
+
+/// Logs a failed attempt. Returns true when it locked the account, so the owner can be told about it.
+pub async fn record_failure(
+    conn: &mut SqliteConnection,
+    user_id: Option<&str>,
+    email: Option<&str>,
+    ip: &str,
+    kind: AttemptKind,
+) -> Result<bool, ThrottleError> {
+    let now = unix_now()?;
+    let kind_name = kind.as_str();
+
+    eprintln!(
+        "Failed {kind_name} attempt for {} from {ip}.",
+        email.or(user_id).unwrap_or("an unknown account")
+    );
+
+    query!(
+        "INSERT INTO failed_logins(user_id, email, ip, kind, created_at) VALUES($1, $2, $3, $4, $5)",
+        user_id,
+        email,
+        ip,
+        kind_name,
+        now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let Some(user_id) = user_id else {
+        return Ok(false);
+    };
+
+    let since = now - WINDOW;
+
+    let failures = query_as!(
+        Failures,
+        r#"SELECT COUNT(*) as "count!: i64", MAX(created_at) as "last?: i64" FROM failed_logins WHERE user_id=$1 AND created_at > $2"#,
+        user_id,
+        since
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(failures.count == ACCOUNT_LIMITS.max_failures)
+}
+
+/// Forgets the account's failed attempts after a successful login.
+pub async fn record_success(conn: &mut SqliteConnection, user_id: &str) -> Result<(), ThrottleError> {
+    query!("DELETE FROM failed_logins WHERE user_id=$1", user_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// A human readable wait time, e.g. "2 minutes".
+pub fn describe_wait(seconds: i64) -> String {
+    match seconds {
+        ..=1 => "a second".to_string(),
+        2..60 => format!("{seconds} seconds"),
+        60..120 => "a minute".to_string(),
+        _ => format!("{} minutes", (seconds + 59) / 60),
+    }
+}
@@ -1,14 +1,14 @@
 use std::fs::File;
-use std::io::Read;
 use std::{collections::HashMap, fs, hash::Hash};
 
-use super::template;
-use axum::Form;
+use super::sections::{self, EditorContents, EditorFile};
+use super::{data, service, template};
+use axum::{Extension, Form};
 use axum::response::{Html, Result};
 use axum::{
     Router,
     extract::{Path, Query},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::get,
 };
@@ -25,8 +25,12 @@ use serde_json::json;
 use serde_with::NoneAsEmptyString;
 use serde_with::serde_as;
 use tl::{HTMLTag, Node};
+use uuid::Uuid;
 
-use crate::admin::Company;
+use crate::admin::{Company, DBConnection};
+use crate::auth::capability::{self, Capability};
+use crate::auth::service::UserData;
+use crate::themes;
 use crate::{
     AppState,
     blocks::set_0::{self, angled_element::AngledElement},
@@ -60,6 +64,12 @@ pub struct ListEntry<'a> {
     image: &'a str,
     from_element_ref: fn(&NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError>,
     default: fn() -> Box<dyn Block>,
+    /// The blocks this block may be placed in, `None` allowing any parent including the page root.
+    allowed_parents: Option<&'a [&'a str]>,
+    /// The blocks this block may contain, `None` allowing any block.
+    allowed_children: Option<&'a [&'a str]>,
+    /// The maximum amount of blocks this block may contain.
+    max_children: Option<usize>,
 }
 
 pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
@@ -70,7 +80,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A block whose top and bottom can be angled, and given the signature Groep van Roey arrow.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::angled_element::AngledElement::from_element_ref,
-        default: set_0::angled_element::AngledElement::default
+        default: set_0::angled_element::AngledElement::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
     });
 
     entries.insert("default-container", ListEntry {
@@ -78,7 +91,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A default container (section).",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::default_container::DefaultContainer::from_element_ref,
-        default: set_0::default_container::DefaultContainer::default
+        default: set_0::default_container::DefaultContainer::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
     });
 
     entries.insert("theme-text", ListEntry {
@@ -86,7 +102,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A small piece of text, used to indicated the theme of a section.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::theme_text::ThemeText::from_element_ref,
-        default: set_0::theme_text::ThemeText::default
+        default: set_0::theme_text::ThemeText::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("h2", ListEntry {
@@ -94,7 +113,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A heading element of type h2.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::heading_2::Heading2::from_element_ref,
-        default: set_0::heading_2::Heading2::default
+        default: set_0::heading_2::Heading2::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("p", ListEntry {
@@ -102,7 +124,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A paragraph element.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::paragraph::Paragraph::from_element_ref,
-        default: set_0::paragraph::Paragraph::default
+        default: set_0::paragraph::Paragraph::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("main-content", ListEntry {
@@ -110,7 +135,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A large block that can contain a title.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::main_content::MainContent::from_element_ref,
-        default: set_0::main_content::MainContent::default
+        default: set_0::main_content::MainContent::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("carousel", ListEntry {
@@ -118,7 +146,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A carousel.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::carousel::Carousel::from_element_ref,
-        default: set_0::carousel::Carousel::default
+        default: set_0::carousel::Carousel::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
     });
 
     entries.insert("animated-button", ListEntry {
@@ -126,7 +157,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A button.",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::animated_button_1::AnimatedButton1::from_element_ref,
-        default: set_0::animated_button_1::AnimatedButton1::default
+        default: set_0::animated_button_1::AnimatedButton1::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("banner-0", ListEntry {
@@ -134,7 +168,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A banner",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::banner_0::Banner0::from_element_ref,
-        default: set_0::banner_0::Banner0::default
+        default: set_0::banner_0::Banner0::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
     });
 
     entries.insert("a-carousel", ListEntry {
@@ -142,7 +179,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A-Carousel",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::a_carousel::ACarousel::from_element_ref,
-        default: set_0::a_carousel::ACarousel::default
+        default: set_0::a_carousel::ACarousel::default,
+        allowed_parents: None,
+        allowed_children: Some(&["a-carousel-entry"]),
+        max_children: None
     });
 
     entries.insert("a-carousel-entry", ListEntry {
@@ -150,7 +190,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "A-Carousel entry",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::a_carousel_entry::ACarouselEntry::from_element_ref,
-        default: set_0::a_carousel_entry::ACarouselEntry::default
+        default: set_0::a_carousel_entry::ACarouselEntry::default,
+        allowed_parents: Some(&["a-carousel"]),
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("tabs", ListEntry {
@@ -158,7 +201,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "Tabs",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::tabs::Tabs::from_element_ref,
-        default: set_0::tabs::Tabs::default
+        default: set_0::tabs::Tabs::default,
+        allowed_parents: None,
+        allowed_children: Some(&["tabs-entry"]),
+        max_children: None
     });
 
     entries.insert("tabs-entry", ListEntry {
@@ -166,7 +212,10 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "Tabs entry",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::tabs_entry::TabsEntry::from_element_ref,
-        default: set_0::tabs_entry::TabsEntry::default
+        default: set_0::tabs_entry::TabsEntry::default,
+        allowed_parents: Some(&["tabs"]),
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries.insert("map", ListEntry {
@@ -174,15 +223,220 @@ pub static CONFIG: Lazy<HashMap<&'static str, ListEntry>> = Lazy::new(|| {
         description: "Map",
         image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
         from_element_ref: set_0::map::Map::from_element_ref,
-        default: set_0::map::Map::default
+        default: set_0::map::Map::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("project-grid", ListEntry {
+        name: "Project Grid",
+        description: "A grid of projects, filtered by sector, lifecycle, delivery year, type and tags.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::project_grid::ProjectGrid::from_element_ref,
+        default: set_0::project_grid::ProjectGrid::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("featured-projects", ListEntry {
+        name: "Featured Projects",
+        description: "The projects that are shown in the carousel, as a carousel or as \"in de kijker\".",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::featured_projects::FeaturedProjects::from_element_ref,
+        default: set_0::featured_projects::FeaturedProjects::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("core-numbers", ListEntry {
+        name: "Core Numbers",
+        description: "The core numbers of a project.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::core_numbers::CoreNumbers::from_element_ref,
+        default: set_0::core_numbers::CoreNumbers::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("contact-form", ListEntry {
+        name: "Contact Form",
+        description: "A contact form, whose submissions are sent to the company.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::contact_form::ContactForm::from_element_ref,
+        default: set_0::contact_form::ContactForm::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("header", ListEntry {
+        name: "Header",
+        description: "The header of the website, shared by all pages.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::header::Header::from_element_ref,
+        default: set_0::header::Header::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
+    });
+
+    entries.insert("footer", ListEntry {
+        name: "Footer",
+        description: "The footer of the website, shared by all pages.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::footer::Footer::from_element_ref,
+        default: set_0::footer::Footer::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: None
+    });
+
+    entries.insert("navigation", ListEntry {
+        name: "Navigation",
+        description: "A navigation menu, built from the pages of the website.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::navigation::Navigation::from_element_ref,
+        default: set_0::navigation::Navigation::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
+    });
+
+    entries.insert("global-section", ListEntry {
+        name: "Global Section",
+        description: "The shared header or footer of the website. It is edited once and shown on every page that uses it.",
+        image: "https://nintendolesite.com/images/uploads/professeur-layton-et-le-nouveau-monde--vapeur.jpg",
+        from_element_ref: set_0::global_section::GlobalSection::from_element_ref,
+        default: set_0::global_section::GlobalSection::default,
+        allowed_parents: None,
+        allowed_children: None,
+        max_children: Some(0)
     });
 
     entries
 });
 
-pub async fn list() -> Markup {
-    html!(
-        @for (block_id, entry) in CONFIG.iter() {
+/// Returns the block id of a node, `None` for the page itself.
+fn block_id_of(node: &NodeRef) -> Option<String> {
+    node.as_element()
+        .and_then(|element| element.attributes.borrow().get("block-id").map(str::to_string))
+}
+
+/// Returns the node a block would end up in when inserted relative to the reference node.
+fn target_parent(reference: &NodeRef, direction: Option<bool>) -> Option<NodeRef> {
+    match direction {
+        Some(_) => reference.parent(),
+        None => Some(reference.clone()),
+    }
+}
+
+/// Checks whether a block may be placed in the given parent, ignoring the block that is being moved.
+fn check_structure(
+    parent: &NodeRef,
+    block_id: &str,
+    moved: Option<&NodeRef>,
+) -> Result<(), (StatusCode, String)> {
+    let entry = CONFIG
+        .get(block_id)
+        .ok_or((StatusCode::NOT_FOUND, format!("Unknown block '{block_id}'.")))?;
+    let parent_block_id = block_id_of(parent);
+
+    if let Some(allowed_parents) = entry.allowed_parents {
+        let allowed = parent_block_id
+            .as_deref()
+            .is_some_and(|parent_block_id| allowed_parents.contains(&parent_block_id));
+
+        if !allowed {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("A {} can only be placed in: {}.", entry.name, allowed_parents.join(", ")),
+            ));
+        }
+    }
+
+    let Some(parent_block_id) = parent_block_id else {
+        return Ok(());
+    };
+
+    let parent_entry = CONFIG
+        .get(parent_block_id.as_str())
+        .ok_or((StatusCode::NOT_FOUND, format!("Unknown block '{parent_block_id}'.")))?;
+
+    if let Some(allowed_children) = parent_entry.allowed_children {
+        if !allowed_children.contains(&block_id) {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("A {} can only contain: {}.", parent_entry.name, allowed_children.join(", ")),
+            ));
+        }
+    }
+
+    if let Some(max_children) = parent_entry.max_children {
+        let child_count = parent
+            .children()
+            .elements()
+            .filter(|child| child.attributes.borrow().contains("block-id"))
+            .filter(|child| moved.is_none_or(|moved| child.as_node() != moved))
+            .count();
+
+        if child_count >= max_children {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("A {} can contain at most {max_children} blocks.", parent_entry.name),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ListQuery {
+    element_id: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    direction: Option<bool>,
+}
+
+pub async fn list(
+    DBConnection(mut connection): DBConnection,
+    Company(company): Company<Option<String>>,
+    EditorContents(contents): EditorContents,
+    Query(query): Query<ListQuery>,
+) -> Result<Markup> {
+    // Loaded before the page is parsed, kuchikiki's nodes can't be held across an await in a handler.
+    let snippets = service::get_snippets(&mut connection, company.as_deref())
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the block snippets: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let parent = match &query.element_id {
+        Some(element_id) => {
+            let html = kuchikiki::parse_html().one(contents);
+
+            let reference_element = find_element_in_list_2(html.children().elements(), element_id)
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            target_parent(reference_element.as_node(), query.direction)
+        }
+        None => None,
+    };
+
+    let is_allowed = |block_id: &str| {
+        parent
+            .as_ref()
+            .is_none_or(|parent| check_structure(parent, block_id, None).is_ok())
+    };
+
+    Ok(html!(
+        @for (block_id, entry) in CONFIG.iter().filter(|(block_id, _)| is_allowed(block_id)) {
             label {
                 img loading="lazy" src=(entry.image);
                 div.name { (entry.name )}
@@ -190,34 +444,64 @@ pub async fn list() -> Markup {
                 input type="radio" name="block_id" value=(block_id);
             }
         }
-    )
-}
-
-pub async fn testpage() -> Html<String> {
-    let path = "home.html";
-    let mut file = match File::open(format!("pages/draft/{path}")) {
-        Ok(file) => file,
-        Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => {
-                fs::copy(format!("pages/live/{path}"), format!("pages/draft/{path}")).unwrap();
-
-                match File::open(format!("pages/draft/{path}")) {
-                    Ok(file) => file,
-                    Err(_) => todo!(),
+        @for snippet in snippets.iter().filter(|snippet| is_allowed(&snippet.block_id)) {
+            @if let Some(entry) = CONFIG.get(snippet.block_id.as_str()) {
+                label.snippet {
+                    img loading="lazy" src=(entry.image);
+                    div.name { (snippet.name) }
+                    div.description { (entry.name) }
+                    input type="radio" name="snippet_id" value=(snippet.id);
+                    button.delete-snippet type="button" title="Delete snippet" hx-delete={"/api/blocks/snippets/"(snippet.id)} hx-confirm={"Delete the snippet "(snippet.name)"?"} hx-target="closest label" hx-swap="delete" {}
                 }
             }
-            _ => todo!(),
-        },
+        }
+    ))
+}
+
+pub async fn testpage(
+    DBConnection(mut connection): DBConnection,
+    Company(company): Company<Option<String>>,
+    EditorContents(contents): EditorContents,
+) -> Result<Html<String>> {
+    let Some(company) = &company else {
+        return Ok(Html(kuchikiki::parse_html().one(contents).to_string()));
     };
 
-    //let fragment = kuchikiki::parse_html().from_utf8().read_from(&mut file).unwrap().last_child().unwrap().last_child().unwrap();
+    // kuchikiki's nodes can't be held across an await in a handler, so the blocks are read from a parse of their own.
+    let blocks = data::preview_blocks(&preview_document(company, &contents));
+
+    let previews = data::load_previews(&mut connection, company, blocks)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to render the data block previews: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let theme = themes::service::get_theme(&mut connection, company)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the theme of {company}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    //let rendered_elements = get_testpage_list_rendered(fragment.children().elements());
+    let html = preview_document(company, &contents);
 
-    let mut x = String::new();
-    file.read_to_string(&mut x).unwrap();
+    data::render_previews(&html, previews);
 
-    Html(x)
+    if let Some(theme) = &theme {
+        themes::endpoint::apply_to_preview(theme, &html);
+    }
+
+    Ok(Html(html.to_string()))
+}
+
+/// The page with the company's shared sections filled in.
+fn preview_document(company: &str, contents: &str) -> NodeRef {
+    let html = kuchikiki::parse_html().one(contents);
+
+    sections::render_previews(company, &html);
+
+    html
 }
 
 fn find_element_in_list_2<'a>(
@@ -231,7 +515,7 @@ fn find_element_in_list_2<'a>(
             .attributes
             .borrow()
             .get("element-id")
-            .map_or(false, |eid| eid == element_id)
+            .is_some_and(|eid| eid == element_id)
         {
             true => Some(e.clone()),
             false => find_element_in_list_2(e.as_node().children().elements(), element_id),
@@ -281,27 +565,12 @@ pub struct TestPageTreeQuery {
     parent: Option<String>,
 }
 
-pub async fn testpage_tree(Query(query): Query<TestPageTreeQuery>) -> Result<Markup> {
-    let path = "home.html";
-    let mut file = match File::open(format!("pages/draft/{path}")) {
-        Ok(file) => file,
-        Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => {
-                fs::copy(format!("pages/live/{path}"), format!("pages/draft/{path}")).unwrap();
-
-                match File::open(format!("pages/draft/{path}")) {
-                    Ok(file) => file,
-                    Err(_) => todo!(),
-                }
-            }
-            _ => todo!(),
-        },
-    };
-
+pub async fn testpage_tree(
+    EditorContents(contents): EditorContents,
+    Query(query): Query<TestPageTreeQuery>,
+) -> Result<Markup> {
     let fragment = kuchikiki::parse_html()
-        .from_utf8()
-        .read_from(&mut file)
-        .unwrap()
+        .one(contents)
         .last_child()
         .unwrap()
         .last_child()
@@ -326,25 +595,160 @@ pub async fn testpage_tree(Query(query): Query<TestPageTreeQuery>) -> Result<Mar
     ))
 }
 
+/// Parses a serialized block subtree back into a detached node.
+fn parse_block_html(html: &str) -> Option<NodeRef> {
+    let document = kuchikiki::parse_html().one(html);
+    let element = document.select_first("body > [block-id]").ok()?;
+    let node = element.as_node().clone();
+
+    node.detach();
+
+    Some(node)
+}
+
+/// Gives every element of a copied subtree a fresh element id, so the copy never collides with the original.
+fn assign_new_element_ids(node: &NodeRef) {
+    for element in node.inclusive_descendants().elements() {
+        let mut attributes = element.attributes.borrow_mut();
+
+        if attributes.contains("element-id") {
+            attributes.insert("element-id", Uuid::new_v4().to_string());
+        }
+    }
+}
+
+/// Inserts a node before (`Some(true)`), after (`Some(false)`) or inside (`None`) the reference node, and returns the matching htmx swap.
+fn insert_relative(reference: &NodeRef, new_node: NodeRef, direction: Option<bool>) -> &'static str {
+    match direction {
+        Some(true) => {
+            reference.insert_before(new_node);
+            "beforebegin"
+        }
+        Some(false) => {
+            reference.insert_after(new_node);
+            "afterend"
+        }
+        None => {
+            reference.append(new_node);
+            "none"
+        }
+    }
+}
+
+/// Renders the tree item of a block straight from its node in the page.
+fn new_tree_item(node: &NodeRef, swap_oob: bool) -> Option<Markup> {
+    let element = node.as_element()?;
+    let attributes = element.attributes.borrow();
+    let element_id = attributes.get("element-id")?;
+    let entry = CONFIG.get(attributes.get("block-id")?)?;
+    let child_count = node.children().elements().count();
+
+    Some(template::tree_item(element_id, entry.name, child_count, swap_oob))
+}
+
+/// Inserts a copied subtree relative to the reference element and returns the updated tree items.
+fn insert_subtree(path: &str, reference_id: &str, html: &str, direction: Option<bool>) -> Result<Markup> {
+    let new_node = parse_block_html(html).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    assign_new_element_ids(&new_node);
+    let new_li = new_tree_item(&new_node, false).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .unwrap();
+
+    let html = kuchikiki::parse_html()
+        .from_utf8()
+        .read_from(&mut file)
+        .unwrap();
+    let reference_element = find_element_in_list_2(html.children().elements(), reference_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let node = reference_element.as_node();
+    let block_id = block_id_of(&new_node).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let parent = target_parent(node, direction).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    check_structure(&parent, &block_id, None)?;
+
+    let direction = insert_relative(node, new_node, direction);
+    let li = new_tree_item(node, true).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    html.serialize_to_file(&path).unwrap();
+
+    Ok(html!(
+        (li)
+        div hx-swap-oob={(direction)":li[element-id='"(reference_id)"']"} {
+            (new_li)
+        }
+    ))
+}
+
+/// Serializes an element of the draft page, including all of its children.
+fn serialize_element(path: &str, element_id: &str) -> Result<(String, String)> {
+    let mut file = File::options()
+        .read(true)
+        .open(&path)
+        .unwrap();
+
+    let html = kuchikiki::parse_html()
+        .from_utf8()
+        .read_from(&mut file)
+        .unwrap();
+
+    let element_ref = find_element_in_list_2(html.children().elements(), element_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let block_id = element_ref
+        .attributes
+        .borrow()
+        .get("block-id")
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?
+        .to_string();
+
+    Ok((block_id, element_ref.as_node().to_string()))
+}
+
 #[serde_as]
 #[derive(Deserialize)]
 pub struct CreateNewElementBody {
-    block_id: String,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    block_id: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    snippet_id: Option<String>,
     element_id: String,
     #[serde_as(as = "NoneAsEmptyString")]
     direction: Option<bool>,
 }
 
-pub async fn create_new_element(Form(body): Form<CreateNewElementBody>) -> Result<Markup> {
-    let new_block = CONFIG.get(body.block_id.as_str()).unwrap();
-    let new_element = (new_block.default)();
-    let rendered_new_element = new_element.render_to_noderef();
-    let new_li = template::tree_item(new_element.id(), new_block.name, 0, false);
+pub async fn create_new_element(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    Form(body): Form<CreateNewElementBody>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    if let Some(snippet_id) = &body.snippet_id {
+        let snippet = service::get_snippet(&mut connection, snippet_id, company.as_deref())
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to get snippet {snippet_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        return insert_subtree(&path, &body.element_id, &snippet.html, body.direction);
+    }
+
+    let block_id = body.block_id.ok_or(StatusCode::BAD_REQUEST)?;
+    let new_block = CONFIG.get(block_id.as_str()).ok_or(StatusCode::NOT_FOUND)?;
 
     let mut file = File::options()
         .read(true)
         .write(true)
-        .open("pages/draft/home.html")
+        .open(&path)
         .unwrap();
 
     let html = kuchikiki::parse_html()
@@ -355,24 +759,17 @@ pub async fn create_new_element(Form(body): Form<CreateNewElementBody>) -> Resul
         find_element_in_list_2(html.children().elements(), &body.element_id).unwrap();
     let reference_elmeent_attributes = reference_element.attributes.borrow();
     let reference_element_block_id = reference_elmeent_attributes.get("block-id").unwrap();
-    let new_block = CONFIG.get(reference_element_block_id).unwrap();
     let node = reference_element.as_node();
+    let parent = target_parent(node, body.direction).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
 
-    let direction = match body.direction {
-        Some(direction) => {
-            if direction {
-                node.insert_before(rendered_new_element);
-                "beforebegin"
-            } else {
-                node.insert_after(rendered_new_element);
-                "afterend"
-            }
-        }
-        None => {
-            node.append(rendered_new_element);
-            "none"
-        }
-    };
+    check_structure(&parent, &block_id, None)?;
+
+    let new_element = (new_block.default)();
+    let rendered_new_element = new_element.render_to_noderef();
+    let new_li = template::tree_item(new_element.id(), new_block.name, 0, false);
+    let new_block = CONFIG.get(reference_element_block_id).unwrap();
+
+    let direction = insert_relative(node, rendered_new_element, body.direction);
 
     let reference_child_count = node.children().elements().count();
 
@@ -383,7 +780,7 @@ pub async fn create_new_element(Form(body): Form<CreateNewElementBody>) -> Resul
         true,
     );
 
-    html.serialize_to_file("pages/draft/home.html").unwrap();
+    html.serialize_to_file(&path).unwrap();
 
     Ok(html!(
         (li)
@@ -393,11 +790,133 @@ pub async fn create_new_element(Form(body): Form<CreateNewElementBody>) -> Resul
     ))
 }
 
-pub async fn delete_element(Path(element_id): Path<String>) -> Result<()> {
+pub async fn duplicate_element(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    Path(element_id): Path<String>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    let (_, html) = serialize_element(&path, &element_id)?;
+
+    insert_subtree(&path, &element_id, &html, Some(false))
+}
+
+pub async fn copy_element(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    Path(element_id): Path<String>,
+) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    let (block_id, html) = serialize_element(&path, &element_id)?;
+
+    service::set_clipboard(&mut connection, &user.id, &block_id, &html)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to copy element {element_id} for user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct PasteElementBody {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    direction: Option<bool>,
+}
+
+pub async fn paste_element(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    Path(element_id): Path<String>,
+    Form(body): Form<PasteElementBody>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    let clipboard = service::get_clipboard(&mut connection, &user.id)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to get the clipboard for user {}: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "The clipboard is empty."))?;
+
+    insert_subtree(&path, &element_id, &clipboard.html, body.direction)
+}
+
+pub async fn save_snippet(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    headers: HeaderMap,
+    Path(element_id): Path<String>,
+) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    let name = headers
+        .get("HX-Prompt")
+        .and_then(|name| name.to_str().ok())
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .ok_or((StatusCode::BAD_REQUEST, "Missing the snippet name."))?;
+
+    let (block_id, html) = serialize_element(&path, &element_id)?;
+
+    service::create_snippet(&mut connection, name, company.as_deref(), &block_id, &html)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to save element {element_id} as a snippet: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(())
+}
+
+pub async fn delete_snippet(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Path(snippet_id): Path<String>,
+) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
+    service::delete_snippet(&mut connection, &snippet_id, company.as_deref())
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            err => {
+                eprintln!("Failed to delete snippet {snippet_id}: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    Ok(())
+}
+
+pub async fn delete_element(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
+    Path(element_id): Path<String>,
+) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
     let mut file = File::options()
         .read(true)
         .write(true)
-        .open("pages/draft/home.html")
+        .open(&path)
         .unwrap();
 
     let html = kuchikiki::parse_html()
@@ -410,22 +929,21 @@ pub async fn delete_element(Path(element_id): Path<String>) -> Result<()> {
 
     element.detach();
 
-    html.serialize_to_file("pages/draft/home.html").unwrap();
+    html.serialize_to_file(&path).unwrap();
 
     Ok(())
 }
 
-pub async fn get_properties(Path(element_id): Path<String>) -> Result<Markup> {
-    let mut file = File::options()
-        .read(true)
-        .write(true)
-        .open("pages/draft/home.html")
-        .unwrap();
+pub async fn get_properties(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorContents(contents): EditorContents,
+    Path(element_id): Path<String>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
 
-    let html = kuchikiki::parse_html()
-        .from_utf8()
-        .read_from(&mut file)
-        .unwrap();
+    let html = kuchikiki::parse_html().one(contents);
 
     let element_ref = find_element_in_list_2(html.children().elements(), &element_id).unwrap();
     let attributes = element_ref.attributes.borrow();
@@ -439,13 +957,19 @@ pub async fn get_properties(Path(element_id): Path<String>) -> Result<Markup> {
 }
 
 pub async fn update_properties(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
     Path(element_id): Path<String>,
     Form(properties): Form<HashMap<String, String>>,
 ) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
     let mut file = File::options()
         .read(true)
         .write(true)
-        .open("pages/draft/home.html")
+        .open(&path)
         .unwrap();
 
     let html = kuchikiki::parse_html()
@@ -465,7 +989,7 @@ pub async fn update_properties(
     node.insert_after(generated_noderef);
     node.detach();
 
-    html.serialize_to_file("pages/draft/home.html").unwrap();
+    html.serialize_to_file(&path).unwrap();
 
     Ok(())
 }
@@ -476,16 +1000,22 @@ pub struct MoveItemBody {
 }
 
 pub async fn move_item(
+    Company(company): Company<Option<String>>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorFile(path): EditorFile,
     Path(element_id): Path<String>,
     Form(body): Form<MoveItemBody>,
 ) -> Result<()> {
+    capability::require_for_company(&mut connection, &user, company.as_deref(), Capability::EditSitePages).await?;
+
     let mut file = File::options()
         .read(true)
         .write(true)
-        .open("pages/draft/home.html")
+        .open(&path)
         .unwrap();
 
-    let file_contents = fs::read_to_string("pages/draft/home.html").unwrap();
+    let file_contents = fs::read_to_string(&path).unwrap();
 
     println!("{file_contents}");
 
@@ -502,6 +1032,7 @@ pub async fn move_item(
     let moved_node = moved_element.as_node();
     let cloned_moved_node = moved_node.clone();
     let parent = moved_node.parent().unwrap();
+    let block_id = block_id_of(moved_node).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
 
     if let Some(next_id) = body.next {
         let next_element = parent
@@ -510,22 +1041,28 @@ pub async fn move_item(
             .next()
             .unwrap();
         let next_node = next_element.as_node();
+        let new_parent = next_node.parent().ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+        check_structure(&new_parent, &block_id, Some(moved_node))?;
 
         next_node.insert_before(cloned_moved_node);
     } else {
+        check_structure(&parent, &block_id, Some(moved_node))?;
+
         parent.append(cloned_moved_node);
     }
 
-    html.serialize_to_file("pages/draft/home.html").unwrap();
+    html.serialize_to_file(&path).unwrap();
 
     Ok(())
 }
 
-const RESERVED_FILE_NAMES: [&str; 1] = [
-    "home"
+const RESERVED_FILE_NAMES: [&str; 2] = [
+    "home",
+    "_sections"
 ];
 
-struct PageList(Vec<(String, Option<PageList>)>);
+pub struct PageList(pub Vec<(String, Option<PageList>)>);
 
 pub fn get_pages(path: &str) -> PageList {
     let mut list = vec![];
@@ -583,7 +1120,13 @@ pub fn render_page_list(company: &str, PageList(page_list): &PageList) -> Markup
     )
 }
 
-pub async fn pages(Company(company): Company<String>,) -> Result<Markup> {
+pub async fn pages(
+    Company(company): Company<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, Some(&company), Capability::EditSitePages).await?;
+
     let list = get_pages(&company);
 
     Ok(render_page_list(&company, &list))
@@ -594,7 +1137,14 @@ pub struct CreatePageBody {
     name: String
 }
 
-pub async fn create_page(Company(company): Company<String>, Form(body): Form<CreatePageBody>) -> Result<Markup> {
+pub async fn create_page(
+    Company(company): Company<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Form(body): Form<CreatePageBody>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, Some(&company), Capability::EditSitePages).await?;
+
     println!("{company:?}: {}", body.name);
 
     Ok(html!())
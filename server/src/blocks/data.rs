@@ -0,0 +1,237 @@
+use kuchikiki::{NodeRef, traits::TendrilSink};
+use maud::{Markup, html};
+use sqlx::{SqliteConnection, query_file_as, query_scalar};
+
+use crate::blocks::set_0::{
+    core_numbers::CoreNumbers, featured_projects::FeaturedProjects, project_grid::ProjectGrid,
+};
+
+// The queries are shared with `sites::projecten::service`, so the editor preview shows the same projects as the public
+// pages.
+
+/// The previews show the Dutch texts, which are in the columns of the projects themselves.
+const PREVIEW_LANGUAGE: &str = "nl";
+
+pub struct Project {
+    pub name: String,
+    pub slug: String,
+    pub location: Option<String>,
+    pub thumbnail: Option<String>,
+}
+
+pub async fn get_projects(
+    conn: &mut SqliteConnection,
+    company: &str,
+    grid: &ProjectGrid,
+) -> Result<Vec<Project>, sqlx::Error> {
+    let tags = match grid.tags.is_empty() {
+        true => None,
+        false => Some(serde_json::to_string(&grid.tags).unwrap_or_default()),
+    };
+    let bedrijf: Option<String> = None;
+    let limit = grid.limit.unwrap_or(12);
+    let offset = 0;
+
+    query_file_as!(
+        Project,
+        "sql/queries/projects.sql",
+        company,
+        grid.lifecycle,
+        grid.sector,
+        grid.publiek_privaat,
+        bedrijf,
+        tags,
+        "%",
+        limit,
+        offset,
+        PREVIEW_LANGUAGE,
+        grid.delivered_from,
+        grid.delivered_to
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+pub struct FeaturedProject {
+    pub name: String,
+    pub slug: Option<String>,
+    pub location: Option<String>,
+    pub header_photo: Option<String>,
+}
+
+pub async fn get_featured_projects(
+    conn: &mut SqliteConnection,
+    company: &str,
+) -> Result<Vec<FeaturedProject>, sqlx::Error> {
+    query_file_as!(FeaturedProject, "sql/queries/featured_projects.sql", company, PREVIEW_LANGUAGE)
+        .fetch_all(&mut *conn)
+        .await
+}
+
+pub struct CoreNumber {
+    pub number: String,
+    pub title: String,
+}
+
+pub async fn get_core_numbers(
+    conn: &mut SqliteConnection,
+    slug: &str,
+) -> Result<Vec<CoreNumber>, sqlx::Error> {
+    let Some(project_id) = query_scalar!("SELECT id FROM PROJECTS WHERE slug=$1 AND draft=0", slug)
+        .fetch_optional(&mut *conn)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    query_file_as!(CoreNumber, "sql/queries/core_numbers.sql", project_id, PREVIEW_LANGUAGE)
+        .fetch_all(conn)
+        .await
+}
+
+fn project_grid(projects: &Vec<Project>) -> Markup {
+    html!(
+        @for project in projects {
+            a.project href={"/projecten/"(project.slug)} {
+                img src=[&project.thumbnail] loading="lazy";
+                div.name {(project.name)}
+                div.location { (project.location.as_deref().unwrap_or_default()) }
+            }
+        }
+    )
+}
+
+fn featured_projects(variant: &str, projects: &Vec<FeaturedProject>) -> Markup {
+    html!(
+        @if variant == "in-de-kijker" {
+            section.project-carousel {
+                div.content {
+                    @for project in projects {
+                        a.item href={"/projecten/"(project.slug.as_deref().unwrap_or_default())} {
+                            img src=(project.header_photo.as_deref().unwrap_or_default());
+                            span { (project.name) }
+                        }
+                    }
+                }
+            }
+        } @else {
+            @for project in projects {
+                div.main-content {
+                    img.background src=[&project.header_photo];
+                    div.project-info {
+                        span.name { (project.name) }
+                        @if let Some(location) = &project.location {
+                            (location)
+                        }
+                        a href={"/projecten/"(project.slug.as_deref().unwrap_or_default())} { "Bekijk project" }
+                    }
+                }
+            }
+        }
+    )
+}
+
+fn core_numbers(core_numbers: &Vec<CoreNumber>) -> Markup {
+    html!(
+        ul #core-numbers {
+            @for core_number in core_numbers {
+                li {
+                    span.number { (core_number.number) }
+                    span.title { (core_number.title) }
+                }
+            }
+        }
+    )
+}
+
+/// Replaces the contents of a node with rendered markup.
+fn replace_children(node: &NodeRef, markup: Markup) {
+    for child in node.children() {
+        child.detach();
+    }
+
+    let fragment = kuchikiki::parse_html().one(markup.into_string());
+
+    if let Ok(body) = fragment.select_first("body") {
+        for child in body.as_node().children() {
+            node.append(child);
+        }
+    }
+}
+
+/// A data block of a page, with what its preview needs from the database.
+pub enum Preview {
+    ProjectGrid(ProjectGrid),
+    FeaturedProjects(FeaturedProjects),
+    CoreNumbers(CoreNumbers),
+}
+
+/// The data blocks of a page, one entry for every element with a block id. kuchikiki's nodes can't be held across an
+/// await, so the page is read before the data is loaded, and the previews are filled in afterwards.
+pub fn preview_blocks(document: &NodeRef) -> Vec<Option<Preview>> {
+    let Ok(elements) = document.select("[block-id]") else {
+        return Vec::new();
+    };
+
+    elements
+        .map(|element| {
+            let block_id = element.attributes.borrow().get("block-id").unwrap_or_default().to_string();
+
+            match block_id.as_str() {
+                "project-grid" => Some(Preview::ProjectGrid(ProjectGrid::parse(&element))),
+                "featured-projects" => Some(Preview::FeaturedProjects(FeaturedProjects::parse(&element))),
+                "core-numbers" => Some(Preview::CoreNumbers(CoreNumbers::parse(&element))),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Renders the previews of the data blocks with the data of the given company.
+pub async fn load_previews(
+    conn: &mut SqliteConnection,
+    company: &str,
+    blocks: Vec<Option<Preview>>,
+) -> Result<Vec<Option<Markup>>, sqlx::Error> {
+    let mut previews = Vec::with_capacity(blocks.len());
+
+    for block in blocks {
+        let preview = match block {
+            Some(Preview::ProjectGrid(grid)) => {
+                let projects = get_projects(&mut *conn, company, &grid).await?;
+
+                Some(project_grid(&projects))
+            }
+            Some(Preview::FeaturedProjects(featured)) => {
+                let projects = get_featured_projects(&mut *conn, company).await?;
+
+                Some(featured_projects(&featured.variant, &projects))
+            }
+            Some(Preview::CoreNumbers(CoreNumbers { project: Some(project), .. })) => {
+                let entries = get_core_numbers(&mut *conn, &project).await?;
+
+                Some(core_numbers(&entries))
+            }
+            _ => None,
+        };
+
+        previews.push(preview);
+    }
+
+    Ok(previews)
+}
+
+/// Fills the data blocks of a page with their previews, in place of their SSI includes. The page has to be the one
+/// the blocks were read from.
+pub fn render_previews(document: &NodeRef, previews: Vec<Option<Markup>>) {
+    let elements = match document.select("[block-id]") {
+        Ok(elements) => elements.collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+
+    for (element, preview) in elements.into_iter().zip(previews) {
+        if let Some(preview) = preview {
+            replace_children(element.as_node(), preview);
+        }
+    }
+}
@@ -1,9 +1,12 @@
 mod template;
 mod properties;
+mod service;
+mod data;
+pub mod sections;
 
-use axum::{routing::{delete, get, post, put}, Router};
+use axum::{middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
 
-use crate::{blocks::config::get_properties, AppState};
+use crate::{auth::service::auth_middleware, AppState};
 
 pub mod config;
 pub mod set_0;
@@ -13,11 +16,17 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/list", get(config::list))
         .route("/testpage", get(config::testpage))
         .route("/tree", get(config::testpage_tree))
-        .route("/add-element", post(config::create_new_element))
-        .route("/delete-element/{element_id}", delete(config::delete_element))
-        .route("/{element_id}/properties", get(config::get_properties))
-        .route("/{element_id}/properties", put(config::update_properties))
-        .route("/{element_id}/move", put(config::move_item))
-        .route("/pages", get(config::pages))
-        .route("/pages", post(config::create_page))
+        .route("/add-element", post(config::create_new_element).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/delete-element/{element_id}", delete(config::delete_element).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/properties", get(config::get_properties).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/properties", put(config::update_properties).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/move", put(config::move_item).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/menu", get(sections::menu).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/duplicate", post(config::duplicate_element).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/copy", post(config::copy_element).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/paste", post(config::paste_element).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{element_id}/snippets", post(config::save_snippet).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/snippets/{snippet_id}", delete(config::delete_snippet).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/pages", get(config::pages).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/pages", post(config::create_page).layer(from_fn_with_state(state.clone(), auth_middleware)))
 }
\ No newline at end of file
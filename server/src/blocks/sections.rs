@@ -0,0 +1,253 @@
+use std::{fs, io::ErrorKind};
+
+use axum::{
+    Extension,
+    extract::{FromRequestParts, Path},
+    http::{StatusCode, request::Parts},
+    response::Result,
+};
+use kuchikiki::{NodeRef, traits::TendrilSink};
+use maud::{Markup, html};
+use url::{Url, form_urlencoded};
+
+use crate::admin::{Company, DBConnection};
+use crate::auth::capability::{self, Capability};
+use crate::auth::service::UserData;
+use crate::blocks::config::{Block, PageList, get_pages};
+use crate::blocks::set_0::{
+    default_container::DefaultContainer, footer::Footer, header::Header, navigation::Navigation,
+};
+
+/// The global sections every company has, shared by all of its pages.
+pub const SECTIONS: [&str; 2] = ["header", "footer"];
+
+const HOME_PAGE: &str = "home.html";
+
+/// The draft file that is being edited: the page, or one of the company's global sections when the
+/// editor was opened with a `section` query parameter. The draft is created when it doesn't exist yet, so this is for
+/// the requests that change the file, `EditorContents` is for the ones that only read it.
+pub struct EditorFile(pub String);
+
+/// The contents of the file that is being edited: its draft, or the file the draft would start from when there's no
+/// draft yet.
+pub struct EditorContents(pub String);
+
+fn section_path(state: &str, company: &str, section: &str) -> String {
+    format!("pages/{state}/{company}/_sections/{section}.html")
+}
+
+/// Companies are named after their domain, so their name is used as a directory as is. Only the names that would leave
+/// `pages/` are refused.
+fn valid_company(company: &str) -> bool {
+    !company.contains(['/', '\\']) && !matches!(company, "" | "." | "..")
+}
+
+/// An empty document with the seed in its body, for a file that has neither a draft nor a live version.
+fn seed_document(seed: fn() -> NodeRef) -> NodeRef {
+    let document = kuchikiki::parse_html().one("");
+
+    if let Ok(body) = document.select_first("body") {
+        body.as_node().append(seed());
+    }
+
+    document
+}
+
+/// Creates the draft of a file when it doesn't exist yet, starting from the live version if there is one.
+fn ensure_draft(draft: &str, live: &str, seed: fn() -> NodeRef) -> std::io::Result<()> {
+    if fs::exists(draft)? {
+        return Ok(());
+    }
+
+    if let Some(parent) = std::path::Path::new(draft).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if fs::exists(live)? {
+        return fs::copy(live, draft).map(|_| ());
+    }
+
+    seed_document(seed).serialize_to_file(draft)
+}
+
+/// The draft and live paths of the file the editor is working on, and what a new one starts with.
+fn editor_paths(parts: &Parts) -> Result<(String, String, fn() -> NodeRef), (StatusCode, String)> {
+    let current_url = parts
+        .headers
+        .get("HX-CURRENT-URL")
+        .and_then(|header_value| header_value.to_str().ok())
+        .and_then(|url| Url::parse(url).ok());
+
+    let pairs: Vec<(String, String)> = match &current_url {
+        Some(url) => url.query_pairs().into_owned().collect(),
+        None => form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect(),
+    };
+
+    let get = |key: &str| {
+        pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match (get("company"), get("section")) {
+        (Some(company), Some(section)) => {
+            if !valid_company(&company) {
+                return Err((StatusCode::BAD_REQUEST, "Invalid company.".to_owned()));
+            }
+
+            let seed: fn() -> NodeRef = match section.as_str() {
+                "header" => || Header::default().render_to_noderef(),
+                "footer" => || Footer::default().render_to_noderef(),
+                _ => return Err((StatusCode::BAD_REQUEST, "Unknown global section.".to_owned())),
+            };
+
+            Ok((
+                section_path("draft", &company, &section),
+                section_path("live", &company, &section),
+                seed,
+            ))
+        }
+        _ => Ok((
+            format!("pages/draft/{HOME_PAGE}"),
+            format!("pages/live/{HOME_PAGE}"),
+            || DefaultContainer::default().render_to_noderef(),
+        )),
+    }
+}
+
+impl<S> FromRequestParts<S> for EditorFile
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let (draft, live, seed) = editor_paths(parts)?;
+
+        ensure_draft(&draft, &live, seed).map_err(|err| {
+            eprintln!("Failed to prepare {draft}: {err}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to open the page.".to_owned())
+        })?;
+
+        Ok(EditorFile(draft))
+    }
+}
+
+impl<S> FromRequestParts<S> for EditorContents
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let (draft, live, seed) = editor_paths(parts)?;
+
+        let contents = match fs::read_to_string(&draft) {
+            Err(err) if err.kind() == ErrorKind::NotFound => match fs::read_to_string(&live) {
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(seed_document(seed).to_string()),
+                contents => contents,
+            },
+            contents => contents,
+        };
+
+        contents.map(EditorContents).map_err(|err| {
+            eprintln!("Failed to read {draft}: {err}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to open the page.".to_owned())
+        })
+    }
+}
+
+/// Shows the draft contents of the company's global sections in the editor preview, in place of their SSI includes.
+pub fn render_previews(company: &str, document: &NodeRef) {
+    let elements = match document.select("[block-id='global-section']") {
+        Ok(elements) => elements.collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+
+    for element in elements {
+        let section = element.attributes.borrow().get("section").unwrap_or_default().to_string();
+
+        if !SECTIONS.contains(&section.as_str()) {
+            continue;
+        }
+
+        let draft = section_path("draft", company, &section);
+        let live = section_path("live", company, &section);
+
+        let Ok(contents) = fs::read_to_string(&draft).or_else(|_| fs::read_to_string(&live)) else {
+            continue;
+        };
+
+        let section_document = kuchikiki::parse_html().one(contents);
+        let node = element.as_node();
+
+        for child in node.children() {
+            child.detach();
+        }
+
+        if let Ok(body) = section_document.select_first("body") {
+            for child in body.as_node().children() {
+                node.append(child);
+            }
+        }
+    }
+}
+
+/// Flattens the page tree into `(path, name, depth)` entries, in the order of the tree.
+fn menu_pages(prefix: &str, PageList(page_list): &PageList, depth: usize, pages: &mut Vec<(String, String, usize)>) {
+    for (name, sub_list) in page_list {
+        let path = format!("{prefix}/{name}");
+
+        pages.push((path.clone(), name.to_string(), depth));
+
+        if let Some(sub_list) = sub_list {
+            menu_pages(&path, sub_list, depth + 1, pages);
+        }
+    }
+}
+
+pub async fn menu(
+    Company(company): Company<String>,
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    EditorContents(contents): EditorContents,
+    Path(element_id): Path<String>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, Some(&company), Capability::EditSitePages).await?;
+
+    let html = kuchikiki::parse_html().one(contents);
+    let element = html
+        .select_first(&format!("[element-id='{element_id}']"))
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let navigation = Navigation::parse(&element);
+
+    if !valid_company(&company) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid company.").into());
+    }
+
+    let mut pages = vec![("/".to_string(), "home".to_string(), 0)];
+
+    if fs::exists(format!("pages/live/{company}")).unwrap_or(false) {
+        menu_pages("", &get_pages(&company), 0, &mut pages);
+    }
+
+    let order = pages.iter().map(|(path, _, _)| path.as_str()).collect::<Vec<_>>().join(",");
+
+    Ok(html!(
+        fieldset.menu {
+            legend { "Menu" }
+            input type="hidden" name="order" value=(order);
+            @for (page_path, name, depth) in &pages {
+                @let label = navigation.items.iter().find(|item| &item.path == page_path).map(|item| item.label.clone());
+                label style={"padding-left: "(depth)"rem"} {
+                    (page_path)
+                    input name={"page:"(page_path)} placeholder=(name) value=[label];
+                }
+            }
+        }
+    ))
+}
@@ -0,0 +1,113 @@
+use sqlx::{query, query_as, SqliteConnection};
+use uuid::Uuid;
+
+pub struct ClipboardEntry {
+    pub block_id: String,
+    pub html: String,
+}
+
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    pub block_id: String,
+    pub html: String,
+}
+
+pub async fn set_clipboard(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    block_id: &str,
+    html: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO block_clipboard(user_id, block_id, html) VALUES($1, $2, $3)
+        ON CONFLICT(user_id) DO UPDATE SET block_id=excluded.block_id, html=excluded.html",
+        user_id,
+        block_id,
+        html
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn get_clipboard(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<Option<ClipboardEntry>, sqlx::Error> {
+    query_as!(
+        ClipboardEntry,
+        "SELECT block_id, html FROM block_clipboard WHERE user_id=$1",
+        user_id
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn create_snippet(
+    conn: &mut SqliteConnection,
+    name: &str,
+    company: Option<&str>,
+    block_id: &str,
+    html: &str,
+) -> Result<(), sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+
+    query!(
+        "INSERT INTO block_snippets(id, name, company, block_id, html) VALUES($1, $2, $3, $4, $5)",
+        id,
+        name,
+        company,
+        block_id,
+        html
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn get_snippets(
+    conn: &mut SqliteConnection,
+    company: Option<&str>,
+) -> Result<Vec<Snippet>, sqlx::Error> {
+    query_as!(
+        Snippet,
+        "SELECT id, name, block_id, html FROM block_snippets WHERE company IS NULL OR company=$1 ORDER BY name",
+        company
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Only finds the snippet when it's shared by all companies or belongs to the company, like `get_snippets`.
+pub async fn get_snippet(
+    conn: &mut SqliteConnection,
+    snippet_id: &str,
+    company: Option<&str>,
+) -> Result<Option<Snippet>, sqlx::Error> {
+    query_as!(
+        Snippet,
+        "SELECT id, name, block_id, html FROM block_snippets WHERE id=$1 AND (company IS NULL OR company=$2)",
+        snippet_id,
+        company
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Only deletes the snippet when it belongs to the company, or when both are shared by all companies.
+pub async fn delete_snippet(
+    conn: &mut SqliteConnection,
+    snippet_id: &str,
+    company: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let result = query!("DELETE FROM block_snippets WHERE id=$1 AND company IS $2", snippet_id, company)
+        .execute(conn)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
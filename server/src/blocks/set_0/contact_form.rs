@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::super::properties;
+use super::new_element;
+
+const FIELDS: [(&str, &str, &str); 5] = [
+    ("first_name", "Voornaam", "text"),
+    ("last_name", "Achternaam", "text"),
+    ("email", "E-mail", "email"),
+    ("phone", "Telefoon", "tel"),
+    ("message", "Bericht", "textarea"),
+];
+
+#[derive(Debug)]
+pub struct ContactForm {
+    pub id: String,
+    pub submit_text: String,
+}
+
+impl Block for ContactForm {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        let attributes = element.attributes.borrow();
+
+        let element_id = attributes.get("element-id").unwrap();
+        let submit_text = attributes.get("submit-text").unwrap_or("Verzenden");
+
+        Ok(Box::new(Self {
+            id: element_id.to_string(),
+            submit_text: submit_text.to_string(),
+        }))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self {
+            id,
+            submit_text: "Verzenden".to_string(),
+        })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "form",
+            vec![
+                ("block-id", Some("contact-form".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("submit-text", Some(self.submit_text.clone())),
+                ("class", Some("contact-form".to_string())),
+                ("hx-post", Some("/api/forms".to_string())),
+                ("hx-swap", Some("outerHTML".to_string())),
+            ],
+        );
+
+        for (name, label_text, r#type) in FIELDS {
+            let label = new_element("label", vec![]);
+            label.append(NodeRef::new_text(label_text));
+
+            let input = match r#type {
+                "textarea" => new_element("textarea", vec![("name", Some(name.to_string())), ("required", Some(String::new()))]),
+                _ => new_element(
+                    "input",
+                    vec![
+                        ("name", Some(name.to_string())),
+                        ("type", Some(r#type.to_string())),
+                        ("required", Some(String::new())),
+                    ],
+                ),
+            };
+
+            label.append(input);
+            element.append(label);
+        }
+
+        let submit = new_element(
+            "input",
+            vec![
+                ("type", Some("submit".to_string())),
+                ("value", Some(self.submit_text.clone())),
+            ],
+        );
+
+        element.append(submit);
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                label {
+                    "Tekst verzendknop"
+                    (properties::inner_text("submit_text", &Some(self.submit_text.clone()), false))
+                }
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        self.submit_text = properties
+            .get("submit_text")
+            .filter(|submit_text| !submit_text.is_empty())
+            .map_or("Verzenden", |v| v)
+            .to_string();
+
+        self.render_to_noderef()
+    }
+}
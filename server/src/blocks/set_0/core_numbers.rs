@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::super::properties;
+use super::{new_element, ssi_include};
+
+#[derive(Debug)]
+pub struct CoreNumbers {
+    pub id: String,
+    pub project: Option<String>,
+}
+
+impl CoreNumbers {
+    pub fn parse(element: &NodeDataRef<ElementData>) -> Self {
+        let attributes = element.attributes.borrow();
+
+        Self {
+            id: attributes.get("element-id").unwrap_or_default().to_string(),
+            project: attributes.get("project").map(str::to_string),
+        }
+    }
+}
+
+impl Block for CoreNumbers {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        Ok(Box::new(Self::parse(element)))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self { id, project: None })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "core-numbers",
+            vec![
+                ("block-id", Some("core-numbers".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("project", self.project.clone()),
+            ],
+        );
+
+        if let Some(project) = &self.project {
+            element.append(ssi_include(&format!("/api/projecten/{project}/kerncijfers")));
+        }
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                label {
+                    "Project (slug)"
+                    (properties::inner_text("project", &self.project, false))
+                }
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        self.project = properties.get("project").map(|project| project.trim().to_string()).filter(|project| !project.is_empty());
+
+        self.render_to_noderef()
+    }
+}
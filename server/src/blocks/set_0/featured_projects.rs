@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::super::properties;
+use super::{new_element, ssi_include};
+
+pub const VARIANTS: [&str; 2] = ["carousel", "in-de-kijker"];
+
+#[derive(Debug)]
+pub struct FeaturedProjects {
+    pub id: String,
+    pub variant: String,
+}
+
+impl FeaturedProjects {
+    pub fn parse(element: &NodeDataRef<ElementData>) -> Self {
+        let attributes = element.attributes.borrow();
+
+        Self {
+            id: attributes.get("element-id").unwrap_or_default().to_string(),
+            variant: attributes
+                .get("variant")
+                .filter(|variant| VARIANTS.contains(variant))
+                .unwrap_or(VARIANTS[0])
+                .to_string(),
+        }
+    }
+}
+
+impl Block for FeaturedProjects {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        Ok(Box::new(Self::parse(element)))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self {
+            id,
+            variant: VARIANTS[0].to_string(),
+        })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "featured-projects",
+            vec![
+                ("block-id", Some("featured-projects".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("variant", Some(self.variant.clone())),
+            ],
+        );
+
+        element.append(ssi_include(&format!("/api/projecten/{}", self.variant)));
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                label {
+                    "Weergave"
+                    (properties::select("variant", &Some(self.variant.clone()), &VARIANTS.to_vec()))
+                }
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        self.variant = properties
+            .get("variant")
+            .map(|variant| variant.as_str())
+            .filter(|variant| VARIANTS.contains(variant))
+            .unwrap_or(VARIANTS[0])
+            .to_string();
+
+        self.render_to_noderef()
+    }
+}
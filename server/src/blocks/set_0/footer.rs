@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::new_element;
+
+#[derive(Debug)]
+pub struct Footer {
+    pub id: String,
+}
+
+impl Block for Footer {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        let attributes = element.attributes.borrow();
+
+        let element_id = attributes.get("element-id").unwrap();
+
+        Ok(Box::new(Self {
+            id: element_id.to_string(),
+        }))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self { id })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        new_element(
+            "footer",
+            vec![
+                ("block-id", Some("footer".to_string())),
+                ("element-id", Some(self.id.clone())),
+            ],
+        )
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        _properties: &HashMap<std::string::String, std::string::String>,
+        original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        let generated = self.render_to_noderef();
+
+        for child in original.as_node().children() {
+            generated.append(child);
+        }
+
+        generated
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+use crate::blocks::sections::SECTIONS;
+
+use super::super::properties;
+use super::{new_element, ssi_include};
+
+#[derive(Debug)]
+pub struct GlobalSection {
+    pub id: String,
+    pub section: String,
+}
+
+impl Block for GlobalSection {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        let attributes = element.attributes.borrow();
+
+        let element_id = attributes.get("element-id").unwrap();
+        let section = attributes
+            .get("section")
+            .filter(|section| SECTIONS.contains(section))
+            .ok_or(BlockError::ParseFromTagError("Unknown global section.".to_string()))?;
+
+        Ok(Box::new(Self {
+            id: element_id.to_string(),
+            section: section.to_string(),
+        }))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self {
+            id,
+            section: SECTIONS[0].to_string(),
+        })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "global-section",
+            vec![
+                ("block-id", Some("global-section".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("section", Some(self.section.clone())),
+            ],
+        );
+
+        element.append(ssi_include(&format!("/_sections/{}.html", self.section)));
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                label {
+                    "Sectie"
+                    (properties::select("section", &Some(self.section.clone()), &SECTIONS.to_vec()))
+                }
+                button type="button" hx-on:click={"const url = new URL(location); url.searchParams.set('section', '"(self.section)"'); location = url;"} {
+                    "Bewerk sectie"
+                }
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        self.section = properties
+            .get("section")
+            .map(|section| section.as_str())
+            .filter(|section| SECTIONS.contains(section))
+            .unwrap_or(SECTIONS[0])
+            .to_string();
+
+        self.render_to_noderef()
+    }
+}
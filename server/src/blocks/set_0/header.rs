@@ -1,20 +1,18 @@
-use std::{collections::HashMap, mem};
+use std::collections::HashMap;
 
-use byteorder::{LittleEndian, WriteBytesExt};
-use kuchikiki::{Attribute, Attributes, ElementData, ExpandedName, NodeDataRef, NodeRef};
-use markup5ever::{LocalName, QualName};
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
 use maud::{Markup, html};
-use tl::Node;
 use uuid::Uuid;
 
 use crate::blocks::config::{Block, BlockError};
 
 use super::super::properties;
+use super::new_element;
 
 #[derive(Debug)]
 pub struct Header {
     pub id: String,
-    pub content: Vec<Box<dyn Block>>
+    pub sticky: Option<bool>,
 }
 
 impl Block for Header {
@@ -22,10 +20,6 @@ impl Block for Header {
         &self.id
     }
 
-    fn render(&self) -> maud::Markup {
-        let node = self.render_to_noderef();
-    }
-
     fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
         let attributes = element.attributes.borrow();
 
@@ -33,7 +27,7 @@ impl Block for Header {
 
         Ok(Box::new(Self {
             id: element_id.to_string(),
-            content: element.text_contents(),
+            sticky: Some(attributes.contains("sticky")),
         }))
     }
 
@@ -42,48 +36,45 @@ impl Block for Header {
 
         Box::new(Self {
             id,
-            content: "Lorem Ipsum Dolor".to_string(),
+            sticky: None,
         })
     }
 
-    fn render_to_noderef(&self) -> kuchikiki::NodeRef {
-        let element = NodeRef::new_element(
-            QualName::new(None, ns!(html), LocalName::from("div")),
+    fn render_to_noderef(&self) -> NodeRef {
+        new_element(
+            "header",
             vec![
-                (
-                    ExpandedName::new(ns!(), LocalName::from("block-id")),
-                    Attribute {
-                        prefix: None,
-                        value: "theme-text".to_string(),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("element-id")),
-                    Attribute {
-                        prefix: None,
-                        value: self.id.clone(),
-                    },
-                ),
+                ("block-id", Some("header".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("sticky", self.sticky.filter(|sticky| *sticky).map(|_| String::new())),
             ],
-        );
-
-        element.append(NodeRef::new_text(&self.content));
-
-        element
+        )
     }
 
     fn properties(&self) -> Markup {
         html!(
             form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
                 label {
-                    "Content"
-                    (properties::inner_text("content", &self.content, false))
+                    "Sticky"
+                    (properties::boolean("sticky", &self.sticky))
                 }
             }
         )
     }
-    
-    fn update(&mut self, properties: &HashMap<std::string::String, std::string::String>) {
-        self.content = properties.get("content").unwrap().to_string();
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        self.sticky = properties.get("sticky").map(|v| v == "true");
+
+        let generated = self.render_to_noderef();
+
+        for child in original.as_node().children() {
+            generated.append(child);
+        }
+
+        generated
     }
 }
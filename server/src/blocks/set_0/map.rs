@@ -11,14 +11,17 @@ use uuid::Uuid;
 use crate::blocks::config::{Block, BlockError};
 
 use super::super::properties;
+use super::new_element;
 
 #[derive(Debug)]
 pub struct Map {
     pub id: String,
-    pub pb: String,
+    /// The embed location, `None` to use the default location of the company's theme.
+    pub pb: Option<String>,
 }
 
-const GVR_PB: &str = "!1m18!1m12!1m3!1d2492.816199321357!2d4.746279676946868!3d51.33290042343565!2m3!1f0!2f0!3f0!3m2!1i1024!2i768!4f13.1!3m3!1m2!1s0x47c6a96e717a03c9%3A0x46004d158a49ceb8!2sGroep%20Van%20Roey!5e0!3m2!1snl!2sbe!4v1694701918719!5m2!1snl!2sbe";
+/// Serves the embed of the company's default map location, see `themes` in the `sites` crate.
+const THEME_MAP_SRC: &str = "/api/theme/map";
 
 impl Block for Map {
     fn id(&self) -> &'_ str {
@@ -30,11 +33,15 @@ impl Block for Map {
 
         let element_id = attributes.get("element-id").unwrap();
 
-        let pb = attributes.get("style").and_then(|pb| {
-            let regex = Regex::new(r#"src="https://www.google.com/maps/embed?pb=([^)]+)""#).unwrap();
+        let pb = attributes.get("pb").map(|pb| pb.to_string()).or_else(|| {
+            let regex = Regex::new(r#"^https://www.google.com/maps/embed\?pb=(.+)$"#).unwrap();
 
-            regex.captures(pb).unwrap().get(1).map(|i| i.as_str().to_string())
-        }).unwrap_or(String::from(GVR_PB));
+            attributes
+                .get("src")
+                .and_then(|src| regex.captures(src))
+                .and_then(|captures| captures.get(1))
+                .map(|pb| pb.as_str().to_string())
+        });
 
         Ok(Box::new(Self {
             id: element_id.to_string(),
@@ -47,68 +54,36 @@ impl Block for Map {
 
         Box::new(Self {
             id,
-            pb: GVR_PB.to_string()
+            pb: None
         })
     }
 
     fn render_to_noderef(&self) -> kuchikiki::NodeRef {
-        let element = NodeRef::new_element(
-            QualName::new(None, ns!(html), LocalName::from("iframe")),
+        let src = match &self.pb {
+            Some(pb) => format!("https://www.google.com/maps/embed?pb={pb}"),
+            None => THEME_MAP_SRC.to_string(),
+        };
+
+        new_element(
+            "iframe",
             vec![
-                (
-                    ExpandedName::new(ns!(), LocalName::from("block-id")),
-                    Attribute {
-                        prefix: None,
-                        value: "map".to_string(),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("element-id")),
-                    Attribute {
-                        prefix: None,
-                        value: self.id.clone(),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("src")),
-                    Attribute {
-                        prefix: None,
-                        value: format!("https://www.google.com/maps/embed?pb={}", self.pb),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("frameborder")),
-                    Attribute {
-                        prefix: None,
-                        value: 0.to_string(),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("allowfullscreen")),
-                    Attribute {
-                        prefix: None,
-                        value: "true".to_string(),
-                    },
-                ),
-                (
-                    ExpandedName::new(ns!(), LocalName::from("loading")),
-                    Attribute {
-                        prefix: None,
-                        value: "lazy".to_string(),
-                    },
-                ),
+                ("block-id", Some("map".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("pb", self.pb.clone()),
+                ("src", Some(src)),
+                ("frameborder", Some(0.to_string())),
+                ("allowfullscreen", Some("true".to_string())),
+                ("loading", Some("lazy".to_string())),
             ],
-        );
-
-        element
+        )
     }
 
     fn properties(&self) -> Markup {
         html!(
             form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
                 label {
-                    "PB-waarde"
-                    (properties::inner_text("pb", &Some(self.pb.clone()), false))
+                    "PB-waarde (leeg voor de standaardlocatie van het thema)"
+                    (properties::inner_text("pb", &self.pb, false))
                 }
             }
         )
@@ -119,7 +94,7 @@ impl Block for Map {
         properties: &HashMap<std::string::String, std::string::String>,
         original: &NodeDataRef<ElementData>,
     ) -> NodeRef {
-        self.pb = properties.get("pb").map_or(GVR_PB, |v| v).to_string();
+        self.pb = properties.get("pb").map(|pb| pb.trim().to_string()).filter(|pb| !pb.is_empty());
 
         self.render_to_noderef()
     }
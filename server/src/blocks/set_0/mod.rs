@@ -1,7 +1,7 @@
 pub mod angled_element;
 pub mod default_container;
 pub mod theme_text;
-/* pub mod header; */
+pub mod header;
 pub mod heading_2;
 pub mod paragraph;
 pub mod animated_button_1;
@@ -12,4 +12,39 @@ pub mod a_carousel;
 pub mod a_carousel_entry;
 pub mod tabs;
 pub mod tabs_entry;
-pub mod map;
\ No newline at end of file
+pub mod map;
+pub mod project_grid;
+pub mod featured_projects;
+pub mod core_numbers;
+pub mod contact_form;
+pub mod footer;
+pub mod global_section;
+pub mod navigation;
+
+use kuchikiki::{Attribute, ExpandedName, NodeRef};
+use markup5ever::{LocalName, QualName};
+
+/// Creates an element with the given attributes, skipping the ones without a value.
+pub fn new_element(tag: &str, attributes: Vec<(&str, Option<String>)>) -> NodeRef {
+    NodeRef::new_element(
+        QualName::new(None, ns!(html), LocalName::from(tag)),
+        attributes
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| {
+                    (
+                        ExpandedName::new(ns!(), LocalName::from(name)),
+                        Attribute {
+                            prefix: None,
+                            value,
+                        },
+                    )
+                })
+            }),
+    )
+}
+
+/// Creates the SSI include through which nginx embeds the live data of a data block.
+pub fn ssi_include(path: &str) -> NodeRef {
+    NodeRef::new_comment(format!(r#"#include virtual="{path}" "#))
+}
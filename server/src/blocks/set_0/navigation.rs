@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::new_element;
+
+#[derive(Debug)]
+pub struct MenuItem {
+    pub path: String,
+    pub label: String,
+}
+
+#[derive(Debug)]
+pub struct Navigation {
+    pub id: String,
+    pub items: Vec<MenuItem>,
+}
+
+impl Navigation {
+    pub fn parse(element: &NodeDataRef<ElementData>) -> Self {
+        let attributes = element.attributes.borrow();
+
+        let items = match element.as_node().select("a") {
+            Ok(links) => links
+                .filter_map(|link| {
+                    let path = link.attributes.borrow().get("href")?.to_string();
+
+                    Some(MenuItem {
+                        path,
+                        label: link.text_contents(),
+                    })
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+
+        Self {
+            id: attributes.get("element-id").unwrap_or_default().to_string(),
+            items,
+        }
+    }
+}
+
+impl Block for Navigation {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        Ok(Box::new(Self::parse(element)))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self {
+            id,
+            items: vec![MenuItem {
+                path: "/".to_string(),
+                label: "Home".to_string(),
+            }],
+        })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "nav",
+            vec![
+                ("block-id", Some("navigation".to_string())),
+                ("element-id", Some(self.id.clone())),
+            ],
+        );
+
+        let list = new_element("ul", vec![]);
+
+        for item in &self.items {
+            let li = new_element("li", vec![]);
+            let link = new_element("a", vec![("href", Some(item.path.clone()))]);
+
+            link.append(NodeRef::new_text(&item.label));
+            li.append(link);
+            list.append(li);
+        }
+
+        element.append(list);
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                div hx-get={"/api/blocks/"(self.id)"/menu"} hx-trigger="load" hx-swap="outerHTML" {}
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        let order = properties.get("order").map_or("", |order| order.as_str());
+
+        self.items = order
+            .split(",")
+            .filter_map(|path| {
+                let label = properties.get(&format!("page:{path}"))?.trim();
+
+                (!label.is_empty()).then(|| MenuItem {
+                    path: path.to_string(),
+                    label: label.to_string(),
+                })
+            })
+            .collect();
+
+        self.render_to_noderef()
+    }
+}
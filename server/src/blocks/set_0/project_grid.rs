@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use kuchikiki::{ElementData, NodeDataRef, NodeRef};
+use maud::{Markup, html};
+use url::form_urlencoded;
+use uuid::Uuid;
+
+use crate::blocks::config::{Block, BlockError};
+
+use super::super::properties;
+use super::{new_element, ssi_include};
+
+#[derive(Debug)]
+pub struct ProjectGrid {
+    pub id: String,
+    pub sector: Option<String>,
+    /// Planned, in progress or delivered, as it's called in `PROJECTS.lifecycle`.
+    pub lifecycle: Option<String>,
+    /// The years the projects were delivered in.
+    pub delivered_from: Option<i64>,
+    pub delivered_to: Option<i64>,
+    pub publiek_privaat: Option<String>,
+    pub tags: Vec<String>,
+    pub limit: Option<u8>,
+}
+
+impl ProjectGrid {
+    pub fn parse(element: &NodeDataRef<ElementData>) -> Self {
+        let attributes = element.attributes.borrow();
+
+        Self {
+            id: attributes.get("element-id").unwrap_or_default().to_string(),
+            sector: attributes.get("sector").map(str::to_string),
+            lifecycle: attributes.get("fase").map(str::to_string),
+            delivered_from: attributes.get("opgeleverd-van").and_then(|year| year.parse().ok()),
+            delivered_to: attributes.get("opgeleverd-tot").and_then(|year| year.parse().ok()),
+            publiek_privaat: attributes.get("publiek-privaat").map(str::to_string),
+            tags: attributes
+                .get("tags")
+                .map(|tags| tags.split(",").map(|tag| tag.to_string()).filter(|tag| !tag.is_empty()).collect())
+                .unwrap_or_default(),
+            limit: attributes.get("limit").and_then(|limit| limit.parse().ok()),
+        }
+    }
+
+    /// The query parameters of the public `/projecten` endpoint that match the preset filters.
+    pub fn query_string(&self) -> String {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+        if let Some(sector) = &self.sector {
+            serializer.append_pair("sector", sector);
+        }
+        if let Some(lifecycle) = &self.lifecycle {
+            serializer.append_pair("fase", lifecycle);
+        }
+        if let Some(delivered_from) = &self.delivered_from {
+            serializer.append_pair("opgeleverd-van", &delivered_from.to_string());
+        }
+        if let Some(delivered_to) = &self.delivered_to {
+            serializer.append_pair("opgeleverd-tot", &delivered_to.to_string());
+        }
+        if let Some(publiek_privaat) = &self.publiek_privaat {
+            serializer.append_pair("publiek-privaat", publiek_privaat);
+        }
+        if let Some(limit) = &self.limit {
+            serializer.append_pair("limit", &limit.to_string());
+        }
+        for tag in &self.tags {
+            serializer.append_pair("tags", tag);
+        }
+
+        serializer.finish()
+    }
+}
+
+impl Block for ProjectGrid {
+    fn id(&self) -> &'_ str {
+        &self.id
+    }
+
+    fn from_element_ref(element: &NodeDataRef<ElementData>) -> Result<Box<dyn Block>, BlockError> {
+        Ok(Box::new(Self::parse(element)))
+    }
+
+    fn default() -> Box<dyn Block> {
+        let id = Uuid::new_v4().to_string();
+
+        Box::new(Self {
+            id,
+            sector: None,
+            lifecycle: None,
+            delivered_from: None,
+            delivered_to: None,
+            publiek_privaat: None,
+            tags: vec![],
+            limit: Some(6),
+        })
+    }
+
+    fn render_to_noderef(&self) -> NodeRef {
+        let element = new_element(
+            "project-grid",
+            vec![
+                ("block-id", Some("project-grid".to_string())),
+                ("element-id", Some(self.id.clone())),
+                ("class", Some("projects".to_string())),
+                ("sector", self.sector.clone()),
+                ("fase", self.lifecycle.clone()),
+                ("opgeleverd-van", self.delivered_from.map(|year| year.to_string())),
+                ("opgeleverd-tot", self.delivered_to.map(|year| year.to_string())),
+                ("publiek-privaat", self.publiek_privaat.clone()),
+                ("tags", (!self.tags.is_empty()).then(|| self.tags.join(","))),
+                ("limit", self.limit.map(|limit| limit.to_string())),
+            ],
+        );
+
+        element.append(ssi_include(&format!("/api/projecten?{}", self.query_string())));
+
+        element
+    }
+
+    fn properties(&self) -> Markup {
+        let limit = self.limit.map(|limit| limit as f32);
+        let delivered_from = self.delivered_from.map(|year| year as f32);
+        let delivered_to = self.delivered_to.map(|year| year as f32);
+        let tags = (!self.tags.is_empty()).then(|| self.tags.join(","));
+
+        html!(
+            form hx-put={"/api/blocks/"(self.id)"/properties"} hx-trigger="change" hx-on::after-request="refresh_preview()" hx-swap="none" {
+                label {
+                    "Sector"
+                    (properties::inner_text("sector", &self.sector, false))
+                }
+                label {
+                    "Fase"
+                    select name="lifecycle" {
+                        option value="" { "Alle" }
+                        @for (value, label) in [("planned", "Gepland"), ("in_progress", "In uitvoering"), ("delivered", "Opgeleverd")] {
+                            option value=(value) selected[self.lifecycle.as_deref() == Some(value)] { (label) }
+                        }
+                    }
+                }
+                label {
+                    "Opgeleverd vanaf"
+                    (properties::number("delivered_from", &delivered_from, Some(1800.0), None, Some(1.0)))
+                }
+                label {
+                    "Opgeleverd tot en met"
+                    (properties::number("delivered_to", &delivered_to, Some(1800.0), None, Some(1.0)))
+                }
+                label {
+                    "Publiek/privaat"
+                    (properties::select("publiek_privaat", &self.publiek_privaat, &vec!["publiek", "privaat", "pps"]))
+                }
+                label {
+                    "Tags (komma-gescheiden)"
+                    (properties::inner_text("tags", &tags, false))
+                }
+                label {
+                    "Aantal projecten"
+                    (properties::number("limit", &limit, Some(1.0), Some(48.0), Some(1.0)))
+                }
+            }
+        )
+    }
+
+    fn update(
+        &mut self,
+        properties: &HashMap<std::string::String, std::string::String>,
+        _original: &NodeDataRef<ElementData>,
+    ) -> NodeRef {
+        let value = |name: &str| properties.get(name).filter(|v| !v.is_empty()).cloned();
+
+        self.sector = value("sector");
+        self.lifecycle = value("lifecycle");
+        self.delivered_from = value("delivered_from").and_then(|year| year.parse().ok());
+        self.delivered_to = value("delivered_to").and_then(|year| year.parse().ok());
+        self.publiek_privaat = value("publiek_privaat");
+        self.tags = value("tags")
+            .map(|tags| tags.split(",").map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect())
+            .unwrap_or_default();
+        self.limit = value("limit").and_then(|limit| limit.parse().ok());
+
+        self.render_to_noderef()
+    }
+}
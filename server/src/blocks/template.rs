@@ -14,6 +14,10 @@ pub fn tree_item(element_id: &str, element_name: &str, child_count: usize, swap_
             span hx-get={"/api/blocks/"(element_id)"/properties"} hx-swap="outerHTML" hx-target="#main-content.site-editor > form" { (element_name)"-"(child_count) }
             div.dropzone.down {}
             button.show-children hx-get="/api/blocks/tree" name="parent" value=(element_id) hx-target="closest ul" {}
+            button.duplicate hx-post={"/api/blocks/"(element_id)"/duplicate"} hx-swap="none" hx-on::after-request="refresh_preview()" title="Duplicate" {}
+            button.copy hx-post={"/api/blocks/"(element_id)"/copy"} hx-swap="none" title="Copy" {}
+            button.paste hx-post={"/api/blocks/"(element_id)"/paste"} hx-vals=r#"{"direction": "false"}"# hx-swap="none" hx-on::after-request="refresh_preview()" title="Paste after" {}
+            button.snippet hx-post={"/api/blocks/"(element_id)"/snippets"} hx-prompt="Snippet name" hx-swap="none" title="Save as snippet" {}
             button.delete hx-delete={"/api/blocks/delete-element/"(element_id)} hx-swap="delete" hx-target="closest li"  hx-on::before-cleanup-element="refresh_preview()" {}
             input type="hidden" name="element_id" value=(element_id);
         }
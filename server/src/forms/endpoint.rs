@@ -1,26 +1,33 @@
+use axum::Extension;
 use axum::extract::Path;
 use axum::http::StatusCode;
 use axum::response::Result;
 use maud::Markup;
 
 use crate::admin::DBConnection;
+use crate::auth::service::UserData;
 
 use super::service;
 use super::template;
 
 pub async fn get_submissions(
-    DBConnection(mut connection): DBConnection
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>
 ) -> Result<Markup> {
-    let submissions = service::get_submissions(&mut *connection).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let submissions = service::get_submissions(&mut *connection, &user.id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(template::submissions_overview(&submissions))
 }
 
 pub async fn get_message(
     DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
     Path(message_id): Path<String>
 ) -> Result<String> {
-    let message = service::get_message(&mut *connection, &message_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let message = service::get_message(&mut *connection, &message_id, &user.id).await.map_err(|err| match err {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
 
     Ok(message)
 }
\ No newline at end of file
@@ -15,14 +15,22 @@ pub struct FormSubmission {
     pub datetime: NaiveDateTime
 }
 
-pub async fn get_submissions(conn: &mut SqliteConnection) -> Result<Vec<FormSubmission>, sqlx::Error> {
-    query_as(r#"SELECT * from form_submissions"#)
+/// The submissions of the companies the user may see the form submissions of.
+pub async fn get_submissions(conn: &mut SqliteConnection, user_id: &str) -> Result<Vec<FormSubmission>, sqlx::Error> {
+    query_as(r#"SELECT fs.* from form_submissions fs WHERE EXISTS(
+        SELECT 1 FROM user_capabilities uc
+        WHERE uc.user=$1 AND uc.capability='view_form_submissions' AND (uc.everywhere=1 OR uc.company=fs.company)
+    )"#)
+    .bind(user_id)
     .fetch_all(&mut *conn)
     .await
 }
 
-pub async fn get_message(conn: &mut SqliteConnection, id: &String) -> Result<String, sqlx::Error> {
-    query_scalar!("SELECT message from form_submissions WHERE id=$1", id)
+pub async fn get_message(conn: &mut SqliteConnection, id: &String, user_id: &str) -> Result<String, sqlx::Error> {
+    query_scalar!("SELECT fs.message from form_submissions fs WHERE fs.id=$1 AND EXISTS(
+        SELECT 1 FROM user_capabilities uc
+        WHERE uc.user=$2 AND uc.capability='view_form_submissions' AND (uc.everywhere=1 OR uc.company=fs.company)
+    )", id, user_id)
     .fetch_one(&mut *conn)
     .await
 }
\ No newline at end of file
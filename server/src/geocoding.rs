@@ -0,0 +1,178 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+/// A point on the map, in degrees.
+#[derive(Clone, Copy)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+            .then_some(Coordinates { latitude, longitude })
+    }
+}
+
+enum Provider {
+    /// OpenStreetMap's Nominatim, or a server running it.
+    Nominatim { url: String },
+    Google { api_key: String },
+}
+
+#[derive(Debug)]
+pub enum GeocodingError {
+    Request(reqwest::Error),
+    /// The provider answered with something other than results, like an invalid API key.
+    Provider(String),
+}
+
+impl From<reqwest::Error> for GeocodingError {
+    fn from(err: reqwest::Error) -> Self {
+        GeocodingError::Request(err)
+    }
+}
+
+impl std::fmt::Display for GeocodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeocodingError::Request(err) => write!(f, "{err}"),
+            GeocodingError::Provider(status) => write!(f, "The geocoding provider answered {status}."),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleResponse {
+    status: String,
+    results: Vec<GoogleResult>,
+}
+
+#[derive(Deserialize)]
+struct GoogleResult {
+    geometry: GoogleGeometry,
+}
+
+#[derive(Deserialize)]
+struct GoogleGeometry {
+    location: GoogleLocation,
+}
+
+#[derive(Deserialize)]
+struct GoogleLocation {
+    lat: f64,
+    lng: f64,
+}
+
+/// Turns the location of a project into coordinates.
+pub struct Geocoder {
+    provider: Provider,
+    http_client: Client,
+    /// ISO 3166-1 codes of the countries the results are limited to, e.g. "be,nl".
+    countries: Option<String>,
+}
+
+impl Geocoder {
+    /// Reads the `GEOCODING_*` environment variables. `GEOCODING_PROVIDER` is `nominatim` or `google`, without it or
+    /// with another provider the coordinates can only be set by placing the pin.
+    pub fn from_env() -> Option<Self> {
+        let provider = match std::env::var("GEOCODING_PROVIDER").ok()?.as_str() {
+            "" => return None,
+            "nominatim" => Provider::Nominatim {
+                url: std::env::var("GEOCODING_URL")
+                    .unwrap_or("https://nominatim.openstreetmap.org".to_string())
+                    .trim_end_matches('/')
+                    .to_string(),
+            },
+            "google" => Provider::Google {
+                api_key: std::env::var("GEOCODING_API_KEY")
+                    .expect("Failed to find the GEOCODING_API_KEY environment variable."),
+            },
+            provider => {
+                eprintln!("Unknown GEOCODING_PROVIDER {provider}, expected nominatim or google. Geocoding is disabled.");
+                return None;
+            }
+        };
+
+        let countries = std::env::var("GEOCODING_COUNTRIES").ok().filter(|countries| !countries.is_empty());
+
+        // Nominatim's usage policy asks for an identifying user agent.
+        let http_client = Client::builder()
+            .user_agent(concat!("project-admin/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .expect("Failed to create the geocoding HTTP client.");
+
+        Some(Geocoder {
+            provider,
+            http_client,
+            countries,
+        })
+    }
+
+    /// The best match for the address, `None` when the provider doesn't know it.
+    pub async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeocodingError> {
+        match &self.provider {
+            Provider::Nominatim { url } => {
+                let mut query = vec![("q", address), ("format", "jsonv2"), ("limit", "1")];
+
+                if let Some(countries) = &self.countries {
+                    query.push(("countrycodes", countries.as_str()));
+                }
+
+                let places: Vec<NominatimPlace> = self
+                    .http_client
+                    .get(format!("{url}/search"))
+                    .query(&query)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+
+                Ok(places.first().and_then(|place| {
+                    Coordinates::new(place.lat.parse().ok()?, place.lon.parse().ok()?)
+                }))
+            }
+            Provider::Google { api_key } => {
+                let components = self.countries.as_ref().map(|countries| {
+                    countries
+                        .split(',')
+                        .map(|country| format!("country:{}", country.trim()))
+                        .collect::<Vec<String>>()
+                        .join("|")
+                });
+
+                let mut query = vec![("address", address), ("key", api_key.as_str())];
+
+                if let Some(components) = &components {
+                    query.push(("components", components.as_str()));
+                }
+
+                let response: GoogleResponse = self
+                    .http_client
+                    .get("https://maps.googleapis.com/maps/api/geocode/json")
+                    .query(&query)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+
+                match response.status.as_str() {
+                    "OK" | "ZERO_RESULTS" => Ok(response.results.first().and_then(|result| {
+                        Coordinates::new(result.geometry.location.lat, result.geometry.location.lng)
+                    })),
+                    status => Err(GeocodingError::Provider(status.to_string())),
+                }
+            }
+        }
+    }
+}
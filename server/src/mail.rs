@@ -0,0 +1,53 @@
+use lettre::{
+    Message, SmtpTransport, Transport,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::{Credentials, Mechanism},
+};
+use maud::Markup;
+
+const SENDER: &str = "info@tophat.be";
+
+#[derive(Debug)]
+pub enum MailInitError {
+    UsernameNotFound,
+    PasswordNotFound,
+}
+
+pub fn init() -> Result<SmtpTransport, MailInitError> {
+    let username = std::env::var("SMTP_USERNAME").map_err(|_| MailInitError::UsernameNotFound)?;
+    let password = std::env::var("SMTP_PASSWORD").map_err(|_| MailInitError::PasswordNotFound)?;
+
+    let creds = Credentials::new(username, password);
+
+    let mailer = SmtpTransport::builder_dangerous("smtp-auth.mailprotect.be")
+        .credentials(creds)
+        .authentication(vec![Mechanism::Login])
+        .build();
+
+    Ok(mailer)
+}
+
+#[derive(Debug)]
+pub enum MailError {
+    Address(lettre::address::AddressError),
+    Message(lettre::error::Error),
+    Transport(lettre::transport::smtp::Error),
+}
+
+pub fn send(mailer: &SmtpTransport, to: &str, subject: &str, body: Markup) -> Result<(), MailError> {
+    let sender: Mailbox = SENDER.parse().map_err(MailError::Address)?;
+    let recipient: Mailbox = to.parse().map_err(MailError::Address)?;
+
+    let email = Message::builder()
+        .from(sender)
+        .to(recipient)
+        .subject(subject)
+        .header(ContentType::TEXT_HTML)
+        .body(body.into_string())
+        .map_err(MailError::Message)?;
+
+    mailer
+        .send(&email)
+        .map(|_| ())
+        .map_err(MailError::Transport)
+}
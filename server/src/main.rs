@@ -1,27 +1,25 @@
 #[macro_use] extern crate markup5ever;
 
 mod admin;
+mod api;
 mod auth;
 mod blocks;
 mod forms;
+mod geocoding;
+mod mail;
+mod themes;
 
-use std::{
-    fs::{self, File},
-    io::{Sink, Write},
-};
+use std::sync::Arc;
 
 use axum::{Router, http::StatusCode, response::IntoResponse};
 
-use core::convert::TryFrom;
 use maud::{Markup, html};
-use pasetors::paserk::FormatAsPaserk;
-use pasetors::{
-    keys::{AsymmetricKeyPair, AsymmetricPublicKey, AsymmetricSecretKey, Generate},
-    version4::V4,
-};
+use lettre::SmtpTransport;
 use sqlx::SqlitePool;
 
+use crate::auth::{keys::{self, Paseto}, oidc::Oidc, password::PasswordPolicy};
 use crate::blocks::config::Block;
+use crate::geocoding::Geocoder;
 
 fn static_stylesheet(file: &str) -> Markup {
     html!(
@@ -43,16 +41,51 @@ fn self_replacing_svg(file: &str) -> Markup {
     )
 }
 
+/// How long a session stays usable after the user's last action, in seconds.
 #[derive(Clone)]
-struct Paseto {
-    private_key: AsymmetricSecretKey<V4>,
-    public_key: AsymmetricPublicKey<V4>,
+struct SessionConfig {
+    /// After this, the user has to enter a TOTP code again.
+    twofa_window: u64,
+    /// After this, the user has to log in again.
+    reauth_window: u64,
+    /// The token is re-issued with a fresh `last-action` once it is older than this.
+    refresh_after: u64,
+    /// How long a device the user chose to trust can skip the TOTP step.
+    trusted_device_window: u64,
+}
+
+impl SessionConfig {
+    fn from_env() -> Self {
+        let hours = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .map(|value| value.parse::<u64>().expect(&format!("{key} must be a number of hours.")))
+                .unwrap_or(default)
+                * 60
+                * 60
+        };
+
+        SessionConfig {
+            twofa_window: hours("SESSION_2FA_WINDOW_HOURS", 3 * 24),
+            reauth_window: hours("SESSION_REAUTH_WINDOW_HOURS", 7 * 24),
+            refresh_after: hours("SESSION_REFRESH_AFTER_HOURS", 1),
+            trusted_device_window: hours("SESSION_TRUSTED_DEVICE_HOURS", 30 * 24),
+        }
+    }
 }
 
 #[derive(Clone)]
 struct AppState {
     pool: SqlitePool,
-    paseto: Paseto,
+    paseto: Arc<Paseto>,
+    sessions: SessionConfig,
+    mailer: SmtpTransport,
+    password_policy: Arc<PasswordPolicy>,
+    oidc: Option<Arc<Oidc>>,
+    geocoder: Option<Arc<Geocoder>>,
+    /// Where the admin is hosted, for the links in emails. Never taken from the request, its `Host` header is up to
+    /// the client.
+    admin_base_url: Arc<str>,
 }
 
 enum TransactionError {
@@ -79,64 +112,53 @@ impl IntoResponse for TransactionError {
 async fn main() {
     dotenv::dotenv().ok();
 
-    let key_path = "./keys/paseto";
-
-    let private_key_file = fs::read_to_string(key_path);
-    let public_key_file = fs::read_to_string(&format!("{key_path}.pub"));
-
-    let (private_key_bytes, public_key_bytes) = match (private_key_file, public_key_file) {
-        (Ok(private_key_bytes), Ok(public_key_bytes)) => (private_key_bytes, public_key_bytes),
-        _ => {
-            let kp =
-                AsymmetricKeyPair::<V4>::generate().expect("Failed to generate a PASETO keypair.");
-            let mut private_key_string = String::new();
-            let mut public_key_string = String::new();
-            kp.secret
-                .fmt(&mut private_key_string)
-                .expect("Failed to format the private key to a string.");
-            kp.public
-                .fmt(&mut public_key_string)
-                .expect("Failed to format the public key to a string.");
-
-            let mut private_key_file =
-                File::create(key_path).expect("Failed to format the create the private key file.");
-            private_key_file
-                .write(private_key_string.as_bytes())
-                .expect("Failed to format the write to the private key file.");
-            let mut public_key_file = File::create(&format!("{key_path}.pub"))
-                .expect("Failed to format the create the public key file.");
-            public_key_file
-                .write(public_key_string.as_bytes())
-                .expect("Failed to format the write to the public key file.");
-
-            (private_key_string, public_key_string)
-        }
-    };
+    if std::env::args().nth(1).as_deref() == Some("rotate-keys") {
+        let kid = Paseto::rotate(keys::grace_from_env()).expect("Failed to rotate the PASETO signing key.");
+        println!("The new signing key is {kid}. Restart the server to start signing with it.");
+        return;
+    }
 
-    let private_key = AsymmetricSecretKey::<V4>::try_from(private_key_bytes.as_str())
-        .expect("Failed to parse the PASETO private key.");
-    let public_key = AsymmetricPublicKey::<V4>::try_from(public_key_bytes.as_str())
-        .expect("Failed to parse the PASETO public key.");
     let database_url = std::env::var("DATABASE_URL")
         .expect("Failed to find the DATABASE_URL environment variable.");
     let port =
         std::env::var("ADMIN_PORT").expect("Failed to find the ADMIN_PORT environment variable.");
+    let admin_base_url: Arc<str> = std::env::var("ADMIN_BASE_URL")
+        .expect("Failed to find the ADMIN_BASE_URL environment variable.")
+        .trim_end_matches('/')
+        .into();
 
     let pool = SqlitePool::connect(&database_url)
         .await
         .expect("Failed to start a database pool.");
-    let paseto = Paseto {
-        private_key,
-        public_key,
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .expect("Failed to run the database migrations.");
+    let sessions = SessionConfig::from_env();
+    let paseto = Arc::new(Paseto::load(keys::grace_from_env()).expect("Failed to load the PASETO keys."));
+    let mailer = mail::init().expect("Failed to create the SMTP client.");
+    let password_policy = Arc::new(PasswordPolicy::from_env());
+    let oidc = Oidc::from_env().await.map(Arc::new);
+    let geocoder = Geocoder::from_env().map(Arc::new);
+
+    let state = AppState {
+        pool,
+        paseto,
+        sessions,
+        mailer,
+        password_policy,
+        oidc,
+        geocoder,
+        admin_base_url,
     };
 
-    let state = AppState { pool, paseto };
-
     let app = Router::new()
         .merge(admin::router(state.clone()))
         .nest("/auth", auth::router(state.clone()))
+        .nest("/v1", api::router(state.clone()))
         .nest("/forms", forms::router(state.clone()))
         .nest("/blocks", blocks::router(state.clone()))
+        .nest("/themes", themes::router(state.clone()))
         .with_state(state);
     let listener = tokio::net::TcpListener::bind(format!("localhost:{port}"))
         .await
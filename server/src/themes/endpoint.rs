@@ -0,0 +1,189 @@
+use axum::{
+    Extension, Form,
+    extract::Path,
+    http::{StatusCode, header},
+    response::{IntoResponse, Result},
+};
+use kuchikiki::{NodeRef, traits::TendrilSink};
+use maud::Markup;
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use url::Url;
+
+use crate::admin::DBConnection;
+use crate::auth::capability::{self, Capability};
+use crate::auth::service::UserData;
+
+use super::service::{self, Theme};
+use super::template::{self, BUTTON_STYLES, FONTS};
+
+fn is_colour(value: &str) -> bool {
+    value.starts_with('#')
+        && matches!(value.len(), 4 | 7 | 9)
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_font(value: &str) -> bool {
+    FONTS.iter().any(|(name, _)| *name == value)
+}
+
+/// Keeps values from breaking out of the generated CSS declaration.
+fn is_css_safe(value: &str) -> bool {
+    !value.contains([';', '{', '}', '<', '>', '"', '\\'])
+}
+
+pub async fn get_companies(DBConnection(mut connection): DBConnection) -> Result<Markup> {
+    let companies = service::get_company_logos(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::company_list(&companies))
+}
+
+pub async fn get_site_editor_companies(DBConnection(mut connection): DBConnection) -> Result<Markup> {
+    let companies = service::get_company_logos(&mut connection)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::site_editor_company_list(&companies))
+}
+
+pub async fn get_theme(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Path(company): Path<String>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, Some(&company), Capability::EditSitePages).await?;
+
+    let theme = service::get_theme(&mut connection, &company)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::theme_form(&company, &theme))
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct UpdateThemeBody {
+    primary_colour: String,
+    secondary_colour: String,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    accent_colour: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    heading_font: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    body_font: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    logo: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    emblem: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    map_pb: Option<String>,
+    #[serde(default)]
+    #[serde_as(as = "NoneAsEmptyString")]
+    button_radius: Option<f64>,
+    button_style: String,
+}
+
+pub async fn update_theme(
+    DBConnection(mut connection): DBConnection,
+    Extension(user): Extension<UserData>,
+    Path(company): Path<String>,
+    Form(body): Form<UpdateThemeBody>,
+) -> Result<Markup> {
+    capability::require_for_company(&mut connection, &user, Some(&company), Capability::EditSitePages).await?;
+
+    let colours_valid = [Some(&body.primary_colour), Some(&body.secondary_colour), body.accent_colour.as_ref()]
+        .into_iter()
+        .flatten()
+        .all(|colour| is_colour(colour));
+
+    if !colours_valid {
+        return Err((StatusCode::BAD_REQUEST, "Colours must be hexadecimal, e.g. #1d1d1b.").into());
+    }
+
+    let fonts_valid = [&body.heading_font, &body.body_font]
+        .into_iter()
+        .flatten()
+        .all(|font| is_font(font));
+
+    let values_safe = [&body.logo, &body.emblem, &body.map_pb]
+        .into_iter()
+        .flatten()
+        .all(|value| is_css_safe(value));
+
+    if !fonts_valid || !values_safe || !BUTTON_STYLES.contains(&body.button_style.as_str()) {
+        return Err((StatusCode::BAD_REQUEST, "The theme contains invalid values.").into());
+    }
+
+    let theme = Theme {
+        company: company.clone(),
+        primary_colour: body.primary_colour,
+        secondary_colour: body.secondary_colour,
+        accent_colour: body.accent_colour,
+        heading_font: body.heading_font,
+        body_font: body.body_font,
+        logo: body.logo,
+        emblem: body.emblem,
+        map_pb: body.map_pb,
+        button_radius: body.button_radius.filter(|radius| *radius >= 0.0),
+        button_style: body.button_style,
+    };
+
+    service::save_theme(&mut connection, &theme).await.map_err(|err| {
+        eprintln!("Failed to save the theme of {company}: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(template::theme_form(&company, &Some(theme)))
+}
+
+pub async fn get_theme_css(
+    DBConnection(mut connection): DBConnection,
+    Path(company): Path<String>,
+) -> Result<impl IntoResponse> {
+    let theme = service::get_theme(&mut connection, &company)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(([(header::CONTENT_TYPE, "text/css")], template::theme_css(&theme)))
+}
+
+/// The path of the company's stylesheet, the company is percent-encoded as a path segment.
+fn theme_css_path(company: &str) -> String {
+    let mut url = Url::parse("http://localhost/api/themes").expect("Failed to parse the theme base URL.");
+
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.push(company).push("theme.css");
+    }
+
+    url.path().to_string()
+}
+
+/// Links the company's theme in the site-editor preview, and points maps without a location of their own to the
+/// company's default location.
+pub fn apply_to_preview(theme: &service::Theme, document: &NodeRef) {
+    if let Ok(head) = document.select_first("head") {
+        let link = kuchikiki::parse_html().one(r#"<link rel="stylesheet">"#);
+
+        if let Ok(link) = link.select_first("link") {
+            link.attributes.borrow_mut().insert("href", theme_css_path(&theme.company));
+            head.as_node().append(link.as_node().clone());
+        }
+    }
+
+    if let (Some(map_pb), Ok(maps)) = (&theme.map_pb, document.select("[block-id='map']:not([pb])")) {
+        for map in maps {
+            map.attributes
+                .borrow_mut()
+                .insert("src", format!("https://www.google.com/maps/embed?pb={map_pb}"));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use axum::{middleware::from_fn_with_state, routing::{get, put}, Router};
+
+use crate::{auth::service::auth_middleware, AppState};
+
+pub mod endpoint;
+pub mod service;
+mod template;
+
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(endpoint::get_companies).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/site-editor", get(endpoint::get_site_editor_companies).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{company}", get(endpoint::get_theme).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{company}", put(endpoint::update_theme).layer(from_fn_with_state(state.clone(), auth_middleware)))
+        .route("/{company}/theme.css", get(endpoint::get_theme_css))
+}
@@ -0,0 +1,78 @@
+use sqlx::{query, query_as, SqliteConnection};
+
+pub struct Theme {
+    pub company: String,
+    pub primary_colour: String,
+    pub secondary_colour: String,
+    pub accent_colour: Option<String>,
+    pub heading_font: Option<String>,
+    pub body_font: Option<String>,
+    pub logo: Option<String>,
+    pub emblem: Option<String>,
+    pub map_pb: Option<String>,
+    pub button_radius: Option<f64>,
+    pub button_style: String,
+}
+
+pub struct CompanyLogo {
+    pub name: String,
+    pub logo: Option<String>,
+}
+
+pub async fn get_theme(
+    conn: &mut SqliteConnection,
+    company: &str,
+) -> Result<Option<Theme>, sqlx::Error> {
+    query_as!(
+        Theme,
+        "SELECT company, primary_colour, secondary_colour, accent_colour, heading_font, body_font, logo, emblem, map_pb, button_radius, button_style
+        FROM company_themes WHERE company=$1",
+        company
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn save_theme(conn: &mut SqliteConnection, theme: &Theme) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO company_themes(company, primary_colour, secondary_colour, accent_colour, heading_font, body_font, logo, emblem, map_pb, button_radius, button_style)
+        VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT(company) DO UPDATE SET
+            primary_colour=excluded.primary_colour,
+            secondary_colour=excluded.secondary_colour,
+            accent_colour=excluded.accent_colour,
+            heading_font=excluded.heading_font,
+            body_font=excluded.body_font,
+            logo=excluded.logo,
+            emblem=excluded.emblem,
+            map_pb=excluded.map_pb,
+            button_radius=excluded.button_radius,
+            button_style=excluded.button_style",
+        theme.company,
+        theme.primary_colour,
+        theme.secondary_colour,
+        theme.accent_colour,
+        theme.heading_font,
+        theme.body_font,
+        theme.logo,
+        theme.emblem,
+        theme.map_pb,
+        theme.button_radius,
+        theme.button_style
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+}
+
+pub async fn get_company_logos(conn: &mut SqliteConnection) -> Result<Vec<CompanyLogo>, sqlx::Error> {
+    query_as!(
+        CompanyLogo,
+        r#"SELECT c.name as "name!", COALESCE(t.emblem, t.logo) as logo
+        FROM companies c
+        LEFT JOIN company_themes t ON t.company=c.name
+        ORDER BY c.name"#
+    )
+    .fetch_all(conn)
+    .await
+}
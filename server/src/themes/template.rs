@@ -0,0 +1,147 @@
+use maud::{Markup, html};
+
+use super::service::{CompanyLogo, Theme};
+
+pub const BUTTON_STYLES: [&str; 3] = ["animated", "filled", "outline"];
+
+/// The fonts a theme can use, by the name that's stored, with the font stack the CSS gets.
+pub const FONTS: [(&str, &str); 7] = [
+    ("Be Vietnam Pro", "'BeVietnamPro', sans-serif"),
+    ("Titillium Web", "TitilliumWeb, sans-serif"),
+    ("Arial", "Arial, Helvetica, sans-serif"),
+    ("Helvetica", "Helvetica, Arial, sans-serif"),
+    ("Verdana", "Verdana, Geneva, sans-serif"),
+    ("Georgia", "Georgia, serif"),
+    ("Times New Roman", "'Times New Roman', Times, serif"),
+];
+
+fn font_stack(name: &str) -> Option<&'static str> {
+    FONTS.iter().find(|(font, _)| *font == name).map(|(_, stack)| *stack)
+}
+
+/// The theme as CSS custom properties, for the site-editor preview and the public websites.
+pub fn theme_css(theme: &Theme) -> String {
+    let mut css = String::from(":root {\n");
+
+    css.push_str(&format!("    --primary-colour: {};\n", theme.primary_colour));
+    css.push_str(&format!("    --secondary-colour: {};\n", theme.secondary_colour));
+
+    if let Some(accent_colour) = &theme.accent_colour {
+        css.push_str(&format!("    --accent-colour: {accent_colour};\n"));
+    }
+    if let Some(heading_font) = theme.heading_font.as_deref().and_then(font_stack) {
+        css.push_str(&format!("    --heading-font: {heading_font};\n"));
+    }
+    if let Some(body_font) = theme.body_font.as_deref().and_then(font_stack) {
+        css.push_str(&format!("    --body-font: {body_font};\n"));
+    }
+    if let Some(logo) = &theme.logo {
+        css.push_str(&format!("    --logo: url(\"{logo}\");\n"));
+    }
+    if let Some(emblem) = &theme.emblem {
+        css.push_str(&format!("    --emblem: url(\"{emblem}\");\n"));
+    }
+    if let Some(button_radius) = &theme.button_radius {
+        css.push_str(&format!("    --button-radius: {button_radius}rem;\n"));
+    }
+
+    css.push_str(&format!("    --button-style: {};\n", theme.button_style));
+    css.push_str("}\n");
+
+    css
+}
+
+pub fn company_list(companies: &Vec<CompanyLogo>) -> Markup {
+    html!(
+        @for company in companies {
+            button hx-get={"/api/themes/"(company.name)} {
+                @if let Some(logo) = &company.logo {
+                    img src=(logo);
+                }
+                (company.name)
+            }
+        }
+    )
+}
+
+pub fn site_editor_company_list(companies: &Vec<CompanyLogo>) -> Markup {
+    html!(
+        @for company in companies {
+            button hx-get="/site-editor/pages" name="company" value=(company.name) {
+                @if let Some(logo) = &company.logo {
+                    img src=(logo);
+                }
+                (company.name)
+            }
+        }
+    )
+}
+
+fn font_select(name: &str, selected: &Option<String>) -> Markup {
+    html!(
+        select name=(name) {
+            option value="" { "Standaard" }
+            @for (font, _) in FONTS {
+                option value=(font) selected[selected.as_deref() == Some(font)] { (font) }
+            }
+        }
+    )
+}
+
+pub fn theme_form(company: &str, theme: &Option<Theme>) -> Markup {
+    let value = |f: fn(&Theme) -> Option<String>| theme.as_ref().and_then(f);
+
+    let primary_colour = value(|t| Some(t.primary_colour.clone())).unwrap_or("#1d1d1b".to_string());
+    let secondary_colour = value(|t| Some(t.secondary_colour.clone())).unwrap_or("#ffffff".to_string());
+    let button_style = value(|t| Some(t.button_style.clone())).unwrap_or(BUTTON_STYLES[0].to_string());
+
+    html!(
+        form.theme hx-put={"/api/themes/"(company)} hx-trigger="change" hx-swap="outerHTML" {
+            h2 { (company) }
+            label {
+                "Primaire kleur"
+                input type="color" name="primary_colour" value=(primary_colour);
+            }
+            label {
+                "Secundaire kleur"
+                input type="color" name="secondary_colour" value=(secondary_colour);
+            }
+            label {
+                "Accentkleur"
+                input name="accent_colour" placeholder="#000000" value=[value(|t| t.accent_colour.clone())];
+            }
+            label {
+                "Lettertype titels"
+                (font_select("heading_font", &value(|t| t.heading_font.clone())))
+            }
+            label {
+                "Lettertype tekst"
+                (font_select("body_font", &value(|t| t.body_font.clone())))
+            }
+            label {
+                "Logo"
+                input type="url" name="logo" value=[value(|t| t.logo.clone())];
+            }
+            label {
+                "Embleem"
+                input type="url" name="emblem" value=[value(|t| t.emblem.clone())];
+            }
+            label {
+                "Standaardlocatie kaart (PB-waarde)"
+                input name="map_pb" value=[value(|t| t.map_pb.clone())];
+            }
+            label {
+                "Afronding knoppen (rem)"
+                input type="number" name="button_radius" min="0" step="0.05" value=[value(|t| t.button_radius.map(|r| r.to_string()))];
+            }
+            label {
+                "Stijl knoppen"
+                select name="button_style" {
+                    @for style in BUTTON_STYLES {
+                        option value=(style) selected[style == button_style] { (style) }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,56 @@
+// Shows the pin of a project on a `.location-map`. Clicking the map or dropping the pin sends the new coordinates to
+// the URL in its `data-coordinates` attribute, the response replaces the whole location.
+if (!window.location_map_initialised) {
+    window.location_map_initialised = true;
+
+    // Without a pin the map shows Belgium.
+    const default_view = [[50.85, 4.35], 7];
+
+    const init_location_map = element => {
+        if (element.dataset.initialised || !window.L) return;
+        element.dataset.initialised = true;
+
+        const latitude = parseFloat(element.dataset.latitude);
+        const longitude = parseFloat(element.dataset.longitude);
+        const pin = isNaN(latitude) || isNaN(longitude) ? null : [latitude, longitude];
+
+        const map = L.map(element).setView(pin ?? default_view[0], pin ? 14 : default_view[1]);
+
+        L.tileLayer("https://tile.openstreetmap.org/{z}/{x}/{y}.png", {
+            maxZoom: 19,
+            attribution: '&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>',
+        }).addTo(map);
+
+        const place_pin = ({ lat, lng }) => {
+            htmx.ajax("PATCH", element.dataset.coordinates, {
+                source: element,
+                target: element.closest(".project-location"),
+                select: ".project-location",
+                swap: "outerHTML",
+                values: { latitude: lat, longitude: lng },
+            });
+        };
+
+        if (pin) {
+            const marker = L.marker(pin, { draggable: true }).addTo(map);
+            marker.on("dragend", () => place_pin(marker.getLatLng()));
+        }
+
+        map.on("click", e => place_pin(e.latlng));
+
+        // The map is drawn before the dialog it's in gets its size.
+        setTimeout(() => map.invalidateSize(), 0);
+    };
+
+    document.addEventListener("htmx:load", e => {
+        e.detail.elt.querySelectorAll?.(".location-map").forEach(init_location_map);
+
+        if (e.detail.elt.matches?.(".location-map")) {
+            init_location_map(e.detail.elt);
+        }
+    });
+
+    document.addEventListener("DOMContentLoaded", () => {
+        document.querySelectorAll(".location-map").forEach(init_location_map);
+    });
+}
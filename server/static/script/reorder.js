@@ -0,0 +1,59 @@
+// Lets the `[data-move]` items of a `[data-reorder]` list be dragged by their `.move` handle. When an item is dropped,
+// the new order is sent to the URL in its `data-move` attribute, with the `data-name` of the item it now comes before
+// as `next`. Items in a `data-reorder="horizontal"` list are laid out next to each other.
+if (!window.reorder_initialised) {
+    window.reorder_initialised = true;
+
+    const item_move_handler = (e, list, item) => {
+        const closest_item = e.target.closest("[data-move]");
+        if (!closest_item || closest_item === item || closest_item.parentElement !== list) return;
+        const rect = closest_item.getBoundingClientRect();
+        const after = list.dataset.reorder === "horizontal"
+            ? e.clientX > rect.left + rect.width / 2
+            : e.clientY > rect.top + rect.height / 2;
+
+        closest_item.insertAdjacentElement(after ? "afterend" : "beforebegin", item);
+    }
+
+    // The handle sits inside items that act on a click, like the parties of a project that are removed by clicking
+    // them.
+    document.addEventListener("click", e => {
+        if (e.target.closest("[data-reorder] .move")) {
+            e.stopPropagation();
+        }
+    }, true);
+
+    document.addEventListener("mousedown", e => {
+        const move = e.target.closest("[data-reorder] [data-move] .move");
+        if (!move) return;
+
+        e.preventDefault();
+
+        const item = move.closest("[data-move]");
+        const list = item.parentElement;
+
+        const local_item_move_handler = e => item_move_handler(e, list, item);
+        const document_mouseup_handler = () => {
+            item.classList.remove("moving");
+            list.removeEventListener("mousemove", local_item_move_handler);
+            document.removeEventListener("mouseup", document_mouseup_handler);
+            const next = item.nextElementSibling;
+            const values = {};
+
+            if (next?.dataset.move) {
+                values.next = next.dataset.name;
+            }
+
+            // Responses can update other parts of the page out of band, like the buttons to save the draft.
+            htmx.ajax("PUT", item.dataset.move, {
+                source: item,
+                swap: "none",
+                values: values
+            });
+        }
+
+        item.classList.add("moving");
+        list.addEventListener("mousemove", local_item_move_handler);
+        document.addEventListener("mouseup", document_mouseup_handler);
+    });
+}
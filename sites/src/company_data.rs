@@ -6,12 +6,16 @@ use serde::Deserialize;
 struct Entry {
     form_address: String,
     domains: Vec<String>,
+    /// The language of the domains that aren't Dutch, e.g. `{ "vanroey.fr": "fr" }`.
+    #[serde(default)]
+    languages: HashMap<String, String>,
 }
 
 #[derive(Clone)]
 pub struct CompanyData {
     domain_to_form_address: HashMap<String, String>,
     domain_to_company: HashMap<String, String>,
+    domain_to_language: HashMap<String, String>,
 }
 
 #[derive(Debug)]
@@ -20,6 +24,7 @@ pub enum CompanyDataError {
     ConfigPathReadFailure,
     ConfigFileDeserialisationError(serde_json::Error),
     DuplicateDomain(String),
+    UnknownLanguageDomain(String),
 }
 
 impl Display for CompanyDataError {
@@ -29,6 +34,7 @@ impl Display for CompanyDataError {
             CompanyDataError::ConfigPathReadFailure => write!(f, "The company data config file could not be read."),
             CompanyDataError::ConfigFileDeserialisationError(error) => write!(f, "The company data config could not be deserialised: {error}."),
             CompanyDataError::DuplicateDomain(domain) => write!(f, "The following domain is used for multiple companies: {domain}."),
+            CompanyDataError::UnknownLanguageDomain(domain) => write!(f, "The following domain has a language but isn't one of the domains of its company: {domain}."),
         }
     }
 }
@@ -46,6 +52,7 @@ impl CompanyData {
 
         let mut domain_to_company = HashMap::new();
         let mut domain_to_form_address = HashMap::new();
+        let mut domain_to_language = HashMap::new();
 
         for (company, entry) in data {
             for domain in entry.domains {
@@ -58,11 +65,20 @@ impl CompanyData {
                 domain_to_company.insert(domain.clone(), company.clone());
                 domain_to_form_address.insert(domain, entry.form_address.clone());
             }
+
+            for (domain, language) in entry.languages {
+                if !domain_to_company.get(&domain).is_some_and(|c| c == &company) {
+                    return Err(CompanyDataError::UnknownLanguageDomain(domain));
+                }
+
+                domain_to_language.insert(domain, language);
+            }
         }
 
         Ok(CompanyData {
             domain_to_form_address,
             domain_to_company,
+            domain_to_language,
         })
     }
 
@@ -70,6 +86,10 @@ impl CompanyData {
         self.domain_to_company.get(domain)
     }
 
+    pub fn domain_to_language(&self, domain: &str) -> Option<&String> {
+        self.domain_to_language.get(domain)
+    }
+
     pub fn domain_to_form_address(&self, domain: &str) -> Option<&String> {
         self.domain_to_form_address.get(domain)
     }
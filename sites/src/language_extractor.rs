@@ -0,0 +1,99 @@
+use axum::{extract::{FromRef, FromRequestParts, OriginalUri}, http::request::Parts};
+use axum_extra::extract::Host;
+use reqwest::StatusCode;
+
+use crate::AppState;
+
+/// The languages of the sites, Dutch is the fallback for texts that weren't translated.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    Nl,
+    Fr,
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::Nl, Language::Fr, Language::En];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Nl => "nl",
+            Language::Fr => "fr",
+            Language::En => "en",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        Language::ALL.into_iter().find(|language| language.code() == code)
+    }
+}
+
+/// The language of the request: the one of the path prefix (`/fr/projecten/...`), else the one of the domain, else
+/// Dutch.
+pub struct LanguageExtractor {
+    pub language: Language,
+    /// The language of the domain, its pages don't need a prefix.
+    pub domain_language: Language,
+    pub host: String,
+}
+
+impl LanguageExtractor {
+    /// The prefix of the links on the page, so visitors stay in the language they came in with.
+    pub fn prefix(&self) -> String {
+        if self.language == self.domain_language {
+            String::new()
+        } else {
+            format!("/{}", self.language.code())
+        }
+    }
+
+    /// The full URL of a project page in the language, for the canonical and `hreflang` links.
+    pub fn project_url(&self, language: Language, slug: &str) -> String {
+        if language == self.domain_language {
+            format!("https://{}/projecten/{slug}", self.host)
+        } else {
+            format!("https://{}/{}/projecten/{slug}", self.host, language.code())
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for LanguageExtractor
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Host(host) = Host::from_request_parts(parts, state)
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "The hostname could not be found"))?;
+
+        // Nested routers only see the path after the prefix.
+        let OriginalUri(uri) = OriginalUri::from_request_parts(parts, state)
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "The path could not be found"))?;
+
+        let state = AppState::from_ref(state);
+
+        let domain_language = state
+            .company_data
+            .domain_to_language(&host)
+            .and_then(|code| Language::from_code(code))
+            .unwrap_or(Language::Nl);
+
+        let language = uri
+            .path()
+            .trim_start_matches('/')
+            .split('/')
+            .next()
+            .and_then(Language::from_code)
+            .unwrap_or(domain_language);
+
+        Ok(LanguageExtractor {
+            language,
+            domain_language,
+            host,
+        })
+    }
+}
@@ -3,8 +3,10 @@ mod search;
 mod forms;
 mod company_data;
 mod company_extractor;
+mod language_extractor;
 mod db_connection_extractor;
 mod cookies;
+mod theme;
 
 use lettre::SmtpTransport;
 use search::search_results;
@@ -48,16 +50,29 @@ async fn main() {
         recaptcha_secret_key
     };
 
-    // build our application with a single route
-    let app = Router::new()
-        .route("/search", get(search_results))
+    // The project pages are also served under a language prefix, e.g. /fr/projecten/{id}.
+    let projecten = Router::new()
         .route("/projecten", get(projecten::endpoint::projecten))
         .route("/projecten/carousel", get(projecten::endpoint::carousel))
+        .route("/projecten/filter", get(projecten::endpoint::filter))
         .route("/projecten/in-de-kijker", get(projecten::endpoint::in_de_kijker))
+        .route("/projecten/kaart", get(projecten::endpoint::kaart))
+        .route("/projecten/kaart.geojson", get(projecten::endpoint::kaart_geojson))
         .route("/projecten/{id}", get(projecten::endpoint::project))
+        .route("/projecten/{id}/kerncijfers", get(projecten::endpoint::core_numbers));
+
+    // build our application with a single route
+    let app = Router::new()
+        .route("/search", get(search_results))
+        .merge(projecten.clone())
+        .nest("/nl", projecten.clone())
+        .nest("/fr", projecten.clone())
+        .nest("/en", projecten)
         .route("/forms", post(forms::endpoint::form))
         .route("/cookies", get(cookies::endpoint::get_cookie_buttons))
         .route("/cookies", post(cookies::endpoint::update_cookie_consent))
+        .route("/theme.css", get(theme::endpoint::theme_css))
+        .route("/theme/map", get(theme::endpoint::map))
         .with_state(state);
 
     // run our app with hyper, listening globally on port 3000
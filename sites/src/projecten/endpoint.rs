@@ -1,5 +1,6 @@
-use axum::response::Result;
-use axum::{extract::Path, response::IntoResponse};
+use axum::http::{HeaderMap, header};
+use axum::response::{Redirect, Response, Result};
+use axum::{extract::{Path, RawQuery}, response::IntoResponse};
 
 use axum_extra::extract::Query;
 
@@ -12,6 +13,7 @@ use crate::projecten::service::Parties;
 use crate::{
     company_extractor::CompanyExtractor,
     db_connection_extractor::SqliteConnectionExtractor,
+    language_extractor::{Language, LanguageExtractor},
     projecten::service::{GetContentError, GetImagesError},
 };
 
@@ -19,6 +21,7 @@ use super::service;
 use super::template;
 
 const LIMIT: u8 = 12;
+const MAX_LIMIT: u8 = 48;
 
 #[serde_as]
 #[derive(Deserialize)]
@@ -29,7 +32,13 @@ pub struct ProjectenQuery {
     sector: Option<String>,
     #[serde(default)]
     #[serde_as(as = "NoneAsEmptyString")]
-    status: Option<bool>,
+    fase: Option<String>,
+    #[serde(default, rename = "opgeleverd-van")]
+    #[serde_as(as = "NoneAsEmptyString")]
+    opgeleverd_van: Option<i64>,
+    #[serde(default, rename = "opgeleverd-tot")]
+    #[serde_as(as = "NoneAsEmptyString")]
+    opgeleverd_tot: Option<i64>,
     #[serde(default, rename = "publiek-privaat")]
     #[serde_as(as = "NoneAsEmptyString")]
     publiek_privaat: Option<String>,
@@ -38,6 +47,7 @@ pub struct ProjectenQuery {
     bedrijf: Option<String>,
     search: Option<String>,
     tags: Option<Vec<String>>,
+    limit: Option<u8>,
 }
 
 pub struct P {
@@ -50,43 +60,57 @@ pub struct P {
 pub async fn carousel(
     SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
     CompanyExtractor(company): CompanyExtractor,
+    language: LanguageExtractor,
 ) -> Result<Markup> {
-    let projects = service::get_carousel_projects(&mut conn, &company)
+    let projects = service::get_carousel_projects(&mut conn, &company, language.language.code())
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(template::carousel(&projects))
+    Ok(template::carousel(&projects, &language.prefix()))
 }
 
 pub async fn in_de_kijker(
     SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
     CompanyExtractor(company): CompanyExtractor,
+    language: LanguageExtractor,
 ) -> Result<Markup> {
-    let projects = service::get_carousel_projects(&mut conn, &company)
+    let projects = service::get_carousel_projects(&mut conn, &company, language.language.code())
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(template::in_de_kijker(&projects))
+    Ok(template::in_de_kijker(&projects, &language.prefix()))
 }
 
 pub async fn projecten(
     SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
     CompanyExtractor(company): CompanyExtractor,
+    language: LanguageExtractor,
     Query(query): Query<ProjectenQuery>,
 ) -> Result<Markup> {
     let page = query.page.unwrap_or(0);
+    let limit = query.limit.unwrap_or(LIMIT).clamp(1, MAX_LIMIT);
 
-    let offset = page * LIMIT;
+    let offset = page.saturating_mul(limit);
 
     let mut query_string = String::new();
 
     query_string.push_str(&format!("page={}", page + 1));
 
+    if query.limit.is_some() {
+        query_string.push_str(&format!("&limit={limit}"));
+    }
+
     if let Some(sector) = &query.sector {
         query_string.push_str(&format!("&sector={sector}"));
     }
-    if let Some(status) = &query.status {
-        query_string.push_str(&format!("&status={status}"));
+    if let Some(fase) = &query.fase {
+        query_string.push_str(&format!("&fase={fase}"));
+    }
+    if let Some(opgeleverd_van) = &query.opgeleverd_van {
+        query_string.push_str(&format!("&opgeleverd-van={opgeleverd_van}"));
+    }
+    if let Some(opgeleverd_tot) = &query.opgeleverd_tot {
+        query_string.push_str(&format!("&opgeleverd-tot={opgeleverd_tot}"));
     }
     if let Some(publiek_privaat) = &query.publiek_privaat {
         query_string.push_str(&format!("&publiek-privaat={publiek_privaat}"));
@@ -108,19 +132,123 @@ pub async fn projecten(
     let projects = service::get_projects(
         &mut conn,
         &company,
-        &query.status,
+        &query.fase,
+        query.opgeleverd_van,
+        query.opgeleverd_tot,
         &query.sector,
         query.publiek_privaat,
-        LIMIT,
+        limit,
         offset,
         &search,
         &query.bedrijf,
         &query.tags,
+        language.language.code(),
+    )
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::projects(&projects, limit, &query_string, &language.prefix()))
+}
+
+/// The map of the projects, it loads them from `kaart_geojson` with the same filters.
+pub async fn kaart(
+    language: LanguageExtractor,
+    RawQuery(query_string): RawQuery,
+) -> Markup {
+    template::project_map(&format!(
+        "/api{}/projecten/kaart.geojson?{}",
+        language.prefix(),
+        query_string.unwrap_or_default()
+    ))
+}
+
+/// The projects that have coordinates as a GeoJSON feature collection, filtered like `projecten`.
+pub async fn kaart_geojson(
+    SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
+    CompanyExtractor(company): CompanyExtractor,
+    language: LanguageExtractor,
+    Query(query): Query<ProjectenQuery>,
+) -> Result<impl IntoResponse> {
+    let search = format!("%{}%", query.search.unwrap_or_default());
+
+    let projects = service::get_map_projects(
+        &mut conn,
+        &company,
+        &query.fase,
+        query.opgeleverd_van,
+        query.opgeleverd_tot,
+        &query.sector,
+        &query.publiek_privaat,
+        &search,
+        &query.bedrijf,
+        &query.tags,
+        language.language.code(),
     )
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(template::projects(&projects, LIMIT, &query_string))
+    let prefix = language.prefix();
+
+    let features: Vec<serde_json::Value> = projects
+        .into_iter()
+        .map(|project| {
+            serde_json::json!({
+                "type": "Feature",
+                // GeoJSON puts the longitude first.
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [project.longitude, project.latitude],
+                },
+                "properties": {
+                    "name": project.name,
+                    "location": project.location,
+                    "thumbnail": project.thumbnail,
+                    "url": format!("{prefix}/projecten/{}", project.slug),
+                },
+            })
+        })
+        .collect();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/geo+json"),
+            (header::CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+        .to_string(),
+    ))
+}
+
+/// The filters above the projects, with the options the company's site offers.
+pub async fn filter(
+    SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
+    CompanyExtractor(company): CompanyExtractor,
+) -> Result<Markup> {
+    let options = service::get_filter_options(&mut conn, &company)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::filter(&options))
+}
+
+pub async fn core_numbers(
+    SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
+    Path(slug): Path<String>,
+    language: LanguageExtractor,
+) -> Result<Markup> {
+    let project_id = service::get_project_id(&mut conn, &slug, language.language.code())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let core_numbers = service::get_core_numbers(&mut conn, &project_id, language.language.code())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(template::core_numbers(&core_numbers))
 }
 
 pub struct ProjectItem {
@@ -129,30 +257,31 @@ pub struct ProjectItem {
     pub location: Option<String>,
     pub year: Option<i64>,
     pub learn_more: Option<String>,
-    pub status: bool,
+    /// Planned, in progress or delivered.
+    pub lifecycle: String,
+    pub start_date: Option<String>,
+    pub delivery_date: Option<String>,
     pub publiek_privaat: String,
     pub header_photo: Option<String>,
     pub header_photo_copyright: Option<String>,
     pub banner_photo: Option<String>,
     pub banner_photo_copyright: Option<String>,
     pub introduction: Option<String>,
-    pub contractors: Parties,
-    pub architects: Parties,
-    pub clients: Parties,
     pub industries: Parties,
 }
 
 pub enum ProjectError {
     HostToCompany,
     ProjectRetrieval(sqlx::Error),
-    ClientsRetrieval(sqlx::Error),
-    ArchitectsRetrieval(sqlx::Error),
-    ContractorsRetrieval(sqlx::Error),
+    PartiesRetrieval(sqlx::Error),
     GroupCompaniesRetrieval(sqlx::Error),
     IndustriesRetrieval(sqlx::Error),
     CoreNumbersRetrieval(sqlx::Error),
+    PhasesRetrieval(sqlx::Error),
     ContentRetrieval(GetContentError),
     ImagesRetrieval(GetImagesError),
+    SlugsRetrieval(sqlx::Error),
+    RelatedProjectsRetrieval(sqlx::Error),
 }
 
 impl IntoResponse for ProjectError {
@@ -161,33 +290,91 @@ impl IntoResponse for ProjectError {
     }
 }
 
+/// Set by nginx when it asks whether to serve the project page, instead of the content that page includes.
+const PROJECT_PAGE_HEADER: &str = "x-project-page";
+
+/// The project page. An old slug is redirected to the current one, so links keep working after the slug changed.
 pub async fn project(
     SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
     Path(slug): Path<String>,
     CompanyExtractor(company): CompanyExtractor,
-) -> Result<Markup> {
-    let project = service::get_project_data(&mut *conn, &slug, &company)
+    language: LanguageExtractor,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let code = language.language.code();
+
+    let project_id = match service::get_project_id(&mut *conn, &slug, code)
+        .await
+        .map_err(|err| ProjectError::ProjectRetrieval(err))?
+    {
+        Some(project_id) => project_id,
+        None => {
+            let current = service::get_current_slug(&mut *conn, &slug, code)
+                .await
+                .map_err(|err| ProjectError::ProjectRetrieval(err))?
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            let location = format!("{}/projecten/{current}", language.prefix());
+            return Ok(Redirect::permanent(&location).into_response());
+        }
+    };
+
+    if headers.contains_key(PROJECT_PAGE_HEADER) {
+        return Ok([("X-Accel-Redirect", "/project.html")].into_response());
+    }
+
+    let project = service::get_project_data(&mut *conn, &project_id, &company, code)
         .await
         .map_err(|err| {
             println!("{err}");
             ProjectError::ProjectRetrieval(err);
         })?;
 
-    let group_companies = service::get_group_companies(&mut *conn, &slug)
+    let parties = service::get_project_parties(&mut *conn, &project_id)
+        .await
+        .map_err(|err| ProjectError::PartiesRetrieval(err))?;
+    let group_companies = service::get_group_companies(&mut *conn, &project_id)
         .await
         .map_err(|err| ProjectError::GroupCompaniesRetrieval(err))?;
-    let core_numbers = service::get_core_numbers(&mut *conn, &slug)
+    let core_numbers = service::get_core_numbers(&mut *conn, &project_id, code)
         .await
         .map_err(|err| ProjectError::CoreNumbersRetrieval(err))?;
-    let content = service::get_content(&mut *conn, &slug, &company)
+    let phases = service::get_phases(&mut *conn, &project_id, code)
+        .await
+        .map_err(|err| ProjectError::PhasesRetrieval(err))?;
+    let content = service::get_content(&mut *conn, &project_id, &company, code)
         .await
         .map_err(|err| ProjectError::ContentRetrieval(err))?;
-    let images = service::get_images(&mut *conn, &slug, &company)
+    let images = service::get_images(&mut *conn, &project_id, &company, code)
         .await
         .map_err(|err| ProjectError::ImagesRetrieval(err))?;
+    let slugs = service::get_project_slugs(&mut *conn, &project_id)
+        .await
+        .map_err(|err| ProjectError::SlugsRetrieval(err))?;
+    let related_projects = service::get_related_projects(&mut *conn, &project_id, &company, code)
+        .await
+        .map_err(|err| ProjectError::RelatedProjectsRetrieval(err))?;
+
+    let alternates: Vec<(&str, String)> = slugs
+        .iter()
+        .filter_map(|slug| Language::from_code(&slug.language).map(|l| (l.code(), language.project_url(l, &slug.slug))))
+        .collect();
+    let canonical = language.project_url(language.language, &project.slug);
 
-    let template =
-        template::project_template(&project, &group_companies, &core_numbers, &content, &images);
+    let template = template::project_template(
+        &project,
+        &parties,
+        &group_companies,
+        &core_numbers,
+        &phases,
+        &content,
+        &images,
+        &related_projects,
+        code,
+        &language.prefix(),
+        &canonical,
+        &alternates,
+    );
 
-    Ok(template)
+    Ok(template.into_response())
 }
@@ -1,4 +1,4 @@
-use sqlx::{prelude::FromRow, query_as, Database, Decode, SqliteConnection};
+use sqlx::{query_as, query_file_as, query_scalar, Database, Decode, SqliteConnection};
 use std::error::Error;
 
 use crate::projecten::endpoint::ProjectItem;
@@ -20,39 +20,116 @@ where
     }
 }
 
-pub async fn get_project_data<'a>(
+/// The id of the published project with the slug in the language, or with the Dutch slug when the name wasn't
+/// translated. The slug of a translation wins over the Dutch slug of another project.
+pub async fn get_project_id(
     conn: &mut SqliteConnection,
     slug: &str,
+    language: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    query_scalar!(r#"
+    SELECT p.id
+    FROM PROJECTS p
+    LEFT JOIN PROJECT_TRANSLATIONS pt
+    ON pt.project_id=p.id AND pt.draft=p.draft AND pt.language=$2 AND pt.slug=$1
+    WHERE p.draft=0 AND (pt.slug IS NOT NULL OR p.slug=$1)
+    ORDER BY pt.slug IS NULL
+    LIMIT 1
+    "#, slug, language)
+    .fetch_optional(conn)
+    .await
+}
+
+/// The current slug in the language of the project that had the slug before, in the language or in Dutch.
+pub async fn get_current_slug(
+    conn: &mut SqliteConnection,
+    old_slug: &str,
+    language: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    query_scalar!(r#"
+    SELECT
+        COALESCE(
+            (SELECT pt.slug FROM PROJECT_TRANSLATIONS pt WHERE pt.project_id=p.id AND pt.draft=0 AND pt.language=$2 AND pt.field='name' AND pt.item=''),
+            p.slug
+        ) as "slug!: String"
+    FROM SLUG_HISTORY sh
+    JOIN PROJECTS p ON p.id=sh.project_id AND p.draft=0
+    WHERE sh.slug=$1 AND sh.language IN ($2, 'nl')
+    ORDER BY sh.language = 'nl'
+    LIMIT 1
+    "#, old_slug, language)
+    .fetch_optional(conn)
+    .await
+}
+
+pub struct ProjectSlug {
+    pub language: String,
+    pub slug: String,
+}
+
+/// The slug of the project in Dutch and in every language it has translations in, for the `hreflang` links.
+pub async fn get_project_slugs(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<Vec<ProjectSlug>, sqlx::Error> {
+    query_as!(ProjectSlug, r#"
+    SELECT 'nl' as "language!: String", slug FROM PROJECTS WHERE id=$1 AND draft=0
+    UNION ALL
+    SELECT
+        pt.language as "language!: String",
+        COALESCE(MAX(pt.slug), p.slug) as "slug!: String"
+    FROM PROJECT_TRANSLATIONS pt
+    JOIN PROJECTS p ON p.id=pt.project_id AND p.draft=pt.draft
+    WHERE pt.project_id=$1 AND pt.draft=0
+    GROUP BY pt.language
+    "#, project_id)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn get_project_data<'a>(
+    conn: &mut SqliteConnection,
+    project_id: &str,
     company_name: &str,
+    language: &str,
 ) -> Result<ProjectItem, sqlx::Error> {
     query_as!(ProjectItem, r#"
     SELECT
-        p.name,
-        p.slug,
-        p.location,
+        COALESCE(
+            (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=p.id AND draft=0 AND language=$3 AND field='name' AND item=''),
+            p.name
+        ) as "name!: String",
+        COALESCE(
+            (SELECT slug FROM PROJECT_TRANSLATIONS WHERE project_id=p.id AND draft=0 AND language=$3 AND field='name' AND item=''),
+            p.slug
+        ) as "slug!: String",
+        COALESCE(
+            (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=p.id AND draft=0 AND language=$3 AND field='location' AND item=''),
+            p.location
+        ) as "location: String",
         p.year,
         p.learn_more,
-        p.status,
+        p.lifecycle,
+        p.start_date,
+        p.delivery_date,
         p.publiek_privaat,
-        COALESCE(pc.introduction, base.introduction) as "introduction: String",
+        CASE
+            WHEN pc.introduction IS NOT NULL THEN COALESCE(
+                (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=p.id AND draft=0 AND language=$3 AND field='introduction' AND item=pc.company_name),
+                pc.introduction
+            )
+            ELSE COALESCE(
+                (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=p.id AND draft=0 AND language=$3 AND field='introduction' AND item=''),
+                base.introduction
+            )
+        END as "introduction: String",
         COALESCE(pc.header_photo, base.header_photo) as "header_photo: String",
         COALESCE(pc.banner_photo, base.banner_photo) as "banner_photo: String",
         pc.header_photo_copyright,
         COALESCE(pc.banner_photo_copyright, base.banner_photo_copyright) as "banner_photo_copyright: String",
-        (SELECT pp.party_name
-        FROM PROJECT_PARTIES pp 
-        WHERE pp.project_id = p.id AND pp.type = 'contractor' AND pp.draft=p.draft) AS "contractors: Parties",
-        
-        (SELECT pp.party_name
-        FROM PROJECT_PARTIES pp 
-        WHERE pp.project_id = p.id AND pp.type = 'architect' AND pp.draft=p.draft) AS "architects: Parties",
-        
-        (SELECT pp.party_name
-        FROM PROJECT_PARTIES pp 
-        WHERE pp.project_id = p.id AND pp.type = 'client' AND pp.draft=p.draft) AS "clients: Parties",
-        
-        (SELECT industry
+        (SELECT GROUP_CONCAT(COALESCE(i.label, i.name))
         FROM PROJECT_INDUSTRIES pi
+        JOIN INDUSTRIES i ON i.name=pi.industry
         WHERE pi.project_id=p.id AND pi.draft=p.draft) AS "industries: Parties"
     FROM
         PROJECTS p
@@ -65,12 +142,43 @@ pub async fn get_project_data<'a>(
         p.id=base.project_id
         AND base.company_name IS NULL
         AND p.draft=base.draft
-    WHERE slug=$2 AND p.draft=0
-    "#, company_name, slug)
+    WHERE p.id=$2 AND p.draft=0
+    "#, company_name, project_id, language)
     .fetch_one(conn)
     .await
 }
 
+pub struct ProjectParty {
+    pub name: String,
+    pub url: Option<String>,
+    pub logo: Option<String>,
+    /// Client, architect or contractor.
+    pub role: String,
+}
+
+pub async fn get_project_parties(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+) -> Result<Vec<ProjectParty>, sqlx::Error> {
+    query_as!(
+        ProjectParty,
+        r#"
+    SELECT
+        pa.name,
+        pa.url,
+        pa.logo,
+        pp.type as role
+    FROM PROJECT_PARTIES pp
+    JOIN PARTIES pa ON pa.name=pp.party_name
+    WHERE pp.project_id=$1 AND pp.draft=0
+    ORDER BY pp.position, pa.name
+    "#,
+        project_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
 pub struct GroupCompany {
     pub name: Option<String>,
     pub url: Option<String>,
@@ -78,7 +186,7 @@ pub struct GroupCompany {
 
 pub async fn get_group_companies<'a>(
     conn: &mut SqliteConnection,
-    project_name: &str,
+    project_id: &str,
 ) -> Result<Vec<GroupCompany>, sqlx::Error> {
     query_as!(
         GroupCompany,
@@ -87,10 +195,9 @@ pub async fn get_group_companies<'a>(
         company_name as "name: String",
         NULL as "url: String"
     FROM PROJECT_COMPANIES pc
-    LEFT JOIN PROJECTS p ON pc.project_id=p.id
-    WHERE p.slug=$1 AND company_name IS NOT NULL AND company_name IS NOT 'Corporate' AND pc.draft=0 AND p.draft=0
+    WHERE pc.project_id=$1 AND company_name IS NOT NULL AND company_name IS NOT 'Corporate' AND pc.draft=0
     "#,
-        project_name
+        project_id
     )
     .fetch_all(conn)
     .await
@@ -101,17 +208,43 @@ pub struct CoreNumber {
     pub title: String,
 }
 
-pub async fn get_core_numbers<'a>(conn: &mut SqliteConnection, project_name: &str) -> Result<Vec<CoreNumber>, sqlx::Error> {
+pub async fn get_core_numbers<'a>(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    language: &str,
+) -> Result<Vec<CoreNumber>, sqlx::Error> {
+    query_file_as!(CoreNumber, "../server/sql/queries/core_numbers.sql", project_id, language)
+    .fetch_all(conn)
+    .await
+}
+
+pub struct Phase {
+    pub name: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+pub async fn get_phases(
+    conn: &mut SqliteConnection,
+    project_id: &str,
+    language: &str,
+) -> Result<Vec<Phase>, sqlx::Error> {
     query_as!(
-        CoreNumber,
+        Phase,
         r#"
     SELECT
-        number, title
-    FROM CORE_NUMBERS cn
-    LEFT JOIN PROJECTS p ON cn.project_id=p.id
-    WHERE p.slug=$1 AND cn.draft=0 AND p.draft=0
+        COALESCE(
+            (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=ph.project_id AND draft=0 AND language=$2 AND field='phase_name' AND item=ph.id),
+            name
+        ) as "name!: String",
+        start_date,
+        end_date
+    FROM PROJECT_PHASES ph
+    WHERE ph.project_id=$1 AND ph.draft=0
+    ORDER BY ph.position
     "#,
-        project_name
+        project_id,
+        language
     )
     .fetch_all(conn)
     .await
@@ -133,8 +266,9 @@ pub enum GetContentError {
 
 pub async fn get_content<'a>(
     conn: &mut SqliteConnection,
-    slug: &str,
+    project_id: &str,
     company_name: &str,
+    language: &str,
 ) -> Result<Vec<ContentEntry>, GetContentError> {
     query_as!(
         ContentEntry,
@@ -147,10 +281,9 @@ pub async fn get_content<'a>(
                 image_copyright,
                 quote
                 FROM PROJECT_COMPANIES pcs 
-            JOIN PROJECTS p ON pcs.project_id=p.id AND pcs.draft=p.draft
             JOIN PROJECT_CONTENT pc ON pc.project_id=pcs.project_id AND pc.draft=pcs.draft AND
             ((custom_content=1 AND pc.company_name=pcs.company_name) OR (custom_content=0 AND pc.company_name IS NULL))
-            WHERE p.slug=$1
+            WHERE pcs.project_id=$1
             AND pcs.company_name=$2
             AND pcs.draft=0
             AND pc.previous_entry IS NULL
@@ -167,8 +300,19 @@ pub async fn get_content<'a>(
             JOIN ContentItems pi ON pc.previous_entry = pi.id AND draft = 0
         )
 
-        SELECT text, image, image_copyright, quote from ContentItems"#,
-        slug, company_name
+        SELECT
+            COALESCE(
+                (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=$1 AND draft=0 AND language=$3 AND field='content_text' AND item=ci.id),
+                ci.text
+            ) as "text: String",
+            ci.image,
+            ci.image_copyright,
+            COALESCE(
+                (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=$1 AND draft=0 AND language=$3 AND field='content_quote' AND item=ci.id),
+                ci.quote
+            ) as "quote: String"
+        FROM ContentItems ci"#,
+        project_id, company_name, language
     )
     .fetch_all(conn)
     .await
@@ -190,18 +334,19 @@ pub enum GetImagesError {
 
 pub async fn get_images<'a>(
     conn: &mut SqliteConnection,
-    slug: &str,
+    project_id: &str,
     company_name: &str,
+    language: &str,
 ) -> Result<Vec<ImageData>, GetImagesError> {
-    query_as!(ImageData, "SELECT
+    query_as!(ImageData, r#"SELECT
             image,
             image_copyright,
-            alt
+            COALESCE(
+                (SELECT value FROM PROJECT_TRANSLATIONS WHERE project_id=i.project_id AND draft=0 AND language=$3 AND field='image_alt' AND item=i.id),
+                alt
+            ) as "alt: String"
         FROM
             IMAGES i
-            JOIN PROJECTS p on i.project_id = p.id
-            AND p.slug = $1
-            AND p.draft = i.draft
             JOIN PROJECT_COMPANIES pc on i.project_id = pc.project_id
             AND pc.draft = i.draft
             AND (
@@ -215,9 +360,11 @@ pub async fn get_images<'a>(
                 )
             )
         WHERE
-            i.draft = 0
-            AND p.slug = 'arts-47'
-            AND (pc.company_name = $2 OR ($2 IS NULL AND pc.company_name IS NULL));", slug, company_name)
+            i.project_id = $1
+            AND i.draft = 0
+            AND (pc.company_name = $2 OR ($2 IS NULL AND pc.company_name IS NULL))
+        ORDER BY
+            i.position;"#, project_id, company_name, language)
     .fetch_all(conn)
     .await
     .map_err(|err| GetImagesError::LightboxImagesRetrievalError(err))
@@ -232,28 +379,14 @@ pub struct CarouselProject {
 
 pub async fn get_carousel_projects(
         conn: &mut SqliteConnection,
-        company_name: &str
+        company_name: &str,
+        language: &str
 ) -> Result<Vec<CarouselProject>, sqlx::Error> {
-    query_as!(
-        CarouselProject,
-        "
-        SELECT 
-            p.name, 
-            p.slug, 
-            p.location, 
-            COALESCE(pc.header_photo, pcb.header_photo) as header_photo 
-        FROM PROJECTS p 
-        LEFT JOIN PROJECT_COMPANIES pc ON pc.project_id=p.id AND pc.company_name=$1
-        LEFT JOIN PROJECT_COMPANIES pcb ON pcb.project_id=p.id AND pcb.company_name IS NULL
-        WHERE pc.show_in_carousel=1
-        GROUP BY p.name, p.slug;",
-        company_name
-    )
+    query_file_as!(CarouselProject, "../server/sql/queries/featured_projects.sql", company_name, language)
     .fetch_all(&mut *conn)
     .await
 }
 
-#[derive(FromRow)]
 pub struct Project {
     pub name: String,
     pub slug: String,
@@ -264,76 +397,267 @@ pub struct Project {
 pub async fn get_projects(
         conn: &mut SqliteConnection,
         company: &str,
-        status: &Option<bool>,
+        lifecycle: &Option<String>,
+        delivered_from: Option<i64>,
+        delivered_to: Option<i64>,
         sector: &Option<String>,
         publiek_privaat: Option<String>,
         limit: u8,
         offset: u8,
         search: &str,
         bedrijf: &Option<String>,
-        tags: &Option<Vec<String>>
+        tags: &Option<Vec<String>>,
+        language: &str
 ) -> Result<Vec<Project>, sqlx::Error> {
-    let tags_len = match tags  {
-        Some(tags) => tags.len(),
-        None => 0,
-    };
+    let tags = tags.as_ref().filter(|tags| !tags.is_empty()).map(|tags| serde_json::to_string(tags).unwrap_or_default());
 
-    let tags = tags.clone().map(|tags| tags.iter().map(|tag| format!("'{tag}'")).collect::<Vec<String>>().join(","));
+    query_file_as!(
+        Project,
+        "../server/sql/queries/projects.sql",
+        company,
+        lifecycle,
+        sector,
+        publiek_privaat,
+        bedrijf,
+        tags,
+        search,
+        limit,
+        offset,
+        language,
+        delivered_from,
+        delivered_to
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
 
-    let tags = tags.map(|tags| format!("AND pt.tag IN ({tags})")).unwrap_or_default();
+/// How many related projects the project page shows.
+pub const RELATED_PROJECTS_LIMIT: i64 = 4;
 
-    let having = if tags_len > 0 {
-        format!("HAVING COUNT(DISTINCT pt.tag) = {tags_len}")
-    } else {
-        String::new()
-    };
+/// The related projects the editors picked, in their order, or else the projects with the most in common with it:
+/// shared industries, labels, parties and TMs, a year close by and a location nearby. Only the projects the company
+/// shows are related, so the scored projects are used when the company shows none of the picked ones.
+pub async fn get_related_projects(
+        conn: &mut SqliteConnection,
+        project_id: &str,
+        company: &str,
+        language: &str
+) -> Result<Vec<Project>, sqlx::Error> {
+    query_as!(
+        Project,
+        r#"
+        WITH picked AS (
+            SELECT related_project_id, position FROM RELATED_PROJECTS WHERE project_id=$1 AND draft=0
+        ),
+        this AS (
+            SELECT year, latitude, longitude FROM PROJECTS WHERE id=$1 AND draft=0
+        ),
+        candidates AS (
+            SELECT
+                p.id,
+                COALESCE(tn.value, p.name) as name,
+                COALESCE(tn.slug, p.slug) as slug,
+                COALESCE(tl.value, p.location) as location,
+                CASE
+                    WHEN pc.thumbnail = 'base' THEN COALESCE(pcb.thumbnail, pcb.header_photo)
+                    WHEN pc.thumbnail IS NULL THEN COALESCE(pc.header_photo, pcb.header_photo)
+                    ELSE pc.thumbnail
+                END as thumbnail,
+                (SELECT position FROM picked WHERE related_project_id=p.id) as position,
+                p.year,
+                3 * (SELECT COUNT(*) FROM PROJECT_INDUSTRIES a JOIN PROJECT_INDUSTRIES b ON b.industry=a.industry
+                    WHERE a.project_id=p.id AND a.draft=0 AND b.project_id=$1 AND b.draft=0)
+                + 2 * (SELECT COUNT(*) FROM PROJECT_TAGS a JOIN PROJECT_TAGS b ON b.tag=a.tag
+                    WHERE a.project_id=p.id AND a.draft=0 AND b.project_id=$1 AND b.draft=0)
+                + 2 * (SELECT COUNT(DISTINCT a.party_name) FROM PROJECT_PARTIES a JOIN PROJECT_PARTIES b ON b.party_name=a.party_name
+                    WHERE a.project_id=p.id AND a.draft=0 AND b.project_id=$1 AND b.draft=0)
+                + (SELECT COUNT(DISTINCT a.tm_name) FROM PROJECT_TMS a JOIN PROJECT_TMS b ON b.tm_name=a.tm_name
+                    WHERE a.project_id=p.id AND a.draft=0 AND b.project_id=$1 AND b.draft=0)
+                + CASE WHEN ABS(p.year - this.year) <= 2 THEN 1 ELSE 0 END
+                -- The distance in km squared, close enough for the size of a country: a degree of latitude is 111
+                -- km and one of longitude about 70 km around Belgium.
+                + CASE
+                    WHEN (p.latitude - this.latitude) * (p.latitude - this.latitude) * 12321
+                        + (p.longitude - this.longitude) * (p.longitude - this.longitude) * 4900 < 625 THEN 2
+                    WHEN (p.latitude - this.latitude) * (p.latitude - this.latitude) * 12321
+                        + (p.longitude - this.longitude) * (p.longitude - this.longitude) * 4900 < 5625 THEN 1
+                    ELSE 0
+                END as score
+            FROM PROJECTS p
+            JOIN this
+            JOIN PROJECT_COMPANIES pc ON pc.project_id=p.id AND pc.draft=p.draft AND pc.company_name=$2
+            LEFT JOIN PROJECT_COMPANIES pcb ON pcb.project_id=p.id AND pcb.company_name IS NULL AND pcb.draft=p.draft
+            LEFT JOIN PROJECT_TRANSLATIONS tn ON tn.project_id=p.id AND tn.draft=p.draft AND tn.language=$3 AND tn.field='name' AND tn.item=''
+            LEFT JOIN PROJECT_TRANSLATIONS tl ON tl.project_id=p.id AND tl.draft=p.draft AND tl.language=$3 AND tl.field='location' AND tl.item=''
+            WHERE p.draft=0 AND p.id!=$1 AND pc.visible=1
+        )
+        SELECT
+            name as "name!: String",
+            slug as "slug!: String",
+            location as "location: String",
+            thumbnail as "thumbnail: String"
+        FROM candidates
+        WHERE
+            CASE WHEN EXISTS(SELECT 1 FROM candidates WHERE position IS NOT NULL) THEN position IS NOT NULL ELSE score > 0 END
+        ORDER BY position, score DESC, year DESC
+        LIMIT $4"#,
+        project_id,
+        company,
+        language,
+        RELATED_PROJECTS_LIMIT
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+pub struct MapProject {
+    pub name: String,
+    pub slug: String,
+    pub location: Option<String>,
+    pub thumbnail: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
 
-    println!("{limit}: {offset}");
+/// The visible projects of the company that have coordinates, with the same filters as `get_projects`.
+pub async fn get_map_projects(
+        conn: &mut SqliteConnection,
+        company: &str,
+        lifecycle: &Option<String>,
+        delivered_from: Option<i64>,
+        delivered_to: Option<i64>,
+        sector: &Option<String>,
+        publiek_privaat: &Option<String>,
+        search: &str,
+        bedrijf: &Option<String>,
+        tags: &Option<Vec<String>>,
+        language: &str
+) -> Result<Vec<MapProject>, sqlx::Error> {
+    let tags = tags.as_ref().filter(|tags| !tags.is_empty()).map(|tags| serde_json::to_string(tags).unwrap_or_default());
 
-    let projects: Vec<Project> = query_as(
-        &format!(r#"
-        SELECT p.name, p.slug, p.location,
+    query_as!(
+        MapProject,
+        r#"
+        SELECT
+            COALESCE(tn.value, p.name) as "name!: String",
+            COALESCE(tn.slug, p.slug) as "slug!: String",
+            COALESCE(tl.value, p.location) as "location: String",
             CASE
                 WHEN pc.thumbnail = 'base' THEN COALESCE(pcb.thumbnail, pcb.header_photo)
                 WHEN pc.thumbnail IS NULL THEN COALESCE(pc.header_photo, pcb.header_photo)
                 ELSE pc.thumbnail
-            END as thumbnail
-            FROM PROJECTS p 
+            END as "thumbnail: String",
+            p.latitude as "latitude!: f64",
+            p.longitude as "longitude!: f64"
+            FROM PROJECTS p
             LEFT JOIN PROJECT_COMPANIES pc ON p.id = pc.project_id AND p.draft = pc.draft
             LEFT JOIN PROJECT_INDUSTRIES pi ON p.id = pi.project_id AND p.draft = pi.draft
             LEFT JOIN PROJECT_COMPANIES pcb ON pcb.project_id=p.id AND pcb.company_name IS NULL AND p.draft = pcb.draft
             LEFT JOIN PROJECT_TAGS pt ON pt.project_id=p.id AND p.draft = pt.draft
-            WHERE 
+            LEFT JOIN PROJECT_TRANSLATIONS tn ON tn.project_id=p.id AND tn.draft=p.draft AND tn.language=$8 AND tn.field='name' AND tn.item=''
+            LEFT JOIN PROJECT_TRANSLATIONS tl ON tl.project_id=p.id AND tl.draft=p.draft AND tl.language=$8 AND tl.field='location' AND tl.item=''
+            WHERE
                 pc.company_name = $1
                 AND
-                ($2 IS NULL OR p.status = $2)
+                ($2 IS NULL OR p.lifecycle = $2)
+                AND
+                ($9 IS NULL OR (p.lifecycle = 'delivered' AND COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) >= $9))
+                AND
+                ($10 IS NULL OR (p.lifecycle = 'delivered' AND COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) <= $10))
                 AND
                 ($3 IS NULL OR pi.industry = $3)
                 AND
                 ($4 IS NULL OR p.publiek_privaat = $4)
                 AND
                 ($5 IS NULL OR EXISTS(SELECT 1 FROM PROJECT_COMPANIES WHERE project_id=p.id AND company_name=$5))
-                {tags}
                 AND
-                p.name LIKE $7
+                ($6 IS NULL OR pt.tag IN (SELECT value FROM json_each($6)))
+                AND
+                (p.name LIKE $7 OR tn.value LIKE $7)
+                AND p.latitude IS NOT NULL AND p.longitude IS NOT NULL
                 AND p.draft=0
                 AND pc.visible=1
             GROUP BY p.id
-            {having}
-            ORDER BY pc.weight DESC, p.year DESC
-            LIMIT $8 OFFSET $9"#))
-            .bind(company)
-            .bind(status)
-            .bind(sector)
-            .bind(publiek_privaat)
-            .bind(bedrijf)
-            .bind(tags)
-            .bind(search)
-            .bind(limit)
-            .bind(offset)
+            HAVING $6 IS NULL OR COUNT(DISTINCT pt.tag) = json_array_length($6)
+            ORDER BY pc.weight DESC, COALESCE(p.delivery_date, p.start_date, CAST(p.year AS TEXT)) DESC"#,
+        company,
+        lifecycle,
+        sector,
+        publiek_privaat,
+        bedrijf,
+        tags,
+        search,
+        language,
+        delivered_from,
+        delivered_to
+    )
     .fetch_all(&mut *conn)
     .await
-    .unwrap();
+}
+
+pub struct FilterOption {
+    pub name: String,
+    pub label: String,
+}
+
+pub struct FilterOptions {
+    pub industries: Vec<FilterOption>,
+    pub publiek_privaat: Vec<FilterOption>,
+    pub tags: Vec<FilterOption>,
+    /// The years the company's projects were delivered in, the most recent first.
+    pub delivery_years: Vec<i64>,
+}
 
-    Ok(projects)
-}
\ No newline at end of file
+/// The industries, publiek/privaat types and tags the company's site offers as filters, in the order of the admin. An
+/// entry without companies in `TAXONOMY_COMPANIES` is offered on every site.
+pub async fn get_filter_options(conn: &mut SqliteConnection, company: &str) -> Result<FilterOptions, sqlx::Error> {
+    let industries = query_as!(FilterOption, r#"
+        SELECT name, COALESCE(label, name) as "label!: String"
+        FROM INDUSTRIES t
+        WHERE
+            NOT EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'industries' AND name = t.name)
+            OR EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'industries' AND name = t.name AND company = $1)
+        ORDER BY position, name
+    "#, company)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let publiek_privaat = query_as!(FilterOption, r#"
+        SELECT name, COALESCE(label, name) as "label!: String"
+        FROM PUBLIEK_PRIVAAT_TYPES t
+        WHERE
+            NOT EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'publiek-privaat' AND name = t.name)
+            OR EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'publiek-privaat' AND name = t.name AND company = $1)
+        ORDER BY position, name
+    "#, company)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let tags = query_as!(FilterOption, r#"
+        SELECT name, COALESCE(label, name) as "label!: String"
+        FROM TAGS t
+        WHERE
+            NOT EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'labels' AND name = t.name)
+            OR EXISTS(SELECT 1 FROM TAXONOMY_COMPANIES WHERE taxonomy = 'labels' AND name = t.name AND company = $1)
+        ORDER BY position, name
+    "#, company)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let delivery_years = query_scalar!(r#"
+        SELECT DISTINCT COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) as "year!: i64"
+        FROM PROJECTS p
+        JOIN PROJECT_COMPANIES pc ON pc.project_id=p.id AND pc.draft=p.draft AND pc.company_name=$1
+        WHERE p.draft=0 AND pc.visible=1 AND p.lifecycle='delivered' AND COALESCE(CAST(substr(p.delivery_date, 1, 4) AS INTEGER), p.year) IS NOT NULL
+        ORDER BY 1 DESC
+    "#, company)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(FilterOptions {
+        industries,
+        publiek_privaat,
+        tags,
+        delivery_years,
+    })
+}
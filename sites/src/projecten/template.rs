@@ -2,7 +2,7 @@ use maud::{Markup, PreEscaped, html};
 
 use crate::projecten::{
     endpoint::ProjectItem,
-    service::{CarouselProject, ContentEntry, CoreNumber, GroupCompany, ImageData, Project},
+    service::{CarouselProject, ContentEntry, CoreNumber, FilterOptions, GroupCompany, ImageData, Phase, Project, ProjectParty},
 };
 
 pub fn basic_list(parties: &Vec<String>, r#type: &str) -> Markup {
@@ -20,6 +20,29 @@ pub fn basic_list(parties: &Vec<String>, r#type: &str) -> Markup {
     )
 }
 
+/// The parties with one role on the project, linked to their website and with their logo when they have one.
+pub fn party_list(parties: &[ProjectParty], r#type: &str) -> Markup {
+    html!(
+        div.item {
+            div style={"mask-image: url(/assets/project_page/"(r#type)".svg"} {}
+            ul {
+                @for party in parties.iter().filter(|party| party.role == r#type) {
+                    li.party {
+                        @if let Some(logo) = &party.logo {
+                            img.logo src=(logo) alt="" loading="lazy";
+                        }
+                        @if let Some(url) = &party.url {
+                            a href=(url) target="_blank" rel="noopener" { (party.name) }
+                        } @else {
+                            (party.name)
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
 pub fn basic_field(value: &str, r#type: &str) -> Markup {
     html!(
         div.item {
@@ -29,6 +52,82 @@ pub fn basic_field(value: &str, r#type: &str) -> Markup {
     )
 }
 
+/// The label of a stage of the lifecycle, as it's called in `PROJECTS.lifecycle`.
+fn lifecycle_label(lifecycle: &str, language: &str) -> &'static str {
+    match (lifecycle, language) {
+        ("delivered", "fr") => "Livré",
+        ("delivered", "en") => "Delivered",
+        ("delivered", _) => "Opgeleverd",
+        ("in_progress", "fr") => "En cours",
+        ("in_progress", "en") => "In progress",
+        ("in_progress", _) => "In uitvoering",
+        (_, "fr") => "Planifié",
+        (_, "en") => "Planned",
+        _ => "Gepland",
+    }
+}
+
+/// A date as DD/MM/YYYY, from the ISO date in the database.
+fn format_date(date: &str) -> String {
+    match date.splitn(3, '-').collect::<Vec<&str>>()[..] {
+        [year, month, day] => format!("{day}/{month}/{year}"),
+        _ => date.to_string(),
+    }
+}
+
+/// The stage of the project with its start and delivery, or just its year when it has no dates.
+fn lifecycle_field(project: &ProjectItem, language: &str) -> Markup {
+    let dates = match (&project.start_date, &project.delivery_date) {
+        (None, None) => project.year.map(|year| year.to_string()),
+        (start, delivery) => Some(
+            [start, delivery]
+                .into_iter()
+                .map(|date| date.as_deref().map(format_date).unwrap_or_default())
+                .collect::<Vec<String>>()
+                .join(" – ")
+        ),
+    };
+
+    html!(
+        div.item.lifecycle data-lifecycle=(project.lifecycle) {
+            div style="mask-image: url(/assets/project_page/year.svg)" {}
+            span {
+                span.stage { (lifecycle_label(&project.lifecycle, language)) }
+                @if let Some(dates) = dates {
+                    span.dates { (dates) }
+                }
+            }
+        }
+    )
+}
+
+fn phases_list(phases: &[Phase], language: &str) -> Markup {
+    let title = match language {
+        "fr" | "en" => "Phases",
+        _ => "Fasen",
+    };
+
+    html!(
+        section #phases {
+            h2 { (title) }
+            ol {
+                @for phase in phases {
+                    li {
+                        span.name { (phase.name) }
+                        @if phase.start_date.is_some() || phase.end_date.is_some() {
+                            span.dates {
+                                (phase.start_date.as_deref().map(format_date).unwrap_or_default())
+                                " – "
+                                (phase.end_date.as_deref().map(format_date).unwrap_or_default())
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
 pub fn group_company_list(group_companies: &Vec<GroupCompany>) -> Markup {
     html!(
         div.item {
@@ -48,14 +147,30 @@ pub fn group_company_list(group_companies: &Vec<GroupCompany>) -> Markup {
 
 pub fn project_template(
     project: &ProjectItem,
+    parties: &[ProjectParty],
     group_companies: &Vec<GroupCompany>,
     core_numbers: &Vec<CoreNumber>,
+    phases: &[Phase],
     content_entries: &Vec<ContentEntry>,
     images: &Vec<ImageData>,
+    related_projects: &[Project],
+    language: &str,
+    prefix: &str,
+    canonical: &str,
+    alternates: &[(&str, String)],
 ) -> Markup {
     html!(
         title { (project.name) }
-        angled-element {
+        link rel="canonical" href=(canonical);
+        @if alternates.len() > 1 {
+            @for (hreflang, url) in alternates {
+                link rel="alternate" hreflang=(hreflang) href=(url);
+                @if *hreflang == "nl" {
+                    link rel="alternate" hreflang="x-default" href=(url);
+                }
+            }
+        }
+        angled-element lang=(language) {
             section #main class="main-0" style={"background-image: url(\""(project.header_photo.as_deref().unwrap_or_default())"\")"} {
                 h1.project { (project.name )}
                 @if let Some(copyright) = &project.header_photo_copyright {
@@ -63,17 +178,15 @@ pub fn project_template(
                 }
             }
         }
-        div.container-0 {
+        div.container-0 lang=(language) {
             section #introduction {
-                @if project.clients.0.len() > 0 { (basic_list(&project.clients.0, "client")) }
-                @if project.architects.0.len() > 0 { (basic_list(&project.architects.0, "architect")) }
-                @if project.contractors.0.len() > 0 { (basic_list(&project.contractors.0, "contractor")) }
+                @for r#type in ["client", "architect", "contractor"] {
+                    @if parties.iter().any(|party| party.role == r#type) { (party_list(parties, r#type)) }
+                }
                 (group_company_list(group_companies))
                 hr;
                 (basic_list(&project.industries.0, "industry"))
-                @if let Some(year) = project.year {
-                    (basic_field(&year.to_string(), "year"))
-                }
+                (lifecycle_field(project, language))
                 @if let Some(location) = &project.location {
                     (basic_field(&location, "location"))
                 }
@@ -84,14 +197,11 @@ pub fn project_template(
             }
 
             @if core_numbers.len() > 0 {
-                ul #core-numbers {
-                    @for core_number in core_numbers {
-                        li {
-                            span.number { (core_number.number) }
-                            span.title { (core_number.title) }
-                        }
-                    }
-                }
+                (core_numbers_list(core_numbers))
+            }
+
+            @if !phases.is_empty() {
+                (phases_list(phases, language))
             }
 
             section #content {
@@ -117,7 +227,7 @@ pub fn project_template(
                 @if images.len() > 0 {
                     section is="light-box" {
                         @for image in images {
-                            img src=[&image.image] copyright=[&image.image_copyright];
+                            img src=[&image.image] copyright=[&image.image_copyright] alt=[&image.alt];
                         }
                     }
                 }
@@ -132,12 +242,60 @@ pub fn project_template(
                 }
             }
 
+            @if !related_projects.is_empty() {
+                (related_projects_list(related_projects, language, prefix))
+            }
+
             (PreEscaped(r#"<!--#include virtual="/map.html" -->"#))
         }
     )
 }
 
-pub fn carousel(projects: &Vec<CarouselProject>) -> Markup {
+fn related_projects_list(projects: &[Project], language: &str, prefix: &str) -> Markup {
+    let title = match language {
+        "fr" => "Projets similaires",
+        "en" => "Related projects",
+        _ => "Gerelateerde projecten",
+    };
+
+    html!(
+        section #related-projects {
+            h2 { (title) }
+            div.projects {
+                @for project in projects {
+                    a.project href={(prefix)"/projecten/"(project.slug)} {
+                        img src=[&project.thumbnail] loading="lazy";
+                        div.name { (project.name) }
+                        div.location { (project.location.as_deref().unwrap_or_default()) }
+                    }
+                }
+            }
+        }
+    )
+}
+
+fn core_numbers_list(core_numbers: &Vec<CoreNumber>) -> Markup {
+    html!(
+        ul #core-numbers {
+            @for core_number in core_numbers {
+                li {
+                    span.number { (core_number.number) }
+                    span.title { (core_number.title) }
+                }
+            }
+        }
+    )
+}
+
+pub fn core_numbers(core_numbers: &Vec<CoreNumber>) -> Markup {
+    html!(
+        @if core_numbers.len() > 0 {
+            (core_numbers_list(core_numbers))
+        }
+    )
+}
+
+pub fn carousel(projects: &Vec<CarouselProject>, prefix: &str) -> Markup {
     html!(
         @for project in projects  {
             div.main-content {
@@ -149,19 +307,19 @@ pub fn carousel(projects: &Vec<CarouselProject>) -> Markup {
                     @if let Some(location) = &project.location {
                         (location)
                     }
-                    a href={"/projecten/"(project.slug.as_deref().unwrap_or_default())} { "Bekijk project" }
+                    a href={(prefix)"/projecten/"(project.slug.as_deref().unwrap_or_default())} { "Bekijk project" }
                 }
             }
         }
     )
 }
 
-pub fn in_de_kijker(projects: &Vec<CarouselProject>) -> Markup {
+pub fn in_de_kijker(projects: &Vec<CarouselProject>, prefix: &str) -> Markup {
     html!(
         section.project-carousel {
             div.content {
                 @for project in projects  {
-                    a.item href={"/projecten/"(project.slug.as_deref().unwrap_or_default())} {
+                    a.item href={(prefix)"/projecten/"(project.slug.as_deref().unwrap_or_default())} {
                         img src=(project.header_photo.as_deref().unwrap_or_default());
                         span { (project.name)}
                     }
@@ -176,10 +334,10 @@ pub fn in_de_kijker(projects: &Vec<CarouselProject>) -> Markup {
     )
 }
 
-pub fn projects(projects: &Vec<Project>, limit: u8, query_string: &str) -> Markup {
+pub fn projects(projects: &Vec<Project>, limit: u8, query_string: &str, prefix: &str) -> Markup {
     html!(
         @for project in projects {
-            a.project href={"/projecten/"(project.slug)} {
+            a.project href={(prefix)"/projecten/"(project.slug)} {
                 img src=[&project.thumbnail] loading="lazy";
                 div.name {(project.name)}
                 div.location { (project.location.as_deref().unwrap_or_default()) }
@@ -188,10 +346,87 @@ pub fn projects(projects: &Vec<Project>, limit: u8, query_string: &str) -> Marku
 
         @if projects.len() == limit as usize {
                 div style="display: hidden;"
-                    hx-get={"/api/projecten?"(query_string)}
+                    hx-get={"/api"(prefix)"/projecten?"(query_string)}
                     hx-target="this"
                     hx-swap="outerHTML"
                     hx-trigger="revealed" {}
         }
     )
 }
+
+
+pub fn filter(options: &FilterOptions) -> Markup {
+    html!(
+        form.filter hx-push-url="true" hx-get="/projecten" hx-target="#projecten" hx-trigger="change" hx-swap="outerHTML" hx-select="#projecten" {
+            div.selects {
+                select name="sector" {
+                    option value="" { "Sector" }
+                    @for industry in &options.industries {
+                        option value=(industry.name) { (industry.label) }
+                    }
+                }
+                select name="fase" {
+                    option value="" { "Fase" }
+                    @for lifecycle in ["planned", "in_progress", "delivered"] {
+                        option value=(lifecycle) { (lifecycle_label(lifecycle, "nl")) }
+                    }
+                }
+                @if !options.delivery_years.is_empty() {
+                    select name="opgeleverd-van" {
+                        option value="" { "Opgeleverd van" }
+                        @for year in options.delivery_years.iter().rev() {
+                            option value=(year) { (year) }
+                        }
+                    }
+                    select name="opgeleverd-tot" {
+                        option value="" { "Opgeleverd tot" }
+                        @for year in &options.delivery_years {
+                            option value=(year) { (year) }
+                        }
+                    }
+                }
+                select name="publiek-privaat" {
+                    option value="" { "Publiek/Privaat" }
+                    @for publiek_privaat in &options.publiek_privaat {
+                        option value=(publiek_privaat.name) { (publiek_privaat.label) }
+                    }
+                }
+                div.background {}
+            }
+
+            div.search {
+                input placeholder="Zoeken..." name="search" hx-trigger="input delay:500ms changed" hx-get="/projecten";
+                div.toggle {
+                    svg viewBox="-0.58 0 58.719 58.719" overflow="visible" {
+                        path d="M683.547,267.547l-18.838-17.8a22.476,22.476,0,1,0-2.274,1.978l19.051,18a1.5,1.5,0,0,0,2.061-2.181Zm-54.1-33.692a19.438,19.438,0,1,1,19.438,19.438A19.46,19.46,0,0,1,629.449,233.855Z"
+                            transform="translate(-626.449 -211.418)" fill="CurrentColor" stroke-width="2" stroke="CurrentColor" {}
+                    }
+                    svg.close fill="none" viewBox="4.58 4.58 14.83 14.83" {
+                        path d="M17 7L7 17M7 7L17 17" stroke="CurrentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" {}
+                    }
+                }
+            }
+
+            @if !options.tags.is_empty() {
+                div.checkbox-group {
+                    @for tag in &options.tags {
+                        label { (tag.label) input name="tags" value=(tag.name) type="checkbox"; }
+                    }
+                }
+            }
+
+            script src="/projecten-filter.js" {}
+        }
+    )
+}
+
+/// An interactive map of the projects in the GeoJSON at `geojson`, each pin links to its project.
+pub fn project_map(geojson: &str) -> Markup {
+    html!(
+        link rel="stylesheet" href="https://unpkg.com/leaflet@1.9.4/dist/leaflet.css";
+        link rel="stylesheet" href="/projecten-kaart.css";
+        div.project-map data-geojson=(geojson) {}
+        script src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js" {}
+        script src="/projecten-kaart.js" {}
+    )
+}
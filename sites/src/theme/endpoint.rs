@@ -0,0 +1,43 @@
+use axum::http::header;
+use axum::response::{IntoResponse, Redirect, Result};
+use reqwest::StatusCode;
+
+use crate::{
+    company_extractor::CompanyExtractor, db_connection_extractor::SqliteConnectionExtractor,
+};
+
+use super::service;
+use super::template;
+
+pub async fn theme_css(
+    SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
+    CompanyExtractor(company): CompanyExtractor,
+) -> Result<impl IntoResponse> {
+    let theme = service::get_theme(&mut conn, &company)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/css"),
+            (header::CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        template::theme_css(&theme),
+    ))
+}
+
+pub async fn map(
+    SqliteConnectionExtractor(mut conn): SqliteConnectionExtractor,
+    CompanyExtractor(company): CompanyExtractor,
+) -> Result<Redirect> {
+    let map_pb = service::get_theme(&mut conn, &company)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|theme| theme.map_pb)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Redirect::temporary(&format!(
+        "https://www.google.com/maps/embed?pb={map_pb}"
+    )))
+}
@@ -0,0 +1,3 @@
+pub mod endpoint;
+mod template;
+mod service;
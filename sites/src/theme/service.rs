@@ -0,0 +1,28 @@
+use sqlx::{query_as, SqliteConnection};
+
+pub struct Theme {
+    pub primary_colour: String,
+    pub secondary_colour: String,
+    pub accent_colour: Option<String>,
+    pub heading_font: Option<String>,
+    pub body_font: Option<String>,
+    pub logo: Option<String>,
+    pub emblem: Option<String>,
+    pub map_pb: Option<String>,
+    pub button_radius: Option<f64>,
+    pub button_style: String,
+}
+
+pub async fn get_theme(
+    conn: &mut SqliteConnection,
+    company_name: &str,
+) -> Result<Option<Theme>, sqlx::Error> {
+    query_as!(
+        Theme,
+        "SELECT primary_colour, secondary_colour, accent_colour, heading_font, body_font, logo, emblem, map_pb, button_radius, button_style
+        FROM company_themes WHERE company=$1",
+        company_name
+    )
+    .fetch_optional(conn)
+    .await
+}
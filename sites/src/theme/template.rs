@@ -0,0 +1,32 @@
+use super::service::Theme;
+
+pub fn theme_css(theme: &Theme) -> String {
+    let mut css = String::from(":root {\n");
+
+    css.push_str(&format!("    --primary-colour: {};\n", theme.primary_colour));
+    css.push_str(&format!("    --secondary-colour: {};\n", theme.secondary_colour));
+
+    if let Some(accent_colour) = &theme.accent_colour {
+        css.push_str(&format!("    --accent-colour: {accent_colour};\n"));
+    }
+    if let Some(heading_font) = &theme.heading_font {
+        css.push_str(&format!("    --heading-font: {heading_font};\n"));
+    }
+    if let Some(body_font) = &theme.body_font {
+        css.push_str(&format!("    --body-font: {body_font};\n"));
+    }
+    if let Some(logo) = &theme.logo {
+        css.push_str(&format!("    --logo: url(\"{logo}\");\n"));
+    }
+    if let Some(emblem) = &theme.emblem {
+        css.push_str(&format!("    --emblem: url(\"{emblem}\");\n"));
+    }
+    if let Some(button_radius) = &theme.button_radius {
+        css.push_str(&format!("    --button-radius: {button_radius}rem;\n"));
+    }
+
+    css.push_str(&format!("    --button-style: {};\n", theme.button_style));
+    css.push_str("}\n");
+
+    css
+}
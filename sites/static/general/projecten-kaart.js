@@ -0,0 +1,74 @@
+(() => {
+    const element = document.currentScript.parentNode.querySelector(".project-map") ?? document.querySelector(".project-map");
+    if (!element || element.dataset.initialised) return;
+    element.dataset.initialised = true;
+
+    // Scripts that htmx swaps in don't wait for each other, Leaflet might still be loading.
+    if (window.L) {
+        init_project_map(element);
+    } else {
+        document.querySelector("script[src*='leaflet']")?.addEventListener("load", () => init_project_map(element));
+    }
+})()
+
+function init_project_map(element) {
+    const map = L.map(element, { scrollWheelZoom: false }).setView([50.85, 4.35], 8);
+
+    L.tileLayer("https://tile.openstreetmap.org/{z}/{x}/{y}.png", {
+        maxZoom: 19,
+        attribution: '&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a>',
+    }).addTo(map);
+
+    const layer = L.geoJSON(null, {
+        onEachFeature: (feature, marker) => {
+            const { name, location, thumbnail, url } = feature.properties;
+
+            const popup = document.createElement("a");
+            popup.className = "project-map-popup";
+            popup.href = url;
+
+            if (thumbnail) {
+                const img = document.createElement("img");
+                img.src = thumbnail;
+                img.loading = "lazy";
+                popup.append(img);
+            }
+
+            const title = document.createElement("span");
+            title.className = "name";
+            title.innerText = name;
+            popup.append(title);
+
+            if (location) {
+                const subtitle = document.createElement("span");
+                subtitle.className = "location";
+                subtitle.innerText = location;
+                popup.append(subtitle);
+            }
+
+            marker.bindPopup(popup);
+        },
+    }).addTo(map);
+
+    const load = async url => {
+        const response = await fetch(url);
+        if (!response.ok) return;
+
+        layer.clearLayers();
+        layer.addData(await response.json());
+
+        const bounds = layer.getBounds();
+        if (bounds.isValid()) {
+            map.fitBounds(bounds, { padding: [32, 32], maxZoom: 14 });
+        }
+    };
+
+    load(element.dataset.geojson);
+
+    // The filter above the projects pushes its values into the URL, the map follows them.
+    document.body.addEventListener("htmx:pushedIntoHistory", () => {
+        const url = new URL(element.dataset.geojson, window.location.origin);
+        url.search = window.location.search;
+        load(url);
+    });
+}